@@ -1,8 +1,9 @@
 #![no_main]
 
 use libfuzzer_sys::fuzz_target;
-use zstd_lib;
+use zstd_lib::{decode_with_options, DecodeOptions};
 
 fuzz_target!(|data: &[u8]| {
-    let _ = zstd_lib::decode(data, false);
+    // Single threaded so that findings replay deterministically
+    let _ = decode_with_options(data, DecodeOptions::new().single_threaded(true));
 });
@@ -0,0 +1,32 @@
+#![no_main]
+
+//! Decode every input with both this crate and the reference implementation,
+//! and compare: both must accept or reject it, with the same content.
+
+use libfuzzer_sys::fuzz_target;
+use zstd_lib::{decode_with_options, DecodeOptions, ErrorKind, ZstdLibError};
+
+/// Inputs libzstd accepts by design but this crate rejects
+fn known_difference(err: &ZstdLibError) -> bool {
+    // Windows are limited to 64MB, libzstd accepts 128MB by default
+    err.kind() == ErrorKind::LimitExceeded && err.window_size().is_some()
+}
+
+fuzz_target!(|data: &[u8]| {
+    // An empty input decodes to nothing, libzstd expects at least one frame
+    if data.is_empty() {
+        return;
+    }
+
+    // Single threaded so that findings replay deterministically
+    let ours = decode_with_options(data, DecodeOptions::new().single_threaded(true));
+    let reference = zstd::stream::decode_all(data);
+
+    match (ours, reference) {
+        (Ok(ours), Ok(reference)) => assert!(ours == reference, "decoded content differs"),
+        (Err(_), Err(_)) => (),
+        (Ok(_), Err(err)) => panic!("accepted input rejected by libzstd: {err}"),
+        (Err(err), Ok(_)) if known_difference(&err) => (),
+        (Err(err), Ok(_)) => panic!("rejected input accepted by libzstd: {err}"),
+    }
+});
@@ -0,0 +1,373 @@
+#![no_main]
+
+//! Decode frames built from a structured input: magic numbers, frame headers,
+//! block headers, section headers and FSE/Huffman table descriptions are valid
+//! or nearly so, bitstreams are fuzzed. Raw bytes rarely get past the headers.
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use zstd_lib::{decode_with_options, DecodeOptions};
+
+const MAX_BLOCK_SIZE: usize = 128 * 1024;
+
+#[derive(Debug, Arbitrary)]
+enum FrameInput {
+    Skippable { magic: u8, content: Vec<u8> },
+    Zstandard(ZstandardFrame),
+}
+
+#[derive(Debug, Arbitrary)]
+struct ZstandardFrame {
+    window_descriptor: u8,
+    content_size: Option<u32>,
+    checksum: Option<u32>,
+    blocks: Vec<BlockInput>,
+}
+
+#[derive(Debug, Arbitrary)]
+enum BlockInput {
+    Raw(Vec<u8>),
+    Rle {
+        byte: u8,
+        size: u16,
+    },
+    Compressed(Box<LiteralsInput>, Box<SequencesInput>),
+    /// Block header with any type and size, followed by any content
+    Fuzzed {
+        header: [u8; 3],
+        content: Vec<u8>,
+    },
+}
+
+#[derive(Debug, Arbitrary)]
+enum LiteralsInput {
+    Raw(Vec<u8>),
+    Rle {
+        byte: u8,
+        size: u16,
+    },
+    Compressed {
+        /// `None` for a treeless block, reusing the previous Huffman table
+        weights: Option<Vec<u8>>,
+        regenerated_size: u16,
+        streams: StreamsInput,
+    },
+}
+
+#[derive(Debug, Arbitrary)]
+enum StreamsInput {
+    One(Vec<u8>),
+    Four([Vec<u8>; 4]),
+}
+
+#[derive(Debug, Arbitrary)]
+struct SequencesInput {
+    number: u16,
+    literals_lengths: ModeInput,
+    offsets: ModeInput,
+    match_lengths: ModeInput,
+    bitstream: Vec<u8>,
+}
+
+#[derive(Debug, Arbitrary)]
+enum ModeInput {
+    Predefined,
+    Rle(u8),
+    Fse { accuracy_log: u8, counts: Vec<u8> },
+    Repeat,
+}
+
+/// Little-endian bit writer, as read by `ForwardBitParser`
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bits: usize,
+}
+
+impl BitWriter {
+    fn write(&mut self, value: u32, len: usize) {
+        for i in 0..len {
+            if self.bits.is_multiple_of(8) {
+                self.bytes.push(0);
+            }
+            let bit = ((value >> i) & 1) as u8;
+            *self.bytes.last_mut().unwrap() |= bit << (self.bits % 8);
+            self.bits += 1;
+        }
+    }
+}
+
+impl ModeInput {
+    fn mode(&self) -> u8 {
+        match self {
+            ModeInput::Predefined => 0,
+            ModeInput::Rle(_) => 1,
+            ModeInput::Fse { .. } => 2,
+            ModeInput::Repeat => 3,
+        }
+    }
+
+    fn encode(&self, output: &mut Vec<u8>) {
+        match self {
+            ModeInput::Rle(byte) => output.push(*byte),
+            ModeInput::Fse {
+                accuracy_log,
+                counts,
+            } => encode_fse_table(5 + accuracy_log % 5, counts, output),
+            ModeInput::Predefined | ModeInput::Repeat => (),
+        }
+    }
+}
+
+/// Turn fuzzed `counts` into a distribution summing to `1 << accuracy_log`
+/// (255 is a "less than 1" probability) and write its description
+fn encode_fse_table(accuracy_log: u8, counts: &[u8], output: &mut Vec<u8>) {
+    let table_size = 1_i32 << accuracy_log;
+    let mut distribution = Vec::new();
+    let mut total = 0;
+    for &count in counts.iter().take(52) {
+        let probability = if count == 255 {
+            -1
+        } else {
+            i32::from(count % 16).min(table_size - total)
+        };
+        total += probability.abs();
+        distribution.push(probability);
+        if total == table_size {
+            break;
+        }
+    }
+    if total < table_size {
+        distribution.push(table_size - total);
+    }
+
+    // Same encoding as `FSE_writeNCount` in the reference implementation
+    let mut writer = BitWriter::default();
+    writer.write(u32::from(accuracy_log - 5), 4);
+    let mut remaining = table_size + 1;
+    let mut threshold = table_size;
+    let mut num_bits = usize::from(accuracy_log) + 1;
+    let mut previous_zero = false;
+    let mut symbol = 0;
+    while symbol < distribution.len() && remaining > 1 {
+        if previous_zero {
+            let start = symbol;
+            while symbol < distribution.len() && distribution[symbol] == 0 {
+                symbol += 1;
+            }
+            if symbol == distribution.len() {
+                break;
+            }
+            let mut repeat = symbol - start;
+            while repeat >= 3 {
+                writer.write(3, 2);
+                repeat -= 3;
+            }
+            writer.write(repeat as u32, 2);
+        }
+
+        let probability = distribution[symbol];
+        symbol += 1;
+        let max = (2 * threshold - 1) - remaining;
+        remaining -= probability.abs();
+        let mut value = probability + 1;
+        if value >= threshold {
+            value += max;
+        }
+        writer.write(value as u32, num_bits);
+        if value < max {
+            writer.bits -= 1;
+            writer.bytes.truncate(writer.bits.div_ceil(8));
+        }
+        previous_zero = value == 1;
+        if remaining < 1 {
+            break;
+        }
+        while remaining < threshold {
+            num_bits -= 1;
+            threshold >>= 1;
+        }
+    }
+    output.extend(writer.bytes);
+}
+
+/// Raw and RLE literals header
+fn encode_literals_size(block_type: u8, size: usize, output: &mut Vec<u8>) {
+    let block_type = usize::from(block_type);
+    if size < 32 {
+        output.push((block_type | size << 3) as u8);
+    } else if size < 4096 {
+        let header = block_type | 0b01 << 2 | size << 4;
+        output.extend_from_slice(&header.to_le_bytes()[..2]);
+    } else {
+        let header = block_type | 0b11 << 2 | (size & 0xF_FFFF) << 4;
+        output.extend_from_slice(&header.to_le_bytes()[..3]);
+    }
+}
+
+impl LiteralsInput {
+    fn encode(&self, output: &mut Vec<u8>) {
+        match self {
+            LiteralsInput::Raw(bytes) => {
+                encode_literals_size(0, bytes.len(), output);
+                output.extend_from_slice(bytes);
+            }
+            LiteralsInput::Rle { byte, size } => {
+                encode_literals_size(1, usize::from(*size), output);
+                output.push(*byte);
+            }
+            LiteralsInput::Compressed {
+                weights,
+                regenerated_size,
+                streams,
+            } => {
+                let mut content = Vec::new();
+                if let Some(weights) = weights {
+                    // Direct representation: 4 bits per weight
+                    let weights = &weights[..weights.len().min(128)];
+                    content.push(127 + weights.len() as u8);
+                    for pair in weights.chunks(2) {
+                        let high = pair[0] % 12;
+                        let low = pair.get(1).map_or(0, |weight| weight % 12);
+                        content.push(high << 4 | low);
+                    }
+                }
+                let size_format = match streams {
+                    StreamsInput::One(stream) => {
+                        content.extend_from_slice(stream);
+                        0
+                    }
+                    StreamsInput::Four(streams) => {
+                        for stream in &streams[..3] {
+                            content.extend_from_slice(&(stream.len() as u16).to_le_bytes());
+                        }
+                        streams.iter().for_each(|stream| content.extend(stream));
+                        if content.len() < 1024 && *regenerated_size < 1024 {
+                            1
+                        } else {
+                            2
+                        }
+                    }
+                };
+                let (size_bits, header_len) = if size_format < 2 { (10, 3) } else { (14, 4) };
+                let block_type = if weights.is_some() { 2 } else { 3 };
+                let mask = (1 << size_bits) - 1;
+                let header = block_type
+                    | size_format << 2
+                    | (usize::from(*regenerated_size) & mask) << 4
+                    | (content.len() & mask) << (4 + size_bits);
+                output.extend_from_slice(&header.to_le_bytes()[..header_len]);
+                output.extend(content);
+            }
+        }
+    }
+}
+
+impl SequencesInput {
+    fn encode(&self, output: &mut Vec<u8>) {
+        let number = usize::from(self.number);
+        if number < 128 {
+            output.push(number as u8);
+        } else if number < 0x7F00 {
+            output.extend_from_slice(&[(number >> 8) as u8 + 128, number as u8]);
+        } else {
+            output.push(0xFF);
+            output.extend_from_slice(&((number - 0x7F00) as u16).to_le_bytes());
+        }
+        if number == 0 {
+            return;
+        }
+
+        output.push(
+            self.literals_lengths.mode() << 6
+                | self.offsets.mode() << 4
+                | self.match_lengths.mode() << 2,
+        );
+        self.literals_lengths.encode(output);
+        self.offsets.encode(output);
+        self.match_lengths.encode(output);
+        output.extend_from_slice(&self.bitstream);
+    }
+}
+
+fn encode_block_header(last: bool, block_type: u32, size: usize, output: &mut Vec<u8>) {
+    let header = u32::from(last) | block_type << 1 | (size as u32) << 3;
+    output.extend_from_slice(&header.to_le_bytes()[..3]);
+}
+
+impl BlockInput {
+    fn encode(&self, last: bool, output: &mut Vec<u8>) {
+        match self {
+            BlockInput::Raw(bytes) => {
+                let bytes = &bytes[..bytes.len().min(MAX_BLOCK_SIZE)];
+                encode_block_header(last, 0, bytes.len(), output);
+                output.extend_from_slice(bytes);
+            }
+            BlockInput::Rle { byte, size } => {
+                encode_block_header(last, 1, usize::from(*size), output);
+                output.push(*byte);
+            }
+            BlockInput::Compressed(literals, sequences) => {
+                let mut content = Vec::new();
+                literals.encode(&mut content);
+                sequences.encode(&mut content);
+                content.truncate(MAX_BLOCK_SIZE);
+                encode_block_header(last, 2, content.len(), output);
+                output.extend(content);
+            }
+            BlockInput::Fuzzed { header, content } => {
+                let last_flag = u8::from(last);
+                output.extend_from_slice(&[header[0] & !1 | last_flag, header[1], header[2]]);
+                output.extend_from_slice(content);
+            }
+        }
+    }
+}
+
+impl FrameInput {
+    fn encode(&self, output: &mut Vec<u8>) {
+        match self {
+            FrameInput::Skippable { magic, content } => {
+                output.extend_from_slice(&(0x184D_2A50 | u32::from(magic % 16)).to_le_bytes());
+                output.extend_from_slice(&(content.len() as u32).to_le_bytes());
+                output.extend_from_slice(content);
+            }
+            FrameInput::Zstandard(frame) => {
+                output.extend_from_slice(&0xFD2F_B528_u32.to_le_bytes());
+                let content_size_flag = if frame.content_size.is_some() { 2 } else { 0 };
+                let checksum_flag = u8::from(frame.checksum.is_some());
+                output.push(content_size_flag << 6 | checksum_flag << 2);
+                // Exponent up to 15: windows under 64MB, the decoder limit
+                let exponent = (frame.window_descriptor >> 3) % 16;
+                output.push(exponent << 3 | frame.window_descriptor & 0b111);
+                if let Some(content_size) = frame.content_size {
+                    output.extend_from_slice(&content_size.to_le_bytes());
+                }
+
+                match frame.blocks.split_last() {
+                    None => encode_block_header(true, 0, 0, output),
+                    Some((last, blocks)) => {
+                        for block in blocks {
+                            block.encode(false, output);
+                        }
+                        last.encode(true, output);
+                    }
+                }
+
+                if let Some(checksum) = frame.checksum {
+                    output.extend_from_slice(&checksum.to_le_bytes());
+                }
+            }
+        }
+    }
+}
+
+fuzz_target!(|frames: Vec<FrameInput>| {
+    let mut data = Vec::new();
+    for frame in &frames {
+        frame.encode(&mut data);
+    }
+
+    // Single threaded so that findings replay deterministically
+    let _ = decode_with_options(&data, DecodeOptions::new().single_threaded(true));
+});
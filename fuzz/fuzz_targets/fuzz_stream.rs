@@ -0,0 +1,40 @@
+#![no_main]
+
+//! Feed the input to `StreamDecoder` in chunks of fuzzed sizes and compare with
+//! the one-shot decoding: both must accept or reject it, with the same content.
+
+use libfuzzer_sys::fuzz_target;
+use zstd_core::{Sequential, StreamDecoder, ZstdLibError};
+use zstd_lib::{decode_with_options, DecodeOptions};
+
+/// Decode `data` in chunks of 1 to 256 bytes cycling through `chunk_sizes`,
+/// whole when there are none
+fn decode_chunks(data: &[u8], chunk_sizes: &[u8]) -> Result<Vec<u8>, ZstdLibError> {
+    let mut decoder = StreamDecoder::with_executor(Sequential);
+    let mut output = Vec::new();
+    let mut sizes = chunk_sizes.iter().map(|&size| usize::from(size) + 1).cycle();
+    let mut remaining = data;
+    while !remaining.is_empty() {
+        let size = sizes.next().unwrap_or(remaining.len()).min(remaining.len());
+        let (chunk, rest) = remaining.split_at(size);
+        decoder.decode(chunk, &mut output)?;
+        remaining = rest;
+    }
+    decoder.finish()?;
+    Ok(output)
+}
+
+fuzz_target!(|input: (Vec<u8>, &[u8])| {
+    let (chunk_sizes, data) = input;
+
+    // Single threaded so that findings replay deterministically
+    let one_shot = decode_with_options(data, DecodeOptions::new().single_threaded(true));
+    let streamed = decode_chunks(data, &chunk_sizes);
+
+    match (one_shot, streamed) {
+        (Ok(one_shot), Ok(streamed)) => assert!(one_shot == streamed, "decoded content differs"),
+        (Err(_), Err(_)) => (),
+        (Ok(_), Err(err)) => panic!("streamed decoding failed: {err}"),
+        (Err(err), Ok(_)) => panic!("one-shot decoding failed: {err}"),
+    }
+});
@@ -0,0 +1,75 @@
+//! Decode every input of up to `EXHAUSTIVE_MAX_LEN` bytes (2 by default) after
+//! valid prefixes with both this crate and the reference implementation, and
+//! compare: both must accept or reject it, with the same content. Slow, run it
+//! in release mode:
+//!     cargo test --release --features differential --test exhaustive
+
+use zstd_lib::{decode_with_options, DecodeOptions};
+
+/// Magic number, no content size nor checksum, 1KB window
+const FRAME_HEADER: [u8; 6] = [0x28, 0xB5, 0x2F, 0xFD, 0x00, 0x00];
+
+/// Inputs hold a single block
+const MAX_CONTENT_SIZE: usize = 128 * 1024;
+
+/// Mismatches reported in full, the rest are only counted
+const MAX_REPORTED: usize = 20;
+
+/// Describe how the two implementations disagree on `data`, if they do
+fn compare(data: &[u8]) -> Option<String> {
+    let ours = decode_with_options(data, DecodeOptions::new().single_threaded(true));
+    // One-shot like `decode_with_options`: the streaming API of libzstd skips
+    // empty compressed blocks, which are invalid
+    let reference = zstd::bulk::decompress(data, MAX_CONTENT_SIZE);
+
+    match (ours, reference) {
+        (Ok(ours), Ok(reference)) if ours != reference => Some("decoded content differs".into()),
+        (Ok(_), Err(err)) => Some(format!("accepted, rejected by libzstd: {err}")),
+        (Err(err), Ok(_)) => Some(format!("rejected, accepted by libzstd: {err}")),
+        _ => None,
+    }
+}
+
+/// Last compressed block header of `size` bytes
+fn compressed_block(size: usize) -> Vec<u8> {
+    let header = 1 | 2 << 1 | (size as u32) << 3;
+    header.to_le_bytes()[..3].to_vec()
+}
+
+/// Prefixes to enumerate `len` bytes after: the block header and content, the
+/// content of a compressed block, or its sequences section after empty literals
+fn prefixes(len: usize) -> Vec<Vec<u8>> {
+    let compressed = [FRAME_HEADER.as_slice(), &compressed_block(len)].concat();
+    let sequences = [FRAME_HEADER.as_slice(), &compressed_block(len + 1), &[0x00]].concat();
+    vec![FRAME_HEADER.to_vec(), compressed, sequences]
+}
+
+#[test]
+fn test_exhaustive() {
+    let max_len = std::env::var("EXHAUSTIVE_MAX_LEN").map_or(2, |len| len.parse().unwrap());
+    assert!(max_len <= 4, "EXHAUSTIVE_MAX_LEN is at most 4");
+
+    let mut inputs = 0_u64;
+    let mut mismatches = Vec::new();
+    for len in 0..=max_len {
+        for prefix in prefixes(len) {
+            for suffix in 0..1_u64 << (8 * len) {
+                let mut data = prefix.clone();
+                data.extend_from_slice(&suffix.to_le_bytes()[..len]);
+                inputs += 1;
+                if let Some(mismatch) = compare(&data) {
+                    mismatches.push(format!("{data:02X?}: {mismatch}"));
+                }
+            }
+        }
+    }
+
+    for mismatch in mismatches.iter().take(MAX_REPORTED) {
+        println!("{mismatch}");
+    }
+    println!(
+        "exhaustive: {} mismatches out of {inputs} inputs up to {max_len} bytes",
+        mismatches.len()
+    );
+    assert!(mismatches.is_empty());
+}
@@ -0,0 +1,98 @@
+//! Regenerate the test fixtures of a directory: every source file gets a `.zst`
+//! fixture compressed with the system `zstd`, and `manifest.txt` records the
+//! size and xxh64 of the content each fixture must decode to.
+//!
+//! `cargo run -p golden -- ./tests/golden`
+
+use clap::Parser;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+use xxhash_rust::xxh64::xxh64;
+
+const MANIFEST: &str = "manifest.txt";
+
+#[derive(Parser, Debug)]
+#[command(version)]
+struct Args {
+    /// Directory of the source files
+    sources: PathBuf,
+
+    /// Directory of the fixtures and manifest, the sources directory by default
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+
+    /// Recompress existing fixtures, which are kept by default since some are
+    /// hand-crafted (e.g. the reference implementation golden files)
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+
+    /// Compression level passed to zstd
+    #[arg(short, long, default_value_t = 19)]
+    level: u8,
+}
+
+fn zstd_installed() -> bool {
+    Command::new("zstd").arg("--version").output().is_ok()
+}
+
+fn compress(source: &Path, fixture: &Path, level: u8) -> eyre::Result<()> {
+    let status = Command::new("zstd")
+        .args(["-q", "-f", &format!("-{level}")])
+        .arg(source)
+        .arg("-o")
+        .arg(fixture)
+        .status()?;
+    if !status.success() {
+        eyre::bail!("zstd failed on {}: {status}", source.display());
+    }
+    Ok(())
+}
+
+fn sources(dir: &Path) -> eyre::Result<Vec<PathBuf>> {
+    let mut sources = vec![];
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        let fixture = path.extension().is_some_and(|ext| ext == "zst");
+        if path.is_file() && !fixture && !path.ends_with(MANIFEST) {
+            sources.push(path);
+        }
+    }
+    sources.sort();
+    Ok(sources)
+}
+
+fn main() -> eyre::Result<()> {
+    let args = Args::parse();
+    let output = args.output.as_ref().unwrap_or(&args.sources);
+    fs::create_dir_all(output)?;
+    let zstd = zstd_installed();
+
+    let mut manifest = String::from("# fixture source size xxh64\n");
+    for source in sources(&args.sources)? {
+        let source_name = source.file_name().unwrap().to_string_lossy();
+        let fixture = output.join(source.with_extension("zst").file_name().unwrap());
+        let fixture_name = fixture.file_name().unwrap().to_string_lossy();
+
+        if args.force || !fixture.exists() {
+            if !zstd {
+                eyre::bail!("zstd is not installed, cannot create {fixture_name}");
+            }
+            compress(&source, &fixture, args.level)?;
+            println!("compressed {source_name} into {fixture_name}");
+        }
+
+        let content = fs::read(&source)?;
+        manifest.push_str(&format!(
+            "{fixture_name} {source_name} {} {:016x}\n",
+            content.len(),
+            xxh64(&content, 0)
+        ));
+    }
+
+    fs::write(output.join(MANIFEST), manifest)?;
+    println!("wrote {}", output.join(MANIFEST).display());
+    Ok(())
+}
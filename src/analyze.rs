@@ -0,0 +1,124 @@
+//! `analyze`: histograms of the literal lengths, match lengths and offsets of
+//! the sequences of a corpus, to tune dictionaries and window sizes
+
+use std::io::{self, Write};
+use zstd_lib::{BlockSequences, BlockType};
+
+/// Counts by power of two: the bucket 0 counts the zeros, the bucket `k` the
+/// values from `2^(k-1)` to `2^k - 1`
+#[derive(Default)]
+struct Histogram(Vec<u64>);
+
+impl Histogram {
+    fn add(&mut self, value: usize) {
+        let bucket = (usize::BITS - value.leading_zeros()) as usize;
+        if bucket >= self.0.len() {
+            self.0.resize(bucket + 1, 0);
+        }
+        self.0[bucket] += 1;
+    }
+
+    /// Smallest and largest value of each bucket, with its count
+    fn buckets(&self) -> impl Iterator<Item = (usize, usize, u64)> + '_ {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(bucket, &count)| match bucket {
+                0 => (0, 0, count),
+                _ => (1 << (bucket - 1), (1 << bucket) - 1, count),
+            })
+    }
+}
+
+#[derive(Default)]
+pub struct Analysis {
+    files: usize,
+    blocks: usize,
+    compressed_blocks: usize,
+    sequences: u64,
+    repeat_offsets: u64,
+    literal_lengths: Histogram,
+    match_lengths: Histogram,
+    offsets: Histogram,
+}
+
+impl Analysis {
+    /// Count a file, whose blocks are then given to `add_block`
+    pub fn add_file(&mut self) {
+        self.files += 1;
+    }
+
+    pub fn add_block(&mut self, block: &BlockSequences) {
+        self.blocks += 1;
+        if block.block_type == BlockType::Compressed {
+            self.compressed_blocks += 1;
+        }
+        self.sequences += block.sequences.len() as u64;
+        self.repeat_offsets += block.repeat_offsets as u64;
+        for sequence in block.sequences {
+            self.literal_lengths.add(sequence.literal_length);
+            self.match_lengths.add(sequence.match_length);
+            self.offsets.add(sequence.offset);
+        }
+    }
+
+    fn histograms(&self) -> [(&'static str, &Histogram); 3] {
+        [
+            ("literal_length", &self.literal_lengths),
+            ("match_length", &self.match_lengths),
+            ("offset", &self.offsets),
+        ]
+    }
+
+    /// Share of the sequences that `count` is, in percent
+    #[allow(clippy::cast_precision_loss)]
+    fn percent(&self, count: u64) -> f64 {
+        if self.sequences == 0 {
+            0.0
+        } else {
+            100.0 * count as f64 / self.sequences as f64
+        }
+    }
+
+    pub fn write_text(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(
+            out,
+            "{} files, {} blocks ({} compressed), {} sequences",
+            self.files, self.blocks, self.compressed_blocks, self.sequences
+        )?;
+        writeln!(
+            out,
+            "repeat offsets: {} ({:.1}%)",
+            self.repeat_offsets,
+            self.percent(self.repeat_offsets)
+        )?;
+        for (name, histogram) in self.histograms() {
+            writeln!(out, "\n{name}")?;
+            for (min, max, count) in histogram.buckets() {
+                let range = if min == max {
+                    min.to_string()
+                } else {
+                    format!("{min}-{max}")
+                };
+                let percent = self.percent(count);
+                writeln!(out, "{range:>21} {count:>12} {percent:>6.2}%")?;
+            }
+        }
+        Ok(())
+    }
+
+    /// One row per bucket, then the counts as rows without a range
+    pub fn write_csv(&self, out: &mut impl Write) -> io::Result<()> {
+        writeln!(out, "metric,min,max,count")?;
+        for (name, histogram) in self.histograms() {
+            for (min, max, count) in histogram.buckets() {
+                writeln!(out, "{name},{min},{max},{count}")?;
+            }
+        }
+        writeln!(out, "files,,,{}", self.files)?;
+        writeln!(out, "blocks,,,{}", self.blocks)?;
+        writeln!(out, "compressed_blocks,,,{}", self.compressed_blocks)?;
+        writeln!(out, "sequences,,,{}", self.sequences)?;
+        writeln!(out, "repeat_offsets,,,{}", self.repeat_offsets)
+    }
+}
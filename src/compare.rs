@@ -0,0 +1,76 @@
+//! `--compare-reference`: decode the same input with this crate and with the
+//! system `zstd`, and report how their outputs and exit behaviours differ
+
+use super::first_difference;
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+    thread,
+};
+use zstd_lib::DecodeOptions;
+
+/// Decode `bytes` with `zstd -d`, given the same window limit as we are. The
+/// error is the first line zstd printed.
+fn reference(bytes: &[u8], window_log: Option<u32>) -> eyre::Result<Result<Vec<u8>, String>> {
+    let mut command = Command::new("zstd");
+    command.args(["-d", "-c", "-q"]);
+    if let Some(window_log) = window_log {
+        command.arg(format!("--long={window_log}"));
+    }
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|err| eyre::eyre!("system zstd not found: {err}"))?;
+
+    // Written on its own thread so that zstd does not block on a full stdout;
+    // a write error only means zstd stopped reading, which its status reports
+    let mut stdin = child.stdin.take().unwrap();
+    let output = thread::scope(|s| {
+        s.spawn(move || stdin.write_all(bytes));
+        child.wait_with_output()
+    })?;
+
+    if output.status.success() {
+        return Ok(Ok(output.stdout));
+    }
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    let message = stderr.lines().next().unwrap_or_default();
+    Ok(Err(format!("{}: {message}", output.status)))
+}
+
+/// Fail with a report of the mismatch when we and zstd disagree on `bytes`
+pub fn compare_reference(
+    source: &str,
+    bytes: &[u8],
+    options: DecodeOptions,
+    window_log: Option<u32>,
+) -> eyre::Result<()> {
+    let ours = zstd_lib::decode_with_options(bytes, options);
+    let reference = reference(bytes, window_log)?;
+
+    match (ours, reference) {
+        (Ok(ours), Ok(reference)) => match first_difference(ours.as_slice(), reference.as_slice())?
+        {
+            None => eprintln!("{source}: same output as zstd, {} bytes", ours.len()),
+            Some(offset) => eyre::bail!(
+                "{source}: output differs from zstd at offset {offset}: {} bytes, zstd {} bytes",
+                ours.len(),
+                reference.len()
+            ),
+        },
+        (Err(ours), Err(reference)) => {
+            eprintln!("{source}: rejected by both\n  ours: {ours}\n  zstd: {reference}");
+        }
+        (Ok(ours), Err(reference)) => eyre::bail!(
+            "{source}: decoded {} bytes, zstd fails with {reference}",
+            ours.len()
+        ),
+        (Err(ours), Ok(reference)) => eyre::bail!(
+            "{source}: fails with {ours}, zstd decodes {} bytes",
+            reference.len()
+        ),
+    }
+    Ok(())
+}
@@ -1,27 +1,875 @@
-use clap::Parser;
-use std::{fs, io::Write};
+mod analyze;
+#[cfg(feature = "dev")]
+mod compare;
+#[cfg(feature = "trace")]
+mod trace;
+
+use clap::{Parser, Subcommand};
+use std::{
+    cmp, fs,
+    io::{self, BufRead, BufReader, IsTerminal, Read, Write},
+    path::Path,
+    time::{Duration, Instant},
+};
+use zstd_lib::{BlockInfo, BlockType, ErrorKind, FrameKind, FrameMetadata, ZstdLibError};
 
 #[derive(Parser, Debug)]
-#[command(version)]
+#[command(
+    version,
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
+)]
 struct Args {
-    /// Source file to decompress
-    source: String,
+    #[command(subcommand)]
+    command: Option<Command>,
+
+    /// Source file to decompress, next to it without its `.zst` extension, `-`
+    /// for stdin
+    #[arg(required = true)]
+    source: Option<String>,
 
-    /// Dump information about frames instead of outputing the result
+    /// Decompress, the only mode: accepted for scripts calling `zstd -d`
+    #[arg(short, long, default_value_t = false)]
+    decompress: bool,
+
+    /// Summarize the frames instead of outputing the result, see `--verbose`
     #[arg(short, long, default_value_t = false)]
     info: bool,
+
+    /// Detail level of `--info`: `-v` lists the frames, `-vv` their blocks and
+    /// `-vvv` the literals and sequences sections of compressed blocks
+    #[arg(short, long, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Output the content decoded before a corrupted frame or block, then fail
+    #[arg(short, long, default_value_t = false)]
+    recover: bool,
+
+    /// Write the decoded content to FILE instead of the source name without
+    /// its `.zst` extension, `-` for stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<String>,
+
+    /// Write the decoded content to stdout, the default for stdin
+    #[arg(short = 'c', long, default_value_t = false, conflicts_with = "output")]
+    stdout: bool,
+
+    /// Overwrite the output file if it exists
+    #[arg(short, long, default_value_t = false)]
+    force: bool,
+
+    /// Do not copy the modification time and permissions of the source to the
+    /// output file
+    #[arg(long, default_value_t = false)]
+    no_preserve_metadata: bool,
+
+    /// Decode on the main thread only, for reproducible runs and profiling
+    #[arg(long, default_value_t = false, conflicts_with = "threads")]
+    single_threaded: bool,
+
+    /// Decode on at most THREADS threads, 0 for one per core
+    #[arg(short = 'T', long, default_value_t = 0)]
+    threads: usize,
+
+    /// Do not show progress on stderr, which is only done when it is a terminal
+    #[arg(long, default_value_t = false)]
+    no_progress: bool,
+
+    /// Accept windows up to 2^WINDOW_LOG bytes (2^27 without WINDOW_LOG)
+    /// instead of 64 MB, for archives written with `zstd --long`
+    #[arg(
+        long,
+        value_name = "WINDOW_LOG",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "27",
+        value_parser = clap::value_parser!(u32).range(10..=31)
+    )]
+    long: Option<u32>,
+
+    /// Keep at most MB of the window of a frame in memory (64 without MB) and
+    /// the rest in a temporary file, to decode `zstd --long` archives on
+    /// machines without the memory for their window
+    #[arg(
+        long,
+        value_name = "MB",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "64",
+        conflicts_with_all = ["info", "verify_against", "test", "recover"]
+    )]
+    spill_window: Option<usize>,
+
+    /// Report issues that do not prevent decoding on stderr, e.g. a frame
+    /// without content checksum
+    #[arg(short, long, default_value_t = false)]
+    warnings: bool,
+
+    /// Compare the decoded content with a reference file instead of outputing
+    /// it, and report the first differing offset
+    #[arg(long, value_name = "FILE")]
+    verify_against: Option<String>,
+
+    /// Check the integrity of the source instead of outputing its content: the
+    /// frames are decoded to check their sizes and checksums, holding only
+    /// their window in memory, and the failing ones are reported
+    #[arg(
+        short,
+        long,
+        default_value_t = false,
+        conflicts_with_all = ["info", "verify_against", "recover", "output", "stdout"]
+    )]
+    test: bool,
+
+    /// Decode with the system `zstd` as well and report how the outputs and
+    /// exit behaviours differ, to check changes against the reference
+    #[cfg(feature = "dev")]
+    #[arg(long, hide = true, default_value_t = false)]
+    compare_reference: bool,
+
+    /// Print every frame, block and sequence decoded on stderr: FSE states,
+    /// codes, extra bits, lengths and offsets, and repeat offsets. Decodes on
+    /// the main thread only.
+    #[cfg(feature = "trace")]
+    #[arg(long, default_value_t = false)]
+    trace: bool,
+
+    /// Skip the first N Zstandard frames, found by reading the headers only
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = 0,
+        conflicts_with_all = ["info", "verify_against", "test"]
+    )]
+    skip_frames: usize,
+
+    /// Decode at most N Zstandard frames, after the skipped ones
+    #[arg(
+        long,
+        value_name = "N",
+        conflicts_with_all = ["info", "verify_against", "test"]
+    )]
+    max_frames: Option<usize>,
 }
 
-fn main() -> eyre::Result<()> {
-    color_eyre::install()?;
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Truncate a damaged file to its last fully decodable block
+    Repair {
+        /// Damaged source file
+        source: String,
 
-    let args = Args::parse();
-    let bytes = fs::read(args.source)?;
+        /// Destination of the repaired file
+        output: String,
+
+        /// Overwrite the output file if it exists
+        #[arg(short, long, default_value_t = false)]
+        force: bool,
+    },
+
+    /// Split a multi-frame file into files of one frame each, named after the
+    /// source with the index of the frame, e.g. `logs.0.zst`
+    Split {
+        /// Multi-frame source file
+        source: String,
+
+        /// Directory of the split files instead of the one of the source
+        #[arg(short, long, value_name = "DIR")]
+        output_dir: Option<String>,
+
+        /// Overwrite the split files if they exist
+        #[arg(short, long, default_value_t = false)]
+        force: bool,
+    },
+
+    /// Concatenate files into one multi-frame file, checking that each is
+    /// made of complete frames
+    Merge {
+        /// Files to concatenate, in order
+        #[arg(required = true)]
+        sources: Vec<String>,
+
+        /// Destination of the merged file
+        #[arg(short, long, value_name = "FILE")]
+        output: String,
 
-    let decoded = zstd_lib::decode(bytes.as_slice(), args.info)?;
+        /// Overwrite the output file if it exists
+        #[arg(short, long, default_value_t = false)]
+        force: bool,
+    },
 
-    let mut stdout = std::io::stdout().lock();
-    stdout.write_all(decoded.as_slice()).unwrap();
+    /// Print the Huffman and FSE tables in use after a block as Graphviz
+    /// graphs, e.g. `dot 'file.zst' --block 2 | dot -Tsvg -O`
+    Dot {
+        /// Source file
+        source: String,
 
+        /// Index of the Zstandard frame, from 0
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        frame: usize,
+
+        /// Index of the block in the frame, from 0
+        #[arg(long, value_name = "N", default_value_t = 0)]
+        block: usize,
+    },
+
+    /// List the LZ77 operations of each block: the literals copied and the
+    /// matches with their distance, at their offset in the output
+    Sequences {
+        /// Source file
+        source: String,
+    },
+
+    /// Aggregate histograms of the literal lengths, match lengths and offsets
+    /// of the sequences of files, and how often repeat offsets are used
+    Analyze {
+        /// Files to analyze
+        #[arg(required = true)]
+        sources: Vec<String>,
+
+        /// Print CSV instead of text
+        #[arg(long, default_value_t = false)]
+        csv: bool,
+    },
+
+    /// Rewrite a file so that all its frames end with a content checksum,
+    /// computed by decoding the frames without one, or without any checksum
+    Checksum {
+        /// Source file to rewrite
+        source: String,
+
+        /// Destination of the rewritten file
+        #[arg(short, long, value_name = "FILE")]
+        output: String,
+
+        /// Remove the content checksums instead of adding them
+        #[arg(long, default_value_t = false)]
+        strip: bool,
+
+        /// Overwrite the output file if it exists
+        #[arg(short, long, default_value_t = false)]
+        force: bool,
+    },
+}
+
+/// Source read from stdin instead of a file
+const STDIN: &str = "-";
+
+/// Output written to stdout instead of a file
+const STDOUT: &str = "-";
+
+fn read_source(source: &str) -> io::Result<Vec<u8>> {
+    if source == STDIN {
+        let mut bytes = Vec::new();
+        io::stdin().lock().read_to_end(&mut bytes)?;
+        Ok(bytes)
+    } else {
+        fs::read(source)
+    }
+}
+
+fn decompress(args: &Args, source: &str) -> eyre::Result<()> {
+    let mut options = zstd_lib::DecodeOptions::new().threads(args.threads);
+    if args.single_threaded {
+        options = options.single_threaded(true);
+    }
+    if let Some(window_log) = args.long {
+        options = options.window_log_max(window_log);
+    }
+    #[cfg(feature = "trace")]
+    if args.trace {
+        trace::install()?;
+        options = options.single_threaded(true);
+    }
+
+    if args.info {
+        return info(source, args.verbose);
+    }
+    if let Some(reference) = &args.verify_against {
+        return verify(source, reference, options);
+    }
+    if args.test {
+        return test(source, options);
+    }
+    #[cfg(feature = "dev")]
+    if args.compare_reference {
+        let bytes = read_source(source)?;
+        return compare::compare_reference(source, &bytes, options, args.long);
+    }
+
+    let progress = !args.no_progress && io::stderr().is_terminal();
+
+    #[cfg(feature = "memmap")]
+    if !progress
+        && !args.recover
+        && !args.warnings
+        && args.skip_frames == 0
+        && args.max_frames.is_none()
+        && args.spill_window.is_none()
+        && source != STDIN
+    {
+        let decoded = zstd_lib::decode_file_with_options(source, options).map_err(decode_error)?;
+        return write_output(args, source, decoded.as_slice());
+    }
+
+    let bytes = read_source(source)?;
+    let bytes = select_frames(&bytes, args.skip_frames, args.max_frames)?;
+
+    if args.recover {
+        return match zstd_lib::decode_partial_with_options(bytes, options) {
+            Ok(decoded) => write_output(args, source, decoded.as_slice()),
+            Err(err) => {
+                write_output(args, source, err.partial.as_slice())?;
+                Err(err.into())
+            }
+        };
+    }
+
+    if let Some(hot_size) = args.spill_window {
+        let options = options.spill_window(hot_size.saturating_mul(1 << 20));
+        write_output_with(args, source, |output| {
+            zstd_lib::decode_to(bytes, output, options).map_err(decode_error)?;
+            Ok(())
+        })?;
+        return warn(args, source, bytes);
+    }
+
+    let decoded = if progress {
+        decode_with_progress(source, bytes, options)
+    } else {
+        zstd_lib::decode_with_options(bytes, options)
+    };
+    write_output(args, source, decoded.map_err(decode_error)?.as_slice())?;
+    warn(args, source, bytes)
+}
+
+/// Report the issues of `bytes` with `--warnings`
+fn warn(args: &Args, source: &str, bytes: &[u8]) -> eyre::Result<()> {
+    if args.warnings {
+        for warning in zstd_lib::warnings(bytes)? {
+            eprintln!("{source}: warning: {warning}");
+        }
+    }
     Ok(())
 }
+
+/// Minimum time between progress updates
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Decode `bytes`, showing the bytes read and written, the percentage of the
+/// content size when known, and the throughput on stderr
+fn decode_with_progress(
+    source: &str,
+    bytes: &[u8],
+    options: zstd_lib::DecodeOptions,
+) -> Result<Vec<u8>, ZstdLibError> {
+    let content_size = zstd_lib::content_size_hint(bytes)?;
+
+    let start = Instant::now();
+    let mut last_update = start;
+    let mut shown = false;
+    let decoded = zstd_lib::decode_with_progress(bytes, options, |progress| {
+        if last_update.elapsed() < PROGRESS_INTERVAL {
+            return;
+        }
+        last_update = Instant::now();
+        shown = true;
+        let percent = content_size.map_or(String::new(), |size| {
+            format!(" ({}%)", progress.output * 100 / cmp::max(size, 1))
+        });
+        let throughput = progress.output as f64 / start.elapsed().as_secs_f64();
+        eprint!(
+            "\r{source}: {} MB read, {} MB written{percent}, {} MB/s\x1b[K",
+            progress.input >> 20,
+            progress.output >> 20,
+            (throughput as usize) >> 20
+        );
+    });
+    if shown {
+        eprint!("\r\x1b[K");
+    }
+    decoded
+}
+
+/// Tell to pass `--long` when a frame window exceeds the accepted size
+fn decode_error(err: ZstdLibError) -> eyre::Report {
+    if let (ErrorKind::LimitExceeded, Some(window_size)) = (err.kind(), err.window_size()) {
+        let window_log = window_size.next_power_of_two().trailing_zeros();
+        return eyre::eyre!("{err}, decode it with `--long={window_log}`");
+    }
+    err.into()
+}
+
+/// Write `decoded` to the output file, which gets the modification time and
+/// permissions of `source` unless `--no-preserve-metadata` is given, or stdout
+fn write_output(args: &Args, source: &str, decoded: &[u8]) -> eyre::Result<()> {
+    write_output_with(args, source, |output| Ok(output.write_all(decoded)?))
+}
+
+/// `write_output` with the content written by `write`, buffered
+fn write_output_with(
+    args: &Args,
+    source: &str,
+    write: impl FnOnce(&mut dyn Write) -> eyre::Result<()>,
+) -> eyre::Result<()> {
+    let path = match &args.output {
+        _ if args.stdout => None,
+        Some(path) if path == STDOUT => None,
+        Some(path) => Some(path.as_str()),
+        None if source == STDIN => None,
+        None => Some(output_name(source)?),
+    };
+    let Some(path) = path else {
+        let mut stdout = io::BufWriter::new(io::stdout().lock());
+        write(&mut stdout)?;
+        return Ok(stdout.flush()?);
+    };
+
+    // Like the decodes writing out a buffer, a failed one leaves no file
+    let mut file = io::BufWriter::new(create_file(path, args.force)?);
+    let written = write(&mut file).and_then(|()| Ok(file.into_inner()?));
+    let file = written.inspect_err(|_| {
+        let _ = fs::remove_file(path);
+    })?;
+    if !args.no_preserve_metadata && source != STDIN {
+        let metadata = fs::metadata(source)?;
+        file.set_modified(metadata.modified()?)?;
+        file.set_permissions(metadata.permissions())?;
+    }
+    Ok(())
+}
+
+/// Output file named after `source`, `foo` for `foo.zst`
+fn output_name(source: &str) -> eyre::Result<&str> {
+    source
+        .strip_suffix(".zst")
+        .filter(|name| !name.is_empty() && !name.ends_with('/'))
+        .ok_or_else(|| {
+            eyre::eyre!(
+                "{source}: no .zst extension to remove, name the output with `--output FILE`"
+            )
+        })
+}
+
+/// Part of `bytes` holding the Zstandard frames after the first `skip` ones, at
+/// most `max` of them. Skippable frames are not counted.
+fn select_frames(bytes: &[u8], skip: usize, max: Option<usize>) -> eyre::Result<&[u8]> {
+    // pzstd archives announce the size of their frames: keep the skippable
+    // frame before each, 12 bytes, for the selection to be one too
+    if let Some(frames) = zstd_lib::pzstd_frames(bytes) {
+        let offset = |index: usize| {
+            frames
+                .get(index)
+                .map_or(bytes.len(), |frame| frame.start - 12)
+        };
+        let end = max.map_or(bytes.len(), |max| offset(skip.saturating_add(max)));
+        return Ok(&bytes[offset(skip)..end]);
+    }
+
+    let (mut start, mut end) = (bytes.len(), bytes.len());
+    let mut index = 0;
+    for frame in zstd_lib::frames_metadata(bytes) {
+        let frame = frame?;
+        if frame.kind == FrameKind::Skippable {
+            continue;
+        }
+        if max.is_some_and(|max| index == skip.saturating_add(max)) {
+            end = frame.offset;
+            break;
+        }
+        if index == skip {
+            start = frame.offset;
+        }
+        index += 1;
+    }
+    Ok(bytes.get(start..end).unwrap_or_default())
+}
+
+/// Check the frames of `source` without keeping their content, reporting the
+/// failing ones on stderr
+fn test(source: &str, options: zstd_lib::DecodeOptions) -> eyre::Result<()> {
+    let bytes = read_source(source)?;
+    let report = zstd_lib::verify_with_options(&bytes, options).map_err(decode_error)?;
+    let mut failed = 0;
+    for (index, frame) in report.frames.iter().enumerate() {
+        if let Some(err) = &frame.error {
+            eprintln!("{source}: frame {index} at offset {}: {err}", frame.offset);
+            failed += 1;
+        }
+    }
+    if failed > 0 {
+        return Err(eyre::eyre!(
+            "{source}: {failed} of {} frames failed",
+            report.frames.len()
+        ));
+    }
+    eprintln!(
+        "{source}: {} frames OK, {} bytes",
+        report.frames.len(),
+        report.content_size()
+    );
+    Ok(())
+}
+
+/// Decode `source` as a stream and compare it with `reference` as it is read:
+/// neither is held in memory
+fn verify(source: &str, reference: &str, options: zstd_lib::DecodeOptions) -> eyre::Result<()> {
+    let input: Box<dyn Read> = if source == STDIN {
+        Box::new(io::stdin().lock())
+    } else {
+        Box::new(fs::File::open(source)?)
+    };
+    let decoded = BufReader::new(zstd_lib::Decoder::with_options(input, options));
+    let expected = BufReader::new(fs::File::open(reference)?);
+
+    let difference = first_difference(decoded, expected).map_err(|err| {
+        // Decoding errors are wrapped by the reader
+        if err
+            .get_ref()
+            .is_some_and(|inner| inner.is::<ZstdLibError>())
+        {
+            let inner = err
+                .into_inner()
+                .unwrap()
+                .downcast::<ZstdLibError>()
+                .unwrap();
+            return decode_error(*inner);
+        }
+        err.into()
+    })?;
+    match difference {
+        None => {
+            eprintln!("{source}: matches {reference}");
+            Ok(())
+        }
+        Some(offset) => Err(eyre::eyre!(
+            "{source}: differs from {reference} at offset {offset}"
+        )),
+    }
+}
+
+/// Offset of the first byte that differs between `left` and `right`, or where
+/// the shorter one ends. `None` if they are identical.
+fn first_difference(mut left: impl BufRead, mut right: impl BufRead) -> io::Result<Option<u64>> {
+    let mut offset = 0;
+    loop {
+        let (left_chunk, right_chunk) = (left.fill_buf()?, right.fill_buf()?);
+        if left_chunk.is_empty() || right_chunk.is_empty() {
+            return Ok((left_chunk.len() != right_chunk.len()).then_some(offset));
+        }
+
+        let len = cmp::min(left_chunk.len(), right_chunk.len());
+        if let Some(index) = left_chunk[..len]
+            .iter()
+            .zip(&right_chunk[..len])
+            .position(|(a, b)| a != b)
+        {
+            return Ok(Some(offset + index as u64));
+        }
+        left.consume(len);
+        right.consume(len);
+        offset += len as u64;
+    }
+}
+
+/// Print the frames of `source` at the detail level of `verbose`, then their
+/// summary. Block details need the input in memory, otherwise only the frame
+/// and block headers are read from stdin.
+fn info(source: &str, verbose: u8) -> eyre::Result<()> {
+    let bytes = if verbose >= 2 || source != STDIN {
+        read_source(source)?
+    } else {
+        Vec::new()
+    };
+    let frames: Box<dyn Iterator<Item = Result<FrameMetadata, ZstdLibError>>> =
+        if source == STDIN && verbose < 2 {
+            Box::new(zstd_lib::frames_metadata_from(io::stdin().lock()))
+        } else {
+            Box::new(zstd_lib::frames_metadata(&bytes))
+        };
+
+    let (mut count, mut skippable, mut compressed_size) = (0, 0, 0);
+    let mut content_size = Some(0);
+    for (index, frame) in frames.enumerate() {
+        let frame = frame?;
+        count += 1;
+        compressed_size += frame.compressed_size;
+        let Some(header) = &frame.header else {
+            skippable += 1;
+            if verbose >= 1 {
+                println!(
+                    "frame {index} at {}: skippable, {} bytes",
+                    frame.offset, frame.compressed_size
+                );
+            }
+            continue;
+        };
+        content_size = content_size.zip(header.content_size()).map(|(a, b)| a + b);
+
+        if verbose >= 1 {
+            let checksum = frame
+                .checksum
+                .map_or("none".to_string(), |checksum| format!("{checksum:08x}"));
+            println!(
+                "frame {index} at {}: zstandard, {} bytes, window {}, content size {}, {} blocks, checksum {checksum}",
+                frame.offset,
+                frame.compressed_size,
+                header.window_size(),
+                size(header.content_size()),
+                frame.block_count,
+            );
+        }
+        if verbose >= 2 {
+            for (index, block) in frame.blocks(&bytes)?.iter().enumerate() {
+                print_block(index, block, verbose);
+            }
+        }
+    }
+
+    println!(
+        "{source}: {count} frames ({skippable} skippable), {compressed_size} bytes, content size {}",
+        size(content_size)
+    );
+    Ok(())
+}
+
+fn size(size: Option<u64>) -> String {
+    size.map_or("unknown".to_string(), |size| size.to_string())
+}
+
+fn print_block(index: usize, block: &BlockInfo, verbose: u8) {
+    let last = if block.last_block { ", last" } else { "" };
+    let (block_type, size) = match block.block_type {
+        BlockType::Raw => ("raw", format!("{} bytes", block.block_size)),
+        BlockType::Rle => ("rle", format!("{} bytes repeated", block.block_size)),
+        BlockType::Compressed => ("compressed", format!("{} bytes", block.block_size)),
+    };
+    println!(
+        "  block {index} at {}: {block_type}, {size}{last}",
+        block.offset
+    );
+
+    if let (Some(sections), 3..) = (&block.sections, verbose) {
+        let modes = sections.modes.map_or(String::new(), |[ll, of, ml]| {
+            format!(", literals lengths/offsets/match lengths modes {ll:?}/{of:?}/{ml:?}")
+        });
+        println!(
+            "    literals {:?}, {} bytes, {} streams; {} sequences{modes}",
+            sections.literals_type, sections.regenerated_size, sections.streams, sections.sequences
+        );
+    }
+}
+
+fn repair(source: &str, output: &str, force: bool) -> eyre::Result<()> {
+    let bytes = fs::read(source)?;
+    let repair = zstd_lib::repair(bytes.as_slice())?;
+    create_file(output, force)?.write_all(&repair.archive)?;
+
+    match repair.damage {
+        None => eprintln!("{source}: no damage found"),
+        Some(damage) => eprintln!(
+            "{source}: damaged at offset {}: {}\n{output}: kept {} of {} bytes ({} bytes decoded)",
+            damage.offset,
+            damage.error,
+            repair.archive.len(),
+            bytes.len(),
+            repair.content_size
+        ),
+    }
+
+    Ok(())
+}
+
+/// Create `path`, unless it exists and `force` is not given
+fn create_file(path: &str, force: bool) -> eyre::Result<fs::File> {
+    if force {
+        return Ok(fs::File::create(path)?);
+    }
+    fs::File::create_new(path).map_err(|err| match err.kind() {
+        io::ErrorKind::AlreadyExists => {
+            eyre::eyre!("{path}: already exists, overwrite it with `--force`")
+        }
+        _ => err.into(),
+    })
+}
+
+fn split(source: &str, output_dir: Option<&str>, force: bool) -> eyre::Result<()> {
+    let bytes = fs::read(source)?;
+    let parts = zstd_lib::split_frames(&bytes).map_err(|err| eyre::eyre!("{source}: {err}"))?;
+
+    let source = Path::new(source);
+    let dir = output_dir.map_or_else(|| source.parent().unwrap_or(Path::new("")), Path::new);
+    let name = source.file_name().unwrap_or_default().to_string_lossy();
+    let stem = name.strip_suffix(".zst").unwrap_or(&name);
+    let width = parts.len().saturating_sub(1).to_string().len();
+    for (index, part) in parts.iter().enumerate() {
+        let path = dir.join(format!("{stem}.{index:0width$}.zst"));
+        create_file(&path.to_string_lossy(), force)?.write_all(part)?;
+    }
+
+    eprintln!("{}: split into {} files", source.display(), parts.len());
+    Ok(())
+}
+
+fn merge(sources: &[String], output: &str, force: bool) -> eyre::Result<()> {
+    let archives = sources
+        .iter()
+        .map(|source| {
+            let bytes = fs::read(source)?;
+            // Check each file apart to tell which one is not valid
+            zstd_lib::merge_frames([bytes.as_slice()]).map_err(|err| eyre::eyre!("{source}: {err}"))
+        })
+        .collect::<eyre::Result<Vec<_>>>()?;
+
+    let mut file = create_file(output, force)?;
+    for archive in archives {
+        file.write_all(&archive)?;
+    }
+    Ok(())
+}
+
+fn dot(source: &str, frame: usize, block: usize) -> eyre::Result<()> {
+    let bytes = read_source(source)?;
+    let options = zstd_lib::DecodeOptions::new();
+    let tables = zstd_lib::block_tables_dot(&bytes, frame, block, &options)
+        .map_err(|err| eyre::eyre!("{source}: {err}"))?
+        .ok_or_else(|| eyre::eyre!("{source}: no block {block} in frame {frame}"))?;
+
+    let mut stdout = io::stdout().lock();
+    for graph in tables.graphs() {
+        stdout.write_all(graph.as_bytes())?;
+    }
+    Ok(())
+}
+
+fn sequences(source: &str) -> eyre::Result<()> {
+    let bytes = read_source(source)?;
+    let options = zstd_lib::DecodeOptions::new();
+    let mut stdout = io::BufWriter::new(io::stdout().lock());
+    let mut result = Ok(());
+    zstd_lib::decode_sequences(&bytes, &options, |block| {
+        if result.is_ok() {
+            result = print_sequences(&mut stdout, block);
+        }
+    })
+    .map_err(|err| eyre::eyre!("{source}: {err}"))?;
+    result?;
+    stdout.flush()?;
+    Ok(())
+}
+
+/// Print the operations of `block`, each at its offset in the output
+fn print_sequences(out: &mut impl Write, block: &zstd_lib::BlockSequences) -> io::Result<()> {
+    writeln!(
+        out,
+        "frame {} block {} ({:?}, {} bytes)",
+        block.frame, block.block, block.block_type, block.content_size
+    )?;
+    let mut offset = block.output_offset;
+    if block.block_type != zstd_lib::BlockType::Compressed {
+        return writeln!(out, "{offset:>10}  LIT {}", block.content_size);
+    }
+    for sequence in block.sequences {
+        if sequence.literal_length > 0 {
+            writeln!(out, "{offset:>10}  LIT {}", sequence.literal_length)?;
+            offset += sequence.literal_length;
+        }
+        writeln!(
+            out,
+            "{offset:>10}  MATCH len={} dist={}",
+            sequence.match_length, sequence.offset
+        )?;
+        offset += sequence.match_length;
+    }
+    let trailing = block.trailing_literals();
+    if trailing > 0 {
+        writeln!(out, "{offset:>10}  LIT {trailing}")?;
+    }
+    Ok(())
+}
+
+fn analyze(sources: &[String], csv: bool) -> eyre::Result<()> {
+    let options = zstd_lib::DecodeOptions::new();
+    let mut analysis = analyze::Analysis::default();
+    for source in sources {
+        let bytes = read_source(source)?;
+        analysis.add_file();
+        zstd_lib::decode_sequences(&bytes, &options, |block| analysis.add_block(block))
+            .map_err(|err| eyre::eyre!("{source}: {err}"))?;
+    }
+
+    let mut stdout = io::stdout().lock();
+    if csv {
+        analysis.write_csv(&mut stdout)?;
+    } else {
+        analysis.write_text(&mut stdout)?;
+    }
+    Ok(())
+}
+
+fn checksum(source: &str, output: &str, strip: bool, force: bool) -> eyre::Result<()> {
+    let bytes = fs::read(source)?;
+    let archive = if strip {
+        zstd_lib::strip_checksums(&bytes)
+    } else {
+        zstd_lib::add_checksums(&bytes, zstd_lib::DecodeOptions::new())
+    }
+    .map_err(|err| eyre::eyre!("{source}: {err}"))?;
+
+    create_file(output, force)?.write_all(&archive)?;
+    Ok(())
+}
+
+fn main() -> eyre::Result<()> {
+    color_eyre::install()?;
+
+    let args = Args::parse();
+
+    match (&args.command, &args.source) {
+        (
+            Some(Command::Repair {
+                source,
+                output,
+                force,
+            }),
+            _,
+        ) => repair(source, output, *force),
+        (
+            Some(Command::Split {
+                source,
+                output_dir,
+                force,
+            }),
+            _,
+        ) => split(source, output_dir.as_deref(), *force),
+        (
+            Some(Command::Merge {
+                sources,
+                output,
+                force,
+            }),
+            _,
+        ) => merge(sources, output, *force),
+        (
+            Some(Command::Dot {
+                source,
+                frame,
+                block,
+            }),
+            _,
+        ) => dot(source, *frame, *block),
+        (Some(Command::Sequences { source }), _) => sequences(source),
+        (Some(Command::Analyze { sources, csv }), _) => analyze(sources, *csv),
+        (
+            Some(Command::Checksum {
+                source,
+                output,
+                strip,
+                force,
+            }),
+            _,
+        ) => checksum(source, output, *strip, *force),
+        (None, Some(source)) => decompress(&args, source),
+        (None, None) => unreachable!("source is required without subcommand"),
+    }
+}
@@ -0,0 +1,99 @@
+//! `--trace`: print the spans and events of the decoder on stderr, down to
+//! every sequence, indented by frame, block and section
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Write as _},
+    io::{self, Write},
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+use tracing::{
+    field::{Field, Visit},
+    level_filters::LevelFilter,
+    span, Event, Metadata, Subscriber,
+};
+
+/// Fields formatted as ` name=value`, the message first
+#[derive(Default)]
+struct Fields(String);
+
+impl Visit for Fields {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        if field.name() == "message" {
+            self.0.insert_str(0, &format!("{value:?}"));
+        } else {
+            let _ = write!(self.0, " {}={value:?}", field.name());
+        }
+    }
+}
+
+#[derive(Default)]
+struct Printer {
+    next_id: AtomicU64,
+    /// Spans not closed yet, printed when entered
+    spans: Mutex<HashMap<u64, String>>,
+    depth: AtomicUsize,
+}
+
+impl Printer {
+    fn print(&self, line: &str) {
+        let indent = 2 * self.depth.load(Ordering::Relaxed);
+        // Tracing goes on when stderr is closed
+        let _ = writeln!(io::stderr().lock(), "{:indent$}{line}", "");
+    }
+}
+
+impl Subscriber for Printer {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn max_level_hint(&self) -> Option<LevelFilter> {
+        Some(LevelFilter::TRACE)
+    }
+
+    fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+        let mut fields = Fields::default();
+        span.record(&mut fields);
+        let line = format!("{}{}", span.metadata().name(), fields.0);
+
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        self.spans.lock().unwrap().insert(id, line);
+        span::Id::from_u64(id)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+        self.print(&fields.0);
+    }
+
+    fn enter(&self, span: &span::Id) {
+        if let Some(line) = self.spans.lock().unwrap().get(&span.into_u64()) {
+            self.print(line);
+        }
+        self.depth.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn exit(&self, _: &span::Id) {
+        self.depth.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    fn try_close(&self, span: span::Id) -> bool {
+        self.spans.lock().unwrap().remove(&span.into_u64());
+        true
+    }
+}
+
+/// Print the decoding steps on stderr from now on
+pub fn install() -> eyre::Result<()> {
+    tracing::subscriber::set_global_default(Printer::default())?;
+    Ok(())
+}
@@ -0,0 +1,92 @@
+/*
+    Conformance harness: decode every `.zst` vector of a directory tree and
+    print a pass/fail report. A vector is expected:
+        - to decode to `<name>.bin` when that file exists
+        - to fail when it is under a directory named `*errors*`, like
+          `golden-decompression-errors` in the reference implementation tests
+        - to decode without error otherwise
+
+    Run it against external vectors, e.g. a checkout of facebook/zstd:
+        ZSTD_CONFORMANCE_DIR=../zstd/tests cargo test --test conformance -- --nocapture
+*/
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const DEFAULT_DIR: &str = "./tests/golden";
+
+enum Expected {
+    Content(Vec<u8>),
+    Failure,
+    Success,
+}
+
+fn vectors(dir: &Path, vectors: &mut Vec<PathBuf>) {
+    for entry in fs::read_dir(dir).unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            self::vectors(&path, vectors);
+        } else if path.extension().is_some_and(|ext| ext == "zst") {
+            vectors.push(path);
+        }
+    }
+}
+
+fn expected(vector: &Path) -> Expected {
+    let content = vector.with_extension("bin");
+    if content.exists() {
+        return Expected::Content(fs::read(content).unwrap());
+    }
+
+    let in_errors_dir = vector
+        .ancestors()
+        .skip(1)
+        .filter_map(Path::file_name)
+        .any(|name| name.to_string_lossy().contains("errors"));
+    if in_errors_dir {
+        Expected::Failure
+    } else {
+        Expected::Success
+    }
+}
+
+/// `Err` describes how the vector failed
+fn check(vector: &Path) -> Result<&'static str, String> {
+    let decoded = zstd_lib::decode(&fs::read(vector).unwrap(), false);
+
+    match (expected(vector), decoded) {
+        (Expected::Content(content), Ok(decoded)) if content == decoded => Ok("content"),
+        (Expected::Content(_), Ok(_)) => Err("decoded content differs".to_string()),
+        (Expected::Success, Ok(_)) => Ok("success"),
+        (Expected::Failure, Err(_)) => Ok("failure"),
+        (Expected::Failure, Ok(_)) => Err("decoded, a failure was expected".to_string()),
+        (Expected::Content(_) | Expected::Success, Err(err)) => Err(err.to_string()),
+    }
+}
+
+#[test]
+fn test_conformance() {
+    let dir = std::env::var("ZSTD_CONFORMANCE_DIR").unwrap_or_else(|_| DEFAULT_DIR.to_string());
+    let mut paths = vec![];
+    vectors(Path::new(&dir), &mut paths);
+    paths.sort();
+
+    let mut failed = 0;
+    for vector in &paths {
+        match check(vector) {
+            Ok(expected) => println!("PASS {} ({expected})", vector.display()),
+            Err(reason) => {
+                failed += 1;
+                println!("FAIL {}: {reason}", vector.display());
+            }
+        }
+    }
+
+    println!(
+        "conformance: {} passed, {failed} failed out of {} vectors in {dir}",
+        paths.len() - failed,
+        paths.len()
+    );
+    assert!(!paths.is_empty(), "no vectors in {dir}");
+    assert_eq!(failed, 0, "{failed} vectors failed");
+}
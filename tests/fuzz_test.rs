@@ -1,5 +1,3 @@
-use zstd_lib;
-
 /*
     Replay the pathological inputs that did broke the implementation once.
     Run fuzzing:
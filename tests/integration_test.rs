@@ -2,11 +2,10 @@ use std::fs;
 use std::path::Path;
 use std::process::Command;
 
-use zstd_lib::{self, ZstdLibError};
+use zstd_lib::{self, ErrorKind, ZstdLibError};
 
 fn read_file(path: &str) -> Vec<u8> {
-    let bytes = fs::read(path).unwrap();
-    bytes
+    fs::read(path).unwrap()
 }
 
 fn decode_file(path: &str) -> Result<Vec<u8>, ZstdLibError> {
@@ -15,64 +14,275 @@ fn decode_file(path: &str) -> Result<Vec<u8>, ZstdLibError> {
 }
 
 #[cfg(test)]
-mod text {
+mod sniff {
     use super::*;
+    use zstd_lib::FrameKind;
 
     #[test]
-    fn test_mobydick() {
+    fn test_sniff_zstd_file() {
+        let bytes = read_file("./tests/txt/mobydick.zst");
+        assert_eq!(zstd_lib::sniff(&bytes), Some(FrameKind::Zstandard));
+    }
+
+    #[test]
+    fn test_sniff_text_file() {
+        let bytes = read_file("./tests/txt/mobydick.txt");
+        assert_eq!(zstd_lib::sniff(&bytes), None);
+    }
+
+    #[test]
+    fn test_decode_not_zstandard() {
+        let bytes = read_file("./tests/txt/mobydick.txt");
+        assert!(matches!(
+            zstd_lib::decode(&bytes, false),
+            Err(ZstdLibError::NotZstandardData)
+        ));
+        assert!(matches!(
+            zstd_lib::decode(&[0x28, 0xB5], false),
+            Err(ZstdLibError::NotZstandardData)
+        ));
+    }
+}
+
+#[cfg(test)]
+mod content_size {
+    use super::*;
+
+    #[test]
+    fn test_content_size_hint() {
+        let bytes = read_file("./tests/txt/mobydick.zst");
+        let expected = read_file("./tests/txt/mobydick.txt").len();
+        assert_eq!(zstd_lib::content_size_hint(&bytes).unwrap(), Some(expected));
+    }
+}
+
+#[cfg(test)]
+mod partial {
+    use super::*;
+
+    #[test]
+    fn test_decode_partial_complete() {
+        let expected = read_file("./tests/txt/mobydick.txt");
+        let bytes = read_file("./tests/txt/mobydick.zst");
+        assert_eq!(expected, zstd_lib::decode_partial(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_decode_partial_truncated() {
         let expected = read_file("./tests/txt/mobydick.txt");
-        let decoded = decode_file("./tests/txt/mobydick.zst").unwrap();
-        assert_eq!(expected, decoded);
+        let bytes = read_file("./tests/txt/mobydick.zst");
+
+        let err = zstd_lib::decode_partial(&bytes[..bytes.len() / 2]).unwrap_err();
+        assert_eq!(err.source.kind(), ErrorKind::Truncated);
+        let (requested, available) = err.source.requested_available().unwrap();
+        assert!(requested > available);
+        assert!(err.offset < bytes.len() / 2);
+        assert!(!err.partial.is_empty());
+        assert!(expected.starts_with(&err.partial));
+    }
+
+    #[test]
+    fn test_decode_partial_trailing_garbage() {
+        let expected = read_file("./tests/golden/block-128k.bin");
+        let mut bytes = read_file("./tests/golden/block-128k.zst");
+        let len = bytes.len();
+        bytes.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+
+        let err = zstd_lib::decode_partial(&bytes).unwrap_err();
+        assert_eq!(err.source.kind(), ErrorKind::NotZstandard);
+        assert_eq!(err.source.requested_available(), None);
+        assert_eq!(err.offset, len);
+        assert_eq!(err.partial, expected);
+    }
+}
+
+#[cfg(test)]
+mod single_threaded {
+    use super::*;
+    use zstd_lib::{decode_with_options, DecodeOptions};
+
+    #[test]
+    fn test_single_threaded() {
+        let options = DecodeOptions::new().single_threaded(true);
+        for (compressed, expected) in [
+            ("./tests/txt/mobydick.zst", "./tests/txt/mobydick.txt"),
+            (
+                "./tests/golden/block-128k.zst",
+                "./tests/golden/block-128k.bin",
+            ),
+        ] {
+            let decoded = decode_with_options(&read_file(compressed), options.clone()).unwrap();
+            assert_eq!(read_file(expected), decoded);
+        }
+    }
+}
+
+#[cfg(test)]
+mod stream {
+    use super::*;
+    use std::io::Read;
+    use zstd_lib::{DecodeOptions, Decoder, StreamDecoder};
+
+    #[test]
+    fn test_decoder() {
+        for (compressed, expected) in [
+            ("./tests/txt/mobydick.zst", "./tests/txt/mobydick.txt"),
+            (
+                "./tests/golden/block-128k.zst",
+                "./tests/golden/block-128k.bin",
+            ),
+        ] {
+            let mut decoded = Vec::new();
+            Decoder::new(fs::File::open(compressed).unwrap())
+                .read_to_end(&mut decoded)
+                .unwrap();
+            assert_eq!(read_file(expected), decoded);
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let bytes = read_file("./tests/txt/mobydick.zst");
+        let mut decoder = StreamDecoder::with_executor(DecodeOptions::new());
+        let mut decoded = Vec::new();
+        // A new decoder every 16KB of input, as a worker resuming the previous one
+        for end in (16 * 1024..bytes.len()).step_by(16 * 1024) {
+            let offset = decoder.input_offset();
+            decoder.decode(&bytes[offset..end], &mut decoded).unwrap();
+            decoder = StreamDecoder::resume(&decoder.checkpoint(), DecodeOptions::new()).unwrap();
+        }
+        let offset = decoder.input_offset();
+        decoder.decode(&bytes[offset..], &mut decoded).unwrap();
+        decoder.finish().unwrap();
+        assert_eq!(read_file("./tests/txt/mobydick.txt"), decoded);
+    }
+
+    #[test]
+    fn test_decode_from() {
+        let file = fs::File::open("./tests/txt/mobydick.zst").unwrap();
+        let decoded = zstd_lib::decode_from(file).unwrap();
+        assert_eq!(read_file("./tests/txt/mobydick.txt"), decoded);
     }
 }
 
 /*
-    Golden decompression from:
-    https://github.com/facebook/zstd/tree/dev/tests/golden-decompression
+    The Huffman table of treeless literals is the one of a previous block of the same
+    frame: a treeless block opening a frame must not reuse the table of the frame before.
 */
 #[cfg(test)]
-mod golden {
+mod treeless {
     use super::*;
+    use zstd_lib::{
+        errors::LiteralsError, DecodeOptions, DecodingContext, FrameIterator, StreamDecoder,
+    };
+
+    /// Single segment frame of 8 bytes: one compressed block with treeless literals
+    const TREELESS_FRAME: [u8; 14] = [
+        0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x08, 0x2D, 0x00, 0x00, 0x83, 0x40, 0x00, 0x80, 0x00,
+    ];
+
+    fn is_missing_huffman(err: &ZstdLibError) -> bool {
+        matches!(
+            err,
+            ZstdLibError::Literals(LiteralsError::MissingHuffmanDecoder)
+        )
+    }
 
     #[test]
-    fn test_block_128k() {
-        let expected = read_file("./tests/golden/block-128k.bin");
-        let decoded = decode_file("./tests/golden/block-128k.zst").unwrap();
-        assert_eq!(expected, decoded);
+    fn test_treeless_after_frame() {
+        let mut bytes = read_file("./tests/txt/mobydick.zst");
+        bytes.extend_from_slice(&TREELESS_FRAME);
+
+        for input in [&TREELESS_FRAME[..], &bytes] {
+            let err = zstd_lib::decode(input, false).unwrap_err();
+            assert!(is_missing_huffman(&err), "{err}");
+
+            let err = zstd_lib::decode_partial(input).unwrap_err();
+            assert!(is_missing_huffman(&err.source), "{}", err.source);
+            // offset of the block, after the 6 bytes of magic number and frame header
+            assert_eq!(err.offset, input.len() - TREELESS_FRAME.len() + 6);
+
+            let mut decoder = StreamDecoder::with_executor(DecodeOptions::new());
+            let err = decoder.decode(input, &mut Vec::new()).unwrap_err();
+            assert!(is_missing_huffman(&err), "{err}");
+        }
     }
 
     #[test]
-    fn test_empty_block() {
-        let expected = read_file("./tests/golden/empty-block.bin");
-        let decoded = decode_file("./tests/golden/empty-block.zst").unwrap();
-        assert_eq!(expected, decoded);
+    fn test_treeless_with_reused_context() {
+        let mut bytes = read_file("./tests/txt/mobydick.zst");
+        bytes.extend_from_slice(&TREELESS_FRAME);
+
+        let mut context = DecodingContext::default();
+        let mut frames = FrameIterator::new(&bytes);
+        let first = frames.next().unwrap().unwrap();
+        first
+            .decode_with_context(&mut context, &DecodeOptions::new())
+            .unwrap();
+        let second = frames.next().unwrap().unwrap();
+        let err = second
+            .decode_with_context(&mut context, &DecodeOptions::new())
+            .unwrap_err();
+        assert!(is_missing_huffman(&err), "{err}");
     }
+}
+
+/*
+    Golden decompression from:
+    https://github.com/facebook/zstd/tree/dev/tests/golden-decompression
+
+    Fixtures listed in the manifests written by the golden tool:
+        cargo run -p golden -- ./tests/golden
+*/
+#[cfg(test)]
+mod golden {
+    use super::*;
+    use xxhash_rust::xxh64::xxh64;
+
+    const FIXTURES: [&str; 2] = ["./tests/golden", "./tests/txt"];
 
     #[test]
-    fn test_rle_first_block() {
-        let expected = read_file("./tests/golden/rle-first-block.bin");
-        let decoded = decode_file("./tests/golden/rle-first-block.zst").unwrap();
-        assert_eq!(expected, decoded);
+    fn test_manifests() {
+        for dir in FIXTURES {
+            let manifest = fs::read_to_string(Path::new(dir).join("manifest.txt")).unwrap();
+            for line in manifest.lines().filter(|line| !line.starts_with('#')) {
+                let [fixture, _source, size, hash] =
+                    line.split_whitespace().collect::<Vec<_>>()[..]
+                else {
+                    panic!("invalid manifest line: {line}");
+                };
+
+                let path = Path::new(dir).join(fixture);
+                let decoded = decode_file(path.to_str().unwrap()).unwrap();
+                assert_eq!(decoded.len().to_string(), size, "{fixture}");
+                assert_eq!(format!("{:016x}", xxh64(&decoded, 0)), hash, "{fixture}");
+            }
+        }
     }
 }
 
 /*
     Compressed files generated by decode corpus tool:
     https://github.com/facebook/zstd/blob/dev/tests/decodecorpus.c
+
+    Each file is compared with the `.bin` next to it when there is one, with
+    its decompression by the system zstd otherwise. Failures are grouped by
+    error in the summary so that a regression points to its cause.
 */
 #[cfg(test)]
 mod decode_corpus {
     use super::*;
+    use std::collections::BTreeMap;
+    use std::panic;
+    use std::path::PathBuf;
+    use std::thread;
 
     const SCRIPT: &str = "./tests/generate_decoded_corpus.sh";
     const DECODED: &str = "./tests/decoded_corpus";
     const CORPUS: &str = "./tests/corpus";
 
-    #[derive(Debug)]
-    enum TestError<'a> {
-        Zstd(ZstdLibError),
-        Other(&'a str),
-    }
+    /// Files listed for each failure in the summary
+    const LISTED: usize = 4;
 
     fn generate_decoded_corpus() {
         // Execute the Bash script
@@ -88,39 +298,87 @@ mod decode_corpus {
         }
     }
 
+    fn expected(input: &Path) -> Option<Vec<u8>> {
+        let bin = input.with_extension("bin");
+        let decoded = Path::new(DECODED).join(bin.file_name().unwrap());
+        fs::read(bin).or_else(|_| fs::read(decoded)).ok()
+    }
+
+    /// Path of the error variants without their fields, e.g. `Block::MaxBlockSize`
+    fn category(err: &ZstdLibError) -> String {
+        format!("{err:?}")
+            .split(['(', ' ', '{'])
+            .map(|name| name.trim_end_matches(')'))
+            .take_while(|name| !name.is_empty() && name.chars().all(char::is_alphanumeric))
+            .collect::<Vec<_>>()
+            .join("::")
+    }
+
+    /// Category of the failure, `None` when `input` decodes to the expected content
+    fn check(input: &Path) -> Option<String> {
+        let decoded = match panic::catch_unwind(|| decode_file(input.to_str().unwrap())) {
+            Ok(Ok(decoded)) => decoded,
+            Ok(Err(err)) => return Some(category(&err)),
+            Err(_) => return Some("panic".to_string()),
+        };
+
+        match expected(input) {
+            Some(expected) if expected != decoded => Some("content mismatch".to_string()),
+            Some(_) => None,
+            None => Some("no expected content".to_string()),
+        }
+    }
+
     #[test]
     fn test_corpus() {
         // Setup the decoded corpus from zstd for cross check
         generate_decoded_corpus();
 
-        let mut errors = vec![];
-
-        for entry in fs::read_dir(CORPUS).unwrap() {
-            let file_name = entry.unwrap().file_name();
-            let file_name_str = file_name.to_string_lossy();
-
-            // Create paths for the input and output files
-            let base_name = file_name_str.trim_end_matches(".zst");
-            let input_file_path = Path::new(CORPUS).join(&file_name);
-            let expected_output_file_path = Path::new(DECODED).join(format!("{}.bin", base_name));
-
-            match decode_file(input_file_path.to_str().unwrap()) {
-                Ok(decoded) => {
-                    let expected = read_file(expected_output_file_path.to_str().unwrap());
-                    if expected != decoded {
-                        errors.push((input_file_path, TestError::Other("expected != decoded")));
-                    }
-                }
-                Err(err) => errors.push((input_file_path, TestError::Zstd(err))),
-            }
+        let mut inputs = fs::read_dir(CORPUS)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "zst"))
+            .collect::<Vec<PathBuf>>();
+        inputs.sort();
+
+        let threads = thread::available_parallelism().map_or(1, usize::from);
+        let chunk_size = inputs.len().div_ceil(threads).max(1);
+        let failures = thread::scope(|scope| {
+            let handles = inputs
+                .chunks(chunk_size)
+                .map(|chunk| {
+                    scope.spawn(|| {
+                        chunk
+                            .iter()
+                            .filter_map(|input| Some((input, check(input)?)))
+                            .collect::<Vec<_>>()
+                    })
+                })
+                .collect::<Vec<_>>();
+            handles
+                .into_iter()
+                .flat_map(|handle| handle.join().unwrap())
+                .collect::<Vec<_>>()
+        });
+
+        let mut categories = BTreeMap::<String, Vec<&PathBuf>>::new();
+        for (input, category) in &failures {
+            categories.entry(category.clone()).or_default().push(input);
         }
 
-        // Error reporting
-        if errors.len() > 0 {
-            for (p, err) in &errors {
-                println!("{p:?}: {err:?}");
-            }
-            panic!("failed: {} corpus", errors.len());
+        // Summary table
+        println!("{:<40} {:>6}  files", "failure", "count");
+        for (category, inputs) in &categories {
+            let listed = inputs
+                .iter()
+                .take(LISTED)
+                .map(|input| input.file_name().unwrap().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join(", ");
+            println!("{category:<40} {:>6}  {listed}", inputs.len());
         }
+        println!("{:<40} {:>6}  of {}", "total", failures.len(), inputs.len());
+
+        assert!(failures.is_empty(), "failed: {} corpus", failures.len());
     }
 }
@@ -0,0 +1,47 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use zstd_core::parsing::{BackwardBitParser, ForwardBitParser};
+
+/// Bitstream of 64KB, read by widths cycling like FSE state updates do
+const LEN: usize = 64 * 1024;
+const WIDTHS: [usize; 8] = [1, 5, 9, 3, 6, 2, 8, 7];
+
+fn bitstream() -> Vec<u8> {
+    // Last byte non zero: it holds the padding marker of backward streams
+    (0..LEN).map(|i| (i * 31 + 7) as u8 | 1).collect()
+}
+
+fn bench_backward(c: &mut Criterion) {
+    let bitstream = bitstream();
+    let mut group = c.benchmark_group("bit_parsers");
+    group.throughput(Throughput::Bytes(LEN as u64));
+    group.bench_function("backward_take", |b| {
+        b.iter(|| {
+            let mut parser = BackwardBitParser::new(black_box(&bitstream)).unwrap();
+            let mut sum = 0;
+            for width in WIDTHS.iter().cycle() {
+                match parser.take(*width) {
+                    Ok(bits) => sum += bits,
+                    Err(_) => break,
+                }
+            }
+            sum
+        });
+    });
+    group.bench_function("forward_take", |b| {
+        b.iter(|| {
+            let mut parser = ForwardBitParser::new(black_box(&bitstream));
+            let mut sum = 0;
+            for width in WIDTHS.iter().cycle() {
+                match parser.take(*width) {
+                    Ok(bits) => sum += bits,
+                    Err(_) => break,
+                }
+            }
+            sum
+        });
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_backward);
+criterion_main!(benches);
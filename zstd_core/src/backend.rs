@@ -0,0 +1,86 @@
+use super::{Block, DecodingContext, Executor, OutputSink, Result};
+
+/// Implementation decoding the content of blocks, see `Executor::backend`.
+/// The frame layer around it is the same for all: parsing the frames and
+/// blocks, the limits, and checking the content size and checksum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Backend {
+    /// The entropy decoders and sequence execution of this crate
+    #[default]
+    PureRust,
+}
+
+/// Decodes the content of the blocks the frame layer hands over, appending it
+/// to the output of their context
+pub(crate) trait BlockDecoder {
+    fn decode_block(
+        &self,
+        block: Block<'_>,
+        context: &mut DecodingContext<impl OutputSink>,
+        executor: &impl Executor,
+    ) -> Result<()>;
+}
+
+struct PureRust;
+
+impl BlockDecoder for PureRust {
+    fn decode_block(
+        &self,
+        block: Block<'_>,
+        context: &mut DecodingContext<impl OutputSink>,
+        executor: &impl Executor,
+    ) -> Result<()> {
+        block.decode_content(context, executor)
+    }
+}
+
+impl BlockDecoder for Backend {
+    fn decode_block(
+        &self,
+        block: Block<'_>,
+        context: &mut DecodingContext<impl OutputSink>,
+        executor: &impl Executor,
+    ) -> Result<()> {
+        match self {
+            Backend::PureRust => PureRust.decode_block(block, context, executor),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Sequential};
+    use super::*;
+
+    /// `Sequential` with its backend chosen explicitly
+    struct WithBackend(Backend);
+
+    impl Executor for WithBackend {
+        fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+        where
+            A: FnOnce() -> RA + Send,
+            B: FnOnce() -> RB + Send,
+            RA: Send,
+            RB: Send,
+        {
+            Sequential.join(a, b)
+        }
+
+        fn backend(&self) -> Backend {
+            self.0
+        }
+    }
+
+    #[test]
+    fn test_backend() {
+        assert_eq!(Sequential.backend(), Backend::PureRust);
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        for bytes in [&FRAMES[..], bytes] {
+            assert_eq!(
+                decode(bytes, &WithBackend(Backend::PureRust)).unwrap(),
+                decode(bytes, &Sequential).unwrap()
+            );
+        }
+    }
+}
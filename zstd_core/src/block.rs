@@ -0,0 +1,558 @@
+pub(crate) use super::format::{
+    BLOCK_SIZE_MAX, COMPRESSED_BLOCK_FLAG, RAW_BLOCK_FLAG, RESERVED_BLOCK_FLAG, RLE_BLOCK_FLAG,
+};
+use super::{
+    reserve_buffer, BlockDecoder, BlockType, BufferKind, DecodingContext, Error, Executor,
+    ForwardByteParser, LiteralsSection, OutputSink, Result, Scratch, Sequences,
+};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum BlockError {
+    #[error("Reserved block type")]
+    ReservedBlockType,
+
+    #[error("block size ({got} bytes) exceeds maximum allowed ({allowed} bytes)")]
+    MaxBlockSize { got: usize, allowed: usize },
+
+    #[error("literals section decoded to {got} bytes instead of {expected} bytes")]
+    LiteralsSizeMismatch { got: usize, expected: usize },
+
+    #[error("block content of {got} bytes instead of {expected} bytes")]
+    ContentSizeMismatch { got: usize, expected: usize },
+}
+use BlockError::*;
+
+#[derive(Debug)]
+pub enum Block<'a> {
+    Raw(&'a [u8]),
+    Rle {
+        byte: u8,
+        repeat: usize,
+    },
+    Compressed {
+        literals: LiteralsSection<'a>,
+        sequences: Sequences<'a>,
+    },
+}
+
+/// Block header fields: parsed without touching the block content
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockHeader {
+    pub last_block: bool,
+    pub block_type: u8,
+    pub block_size: usize,
+}
+
+impl BlockHeader {
+    pub fn parse(input: &mut ForwardByteParser) -> Result<Self> {
+        let header = input.slice(3)?;
+
+        // Parse header with bit-mask and bit-shifts:
+        //  last_block is LSB bit0
+        //  block_type is bits1-2
+        //  block_size is bits3-23 (need to Rshift by 3)
+        let last_block = (header[0] & 0b0000_0001) != 0;
+        let block_type = (header[0] & 0b0000_0110) >> 1;
+        let block_size =
+            ((header[2] as usize) << 16 | (header[1] as usize) << 8 | (header[0] as usize)) >> 3;
+
+        Ok(BlockHeader {
+            last_block,
+            block_type,
+            block_size,
+        })
+    }
+
+    /// Number of bytes following the header in the input
+    #[must_use]
+    pub fn content_size(&self) -> usize {
+        if self.block_type == RLE_BLOCK_FLAG {
+            1
+        } else {
+            self.block_size
+        }
+    }
+}
+
+impl<'a> Block<'a> {
+    pub fn parse(
+        input: &mut ForwardByteParser<'a>,
+        window_size: usize,
+    ) -> Result<(Block<'a>, bool)> {
+        let header = BlockHeader::parse(input)?;
+        let block = Self::parse_content(header, input, window_size)?;
+        Ok((block, header.last_block))
+    }
+
+    /// Parse the content of the block described by `header`, which `input`
+    /// starts with
+    pub(crate) fn parse_content(
+        header: BlockHeader,
+        input: &mut ForwardByteParser<'a>,
+        window_size: usize,
+    ) -> Result<Block<'a>> {
+        let BlockHeader {
+            block_type,
+            block_size,
+            ..
+        } = header;
+
+        // The size of Block_Content, and the decompressed size for raw and RLE
+        // blocks, is limited by the smallest of: window_size or 128 KB. With the
+        // Single_Segment_Flag, the window is the frame content size.
+        let max_block_size = core::cmp::min(BLOCK_SIZE_MAX, window_size);
+        if block_size > max_block_size && block_type != RESERVED_BLOCK_FLAG {
+            return Err(Error::Block(MaxBlockSize {
+                got: block_size,
+                allowed: max_block_size,
+            }));
+        }
+
+        match block_type {
+            RAW_BLOCK_FLAG => Ok(Block::Raw(input.slice(block_size)?)),
+
+            RLE_BLOCK_FLAG => Ok(Block::Rle {
+                repeat: block_size,
+                byte: input.u8()?,
+            }),
+
+            COMPRESSED_BLOCK_FLAG => {
+                let compressed_data = input.slice(block_size)?;
+                let mut parser = ForwardByteParser::new(compressed_data);
+
+                let literals = LiteralsSection::parse(&mut parser)?;
+                let sequences = Sequences::parse(&mut parser)?;
+
+                Ok(Block::Compressed {
+                    literals,
+                    sequences,
+                })
+            }
+
+            RESERVED_BLOCK_FLAG => Err(Error::Block(ReservedBlockType)),
+
+            _ => panic!("unexpected block_type {block_type}"),
+        }
+    }
+
+    pub(crate) fn block_type(&self) -> BlockType {
+        match self {
+            Block::Raw(_) => BlockType::Raw,
+            Block::Rle { .. } => BlockType::Rle,
+            Block::Compressed { .. } => BlockType::Compressed,
+        }
+    }
+
+    /// Whether decoding the block executes sequences, leaving them in the
+    /// scratch buffers of the context
+    pub(crate) fn has_sequences(&self) -> bool {
+        matches!(self, Block::Compressed { sequences, .. } if !sequences.is_empty())
+    }
+
+    /// Block type, for the traces
+    #[cfg(feature = "tracing")]
+    fn kind(&self) -> &'static str {
+        match self {
+            Block::Raw(_) => "raw",
+            Block::Rle { .. } => "rle",
+            Block::Compressed { .. } => "compressed",
+        }
+    }
+
+    /// Work units of the block, see `Executor::budget`
+    pub(crate) fn work(&self) -> usize {
+        match self {
+            Block::Raw(raw) => raw.len(),
+            Block::Rle { repeat, .. } => *repeat,
+            Block::Compressed {
+                literals,
+                sequences,
+            } => literals.regenerated_size().saturating_add(sequences.len()),
+        }
+    }
+
+    /// Decode the block into `context`, running the literals and sequences
+    /// sections on `executor`
+    pub fn decode(
+        self,
+        context: &mut DecodingContext<impl OutputSink>,
+        executor: &impl Executor,
+    ) -> Result<()> {
+        let _span = span!("decode_block", kind = self.kind(), work = self.work());
+        context.charge(self.work())?;
+        context.reserve_memory(
+            match &self {
+                Block::Raw(raw) => raw.len(),
+                Block::Rle { repeat, .. } => *repeat,
+                Block::Compressed { .. } => context.block_size_max,
+            },
+            executor,
+        )?;
+        context.stats.count_block(&self);
+        executor.backend().decode_block(self, context, executor)?;
+        context.record_memory_usage()?;
+        Ok(())
+    }
+
+    /// Append the content of the block to `context`, see `Backend::PureRust`
+    pub(crate) fn decode_content(
+        self,
+        context: &mut DecodingContext<impl OutputSink>,
+        executor: &impl Executor,
+    ) -> Result<()> {
+        match self {
+            Block::Raw(v) => context.decoded.write(v)?,
+            Block::Rle { byte, repeat } => context.decoded.fill(byte, repeat)?,
+            Block::Compressed {
+                literals,
+                sequences,
+            } => {
+                let regenerated_size = literals.regenerated_size();
+                if sequences.is_empty() {
+                    // The literals are the block content: decode them in place
+                    // when the sink is a `Vec`, without scratch buffers nor
+                    // sequence decoders
+                    context.start_literals_block(regenerated_size)?;
+                    if let Some(decoded) = context.decoded.as_mut_vec() {
+                        let start = decoded.len();
+                        let result = literals.decode(&mut context.huffman, decoded, executor);
+                        if result.is_err() {
+                            decoded.truncate(start);
+                        }
+                        return result;
+                    }
+                    let buffer = &mut context.scratch.literals;
+                    buffer.clear();
+                    reserve_buffer(
+                        buffer,
+                        regenerated_size,
+                        true,
+                        BufferKind::Literals,
+                        executor,
+                    )?;
+                    literals.decode(&mut context.huffman, buffer, executor)?;
+                    context.decoded.write(&context.scratch.literals)?;
+                    return Ok(());
+                }
+
+                // The block is decoded into the scratch buffers of the context,
+                // taken out while the sequences are executed
+                let Scratch {
+                    literals: literals_buffer,
+                    sequences: sequences_buffer,
+                    ..
+                } = &mut context.scratch;
+                literals_buffer.clear();
+                reserve_buffer(
+                    literals_buffer,
+                    regenerated_size,
+                    true,
+                    BufferKind::Literals,
+                    executor,
+                )?;
+                sequences_buffer.clear();
+                reserve_buffer(
+                    sequences_buffer,
+                    sequences.len(),
+                    true,
+                    BufferKind::Sequences,
+                    executor,
+                )?;
+                let mut scratch = core::mem::take(&mut context.scratch);
+                let max_offset_code = context.max_offset_code();
+                let DecodingContext {
+                    huffman,
+                    sequence_tables,
+                    ..
+                } = context;
+                let Scratch {
+                    literals: literals_buffer,
+                    sequences: sequences_buffer,
+                    predefined_tables,
+                } = &mut scratch;
+
+                let (literals, sequences) = executor.join(
+                    || literals.decode(huffman, literals_buffer, executor),
+                    || {
+                        sequences.decode(
+                            sequence_tables,
+                            predefined_tables,
+                            max_offset_code,
+                            sequences_buffer,
+                        )
+                    },
+                )?;
+
+                let result = literals.and(sequences).and_then(|()| {
+                    if scratch.literals.len() != regenerated_size {
+                        return Err(Error::Block(LiteralsSizeMismatch {
+                            got: scratch.literals.len(),
+                            expected: regenerated_size,
+                        }));
+                    }
+                    Ok(context.execute_sequences(&mut scratch.sequences, &scratch.literals)?)
+                });
+                context.scratch = scratch;
+                result?;
+            }
+        };
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{ContextError, DecoderError, ParsingError, SequencesError, Sequential},
+        *,
+    };
+
+    mod parse {
+        use super::*;
+
+        #[test]
+        fn test_parse_raw_block_last() {
+            let mut parser = ForwardByteParser::new(&[
+                0b0010_0001,
+                0x0,
+                0x0, // raw, last, len 4
+                0x10,
+                0x20,
+                0x30,
+                0x40, // content
+                0x50, // +extra byte
+            ]);
+            let (block, last) = Block::parse(&mut parser, 1024).unwrap();
+            assert!(last);
+            assert!(matches!(block, Block::Raw(&[0x10, 0x20, 0x30, 0x40])));
+            assert_eq!(parser.len(), 1);
+        }
+
+        #[test]
+        fn test_parse_rle_block_not_last() {
+            let mut parser = ForwardByteParser::new(&[
+                0x22, 0x20, 0x0,  // rle, not last, repeat  0x404
+                0x42, // content
+                0x50, // +extra byte
+            ]);
+            let (block, last) = Block::parse(&mut parser, 2048).unwrap();
+            assert!(!last);
+            assert!(matches!(
+                block,
+                Block::Rle {
+                    byte: 0x42,
+                    repeat: 1028
+                }
+            ));
+            assert_eq!(parser.len(), 1);
+        }
+
+        #[test]
+        fn test_parse_rle_block_too_large() {
+            let bytes = [
+                0x22, 0x0, 0x18, // rle, not last, repeat  0x30004
+                0x42, // content
+            ];
+            // beyond 128 KB, whatever the window
+            assert!(matches!(
+                Block::parse(&mut ForwardByteParser::new(&bytes), 1 << 20),
+                Err(Error::Block(MaxBlockSize {
+                    got: 196_612,
+                    allowed: BLOCK_SIZE_MAX
+                }))
+            ));
+            // beyond the window
+            assert!(matches!(
+                Block::parse(&mut ForwardByteParser::new(&[0x22, 0x20, 0x0, 0x42]), 1024),
+                Err(Error::Block(MaxBlockSize {
+                    got: 1028,
+                    allowed: 1024
+                }))
+            ));
+        }
+
+        #[test]
+        fn test_parse_reserved() {
+            let mut parser = ForwardByteParser::new(&[
+                0b0000_0110,
+                0x0,
+                0x0, // reserved
+            ]);
+            assert!(matches!(
+                Block::parse(&mut parser, 1024),
+                Err(Error::Block(ReservedBlockType))
+            ));
+        }
+
+        #[test]
+        fn test_parse_no_sequences_trailing_data() {
+            let mut parser = ForwardByteParser::new(&[
+                0x1D, 0x0, 0x0,  // compressed, last, len 3
+                0x0,  // raw literals, len 0
+                0x0,  // no sequences
+                0x42, // trailing byte
+            ]);
+            assert!(matches!(
+                Block::parse(&mut parser, 1024),
+                Err(Error::Sequences(SequencesError::TrailingData))
+            ));
+        }
+
+        #[test]
+        fn test_parse_not_enough_byte() {
+            let mut parser = ForwardByteParser::new(&[0x0, 0x0]);
+            assert!(matches!(
+                Block::parse(&mut parser, 1024),
+                Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                    requested: 3,
+                    available: 2
+                }))
+            ));
+
+            assert_eq!(parser.len(), 2);
+        }
+
+        #[test]
+        fn test_parse_rle_not_enough_byte() {
+            let mut parser = ForwardByteParser::new(&[
+                0b0000_0010,
+                0x0,
+                0x0, // RLE not last
+            ]);
+            assert!(matches!(
+                Block::parse(&mut parser, 1024),
+                Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                    requested: 1,
+                    available: 0
+                }))
+            ));
+            assert_eq!(parser.len(), 0);
+        }
+
+        #[test]
+        fn test_parse_raw_block_not_enough_size() {
+            let mut parser = ForwardByteParser::new(&[
+                // Raw block, not last, len 8, content len 3
+                0b0010_0000,
+                0x0,
+                0x0, // raw, not last, len 4
+                0x10,
+                0x20,
+                0x30, // content
+            ]);
+            assert!(matches!(
+                Block::parse(&mut parser, 1024),
+                Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                    requested: 4,
+                    available: 3
+                }))
+            ));
+            assert_eq!(parser.len(), 3);
+        }
+    }
+
+    mod block_header {
+        use super::*;
+
+        #[test]
+        fn test_parse() {
+            let mut parser = ForwardByteParser::new(&[0x22, 0x0, 0x18, 0x42]);
+            let header = BlockHeader::parse(&mut parser).unwrap();
+            assert_eq!(
+                header,
+                BlockHeader {
+                    last_block: false,
+                    block_type: RLE_BLOCK_FLAG,
+                    block_size: 196_612,
+                }
+            );
+            assert_eq!(header.content_size(), 1);
+            assert_eq!(parser.len(), 1);
+
+            let mut parser = ForwardByteParser::new(&[0b0010_0101, 0x0, 0x0]);
+            let header = BlockHeader::parse(&mut parser).unwrap();
+            assert!(header.last_block);
+            assert_eq!(header.block_type, COMPRESSED_BLOCK_FLAG);
+            assert_eq!(header.content_size(), 4);
+        }
+    }
+
+    mod decode {
+        use super::*;
+
+        #[test]
+        fn test_decode_raw() {
+            let mut ctx = DecodingContext::new(0).unwrap();
+            let block = Block::Raw(&[0x10, 0x20, 0x30, 0x40]);
+            block.decode(&mut ctx, &Sequential).unwrap();
+            assert_eq!(ctx.decoded, vec![0x10, 0x20, 0x30, 0x40]);
+        }
+
+        #[test]
+        fn test_decode_rle() {
+            let mut ctx = DecodingContext::new(0).unwrap();
+            let block = Block::Rle {
+                byte: 0x42,
+                repeat: 196612,
+            };
+            block.decode(&mut ctx, &Sequential).unwrap();
+            assert_eq!(196612, ctx.decoded.len());
+            assert!(ctx.decoded.into_iter().all(|b| b == 0x42));
+        }
+
+        #[test]
+        fn test_decode_literals_only() {
+            let bytes = [
+                0x2D, 0x0, 0x0, // compressed block, last, len 5
+                0x18, b'c', b'd', b'e', // raw literals, len 3
+                0x0,  // no sequences
+            ];
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            ctx.decoded.extend_from_slice(b"ab");
+            let (block, _) = Block::parse(&mut ForwardByteParser::new(&bytes), 1024).unwrap();
+            block.decode(&mut ctx, &Sequential).unwrap();
+            assert_eq!(ctx.decoded, b"abcde");
+
+            // The literals are still limited to the maximum block size
+            let mut ctx = DecodingContext::new(2).unwrap();
+            let (block, _) = Block::parse(&mut ForwardByteParser::new(&bytes), 1024).unwrap();
+            assert!(matches!(
+                block.decode(&mut ctx, &Sequential),
+                Err(Error::Decoder(DecoderError::Context(
+                    ContextError::MaxBlockOutput {
+                        size: 3,
+                        allowed: 2
+                    }
+                )))
+            ));
+            assert!(ctx.decoded.is_empty());
+        }
+
+        #[test]
+        fn test_decode_compressed() {
+            // bitstream obtained via the reference implementation
+
+            let mut ctx = DecodingContext::new(1000).unwrap();
+            let bitstream = [
+                189, 1, 0, 228, 2, 35, 35, 10, 35, 32, 87, 101, 108, 99, 111, 109, 101, 32, 116,
+                111, 32, 84, 101, 108, 101, 99, 111, 109, 32, 80, 97, 114, 105, 115, 32, 122, 115,
+                116, 100, 32, 101, 120, 97, 109, 112, 108, 101, 32, 35, 10, 35, 2, 0, 12, 202, 162,
+                4, 109, 63, 5, 217, 139,
+            ];
+            let mut parser = ForwardByteParser::new(&bitstream);
+            let (block, _) = Block::parse(&mut parser, 1024).unwrap();
+            block.decode(&mut ctx, &Sequential).unwrap();
+            let decoded = String::from_utf8(ctx.decoded).unwrap();
+
+            let expected = r##"
+#########################################
+# Welcome to Telecom Paris zstd example #
+#########################################
+            "##;
+
+            assert_eq!(expected.trim(), decoded);
+        }
+    }
+}
@@ -0,0 +1,114 @@
+use super::{
+    Block, BlockError, BlockHeader, DecodingContext, Error, Executor, ForwardByteParser,
+    OutputSink, Result, DEFAULT_MAX_WINDOW_SIZE,
+};
+
+/*
+    Low-level API for containers that embed Zstandard blocks instead of
+    frames: the caller delimits the blocks, and checks the content size and
+    checksum if its format has them. The context keeps the entropy tables,
+    repeat offsets and window from one block to the next, as in a frame.
+*/
+
+impl<S: OutputSink> DecodingContext<S> {
+    /// Reset the context to decode blocks whose offsets reach up to
+    /// `window_size` bytes back, which also bounds their size as in a frame.
+    /// Return `WindowSizeError` when it exceeds `DEFAULT_MAX_WINDOW_SIZE` or
+    /// the history of the sink.
+    pub fn start_blocks(&mut self, window_size: usize) -> Result<()> {
+        Ok(self.start_frame(window_size, DEFAULT_MAX_WINDOW_SIZE)?)
+    }
+}
+
+/// Decode the block described by `header`, whose `content` follows the 3-byte
+/// header, appending it to `context` and returning its size. `content` is the
+/// byte repeated for an RLE block.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode_block, BlockHeader, DecodingContext, Sequential};
+/// # use zstd_core::format::{RAW_BLOCK_FLAG, RLE_BLOCK_FLAG};
+/// let mut context = DecodingContext::default();
+/// context.start_blocks(1024)?;
+/// let raw = BlockHeader { last_block: false, block_type: RAW_BLOCK_FLAG, block_size: 2 };
+/// assert_eq!(decode_block(&mut context, raw, &[0xCA, 0xFE], &Sequential)?, 2);
+/// let rle = BlockHeader { last_block: true, block_type: RLE_BLOCK_FLAG, block_size: 3 };
+/// assert_eq!(decode_block(&mut context, rle, &[0xBA], &Sequential)?, 3);
+/// assert_eq!(context.sink(), &[0xCA, 0xFE, 0xBA, 0xBA, 0xBA]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decode_block<S: OutputSink>(
+    context: &mut DecodingContext<S>,
+    header: BlockHeader,
+    content: &[u8],
+    executor: &impl Executor,
+) -> Result<usize> {
+    if content.len() != header.content_size() {
+        return Err(Error::Block(BlockError::ContentSizeMismatch {
+            got: content.len(),
+            expected: header.content_size(),
+        }));
+    }
+    let mut input = ForwardByteParser::new(content);
+    let block = Block::parse_content(header, &mut input, context.window_size)?;
+
+    let start = context.decoded.len();
+    block.decode(context, executor)?;
+    Ok(context.decoded.len() - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        decode, format::RAW_BLOCK_FLAG, BlockError, FrameMetadata, Sequential, ZstdLibError,
+    };
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_decode_block() {
+        // The blocks of a frame decode to its content out of the frame
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let metadata = FrameMetadata::scan(bytes, 0).unwrap();
+        let window_size = metadata.header.as_ref().unwrap().window_size();
+        let mut context = DecodingContext::default();
+        context.start_blocks(window_size).unwrap();
+
+        let blocks = metadata.blocks(bytes).unwrap();
+        assert!(blocks.len() > 1);
+        for block in &blocks {
+            let mut input = ForwardByteParser::new(&bytes[block.offset..]);
+            let header = BlockHeader::parse(&mut input).unwrap();
+            let content = input.slice(header.content_size()).unwrap();
+            decode_block(&mut context, header, content, &Sequential).unwrap();
+        }
+        assert_eq!(context.sink(), &decode(bytes, &Sequential).unwrap());
+    }
+
+    #[test]
+    fn test_decode_block_errors() {
+        let mut context = DecodingContext::<Vec<u8>>::default();
+        context.start_blocks(1024).unwrap();
+        let raw = BlockHeader {
+            last_block: false,
+            block_type: RAW_BLOCK_FLAG,
+            block_size: 2,
+        };
+        assert!(matches!(
+            decode_block(&mut context, raw, &[0xCA], &Sequential),
+            Err(ZstdLibError::Block(BlockError::ContentSizeMismatch {
+                got: 1,
+                expected: 2
+            }))
+        ));
+        let large = BlockHeader {
+            block_size: 2048,
+            ..raw
+        };
+        assert!(matches!(
+            decode_block(&mut context, large, &[0; 2048], &Sequential),
+            Err(ZstdLibError::Block(BlockError::MaxBlockSize { .. }))
+        ));
+        assert!(context.sink().is_empty());
+    }
+}
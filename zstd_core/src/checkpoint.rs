@@ -0,0 +1,155 @@
+use super::{ForwardByteParser, ParsingError};
+use alloc::vec::Vec;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum CheckpointError {
+    #[error("Not a decoder checkpoint")]
+    InvalidMagic,
+
+    #[error("Unsupported checkpoint version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Truncated checkpoint: {0}")]
+    Truncated(#[from] ParsingError),
+
+    #[error("Corrupted checkpoint")]
+    Corrupted,
+}
+
+type Result<T, E = CheckpointError> = core::result::Result<T, E>;
+
+/// Start of every checkpoint, followed by the version of its layout
+pub(crate) const CHECKPOINT_MAGIC: [u8; 4] = *b"ZSCP";
+/// 2 since frame headers record whether the content size is known, 3 since
+/// skippable frames record their magic number and the payload received
+pub(crate) const CHECKPOINT_VERSION: u8 = 3;
+
+/// Part of the decoder state written to checkpoints, see `StreamDecoder::checkpoint`.
+/// Integers are little-endian, sizes and offsets take 8 bytes.
+pub(crate) trait Checkpoint: Sized {
+    fn save(&self, checkpoint: &mut Vec<u8>);
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self>;
+}
+
+impl Checkpoint for u8 {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.push(*self);
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        Ok(checkpoint.u8()?)
+    }
+}
+
+impl Checkpoint for u16 {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        Self::try_from(checkpoint.le(2)?).map_err(|_| CheckpointError::Corrupted)
+    }
+}
+
+impl Checkpoint for u64 {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.extend_from_slice(&self.to_le_bytes());
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        Ok(checkpoint.le_u64()?)
+    }
+}
+
+impl Checkpoint for usize {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.extend_from_slice(&(*self as u64).to_le_bytes());
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        Ok(checkpoint.le(8)?)
+    }
+}
+
+impl Checkpoint for bool {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        u8::from(*self).save(checkpoint);
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        match u8::restore(checkpoint)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(CheckpointError::Corrupted),
+        }
+    }
+}
+
+impl<T: Checkpoint> Checkpoint for Option<T> {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.is_some().save(checkpoint);
+        if let Some(value) = self {
+            value.save(checkpoint);
+        }
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        if bool::restore(checkpoint)? {
+            Ok(Some(T::restore(checkpoint)?))
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Length-prefixed bytes
+impl Checkpoint for Vec<u8> {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.len().save(checkpoint);
+        checkpoint.extend_from_slice(self);
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self> {
+        let len = usize::restore(checkpoint)?;
+        Ok(checkpoint.slice(len)?.to_vec())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn round_trip<T: Checkpoint>(value: &T) -> T {
+        let mut checkpoint = Vec::new();
+        value.save(&mut checkpoint);
+        let mut parser = ForwardByteParser::new(&checkpoint);
+        let restored = T::restore(&mut parser).unwrap();
+        assert!(parser.is_empty());
+        restored
+    }
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(round_trip(&0xAB_u8), 0xAB);
+        assert_eq!(round_trip(&0xABCD_u16), 0xABCD);
+        assert_eq!(round_trip(&0x1234_5678_usize), 0x1234_5678);
+        assert!(round_trip(&true));
+        assert_eq!(round_trip(&Some(42_u16)), Some(42));
+        assert_eq!(round_trip(&None::<u16>), None);
+        assert_eq!(round_trip(&vec![0xCA, 0xFE]), [0xCA, 0xFE]);
+    }
+
+    #[test]
+    fn test_invalid() {
+        assert!(matches!(
+            bool::restore(&mut ForwardByteParser::new(&[2])),
+            Err(CheckpointError::Corrupted)
+        ));
+        assert!(matches!(
+            Vec::<u8>::restore(&mut ForwardByteParser::new(&[3, 0, 0, 0, 0, 0, 0, 0, 0xCA])),
+            Err(CheckpointError::Truncated(_))
+        ));
+    }
+}
@@ -0,0 +1,73 @@
+/// Hash of the decoded content of a frame, compared to its content checksum.
+/// Implement it to plug another XXH64 implementation.
+pub trait ContentHasher: Default {
+    fn update(&mut self, bytes: &[u8]);
+
+    /// Lowest 32 bits of the XXH64 digest (seed 0) of the content, `None` when
+    /// the content is not hashed and checksums are not verified
+    fn checksum(&self) -> Option<u32>;
+}
+
+/// XXH64 from `xxhash-rust`
+#[cfg(feature = "xxhash")]
+#[derive(Default)]
+pub struct XxHash64(xxhash_rust::xxh64::Xxh64);
+
+#[cfg(feature = "xxhash")]
+impl XxHash64 {
+    /// Lowest 32 bits of the digest, the content checksum of the content hashed
+    pub(crate) fn digest(&self) -> u32 {
+        (self.0.digest() & 0xFFFF_FFFF) as u32
+    }
+}
+
+#[cfg(feature = "xxhash")]
+impl ContentHasher for XxHash64 {
+    fn update(&mut self, bytes: &[u8]) {
+        self.0.update(bytes);
+    }
+
+    fn checksum(&self) -> Option<u32> {
+        Some(self.digest())
+    }
+}
+
+/// Skip hashing, content checksums are ignored
+#[derive(Debug, Default)]
+pub struct NoChecksum;
+
+impl ContentHasher for NoChecksum {
+    fn update(&mut self, _bytes: &[u8]) {}
+
+    fn checksum(&self) -> Option<u32> {
+        None
+    }
+}
+
+/// `XxHash64`, or `NoChecksum` without the `xxhash` feature
+#[cfg(feature = "xxhash")]
+pub type DefaultHasher = XxHash64;
+#[cfg(not(feature = "xxhash"))]
+pub type DefaultHasher = NoChecksum;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn test_xxhash64() {
+        let mut hasher = XxHash64::default();
+        assert_eq!(hasher.checksum(), Some(0x51D8_E999));
+        hasher.update(&[0xCA, 0xFE]);
+        hasher.update(&[0xBA, 0xBA]);
+        assert_eq!(hasher.checksum(), Some(0x3960_2156));
+    }
+
+    #[test]
+    fn test_no_checksum() {
+        let mut hasher = NoChecksum;
+        hasher.update(&[0xCA, 0xFE]);
+        assert_eq!(hasher.checksum(), None);
+    }
+}
@@ -0,0 +1,157 @@
+use super::{
+    check_magic, Block, DecodingContext, DefaultHasher, Error, Executor, Frame, FrameIterator,
+    Result, ZstandardFrame,
+};
+use alloc::vec::{IntoIter, Vec};
+
+/// Iterator over the content of the frames of an input, a block at a time,
+/// see `decoded_chunks`
+pub struct DecodedChunks<'a, E> {
+    frames: FrameIterator<'a>,
+    /// Frame being decoded, with its blocks left
+    frame: Option<(ZstandardFrame<'a>, IntoIter<Block<'a>>)>,
+    context: DecodingContext,
+    executor: E,
+    /// The input does not start with a frame: the first chunk is the error
+    not_zstandard: bool,
+    done: bool,
+}
+
+/// Decode the frames of `bytes` one block at a time as the chunks are pulled,
+/// each block giving one, empty ones included. The last chunk of a frame is
+/// only given once the frame is verified: on failure, an error is given
+/// instead and the iteration ends.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decoded_chunks, Sequential};
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x10, 0x0, 0x0, 0xCA, 0xFE, // raw block
+///     0x13, 0x0, 0x0, 0xBA, // last block, RLE
+/// ];
+/// let chunks = decoded_chunks(&bytes, Sequential).collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(chunks, [[0xCA, 0xFE], [0xBA, 0xBA]]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decoded_chunks<E: Executor>(bytes: &[u8], executor: E) -> DecodedChunks<'_, E> {
+    DecodedChunks {
+        frames: FrameIterator::new(bytes),
+        frame: None,
+        context: DecodingContext::default(),
+        executor,
+        not_zstandard: check_magic(bytes).is_err(),
+        done: false,
+    }
+}
+
+impl<E: Executor> DecodedChunks<'_, E> {
+    /// Start the next Zstandard frame, skipping the skippable ones. `None` at
+    /// the end of the input.
+    fn next_frame(&mut self) -> Option<Result<()>> {
+        loop {
+            let mut frame = match self.frames.next()? {
+                Ok(Frame::ZstandardFrame(frame)) => frame,
+                Ok(Frame::SkippableFrame(frame)) => {
+                    self.executor.skippable_frame(&frame);
+                    self.context.stats.skippable_frames += 1;
+                    continue;
+                }
+                Err(error) => return Some(Err(error)),
+            };
+            self.context.set_limits(&self.executor);
+            let window_size = frame.header().window_size();
+            let started = self
+                .context
+                .start_frame(window_size, self.executor.max_window_size());
+            let blocks = frame.take_blocks().into_iter();
+            self.frame = Some((frame, blocks));
+            return Some(started.map_err(Error::from));
+        }
+    }
+
+    /// Decode the next block of the current frame, verifying the frame after
+    /// its last one
+    fn next_block(&mut self) -> Option<Result<Vec<u8>>> {
+        let (frame, blocks) = self.frame.as_mut()?;
+        let Some(block) = blocks.next() else {
+            self.frame = None;
+            return None;
+        };
+        let block_start = self.context.decoded.len();
+        let mut result = block.decode(&mut self.context, &self.executor);
+        if blocks.len() == 0 {
+            result = result.and_then(|()| frame.finish::<DefaultHasher>(&mut self.context));
+            self.frame = None;
+        }
+        Some(result.map(|()| self.context.decoded[block_start..].to_vec()))
+    }
+}
+
+impl<E: Executor> Iterator for DecodedChunks<'_, E> {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        if self.not_zstandard {
+            self.done = true;
+            return Some(Err(Error::NotZstandardData));
+        }
+        let chunk = loop {
+            if let Some(chunk) = self.next_block() {
+                break chunk;
+            }
+            match self.next_frame() {
+                Some(Ok(())) => continue,
+                Some(Err(error)) => break Err(error),
+                None => return None,
+            }
+        };
+        self.done = chunk.is_err();
+        Some(chunk)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_decoded_chunks() {
+        let chunks = decoded_chunks(&FRAMES, Sequential)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(chunks, [&[0xCA, 0xFE][..], &[0xBA, 0xBA], &[]]);
+
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let chunks = decoded_chunks(bytes, &Sequential)
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(chunks.len() > 1);
+        assert_eq!(chunks.concat(), decode(bytes, &Sequential).unwrap());
+
+        assert_eq!(decoded_chunks(&[], Sequential).count(), 0);
+    }
+
+    #[test]
+    fn test_decoded_chunks_errors() {
+        // The last block of a frame is not given when the checksum mismatches
+        let mut corrupted = FRAMES;
+        corrupted[25] ^= 1;
+        let mut chunks = decoded_chunks(&corrupted, Sequential);
+        assert_eq!(chunks.next().unwrap().unwrap(), [0xCA, 0xFE]);
+        assert!(chunks.next().unwrap().is_err());
+        assert!(chunks.next().is_none());
+
+        // Frames are parsed as they are reached
+        let mut chunks = decoded_chunks(&FRAMES[..37], Sequential).skip(2);
+        assert!(matches!(chunks.next(), Some(Err(Error::Parsing(_)))));
+        assert!(chunks.next().is_none());
+
+        let mut chunks = decoded_chunks(&[0xCA, 0xFE, 0xBA, 0xBE], Sequential);
+        assert!(matches!(chunks.next(), Some(Err(Error::NotZstandardData))));
+        assert!(chunks.next().is_none());
+    }
+}
@@ -1,4 +1,5 @@
 use super::{BackwardBitParser, BitDecoder, Error, FseDecoder, FseTable};
+use alloc::vec::Vec;
 
 pub struct AlternatingDecoder {
     decoder_1: FseDecoder,
@@ -58,6 +59,11 @@ impl BitDecoder<u16, Error> for AlternatingDecoder {
         Ok(zeroes)
     }
 
+    /// Both decoders share the table, saved once
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.decoder_1.save(checkpoint);
+    }
+
     fn reset(&mut self) {
         self.mut_decoder().reset();
     }
@@ -1,4 +1,5 @@
 use super::BackwardBitParser;
+use alloc::{string::String, vec::Vec};
 
 /// A (possibly) stateful bit-level decoder
 pub trait BitDecoder<T, E>: Send + Sync {
@@ -36,7 +37,29 @@ pub trait BitDecoder<T, E>: Send + Sync {
     /// the last update.
     fn update_bits(&mut self, bitstream: &mut BackwardBitParser) -> Result<bool, E>;
 
+    /// Write the decoding table to a checkpoint, the state is left out: it is
+    /// only saved between blocks
+    fn save(&self, checkpoint: &mut Vec<u8>);
+
     /// Reset the table at its state before `initialize` is called. It allows
     /// reusing the same decoder.
     fn reset(&mut self);
+
+    /// Index of the current state in the decoding table, `None` for stateless
+    /// decoders
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    fn state(&self) -> Option<usize> {
+        None
+    }
+
+    /// Graphviz graph `name` of the decoding table, `None` for decoders
+    /// without one
+    fn to_dot(&self, _name: &str) -> Option<String> {
+        None
+    }
+
+    /// Bytes of memory held by the decoder, its tables included
+    fn memory_usage(&self) -> usize {
+        core::mem::size_of_val(self)
+    }
 }
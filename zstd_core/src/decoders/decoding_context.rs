@@ -0,0 +1,1054 @@
+use super::{
+    BufferKind, Checkpoint, CheckpointError, DecodeStats, Error, Executor, ForwardByteParser,
+    HuffmanDecoder, OutputSink, PredefinedTables, Result, SequenceCommand, SequenceDecoder,
+    SymbolDecoder, BLOCK_SIZE_MAX,
+};
+use alloc::{boxed::Box, vec::Vec};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ContextError {
+    #[error("Window size {window_size} exceeds the maximum of {max}")]
+    WindowSizeError { window_size: usize, max: usize },
+
+    #[error("Sequence {index}: offset is zero")]
+    ZeroOffset { index: usize },
+
+    #[error("Sequence {index}: offset {offset} is beyond window size {window_size}")]
+    OffsetBeyondWindow {
+        index: usize,
+        offset: usize,
+        window_size: usize,
+    },
+
+    #[error("Sequence {index}: offset {offset} is beyond the {produced} bytes of produced output")]
+    OffsetBeyondOutput {
+        index: usize,
+        offset: usize,
+        produced: usize,
+    },
+
+    #[error("Block output ({size} bytes) exceeds maximum allowed ({allowed} bytes)")]
+    MaxBlockOutput { size: usize, allowed: usize },
+
+    #[error("Missing symbol decoder")]
+    MissingSymbolDecoder,
+
+    #[error("Sequences consume {consumed} literals out of {available} available")]
+    NotEnoughLiterals { consumed: usize, available: usize },
+
+    #[error("Sequence {index}: lengths overflow the size of the block")]
+    LengthsOverflow { index: usize },
+
+    #[error("Frame needs more than its budget of {budget} work units")]
+    BudgetExceeded { budget: u64 },
+
+    #[error("Decoding needs {usage} bytes of memory, more than the limit of {limit} bytes")]
+    MemoryLimitExceeded { usage: usize, limit: usize },
+
+    #[error("Allocation of {bytes} bytes for the {buffer:?} buffer failed")]
+    AllocationFailed { buffer: BufferKind, bytes: usize },
+
+    #[error("Output buffer of {capacity} bytes too small for the content")]
+    OutputTooSmall { capacity: usize },
+}
+use ContextError::*;
+
+/// Make room in `vec` for `additional` more elements, exactly or amortized,
+/// failing with `AllocationFailed` instead of aborting when the allocator
+/// cannot serve it
+pub(crate) fn try_reserve<T>(
+    vec: &mut Vec<T>,
+    additional: usize,
+    exact: bool,
+    buffer: BufferKind,
+) -> Result<()> {
+    let bytes = vec
+        .len()
+        .saturating_add(additional)
+        .saturating_mul(core::mem::size_of::<T>());
+    let reserved = if exact {
+        vec.try_reserve_exact(additional)
+    } else {
+        vec.try_reserve(additional)
+    };
+    reserved.map_err(|_| Error::Context(AllocationFailed { buffer, bytes }))
+}
+
+/// `try_reserve`, asking `executor` first when `vec` has to grow, see
+/// `Executor::allocate`
+pub(crate) fn reserve_buffer<T>(
+    vec: &mut Vec<T>,
+    additional: usize,
+    exact: bool,
+    buffer: BufferKind,
+    executor: &impl Executor,
+) -> Result<()> {
+    let needed = vec.len().saturating_add(additional);
+    if needed <= vec.capacity() {
+        return Ok(());
+    }
+    let bytes = needed.saturating_mul(core::mem::size_of::<T>());
+    if !executor.allocate(buffer, bytes) {
+        return Err(Error::Context(AllocationFailed { buffer, bytes }));
+    }
+    try_reserve(vec, additional, exact, buffer)
+}
+
+/// State carried from block to block while decoding a frame: the decoded
+/// content, written to the sink `S`, the entropy tables and the repeat offsets.
+/// A context can be reset and reused for another frame, keeping its buffer.
+pub struct DecodingContext<S = Vec<u8>> {
+    // Entropy tables
+    pub(crate) huffman: Option<HuffmanDecoder>,
+    pub(crate) sequence_tables: SequenceTables,
+
+    // Raw content for back references, the content of the frame starts at
+    // `frame_start` when it is appended to the output of previous frames
+    pub(crate) decoded: S,
+    pub(crate) frame_start: usize,
+
+    // Buffers of the block being decoded
+    pub(crate) scratch: Scratch,
+    pub(crate) window_size: usize,
+
+    // Start of the block being decoded in `decoded`, and its maximum size
+    block_start: usize,
+    pub(crate) block_size_max: usize,
+
+    // Offset history
+    repeat_offsets: RepeatOffset,
+
+    // Work units allowed for the frame and left, see `Executor::budget`
+    budget: Option<u64>,
+    fuel: u64,
+
+    // Memory allowed and the most used, see `Executor::max_memory`
+    memory_limit: Option<usize>,
+    peak_memory_usage: usize,
+
+    // Counts of everything decoded with the context
+    pub(crate) stats: DecodeStats,
+}
+
+/// Buffers reused from block to block, so that decoding stops allocating once
+/// they have grown to the size of the largest block. Their content does not
+/// outlive a block, except for the predefined tables built on first use.
+#[derive(Default)]
+pub(crate) struct Scratch {
+    pub(crate) literals: Vec<u8>,
+    pub(crate) sequences: Vec<SequenceCommand>,
+    pub(crate) predefined_tables: PredefinedTables,
+}
+
+/// Symbol decoders of the last sequences section, reused by the `Repeat` mode.
+/// Kept apart from the Huffman decoder so that literals and sequences can be
+/// decoded in parallel.
+#[derive(Default)]
+pub struct SequenceTables {
+    pub literals_lengths_decoder: Option<Box<SymbolDecoder>>,
+    pub match_lengths_decoder: Option<Box<SymbolDecoder>>,
+    pub offsets_decoder: Option<Box<SymbolDecoder>>,
+}
+
+impl SequenceTables {
+    pub fn get_sequence_decoder(&mut self) -> Result<SequenceDecoder<'_>> {
+        Ok(SequenceDecoder::new(
+            self.literals_lengths_decoder
+                .as_mut()
+                .ok_or(Error::Context(MissingSymbolDecoder))?,
+            self.offsets_decoder
+                .as_mut()
+                .ok_or(Error::Context(MissingSymbolDecoder))?,
+            self.match_lengths_decoder
+                .as_mut()
+                .ok_or(Error::Context(MissingSymbolDecoder))?,
+        ))
+    }
+}
+
+struct RepeatOffset {
+    offset_1: usize,
+    offset_2: usize,
+    offset_3: usize,
+}
+
+impl Default for RepeatOffset {
+    fn default() -> Self {
+        Self {
+            offset_1: 1,
+            offset_2: 4,
+            offset_3: 8,
+        }
+    }
+}
+
+impl RepeatOffset {
+    /// Decode an offset and properly maintain the three repeat offsets
+    fn compute_offset(&mut self, offset: usize, literals_length: usize) -> usize {
+        match offset {
+            1 => {
+                if literals_length == 0 {
+                    core::mem::swap(&mut self.offset_1, &mut self.offset_2);
+                }
+            }
+            2 => {
+                if literals_length == 0 {
+                    let offset_1 = self.offset_1;
+                    let offset_2 = self.offset_2;
+                    self.offset_1 = self.offset_3;
+                    self.offset_2 = offset_1;
+                    self.offset_3 = offset_2;
+                } else {
+                    core::mem::swap(&mut self.offset_1, &mut self.offset_2);
+                }
+            }
+            3 => {
+                if literals_length == 0 {
+                    // a resulting offset of 0 is corrupted data, reported by the caller
+                    self.offset_3 = self.offset_2;
+                    self.offset_2 = self.offset_1;
+                    self.offset_1 = self.offset_1.saturating_sub(1);
+                } else {
+                    let offset_1 = self.offset_1;
+                    let offset_2 = self.offset_2;
+                    self.offset_1 = self.offset_3;
+                    self.offset_2 = offset_1;
+                    self.offset_3 = offset_2;
+                }
+            }
+            _ => {
+                self.offset_3 = self.offset_2;
+                self.offset_2 = self.offset_1;
+                self.offset_1 = offset - 3;
+            }
+        }
+        self.offset_1
+    }
+}
+
+/// Largest window accepted unless the executor allows more, see
+/// `Executor::max_window_size`
+pub const DEFAULT_MAX_WINDOW_SIZE: usize = 1024 * 1024 * 64; // 64Mib
+
+/// Largest offset code supported, as in the reference implementation
+const MAX_OFFSET_CODE: u32 = 31;
+
+/// Largest part of an RLE block expanded at once by `decode_rle`
+const RLE_CHUNK_SIZE: usize = 16 * 1024;
+
+impl Default for DecodingContext {
+    fn default() -> Self {
+        Self::with_sink(Vec::new())
+    }
+}
+
+impl DecodingContext {
+    /// Create a new decoding context instance. Return `WindowSizeError` when `window_size` exceeds 64Mb
+    pub fn new(window_size: usize) -> Result<Self> {
+        let mut context = Self::default();
+        context.start_frame(window_size, DEFAULT_MAX_WINDOW_SIZE)?;
+        Ok(context)
+    }
+
+    /// Reset the context to decode a frame at the end of `output`, out of reach
+    /// of its offsets, instead of in a buffer of its own. The context must not
+    /// be trimmed to the window.
+    pub(crate) fn start_frame_after(
+        &mut self,
+        output: Vec<u8>,
+        window_size: usize,
+        max_window_size: usize,
+    ) -> Result<()> {
+        self.start_frame(window_size, max_window_size)?;
+        self.frame_start = output.len();
+        self.block_start = output.len();
+        self.decoded = output;
+        Ok(())
+    }
+
+    /// Drop the decoded content that can no longer be referenced by an offset,
+    /// once it has been handed over. Only happens past twice the window size to
+    /// amortize the move of the remaining content.
+    pub(crate) fn trim_to_window(&mut self) {
+        let len = self.decoded.len();
+        if len > 2 * self.window_size {
+            self.decoded.drain(..len - self.window_size);
+        }
+    }
+
+    /// Append an RLE block, handing its content to `sink` in chunks of at most
+    /// `RLE_CHUNK_SIZE` bytes, which are dropped from the window as soon as
+    /// back-references cannot reach them: a long repetition is never expanded
+    /// whole
+    pub(crate) fn decode_rle(
+        &mut self,
+        byte: u8,
+        repeat: usize,
+        executor: &impl Executor,
+        mut sink: impl FnMut(&[u8]),
+    ) -> Result<()> {
+        let mut remaining = repeat;
+        while remaining > 0 {
+            let len = core::cmp::min(remaining, RLE_CHUNK_SIZE);
+            self.reserve_memory(len, executor)?;
+            let start = self.decoded.len();
+            self.decoded.resize(start + len, byte);
+            sink(&self.decoded[start..]);
+            self.trim_to_window();
+            remaining -= len;
+        }
+        Ok(())
+    }
+}
+
+impl<S: OutputSink> DecodingContext<S> {
+    /// Context writing the content to `sink` instead of a `Vec`, see
+    /// `ZstandardFrame::decode_to_sink`
+    pub fn with_sink(sink: S) -> Self {
+        Self {
+            huffman: None,
+            sequence_tables: SequenceTables::default(),
+            decoded: sink,
+            frame_start: 0,
+            scratch: Scratch::default(),
+            window_size: 0,
+            block_start: 0,
+            block_size_max: 0,
+            repeat_offsets: RepeatOffset::default(),
+            budget: None,
+            fuel: 0,
+            memory_limit: None,
+            peak_memory_usage: 0,
+            stats: DecodeStats::default(),
+        }
+    }
+
+    /// The sink the content is written to
+    pub fn sink(&self) -> &S {
+        &self.decoded
+    }
+
+    pub fn into_sink(self) -> S {
+        self.decoded
+    }
+
+    /// Clear the decoded content, the entropy tables and the repeat offsets,
+    /// keeping the allocated buffer
+    pub fn reset(&mut self) {
+        self.decoded.clear();
+        self.frame_start = 0;
+        self.block_start = 0;
+        self.huffman = None;
+        self.sequence_tables = SequenceTables::default();
+        self.repeat_offsets = RepeatOffset::default();
+        self.fuel = self.budget.unwrap_or(0);
+    }
+
+    /// Apply the limits of `executor` to the frames decoded from now on
+    pub(crate) fn set_limits(&mut self, executor: &impl Executor) {
+        self.set_budget(executor.budget());
+        self.memory_limit = executor.max_memory();
+    }
+
+    /// Allow `budget` work units to the frames decoded from now on, or no limit
+    /// for `None`, see `Executor::budget`
+    pub(crate) fn set_budget(&mut self, budget: Option<u64>) {
+        self.budget = budget;
+        self.fuel = budget.unwrap_or(0);
+    }
+
+    /// Spend `units` of the budget of the frame before doing the work. Return
+    /// `BudgetExceeded` when there are not as many left.
+    pub(crate) fn charge(&mut self, units: usize) -> Result<()> {
+        let Some(budget) = self.budget else {
+            return Ok(());
+        };
+        self.fuel = u64::try_from(units)
+            .ok()
+            .and_then(|units| self.fuel.checked_sub(units))
+            .ok_or(Error::Context(BudgetExceeded { budget }))?;
+        Ok(())
+    }
+
+    /// Reset the context to decode a frame with a window of `window_size`.
+    /// Return `WindowSizeError` when it exceeds `max_window_size`, or the
+    /// content kept by the sink.
+    pub(crate) fn start_frame(&mut self, window_size: usize, max_window_size: usize) -> Result<()> {
+        let max_window_size = core::cmp::min(max_window_size, self.decoded.max_history());
+        if window_size > max_window_size {
+            return Err(Error::Context(WindowSizeError {
+                window_size,
+                max: max_window_size,
+            }));
+        }
+
+        self.reset();
+        self.window_size = window_size;
+        // The decompressed size of a block is limited by the smallest of:
+        // window_size or 128 KB
+        self.block_size_max = core::cmp::min(window_size, BLOCK_SIZE_MAX);
+        Ok(())
+    }
+
+    /// Bytes of memory held by the context: the decoded content, the scratch
+    /// buffers and the entropy tables
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        let sequence_tables = [
+            &self.sequence_tables.literals_lengths_decoder,
+            &self.sequence_tables.match_lengths_decoder,
+            &self.sequence_tables.offsets_decoder,
+        ];
+        self.decoded.memory_usage()
+            + self.scratch.literals.capacity()
+            + self.scratch.sequences.capacity() * core::mem::size_of::<SequenceCommand>()
+            + self.scratch.predefined_tables.heap_size()
+            + self
+                .huffman
+                .as_ref()
+                .map_or(0, HuffmanDecoder::memory_usage)
+            + sequence_tables
+                .into_iter()
+                .flatten()
+                .map(|decoder| decoder.memory_usage())
+                .sum::<usize>()
+    }
+
+    /// Counts of the frames and blocks decoded since the context was created,
+    /// resets included
+    #[must_use]
+    pub fn stats(&self) -> &DecodeStats {
+        &self.stats
+    }
+
+    /// Highest `memory_usage` seen after a block since the context was created
+    #[must_use]
+    pub fn peak_memory_usage(&self) -> usize {
+        self.peak_memory_usage
+    }
+
+    /// Make room for `size` more bytes of content before producing them,
+    /// within the memory limit. Return `MemoryLimitExceeded` when they do not
+    /// fit, `AllocationFailed` when they cannot be allocated.
+    pub(crate) fn reserve_memory(&mut self, size: usize, executor: &impl Executor) -> Result<()> {
+        let spare = self.decoded.spare_capacity();
+        if size <= spare {
+            return Ok(());
+        }
+        let Some(limit) = self.memory_limit else {
+            return self.decoded.reserve(size, false, executor);
+        };
+        let capacity = self.decoded.memory_usage();
+        let needed = capacity.saturating_add(size - spare);
+
+        // Grow as `Vec` would when it fits, to keep the growth amortized, or
+        // only by what is needed
+        let usage = self.memory_usage() - capacity;
+        let exact = if usage.saturating_add(core::cmp::max(2 * capacity, needed)) <= limit {
+            false
+        } else if usage.saturating_add(needed) <= limit {
+            true
+        } else {
+            return Err(Error::Context(MemoryLimitExceeded {
+                usage: usage.saturating_add(needed),
+                limit,
+            }));
+        };
+        self.decoded.reserve(size, exact, executor)
+    }
+
+    /// Record the memory used after a block for `peak_memory_usage`. Return
+    /// `MemoryLimitExceeded` when it is over the limit, the scratch buffers and
+    /// tables having grown.
+    pub(crate) fn record_memory_usage(&mut self) -> Result<()> {
+        let usage = self.memory_usage();
+        self.peak_memory_usage = core::cmp::max(self.peak_memory_usage, usage);
+        match self.memory_limit {
+            Some(limit) if usage > limit => {
+                Err(Error::Context(MemoryLimitExceeded { usage, limit }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Largest offset code of the sequences: the offsets of larger codes, at
+    /// least `(1 << code) - 3`, are all beyond the window
+    pub(crate) fn max_offset_code(&self) -> u16 {
+        let log = usize::BITS - 1 - self.window_size.saturating_add(3).leading_zeros();
+        core::cmp::min(log, MAX_OFFSET_CODE) as u16
+    }
+
+    /// Decode an offset and properly maintain the three repeat offsets. `index`
+    /// is the index of the sequence in the block and `produced` the size of the
+    /// frame content before its match, both used for validation.
+    fn compute_offset(
+        &mut self,
+        offset: usize,
+        literals_length: usize,
+        index: usize,
+        produced: usize,
+    ) -> Result<usize> {
+        let offset = self.repeat_offsets.compute_offset(offset, literals_length);
+
+        if offset == 0 {
+            return Err(Error::Context(ZeroOffset { index }));
+        }
+        if offset > self.window_size {
+            return Err(Error::Context(OffsetBeyondWindow {
+                index,
+                offset,
+                window_size: self.window_size,
+            }));
+        }
+        if offset > produced {
+            return Err(Error::Context(OffsetBeyondOutput {
+                index,
+                offset,
+                produced,
+            }));
+        }
+
+        Ok(offset)
+    }
+
+    /// Check that `size` more bytes fit in the current block before producing them
+    fn reserve_block_output(&self, size: usize) -> Result<()> {
+        let size = self.decoded.len() - self.block_start + size;
+        if size > self.block_size_max {
+            return Err(Error::Context(MaxBlockOutput {
+                size,
+                allowed: self.block_size_max,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Start a block without sequences, whose `size` bytes of literals are
+    /// decoded in place at the end of the content
+    pub(crate) fn start_literals_block(&mut self, size: usize) -> Result<()> {
+        self.block_start = self.decoded.len();
+        self.reserve_block_output(size)
+    }
+
+    /// Execute the sequences while updating the offsets. The offset values of
+    /// `sequences` are replaced by the offsets they resolve to.
+    ///
+    /// The whole block is validated first (literals, offsets and output size)
+    /// so that the copies do not check anything: nothing is produced from a
+    /// corrupted block.
+    pub(crate) fn execute_sequences(
+        &mut self,
+        sequences: &mut [SequenceCommand],
+        literals: &[u8],
+    ) -> Result<()> {
+        let _span = span!(
+            "execute_sequences",
+            sequences = sequences.len(),
+            literals = literals.len()
+        );
+        // Lengths are up to 128 KB each, their sums can overflow on 32 bits
+        let mut consumed = 0_usize;
+        for (index, sequence) in sequences.iter().enumerate() {
+            consumed = consumed
+                .checked_add(sequence.literal_length)
+                .ok_or(LengthsOverflow { index })?;
+        }
+        if consumed > literals.len() {
+            return Err(Error::Context(NotEnoughLiterals {
+                consumed,
+                available: literals.len(),
+            }));
+        }
+
+        let start = self.decoded.len() - self.frame_start;
+        let mut produced = start;
+        let mut repeats = 0;
+        for (index, sequence) in sequences.iter_mut().enumerate() {
+            produced = produced
+                .checked_add(sequence.literal_length)
+                .ok_or(LengthsOverflow { index })?;
+            // Offset values 1 to 3 are repeat offsets
+            repeats += usize::from(sequence.offset <= 3);
+            let offset =
+                self.compute_offset(sequence.offset, sequence.literal_length, index, produced)?;
+            trace_event!(
+                index,
+                offset_value = sequence.offset,
+                offset,
+                repeat_offsets = ?[
+                    self.repeat_offsets.offset_1,
+                    self.repeat_offsets.offset_2,
+                    self.repeat_offsets.offset_3,
+                ],
+                "offset"
+            );
+            sequence.offset = offset;
+            produced = produced
+                .checked_add(sequence.match_length)
+                .ok_or(LengthsOverflow { index })?;
+        }
+        let matched = produced - start - consumed;
+        // Beyond the maximum block output when saturated
+        let size = (produced - start).saturating_add(literals.len() - consumed);
+        event!(size, "block output");
+        self.block_start = self.decoded.len();
+        self.reserve_block_output(size)?;
+
+        let mut position = 0;
+        for sequence in sequences.iter() {
+            let literals = &literals[position..position + sequence.literal_length];
+            self.decoded.write(literals)?;
+            position += sequence.literal_length;
+            self.decoded
+                .copy_match(sequence.offset, sequence.match_length)?;
+        }
+        self.decoded.write(&literals[position..])?;
+        self.stats.match_bytes += matched;
+        self.stats.repeat_offsets += repeats;
+        Ok(())
+    }
+}
+
+/// The window size, the repeat offsets, the content that can still be
+/// referenced and the entropy tables. Only saved between blocks. Not a
+/// `Checkpoint`: restoring needs the largest window the executor accepts.
+impl DecodingContext {
+    pub(crate) fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.window_size.save(checkpoint);
+        self.repeat_offsets.offset_1.save(checkpoint);
+        self.repeat_offsets.offset_2.save(checkpoint);
+        self.repeat_offsets.offset_3.save(checkpoint);
+
+        let window = &self.decoded[self.decoded.len().saturating_sub(self.window_size)..];
+        window.len().save(checkpoint);
+        checkpoint.extend_from_slice(window);
+
+        self.huffman.save(checkpoint);
+        self.sequence_tables
+            .literals_lengths_decoder
+            .save(checkpoint);
+        self.sequence_tables.match_lengths_decoder.save(checkpoint);
+        self.sequence_tables.offsets_decoder.save(checkpoint);
+    }
+
+    /// Restore a context saved while decoding a frame accepted with windows
+    /// up to `max_window_size`, see `Executor::max_window_size`
+    pub(crate) fn restore(
+        checkpoint: &mut ForwardByteParser,
+        max_window_size: usize,
+    ) -> Result<Self, CheckpointError> {
+        let window_size = usize::restore(checkpoint)?;
+        let mut context = Self::default();
+        context
+            .start_frame(window_size, max_window_size)
+            .map_err(|_| CheckpointError::Corrupted)?;
+        context.repeat_offsets = RepeatOffset {
+            offset_1: usize::restore(checkpoint)?,
+            offset_2: usize::restore(checkpoint)?,
+            offset_3: usize::restore(checkpoint)?,
+        };
+
+        let window = Vec::restore(checkpoint)?;
+        if window.len() > window_size {
+            return Err(CheckpointError::Corrupted);
+        }
+        context.decoded = window;
+
+        context.huffman = Option::restore(checkpoint)?;
+        context.sequence_tables = SequenceTables {
+            literals_lengths_decoder: Option::restore(checkpoint)?,
+            match_lengths_decoder: Option::restore(checkpoint)?,
+            offsets_decoder: Option::restore(checkpoint)?,
+        };
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod repeat_offset {
+        use super::*;
+
+        fn repeat_offsets(offsets: (usize, usize, usize)) -> RepeatOffset {
+            RepeatOffset {
+                offset_1: offsets.0,
+                offset_2: offsets.1,
+                offset_3: offsets.2,
+            }
+        }
+
+        fn state(repeat: &RepeatOffset) -> (usize, usize, usize) {
+            (repeat.offset_1, repeat.offset_2, repeat.offset_3)
+        }
+
+        #[test]
+        fn test_new_offset() {
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(10, 0), 7);
+            assert_eq!(state(&repeat), (7, 1, 4));
+            assert_eq!(repeat.compute_offset(4, 3), 1);
+            assert_eq!(state(&repeat), (1, 7, 1));
+        }
+
+        #[test]
+        fn test_repeat_with_literals() {
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(1, 5), 1);
+            assert_eq!(state(&repeat), (1, 4, 8));
+
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(2, 5), 4);
+            assert_eq!(state(&repeat), (4, 1, 8));
+
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(3, 5), 8);
+            assert_eq!(state(&repeat), (8, 1, 4));
+        }
+
+        #[test]
+        fn test_repeat_without_literals() {
+            // Repeat_Offset_1 code is Repeat_Offset_2
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(1, 0), 4);
+            assert_eq!(state(&repeat), (4, 1, 8));
+
+            // Repeat_Offset_2 code is Repeat_Offset_3
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(2, 0), 8);
+            assert_eq!(state(&repeat), (8, 1, 4));
+
+            // Repeat_Offset_3 code is Repeat_Offset_1 - 1
+            let mut repeat = repeat_offsets((5, 4, 8));
+            assert_eq!(repeat.compute_offset(3, 0), 4);
+            assert_eq!(state(&repeat), (4, 5, 4));
+
+            // Repeat_Offset_1 - 1 may be zero
+            let mut repeat = repeat_offsets((1, 4, 8));
+            assert_eq!(repeat.compute_offset(3, 0), 0);
+        }
+    }
+
+    mod execute_sequences {
+        use super::*;
+        use crate::Sequential;
+
+        fn sequence(literal_length: usize, offset: usize, match_length: usize) -> SequenceCommand {
+            SequenceCommand {
+                literal_length,
+                match_length,
+                offset,
+            }
+        }
+
+        #[test]
+        fn test_execute_sequences() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            // new offset 2, then repeat offset 1 without literals (Repeat_Offset_2 = 1)
+            let mut sequences = vec![sequence(2, 5, 4), sequence(0, 1, 3), sequence(1, 4, 3)];
+            ctx.execute_sequences(&mut sequences, b"abcde").unwrap();
+            assert_eq!(ctx.decoded, b"abababbbbccccde");
+        }
+
+        #[test]
+        fn test_reset() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            ctx.execute_sequences(&mut [sequence(2, 5, 4)], b"ab")
+                .unwrap();
+            let capacity = ctx.decoded.capacity();
+
+            ctx.reset();
+            assert!(ctx.decoded.is_empty());
+            assert_eq!(ctx.decoded.capacity(), capacity);
+            // back to the initial repeat offsets: Repeat_Offset_1 is 1
+            ctx.execute_sequences(&mut [sequence(2, 1, 3)], b"ab")
+                .unwrap();
+            assert_eq!(ctx.decoded, b"abbbb");
+
+            assert!(matches!(
+                ctx.start_frame(DEFAULT_MAX_WINDOW_SIZE + 1, DEFAULT_MAX_WINDOW_SIZE),
+                Err(Error::Context(WindowSizeError {
+                    window_size: 67_108_865,
+                    max: DEFAULT_MAX_WINDOW_SIZE
+                }))
+            ));
+            ctx.start_frame(DEFAULT_MAX_WINDOW_SIZE + 1, 2 * DEFAULT_MAX_WINDOW_SIZE)
+                .unwrap();
+        }
+
+        #[test]
+        fn test_charge() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            ctx.charge(usize::MAX).unwrap();
+
+            ctx.set_budget(Some(10));
+            ctx.charge(4).unwrap();
+            ctx.charge(6).unwrap();
+            assert!(matches!(
+                ctx.charge(1),
+                Err(Error::Context(BudgetExceeded { budget: 10 }))
+            ));
+
+            // The budget is per frame
+            ctx.start_frame(1024, 1024).unwrap();
+            ctx.charge(10).unwrap();
+            assert!(ctx.charge(usize::MAX).is_err());
+        }
+
+        #[test]
+        fn test_memory_usage() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            assert_eq!(ctx.memory_usage(), 0);
+            ctx.execute_sequences(&mut [sequence(2, 5, 4)], b"ab")
+                .unwrap();
+            assert!(ctx.memory_usage() >= ctx.decoded.len());
+            ctx.record_memory_usage().unwrap();
+            let peak = ctx.peak_memory_usage();
+            assert_eq!(peak, ctx.memory_usage());
+
+            // The peak outlives the frame
+            ctx.decoded = Vec::new();
+            ctx.record_memory_usage().unwrap();
+            assert_eq!(ctx.peak_memory_usage(), peak);
+        }
+
+        #[test]
+        fn test_reserve_memory() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            assert!(matches!(
+                ctx.reserve_memory(usize::MAX, &Sequential),
+                Err(Error::Context(AllocationFailed {
+                    buffer: BufferKind::Content,
+                    ..
+                }))
+            ));
+
+            ctx.memory_limit = Some(100);
+            ctx.reserve_memory(60, &Sequential).unwrap();
+            assert!(ctx.decoded.capacity() >= 60);
+            ctx.decoded.resize(60, 0);
+            // Doubling the buffer would not fit, growing it exactly does
+            ctx.reserve_memory(40, &Sequential).unwrap();
+            assert_eq!(ctx.decoded.capacity(), 100);
+            ctx.decoded.resize(100, 0);
+            assert!(matches!(
+                ctx.reserve_memory(1, &Sequential),
+                Err(Error::Context(MemoryLimitExceeded {
+                    usage: 101,
+                    limit: 100
+                }))
+            ));
+            ctx.record_memory_usage().unwrap();
+            ctx.memory_limit = Some(99);
+            assert!(ctx.record_memory_usage().is_err());
+        }
+
+        #[test]
+        fn test_checkpoint() {
+            let mut ctx = DecodingContext::new(4).unwrap();
+            ctx.execute_sequences(&mut [sequence(2, 5, 2)], b"ab")
+                .unwrap();
+            ctx.execute_sequences(&mut [], b"cd").unwrap();
+            let mut checkpoint = Vec::new();
+            ctx.save(&mut checkpoint);
+
+            let mut parser = ForwardByteParser::new(&checkpoint);
+            let mut restored = DecodingContext::restore(&mut parser, 4).unwrap();
+            assert_eq!(restored.decoded, b"abcd");
+            // Repeat_Offset_1 is 2
+            restored
+                .execute_sequences(&mut [sequence(1, 1, 2)], b"e")
+                .unwrap();
+            assert_eq!(restored.decoded, b"abcdede");
+
+            // The window exceeds the limit given
+            let mut parser = ForwardByteParser::new(&checkpoint);
+            assert!(DecodingContext::restore(&mut parser, 2).is_err());
+        }
+
+        #[test]
+        fn test_decode_rle() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            let mut chunks = Vec::new();
+            ctx.decode_rle(b'a', 40_000, &Sequential, |chunk| {
+                assert!(chunk.iter().all(|&byte| byte == b'a'));
+                chunks.push(chunk.len());
+            })
+            .unwrap();
+            assert_eq!(chunks, [16_384, 16_384, 7_232]);
+            // Only the window is kept
+            assert_eq!(ctx.decoded.len(), 1024);
+            assert!(ctx.decoded.capacity() < 40_000);
+        }
+
+        #[test]
+        fn test_zero_offset() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            // Repeat_Offset_1 is 1 after the first sequence
+            let mut sequences = vec![sequence(2, 4, 3), sequence(0, 3, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"ab"),
+                Err(Error::Context(ZeroOffset { index: 1 }))
+            ));
+        }
+
+        #[test]
+        fn test_offset_beyond_output() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            let mut sequences = vec![sequence(2, 4, 3), sequence(1, 10, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"abc"),
+                Err(Error::Context(OffsetBeyondOutput {
+                    index: 1,
+                    offset: 7,
+                    produced: 6,
+                }))
+            ));
+            // the valid first sequence is not executed either
+            assert!(ctx.decoded.is_empty());
+        }
+
+        #[test]
+        fn test_long_overlapping_match() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            ctx.execute_sequences(&mut [sequence(3, 3 + 3, 100)], b"abcd")
+                .unwrap();
+            assert_eq!(ctx.decoded.len(), 104);
+            assert!(ctx.decoded[..103]
+                .chunks(3)
+                .all(|chunk| b"abc".starts_with(chunk)));
+            assert_eq!(ctx.decoded[103], b'd');
+        }
+
+        #[test]
+        fn test_offset_before_frame() {
+            let mut ctx = DecodingContext::default();
+            ctx.start_frame_after(b"abcd".to_vec(), 1024, 1024).unwrap();
+            ctx.execute_sequences(&mut [sequence(1, 1 + 3, 3)], b"e")
+                .unwrap();
+            assert_eq!(ctx.decoded, b"abcdeeee");
+
+            let mut ctx = DecodingContext::default();
+            ctx.start_frame_after(b"abcd".to_vec(), 1024, 1024).unwrap();
+            assert!(matches!(
+                ctx.execute_sequences(&mut [sequence(1, 2 + 3, 3)], b"e"),
+                Err(Error::Context(OffsetBeyondOutput {
+                    index: 0,
+                    offset: 2,
+                    produced: 1
+                }))
+            ));
+        }
+
+        #[test]
+        fn test_offset_beyond_window() {
+            let mut ctx = DecodingContext::new(4).unwrap();
+            let mut sequences = vec![sequence(4, 8, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"abcd"),
+                Err(Error::Context(OffsetBeyondWindow {
+                    index: 0,
+                    offset: 5,
+                    window_size: 4,
+                }))
+            ));
+        }
+
+        #[test]
+        fn test_max_offset_code() {
+            let max_offset_code = |window_size| {
+                let mut ctx = DecodingContext::default();
+                ctx.start_frame(window_size, usize::MAX).unwrap();
+                ctx.max_offset_code()
+            };
+            // Offset code N is at least an offset of 2^N - 3
+            assert_eq!(max_offset_code(0), 1);
+            assert_eq!(max_offset_code(4), 2);
+            assert_eq!(max_offset_code(5), 3);
+            assert_eq!(max_offset_code(1024), 10);
+            assert_eq!(max_offset_code(2045), 11);
+            assert_eq!(max_offset_code(DEFAULT_MAX_WINDOW_SIZE), 26);
+            assert_eq!(max_offset_code(usize::MAX), 31);
+        }
+
+        #[test]
+        fn test_not_enough_literals() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            let mut sequences = vec![sequence(2, 5, 4), sequence(2, 1, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"abc"),
+                Err(Error::Context(NotEnoughLiterals {
+                    consumed: 4,
+                    available: 3
+                }))
+            ));
+            // nothing is produced from a corrupted block
+            assert!(ctx.decoded.is_empty());
+        }
+
+        #[test]
+        fn test_lengths_overflow() {
+            let mut ctx = DecodingContext::new(1024).unwrap();
+            let mut sequences = vec![sequence(2, 5, 4), sequence(usize::MAX, 1, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"abc"),
+                Err(Error::Context(LengthsOverflow { index: 1 }))
+            ));
+            let mut sequences = vec![
+                sequence(1, 1, usize::MAX / 2),
+                sequence(1, 1, usize::MAX / 2),
+            ];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"abc"),
+                Err(Error::Context(LengthsOverflow { index: 1 }))
+            ));
+            assert!(ctx.decoded.is_empty());
+        }
+
+        #[test]
+        fn test_max_block_output() {
+            let mut ctx = DecodingContext::new(8).unwrap();
+            let mut sequences = vec![sequence(2, 5, 4), sequence(0, 1, 3)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"ab"),
+                Err(Error::Context(MaxBlockOutput {
+                    size: 9,
+                    allowed: 8
+                }))
+            ));
+
+            // the limit applies to each block, not to the whole frame
+            let mut ctx = DecodingContext::new(8).unwrap();
+            ctx.execute_sequences(&mut [sequence(2, 5, 4)], b"ab")
+                .unwrap();
+            ctx.execute_sequences(&mut [sequence(0, 1, 6)], b"")
+                .unwrap();
+            assert_eq!(ctx.decoded.len(), 12);
+
+            // trailing literals count as well
+            let mut ctx = DecodingContext::new(8).unwrap();
+            assert!(matches!(
+                ctx.execute_sequences(&mut [sequence(2, 5, 4)], b"abcde"),
+                Err(Error::Context(MaxBlockOutput {
+                    size: 9,
+                    allowed: 8
+                }))
+            ));
+        }
+
+        #[test]
+        fn test_max_block_output_128kb() {
+            let mut ctx = DecodingContext::new(DEFAULT_MAX_WINDOW_SIZE).unwrap();
+            let mut sequences = vec![sequence(1, 4, BLOCK_SIZE_MAX)];
+            assert!(matches!(
+                ctx.execute_sequences(&mut sequences, b"a"),
+                Err(Error::Context(MaxBlockOutput {
+                    size: 131_073,
+                    allowed: BLOCK_SIZE_MAX
+                }))
+            ));
+        }
+    }
+}
@@ -1,8 +1,13 @@
-use std::collections::HashSet;
-
-use super::{BackwardBitParser, BitDecoder, Error, ForwardBitParser, Result};
+use super::{
+    try_reserve, BackwardBitParser, BitDecoder, BufferKind, Checkpoint, CheckpointError, Error,
+    ForwardBitParser, ForwardByteParser, Result, SAVED_FSE,
+};
+use crate::format::FSE_ACCURACY_LOG_MAX;
+use alloc::{collections::BTreeSet, format, string::String, vec::Vec};
+use core::fmt::Write;
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum FseError {
     #[error("Missing FSE state")]
     MissingState,
@@ -31,9 +36,13 @@ pub struct FseState {
 }
 
 const ACC_LOG_OFFSET: u8 = 5;
-const ACC_LOG_MAX: u8 = 9;
 
 impl FseTable {
+    /// Bytes of memory allocated for the states
+    pub(crate) fn heap_size(&self) -> usize {
+        self.states.capacity() * core::mem::size_of::<FseState>()
+    }
+
     pub fn accuracy_log(&self) -> u32 {
         // by design: 1 << AL == states.len()
         assert!(
@@ -43,6 +52,33 @@ impl FseTable {
         self.states.len().trailing_zeros()
     }
 
+    /// Graphviz graph `name` of the state transitions: each state decodes its
+    /// symbol, then reads Number_of_Bits to add to its Baseline, the edge
+    /// going to the first of the states it can lead to and labelled with
+    /// their range
+    #[must_use]
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph {name} {{\n    node [shape=record];\n");
+        for (index, state) in self.states.iter().enumerate() {
+            let (base_line, num_bits) = (state.base_line, state.num_bits);
+            writeln!(
+                dot,
+                "    s{index} [label=\"state {index}|symbol {}|+{num_bits} bits\"];",
+                state.symbol
+            )
+            .ok();
+            let last = base_line + (1 << num_bits) - 1;
+            let range = if last == base_line {
+                format!("{base_line}")
+            } else {
+                format!("{base_line}..={last}")
+            };
+            writeln!(dot, "    s{index} -> s{base_line} [label=\"{range}\"];").ok();
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
     fn get(&self, index: usize) -> Result<&FseState> {
         self.states.get(index).ok_or(Error::Fse(MissingState))
     }
@@ -54,8 +90,10 @@ impl FseTable {
 
     pub fn from_distribution(accuracy_log: u8, distribution: &[Probability]) -> Result<Self> {
         let table_length = 1 << accuracy_log;
-        let mut states = vec![FseState::default(); table_length];
-        let mut set_index = HashSet::<usize>::new();
+        let mut states = Vec::new();
+        try_reserve(&mut states, table_length, true, BufferKind::Tables)?;
+        states.resize(table_length, FseState::default());
+        let mut set_index = BTreeSet::<usize>::new();
 
         let distribution: Vec<(Symbol, Probability)> = distribution
             .iter()
@@ -84,7 +122,7 @@ impl FseTable {
         }
 
         // closure iterator that generates next state index
-        let mut state_index = std::iter::successors(Some(0_usize), |s| {
+        let mut state_index = core::iter::successors(Some(0_usize), |s| {
             let new_state =
                 (s + (table_length >> 1) + (table_length >> 3) + 3) & (table_length - 1);
             if new_state == 0 {
@@ -120,24 +158,17 @@ impl FseTable {
 
         let positives = positives?;
 
+        // As in the reference decoder, the k-th state of a symbol of probability
+        // p, in increasing order, counts as p + k: it reads
+        // num_bits = accuracy_log - log2(p + k) bits, added to a baseline of
+        // (p + k) * 2^num_bits - table_length
         for (symbol, probability, symbol_states) in positives {
-            // probability is > 0, casting does not loose sign
-            let p = probability.next_power_of_two() as usize;
-            let b = usize::try_from((table_length / p).trailing_zeros()).unwrap(); // log2(R/p)
-            let e = p - probability as usize;
-
-            let mut base_line = 0;
-            for (i, &index) in symbol_states.iter().cycle().skip(e).enumerate() {
-                if i == symbol_states.len() {
-                    break;
-                }
-
-                let i = (i + e) % symbol_states.len();
+            for (next, &index) in (usize::from(probability)..).zip(&symbol_states) {
+                let num_bits = usize::from(accuracy_log) - usize::try_from(next.ilog2()).unwrap();
                 let state = &mut states[index];
                 state.symbol = symbol;
-                state.num_bits = if i < e { b + 1 } else { b };
-                state.base_line = base_line;
-                base_line += 1 << state.num_bits;
+                state.num_bits = num_bits;
+                state.base_line = (next << num_bits) - table_length;
             }
         }
 
@@ -145,20 +176,90 @@ impl FseTable {
     }
 }
 
-fn parse_fse_table(parser: &mut ForwardBitParser) -> Result<(u8, Vec<Probability>)> {
+/// Number of states, then the symbol, baseline and number of bits of each state
+impl Checkpoint for FseTable {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.states.len().save(checkpoint);
+        for state in &self.states {
+            state.symbol.save(checkpoint);
+            state.base_line.save(checkpoint);
+            state.num_bits.save(checkpoint);
+        }
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self, CheckpointError> {
+        let len = usize::restore(checkpoint)?;
+        if !len.is_power_of_two() || len > 1 << FSE_ACCURACY_LOG_MAX {
+            return Err(CheckpointError::Corrupted);
+        }
+
+        let accuracy_log = len.trailing_zeros() as usize;
+        let states = (0..len)
+            .map(|_| {
+                let state = FseState {
+                    symbol: Symbol::restore(checkpoint)?,
+                    base_line: usize::restore(checkpoint)?,
+                    num_bits: usize::restore(checkpoint)?,
+                };
+                // The states it leads to must be in the table
+                let end = state.base_line.checked_add(1 << state.num_bits);
+                if state.num_bits > accuracy_log || end.is_none_or(|end| end > len) {
+                    return Err(CheckpointError::Corrupted);
+                }
+                Ok(state)
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(Self { states })
+    }
+}
+
+/// Largest number of symbols of a distribution, one per byte value
+const MAX_SYMBOLS: usize = 256;
+
+/// Probabilities of the symbols of a FSE table, kept on the stack while the
+/// table is built
+struct Distribution {
+    probabilities: [Probability; MAX_SYMBOLS],
+    len: usize,
+}
+
+impl Distribution {
+    fn new() -> Self {
+        Self {
+            probabilities: [0; MAX_SYMBOLS],
+            len: 0,
+        }
+    }
+
+    fn push(&mut self, probability: Probability) -> Result<()> {
+        let slot = self
+            .probabilities
+            .get_mut(self.len)
+            .ok_or(Error::Fse(DistributionCorrupted))?;
+        *slot = probability;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn as_slice(&self) -> &[Probability] {
+        &self.probabilities[..self.len]
+    }
+}
+
+fn parse_fse_table(parser: &mut ForwardBitParser) -> Result<(u8, Distribution)> {
     // will not panic as 4bits value < u8::MAX
     let accuracy_log = u8::try_from(parser.take(4)?).unwrap() + ACC_LOG_OFFSET;
 
-    if accuracy_log > ACC_LOG_MAX {
+    if accuracy_log > FSE_ACCURACY_LOG_MAX {
         return Err(Error::Fse(ALTooLarge {
             log: accuracy_log,
-            max: ACC_LOG_MAX,
+            max: FSE_ACCURACY_LOG_MAX,
         }));
     }
 
     let probability_sum: u32 = 1 << accuracy_log;
     let mut probability_counter: u32 = 0;
-    let mut probabilities: Vec<Probability> = Vec::new();
+    let mut probabilities = Distribution::new();
 
     while probability_counter < probability_sum {
         let max_remaining_value: u32 = probability_sum + 1 - probability_counter;
@@ -192,12 +293,14 @@ fn parse_fse_table(parser: &mut ForwardBitParser) -> Result<(u8, Vec<Probability
             <i16>::try_from(decoded_value).map_err(|_| Error::Fse(DistributionCorrupted))? - 1;
 
         probability_counter += u32::from(probability.unsigned_abs());
-        probabilities.push(probability);
+        probabilities.push(probability)?;
 
         if probability == 0 {
             loop {
                 let num_zeroes = usize::try_from(parser.take(2)?).unwrap();
-                probabilities.extend_from_slice(&vec![0; num_zeroes]);
+                for _ in 0..num_zeroes {
+                    probabilities.push(0)?;
+                }
                 if num_zeroes != 0b11 {
                     break;
                 }
@@ -217,6 +320,7 @@ fn parse_fse_table(parser: &mut ForwardBitParser) -> Result<(u8, Vec<Probability
 pub struct FseDecoder {
     initialized: bool,
     table: FseTable,
+    state: usize,
     base_line: usize,
     num_bits: usize,
     symbol: Option<Symbol>,
@@ -227,6 +331,7 @@ impl FseDecoder {
         Self {
             table,
             initialized: false,
+            state: 0,
             base_line: 0,
             num_bits: 0,
             symbol: None,
@@ -243,7 +348,8 @@ impl BitDecoder<Symbol, Error> for FseDecoder {
         self.initialized = true;
 
         let index = bitstream.take(self.table.accuracy_log() as usize)?;
-        let state = self.table.get(usize::try_from(index).unwrap())?;
+        self.state = usize::try_from(index).unwrap();
+        let state = self.table.get(self.state)?;
 
         self.symbol = Some(state.symbol);
         self.num_bits = state.num_bits;
@@ -279,7 +385,8 @@ impl BitDecoder<Symbol, Error> for FseDecoder {
             (index + self.base_line as u64, true)
         };
 
-        let state = self.table.get(usize::try_from(index).unwrap())?;
+        self.state = usize::try_from(index).unwrap();
+        let state = self.table.get(self.state)?;
 
         self.symbol = Some(state.symbol);
         self.num_bits = state.num_bits;
@@ -288,17 +395,35 @@ impl BitDecoder<Symbol, Error> for FseDecoder {
         Ok(zeroes)
     }
 
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.push(SAVED_FSE);
+        self.table.save(checkpoint);
+    }
+
     fn reset(&mut self) {
         self.initialized = false;
+        self.state = 0;
         self.symbol = None;
         self.num_bits = 0;
         self.base_line = 0;
     }
+
+    fn state(&self) -> Option<usize> {
+        self.initialized.then_some(self.state)
+    }
+
+    fn memory_usage(&self) -> usize {
+        core::mem::size_of::<Self>() + self.table.heap_size()
+    }
+
+    fn to_dot(&self, name: &str) -> Option<String> {
+        Some(self.table.to_dot(name))
+    }
 }
 
 // #[cfg(test)]
-impl std::fmt::Display for FseTable {
-    fn fmt(&self, fmt: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl core::fmt::Display for FseTable {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         writeln!(fmt, "State,Sym,BL,NB").ok();
         for (i, state) in self.states.iter().enumerate() {
             writeln!(
@@ -319,6 +444,31 @@ mod tests {
     mod fse_decoder {
         use super::*;
 
+        #[test]
+        fn test_checkpoint() {
+            let mut parser = ForwardBitParser::new(&[0x30, 0x6f, 0x9b, 0x03]);
+            let fse_table = FseTable::parse(&mut parser).unwrap();
+            let mut checkpoint = Vec::new();
+            fse_table.save(&mut checkpoint);
+            let restored = FseTable::restore(&mut ForwardByteParser::new(&checkpoint)).unwrap();
+            assert_eq!(restored.to_string(), fse_table.to_string());
+
+            // Baseline of the first state beyond the table
+            let mut base_line = checkpoint.clone();
+            base_line[10..18].fill(0xFF);
+            assert!(matches!(
+                FseTable::restore(&mut ForwardByteParser::new(&base_line)),
+                Err(CheckpointError::Corrupted)
+            ));
+
+            // Not a power of two
+            checkpoint[0] = 3;
+            assert!(matches!(
+                FseTable::restore(&mut ForwardByteParser::new(&checkpoint)),
+                Err(CheckpointError::Corrupted)
+            ));
+        }
+
         #[test]
         fn test_decoder() {
             let mut bitstream = BackwardBitParser::new(&[0b0011_1100, 0b0001_0111]).unwrap();
@@ -332,12 +482,34 @@ mod tests {
     mod fse_table {
         use super::*;
 
+        #[test]
+        fn test_to_dot() {
+            let mut parser = ForwardBitParser::new(&[0x30, 0x6f, 0x9b, 0x03]);
+            let dot = FseTable::parse(&mut parser).unwrap().to_dot("offsets");
+            let lines: Vec<&str> = dot.lines().collect();
+            assert_eq!(lines.len(), 2 + 2 * 32 + 1);
+            assert_eq!(
+                lines[..2],
+                ["digraph offsets {", "    node [shape=record];"]
+            );
+            assert_eq!(
+                lines[2..4],
+                [
+                    r#"    s0 [label="state 0|symbol 0|+1 bits"];"#,
+                    r#"    s0 -> s4 [label="4..=5"];"#
+                ]
+            );
+            // Number_of_Bits of 0
+            assert_eq!(lines[2 + 2 * 24 + 1], r#"    s24 -> s0 [label="0"];"#);
+            assert_eq!(lines[lines.len() - 1], "}");
+        }
+
         #[test]
         fn test_parse_distribution() {
             let mut parser = ForwardBitParser::new(&[0x30, 0x6f, 0x9b, 0x03]);
             let (accuracy_log, table) = parse_fse_table(&mut parser).unwrap();
             assert_eq!(5, accuracy_log);
-            assert_eq!(&[18, 6, 2, 2, 2, 1, 1][..], &table);
+            assert_eq!(&[18, 6, 2, 2, 2, 1, 1][..], table.as_slice());
             assert_eq!(parser.available_bits(), 6);
             assert_eq!(parser.len(), 0);
         }
@@ -653,6 +825,96 @@ State,Sym,BL,NB
 0x1d,s26,0x00,5
 0x1e,s25,0x00,5
 0x1f,s24,0x00,5
+"#;
+            assert_eq!(expected.trim(), format!("{}", state).trim());
+        }
+
+        #[test]
+        fn test_from_distribution_less_than_one() {
+            // Tables dumped from FSE_buildDTable of the reference implementation
+
+            // 30 "less than 1" probabilities around a single positive one
+            let mut distribution = vec![-1; 32];
+            distribution[10] = 2;
+            distribution[21] = 0;
+            let state = FseTable::from_distribution(5, &distribution).unwrap();
+            let expected = r#"
+State,Sym,BL,NB
+0x00,s10,0x00,4
+0x01,s10,0x10,4
+0x02,s31,0x00,5
+0x03,s30,0x00,5
+0x04,s29,0x00,5
+0x05,s28,0x00,5
+0x06,s27,0x00,5
+0x07,s26,0x00,5
+0x08,s25,0x00,5
+0x09,s24,0x00,5
+0x0a,s23,0x00,5
+0x0b,s22,0x00,5
+0x0c,s20,0x00,5
+0x0d,s19,0x00,5
+0x0e,s18,0x00,5
+0x0f,s17,0x00,5
+0x10,s16,0x00,5
+0x11,s15,0x00,5
+0x12,s14,0x00,5
+0x13,s13,0x00,5
+0x14,s12,0x00,5
+0x15,s11,0x00,5
+0x16,s9,0x00,5
+0x17,s8,0x00,5
+0x18,s7,0x00,5
+0x19,s6,0x00,5
+0x1a,s5,0x00,5
+0x1b,s4,0x00,5
+0x1c,s3,0x00,5
+0x1d,s2,0x00,5
+0x1e,s1,0x00,5
+0x1f,s0,0x00,5
+"#;
+            assert_eq!(expected.trim(), format!("{}", state).trim());
+
+            // Positive probabilities that are not powers of 2
+            let mut distribution = vec![-1; 20];
+            distribution[0] = 3;
+            distribution[9] = 5;
+            distribution[18] = 7;
+            let state = FseTable::from_distribution(5, &distribution).unwrap();
+            let expected = r#"
+State,Sym,BL,NB
+0x00,s0,0x10,4
+0x01,s9,0x08,3
+0x02,s9,0x10,3
+0x03,s18,0x18,3
+0x04,s18,0x00,2
+0x05,s0,0x00,3
+0x06,s9,0x18,3
+0x07,s18,0x04,2
+0x08,s18,0x08,2
+0x09,s18,0x0c,2
+0x0a,s9,0x00,2
+0x0b,s9,0x04,2
+0x0c,s18,0x10,2
+0x0d,s18,0x14,2
+0x0e,s0,0x08,3
+0x0f,s19,0x00,5
+0x10,s17,0x00,5
+0x11,s16,0x00,5
+0x12,s15,0x00,5
+0x13,s14,0x00,5
+0x14,s13,0x00,5
+0x15,s12,0x00,5
+0x16,s11,0x00,5
+0x17,s10,0x00,5
+0x18,s8,0x00,5
+0x19,s7,0x00,5
+0x1a,s6,0x00,5
+0x1b,s5,0x00,5
+0x1c,s4,0x00,5
+0x1d,s3,0x00,5
+0x1e,s2,0x00,5
+0x1f,s1,0x00,5
 "#;
             assert_eq!(expected.trim(), format!("{}", state).trim());
         }
@@ -0,0 +1,675 @@
+use super::{
+    AlternatingDecoder, BackwardBitParser, BitDecoder, Checkpoint, CheckpointError, Error,
+    ForwardBitParser, ForwardByteParser, FseTable, Result,
+};
+use crate::format::{HUFFMAN_MAX_BITS, HUFFMAN_WEIGHTS_ACCURACY_LOG_MAX};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
+use core::fmt::{self, Write};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum HuffmanError {
+    #[error("Cannot compute missing huffman weight")]
+    ComputeMissingWeight,
+
+    #[error("Huffman weight: {weight} bigger than max_weight: {max}")]
+    WeightTooBig { weight: u32, max: u32 },
+
+    #[error("Missing symbol for huffman code")]
+    MissingSymbol,
+
+    #[error("Huffman weights are corrupted")]
+    WeightCorruption,
+
+    #[error("Too many Huffman weights")]
+    TooManyWeights,
+
+    #[error("Huffman fse compressed AL is too large")]
+    FseALTooLarge,
+}
+use HuffmanError::*;
+
+#[derive(PartialEq, Clone)]
+pub enum HuffmanDecoder {
+    Absent,
+    Symbol(u8),
+    Tree(Box<HuffmanDecoder>, Box<HuffmanDecoder>),
+}
+
+const MAX_NUM_WEIGTHS: usize = 256;
+
+impl<'a> HuffmanDecoder {
+    /// Bytes of memory held by the tree
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        let children = match self {
+            HuffmanDecoder::Tree(left, right) => left.memory_usage() + right.memory_usage(),
+            _ => 0,
+        };
+        core::mem::size_of::<Self>() + children
+    }
+
+    /// # Panics
+    /// Panics when `widths.len() > MAX_NUM_WEIGTHS`
+    fn from_number_of_bits(widths: &[u8]) -> Self {
+        assert!(widths.len() <= MAX_NUM_WEIGTHS);
+
+        // Build a list of symbols and their widths, on the stack
+        // `u8::try_from(symbol).unwrap()` will not panic
+        // because of above assertion
+        let mut symbols = [(0_u8, 0_u8); MAX_NUM_WEIGTHS];
+        let mut len = 0;
+        for (symbol, &width) in widths.iter().enumerate().filter(|(_, &width)| width > 0) {
+            symbols[len] = (u8::try_from(symbol).unwrap(), width);
+            len += 1;
+        }
+        let symbols = &mut symbols[..len];
+
+        // Sort symbols based on highest width and lowest symbol value
+        symbols.sort_unstable_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+
+        let mut tree = HuffmanDecoder::Absent;
+        for &(symbol, width) in symbols.iter() {
+            tree.insert(symbol, width);
+        }
+
+        tree
+    }
+
+    fn from_weights(weights: &HuffmanWeights) -> Self {
+        let mut widths = [0_u8; MAX_NUM_WEIGTHS];
+        for (width, weight_width) in widths.iter_mut().zip(weights.widths()) {
+            *width = weight_width;
+        }
+        Self::from_number_of_bits(&widths[..weights.len()])
+    }
+
+    fn insert(&mut self, symbol: u8, width: u8) -> bool {
+        if width == 0 {
+            if let HuffmanDecoder::Absent = self {
+                *self = HuffmanDecoder::Symbol(symbol);
+                return true;
+            }
+            return false;
+        }
+
+        match self {
+            HuffmanDecoder::Symbol(_) => panic!("unexpected: invalid Huffman tree decoder"),
+            HuffmanDecoder::Tree(lhs, rhs) => {
+                if lhs.insert(symbol, width - 1) {
+                    return true;
+                }
+                rhs.insert(symbol, width - 1)
+            }
+            HuffmanDecoder::Absent => {
+                let lhs = Box::new(HuffmanDecoder::Absent);
+                let rhs = Box::new(HuffmanDecoder::Absent);
+                *self = HuffmanDecoder::Tree(lhs, rhs);
+                self.insert(symbol, width)
+            }
+        }
+    }
+
+    pub fn decode(&self, parser: &mut BackwardBitParser) -> Result<u8> {
+        match self {
+            HuffmanDecoder::Absent => Err(Error::Huffman(MissingSymbol)),
+            HuffmanDecoder::Symbol(s) => Ok(*s),
+            HuffmanDecoder::Tree(lhs, rhs) => match parser.take(1)? {
+                0 => lhs.decode(parser),
+                1 => rhs.decode(parser),
+                b => panic!("unexpected: invalid bit value: {b}"),
+            },
+        }
+    }
+
+    pub fn iter(&'a self) -> HuffmanDecoderIterator<'a> {
+        HuffmanDecoderIterator::new(self)
+    }
+
+    /// Graphviz graph `name` of the tree: the branches are labelled with
+    /// their bit, the leaves with their symbol and code
+    #[must_use]
+    pub fn to_dot(&self, name: &str) -> String {
+        let mut dot = format!("digraph {name} {{\n    node [shape=circle, label=\"\"];\n");
+        self.write_dot(&mut dot, &mut 0, HuffmanCode::default());
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Write the node numbered `*next` and its children, numbered after it
+    fn write_dot(&self, dot: &mut String, next: &mut usize, code: HuffmanCode) {
+        let node = *next;
+        *next += 1;
+        match self {
+            HuffmanDecoder::Absent => {
+                writeln!(dot, "    n{node} [shape=point];").ok();
+            }
+            HuffmanDecoder::Symbol(symbol) => {
+                // Quotes and backslashes would need escaping
+                let symbol = match char::from(*symbol) {
+                    c if c.is_ascii_graphic() && c != '"' && c != '\\' => format!("'{c}'"),
+                    _ => format!("0x{symbol:02X}"),
+                };
+                writeln!(dot, "    n{node} [shape=box, label=\"{symbol}\\n{code}\"];").ok();
+            }
+            HuffmanDecoder::Tree(lhs, rhs) => {
+                writeln!(dot, "    n{node};").ok();
+                for (bit, child) in [(0, lhs), (1, rhs)] {
+                    writeln!(dot, "    n{node} -> n{} [label=\"{bit}\"];", *next).ok();
+                    child.write_dot(dot, next, code.push(bit));
+                }
+            }
+        }
+    }
+
+    /// Build a Huffman table from the given stream. Only the bytes needed to
+    /// build the table are consumed from the stream.
+    pub fn parse(input: &mut ForwardByteParser) -> Result<Self> {
+        let header = input.u8()?;
+
+        let weights = if header < 128 {
+            Self::parse_fse(input, header)?
+        } else {
+            Self::parse_direct(input, header as usize - 127)?
+        };
+
+        Ok(Self::from_weights(&weights.complete()?))
+    }
+
+    /// Parse the Huffman table weights directly from the stream, 4
+    /// bits per weights. If there are an odd number of weights, the
+    /// last four bits are lost. `number_of_weights/2` bytes (rounded
+    /// up) will be consumed from the `input` stream.
+    fn parse_direct(
+        input: &mut ForwardByteParser,
+        number_of_weights: usize,
+    ) -> Result<HuffmanWeights> {
+        assert!(number_of_weights <= 128, "expected n_weights <= 128");
+
+        let mut weights = HuffmanWeights::new();
+        let mut number_of_weights = number_of_weights;
+
+        'outer: loop {
+            let byte = input.u8()?;
+
+            for shift in &[4, 0] {
+                let weight = (byte >> shift) & 0b0000_1111;
+                weights.push(weight)?;
+                number_of_weights -= 1;
+
+                if number_of_weights == 0 {
+                    break 'outer;
+                }
+            }
+        }
+
+        Ok(weights)
+    }
+
+    /// Decode a FSE table and use an alternating FSE decoder to parse
+    /// the Huffman table weights. `compressed_size` bytes will be
+    /// consumed from the `input` stream.
+    fn parse_fse(input: &mut ForwardByteParser, compressed_size: u8) -> Result<HuffmanWeights> {
+        // symbol is u16, but huffman weight is u8. Return an error in case of
+        // uint overflow
+        fn get_huffman_weight(decoder: &mut AlternatingDecoder) -> Result<u8> {
+            let symbol = decoder.symbol();
+            <u8>::try_from(symbol).map_err(|_| Error::Huffman(WeightCorruption))
+        }
+
+        let mut weights = HuffmanWeights::new();
+
+        let bitstream = input.slice(compressed_size as usize)?;
+        let mut forward_bit_parser = ForwardBitParser::new(bitstream);
+        let fse_table = FseTable::parse(&mut forward_bit_parser)?;
+
+        // `The maximum possible decompressed size is 255, since literal values span from 0 to 255,
+        // and last symbol's Weight is not represented.`
+        // `For a list of Huffman weights, the maximum accuracy log is 6 bits.`
+        if fse_table.accuracy_log() > HUFFMAN_WEIGHTS_ACCURACY_LOG_MAX {
+            return Err(Error::Huffman(FseALTooLarge));
+        }
+
+        let mut decoder = AlternatingDecoder::new(&fse_table);
+        let mut backward_bit_parser = BackwardBitParser::try_from(forward_bit_parser)?;
+        decoder.initialize(&mut backward_bit_parser)?;
+
+        // see fuzz_test_10
+        while weights.len() < MAX_NUM_WEIGTHS {
+            weights.push(get_huffman_weight(&mut decoder)?)?;
+
+            if decoder.update_bits(&mut backward_bit_parser)? {
+                weights.push(get_huffman_weight(&mut decoder)?)?;
+                break;
+            }
+        }
+
+        Ok(weights)
+    }
+}
+
+/// Weights of a Huffman table, at most one per byte value, kept on the stack
+/// while the table is built. The weights of the table description are pushed
+/// one by one, then `complete` derives the weight of the last symbol and
+/// validates the whole table.
+struct HuffmanWeights {
+    weights: [u8; MAX_NUM_WEIGTHS],
+    len: usize,
+    // Width of the longest codes, set by `complete`
+    max_width: u8,
+}
+
+impl HuffmanWeights {
+    fn new() -> Self {
+        Self {
+            weights: [0; MAX_NUM_WEIGTHS],
+            len: 0,
+            max_width: 0,
+        }
+    }
+
+    /// Push a weight of the description: at most `HUFFMAN_MAX_BITS`, and at most
+    /// 255 weights since the last symbol is not described
+    fn push(&mut self, weight: u8) -> Result<()> {
+        if u32::from(weight) > HUFFMAN_MAX_BITS {
+            return Err(Error::Huffman(WeightTooBig {
+                weight: u32::from(weight),
+                max: HUFFMAN_MAX_BITS,
+            }));
+        }
+        if self.len == MAX_NUM_WEIGTHS - 1 {
+            return Err(Error::Huffman(TooManyWeights));
+        }
+        self.weights[self.len] = weight;
+        self.len += 1;
+        Ok(())
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn as_slice(&self) -> &[u8] {
+        &self.weights[..self.len]
+    }
+
+    /// Append the last weight, which completes the sum of `2^(weight - 1)` to
+    /// the next power of 2, `2^max_width`. The codes are at most `HUFFMAN_MAX_BITS`
+    /// long, and at least two symbols have weight 1, the codes of `max_width` bits.
+    fn complete(mut self) -> Result<Self> {
+        let weights_sum: u32 = self
+            .as_slice()
+            .iter()
+            .filter(|&&weight| weight > 0)
+            .map(|&weight| 1 << (weight - 1))
+            .sum();
+        if weights_sum == 0 {
+            return Err(Error::Huffman(ComputeMissingWeight));
+        }
+
+        let max_width = u32::BITS - weights_sum.leading_zeros();
+        if max_width > HUFFMAN_MAX_BITS {
+            return Err(Error::Huffman(WeightTooBig {
+                weight: max_width,
+                max: HUFFMAN_MAX_BITS,
+            }));
+        }
+
+        // weights_sum + 2^(last_weight - 1) = 2^max_width
+        let left_over = (1 << max_width) - weights_sum;
+        if !left_over.is_power_of_two() {
+            return Err(Error::Huffman(ComputeMissingWeight));
+        }
+        // Will not panic: last_weight <= max_width <= HUFFMAN_MAX_BITS, and the
+        // description has at most 255 weights
+        self.weights[self.len] = u8::try_from(left_over.trailing_zeros() + 1).unwrap();
+        self.len += 1;
+        self.max_width = u8::try_from(max_width).unwrap();
+
+        if self
+            .as_slice()
+            .iter()
+            .filter(|&&weight| weight == 1)
+            .count()
+            < 2
+        {
+            return Err(Error::Huffman(WeightCorruption));
+        }
+        Ok(self)
+    }
+
+    /// Width of the code of each symbol of a complete table, 0 when absent
+    fn widths(&self) -> impl Iterator<Item = u8> + '_ {
+        self.as_slice().iter().map(|&weight| {
+            if weight > 0 {
+                self.max_width + 1 - weight
+            } else {
+                0
+            }
+        })
+    }
+}
+
+/// Code of a symbol in a Huffman tree: the `len` lowest bits of `bits`, the
+/// most significant one being the first branch taken from the root
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct HuffmanCode {
+    bits: u32,
+    len: u8,
+}
+
+impl HuffmanCode {
+    /// Codes are at most `HUFFMAN_MAX_BITS` long, trees deeper than `u32::BITS`
+    /// cannot be built
+    fn push(self, bit: u32) -> Self {
+        Self {
+            bits: (self.bits << 1) | bit,
+            len: self.len + 1,
+        }
+    }
+
+    /// Write the code as `0` and `1` characters to `buffer`, to format it
+    /// without allocating
+    fn write<'b>(&self, buffer: &'b mut [u8; u32::BITS as usize]) -> &'b str {
+        let len = usize::from(self.len);
+        for (i, char) in buffer[..len].iter_mut().enumerate() {
+            let bit = (self.bits >> (len - 1 - i)) & 1;
+            *char = if bit == 1 { b'1' } else { b'0' };
+        }
+        // '0' and '1' are ASCII
+        core::str::from_utf8(&buffer[..len]).unwrap()
+    }
+}
+
+impl fmt::Display for HuffmanCode {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt.write_str(self.write(&mut [0; u32::BITS as usize]))
+    }
+}
+
+pub struct HuffmanDecoderIterator<'a> {
+    nodes: Vec<(&'a HuffmanDecoder, HuffmanCode)>,
+}
+impl<'a> HuffmanDecoderIterator<'a> {
+    pub fn new(tree: &'a HuffmanDecoder) -> Self {
+        Self {
+            nodes: vec![(tree, HuffmanCode::default())],
+        }
+    }
+}
+impl<'a> Iterator for HuffmanDecoderIterator<'a> {
+    type Item = (HuffmanCode, u8);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (decoder, code) = self.nodes.pop()?;
+        match decoder {
+            HuffmanDecoder::Absent => None,
+            HuffmanDecoder::Symbol(s) => Some((code, *s)),
+            HuffmanDecoder::Tree(lhs, rhs) => {
+                self.nodes.push((lhs, code.push(0)));
+                self.nodes.push((rhs, code.push(1)));
+                self.next()
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HuffmanDecoder {
+    fn fmt(&self, fmt: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut res = fmt.debug_struct("HuffmanDecoder");
+        let mut buffer = [0; u32::BITS as usize];
+        for (code, symbol) in self.iter() {
+            res.field(code.write(&mut buffer), &symbol);
+        }
+        res.finish()
+    }
+}
+
+/// Nodes in pre-order: `0` for absent, `1` and the symbol, `2` and both branches
+impl Checkpoint for HuffmanDecoder {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        match self {
+            HuffmanDecoder::Absent => checkpoint.push(0),
+            HuffmanDecoder::Symbol(symbol) => checkpoint.extend_from_slice(&[1, *symbol]),
+            HuffmanDecoder::Tree(lhs, rhs) => {
+                checkpoint.push(2);
+                lhs.save(checkpoint);
+                rhs.save(checkpoint);
+            }
+        }
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self, CheckpointError> {
+        fn restore_node(
+            checkpoint: &mut ForwardByteParser,
+            depth: u32,
+        ) -> Result<HuffmanDecoder, CheckpointError> {
+            match checkpoint.u8()? {
+                0 => Ok(HuffmanDecoder::Absent),
+                1 => Ok(HuffmanDecoder::Symbol(checkpoint.u8()?)),
+                // Codes are at most `HUFFMAN_MAX_BITS` long
+                2 if depth < HUFFMAN_MAX_BITS => Ok(HuffmanDecoder::Tree(
+                    Box::new(restore_node(checkpoint, depth + 1)?),
+                    Box::new(restore_node(checkpoint, depth + 1)?),
+                )),
+                _ => Err(CheckpointError::Corrupted),
+            }
+        }
+        restore_node(checkpoint, 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fixture_tree() -> HuffmanDecoder {
+        let mut tree = HuffmanDecoder::Absent;
+        tree.insert(b'A', 2);
+        tree.insert(b'C', 2);
+        tree.insert(b'B', 1);
+        tree
+    }
+
+    #[test]
+    fn test_insert() {
+        let mut tree = HuffmanDecoder::Absent;
+        assert!(tree.insert(b'A', 2));
+        assert!(tree.insert(b'C', 2));
+        assert!(tree.insert(b'B', 1));
+    }
+
+    #[test]
+    fn test_debug() {
+        let tree = fixture_tree();
+        assert_eq!(
+            format!("{:?}", tree),
+            "HuffmanDecoder { 1: 66, 01: 67, 00: 65 }"
+        );
+        assert_eq!(
+            format!("{:#?}", tree),
+            "HuffmanDecoder {\n    1: 66,\n    01: 67,\n    00: 65,\n}"
+        );
+    }
+
+    #[test]
+    fn test_to_dot() {
+        assert_eq!(
+            fixture_tree().to_dot("literals"),
+            r#"digraph literals {
+    node [shape=circle, label=""];
+    n0;
+    n0 -> n1 [label="0"];
+    n1;
+    n1 -> n2 [label="0"];
+    n2 [shape=box, label="'A'\n00"];
+    n1 -> n3 [label="1"];
+    n3 [shape=box, label="'C'\n01"];
+    n0 -> n4 [label="1"];
+    n4 [shape=box, label="'B'\n1"];
+}
+"#
+        );
+        let tree = HuffmanDecoder::Symbol(b'"');
+        assert!(tree.to_dot("quote").contains("label=\"0x22\\n\""));
+    }
+
+    #[test]
+    fn test_iterator() {
+        let tree = fixture_tree();
+        let mut iter = tree.iter();
+        let mut next = || iter.next().map(|(code, symbol)| (code.to_string(), symbol));
+        assert_eq!(next(), Some((String::from("1"), b'B')));
+        assert_eq!(next(), Some((String::from("01"), b'C')));
+        assert_eq!(next(), Some((String::from("00"), b'A')));
+        assert_eq!(next(), None);
+    }
+
+    #[test]
+    fn test_from_number_of_bits() {
+        let widths: Vec<u8> = core::iter::repeat_n(0, 65).chain([2, 1, 2]).collect();
+        let tree = HuffmanDecoder::from_number_of_bits(widths.as_slice());
+        assert_eq!(
+            format!("{:?}", tree),
+            "HuffmanDecoder { 1: 66, 01: 67, 00: 65 }"
+        );
+    }
+
+    fn weights(declared: &[u8]) -> Result<HuffmanWeights> {
+        let mut weights = HuffmanWeights::new();
+        for &weight in declared {
+            weights.push(weight)?;
+        }
+        weights.complete()
+    }
+
+    #[test]
+    fn test_weights_push() {
+        let mut weights = HuffmanWeights::new();
+        assert!(matches!(
+            weights.push(12),
+            Err(Error::Huffman(WeightTooBig {
+                weight: 12,
+                max: 11
+            }))
+        ));
+        for _ in 0..255 {
+            weights.push(0).unwrap();
+        }
+        assert!(matches!(
+            weights.push(0),
+            Err(Error::Huffman(TooManyWeights))
+        ));
+    }
+
+    #[test]
+    fn test_weights_complete() {
+        let complete = |declared: &[u8]| {
+            let weights = weights(declared).unwrap();
+            (weights.as_slice().to_vec(), weights.max_width)
+        };
+        assert_eq!(complete(&[1]), (vec![1, 1], 1));
+        assert_eq!(complete(&[1, 2]), (vec![1, 2, 1], 2));
+        assert_eq!(complete(&[1, 1, 0, 2]), (vec![1, 1, 0, 2, 3], 3));
+        assert_eq!(complete(&[3, 2, 1]), (vec![3, 2, 1, 1], 3));
+        // Longest codes of 11 bits
+        let declared: Vec<_> = (1..=11).rev().collect();
+        assert_eq!(complete(&declared).1, 11);
+        let mut declared = vec![0; 254];
+        declared.push(1);
+        assert_eq!(complete(&declared).0.len(), 256);
+
+        let error = |declared: &[u8]| match weights(declared) {
+            Err(Error::Huffman(err)) => err,
+            _ => panic!("{declared:?} is valid"),
+        };
+        assert!(matches!(error(&[]), ComputeMissingWeight));
+        assert!(matches!(error(&[0, 0]), ComputeMissingWeight));
+        // 2^0 + 2^2 = 5: no single weight completes it to 8
+        assert!(matches!(error(&[1, 3]), ComputeMissingWeight));
+        assert!(matches!(
+            error(&[11, 11]),
+            WeightTooBig {
+                weight: 12,
+                max: 11
+            }
+        ));
+        // [2, 2] and [3, 2, 2]: no code is `max_width` bits long
+        assert!(matches!(error(&[2]), WeightCorruption));
+        assert!(matches!(error(&[3, 2]), WeightCorruption));
+    }
+
+    #[test]
+    fn test_weights_exhaustive() {
+        // Every description of up to 3 weights: the table is valid when a last
+        // weight completes the sum to a power of 2, and then it is the Kraft
+        // equality of a full tree of codes at most 11 bits long
+        for len in 0..=3 {
+            for n in 0..12_u32.pow(len) {
+                let declared: Vec<u8> = (0..len).map(|i| (n / 12_u32.pow(i) % 12) as u8).collect();
+                let power = |weight: u8| if weight > 0 { 1_u32 << (weight - 1) } else { 0 };
+                let sum: u32 = declared.iter().map(|&w| power(w)).sum();
+                let valid = (1..=11).any(|last: u8| {
+                    let total = sum + power(last);
+                    let ones = declared.iter().chain([&last]).filter(|&&w| w == 1).count();
+                    total.is_power_of_two()
+                        && power(last) <= total / 2
+                        && total <= 1 << 10
+                        && ones >= 2
+                });
+
+                let result = weights(&declared);
+                assert_eq!(result.is_ok(), valid, "{declared:?}");
+                if let Ok(weights) = result {
+                    let kraft: u32 = weights
+                        .widths()
+                        .filter(|&width| width > 0)
+                        .map(|width| 1 << (11 - width))
+                        .sum();
+                    assert_eq!(kraft, 1 << 11, "{declared:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_from_weights() {
+        let declared: Vec<_> = core::iter::repeat_n(0, 65).chain([1, 2]).collect();
+        let tree = HuffmanDecoder::from_weights(&weights(&declared).unwrap());
+        assert_eq!(
+            format!("{:?}", tree),
+            "HuffmanDecoder { 1: 66, 01: 67, 00: 65 }"
+        );
+    }
+
+    #[test]
+    fn test_decode() {
+        // 0 repeated 65 times, 1, 2
+        let declared: Vec<_> = core::iter::repeat_n(0, 65).chain([1, 2]).collect();
+        let decoder = HuffmanDecoder::from_weights(&weights(&declared).unwrap());
+        let mut parser = BackwardBitParser::new(&[0x97, 0x01]).unwrap();
+        let mut result = String::new();
+        while !parser.is_empty() {
+            let decoded = decoder.decode(&mut parser).unwrap();
+            result.push(decoded as char); // We know they are valid A, B, or C char
+        }
+        assert_eq!(result, "BABCBB");
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        let tree = fixture_tree();
+        let mut checkpoint = Vec::new();
+        tree.save(&mut checkpoint);
+        assert_eq!(checkpoint, [2, 2, 1, b'A', 1, b'C', 1, b'B']);
+        let restored = HuffmanDecoder::restore(&mut ForwardByteParser::new(&checkpoint)).unwrap();
+        assert!(restored == tree);
+
+        // Deeper than the longest code
+        let checkpoint = [2; 12];
+        assert!(matches!(
+            HuffmanDecoder::restore(&mut ForwardByteParser::new(&checkpoint)),
+            Err(CheckpointError::Corrupted)
+        ));
+    }
+}
@@ -0,0 +1,57 @@
+mod alternating;
+mod bit_decoder;
+mod decoding_context;
+mod fse;
+mod huffman;
+mod rle;
+mod sequence;
+mod sink;
+
+pub(crate) use crate::checkpoint::{Checkpoint, CheckpointError};
+pub(crate) use crate::executor::{BufferKind, Executor};
+pub use crate::format::BLOCK_SIZE_MAX;
+pub use crate::parsing::{BackwardBitParser, ForwardBitParser, ForwardByteParser, ParsingError};
+pub use crate::sequences::{PredefinedTables, SequenceCommand};
+pub(crate) use crate::stats::DecodeStats;
+pub use alternating::*;
+pub use bit_decoder::*;
+pub use decoding_context::*;
+pub use fse::*;
+pub use huffman::*;
+pub use rle::*;
+pub use sequence::*;
+pub use sink::*;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum DecoderError {
+    #[error("decoder parsing: {0}")]
+    Parsing(#[from] ParsingError),
+
+    #[error("decoder context: {0}")]
+    Context(#[from] ContextError),
+
+    #[error("decoder fse: {0}")]
+    Fse(#[from] FseError),
+
+    #[error("decoder huffman: {0}")]
+    Huffman(#[from] HuffmanError),
+
+    #[cfg(feature = "std")]
+    #[error("decoder output: {0}")]
+    Output(#[from] std::io::Error),
+}
+
+// The tables of a context, built once per block or frame, are read by the
+// threads decoding its literals and sequences
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+    send_sync::<HuffmanDecoder>();
+    send_sync::<FseTable>();
+    send_sync::<PredefinedTables>();
+    send_sync::<SequenceTables>();
+    send_sync::<alloc::boxed::Box<SymbolDecoder>>();
+};
+
+type Error = DecoderError;
+type Result<T, E = DecoderError> = core::result::Result<T, E>;
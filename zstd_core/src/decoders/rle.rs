@@ -1,4 +1,5 @@
-use super::{BackwardBitParser, BitDecoder, Error};
+use super::{BackwardBitParser, BitDecoder, Checkpoint, Error, SAVED_RLE};
+use alloc::{format, string::String, vec::Vec};
 
 #[derive(Debug)]
 pub struct RLEDecoder {
@@ -29,5 +30,17 @@ impl BitDecoder<u16, Error> for RLEDecoder {
         Ok(false)
     }
 
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        checkpoint.push(SAVED_RLE);
+        self.symbol.save(checkpoint);
+    }
+
     fn reset(&mut self) {}
+
+    fn to_dot(&self, name: &str) -> Option<String> {
+        Some(format!(
+            "digraph {name} {{\n    s0 [shape=record, label=\"RLE|symbol {}\"];\n}}\n",
+            self.symbol
+        ))
+    }
 }
@@ -1,6 +1,29 @@
-use super::{BackwardBitParser, BitDecoder, Error};
+use super::{
+    BackwardBitParser, BitDecoder, Checkpoint, CheckpointError, Error, ForwardByteParser,
+    FseDecoder, FseTable, RLEDecoder,
+};
+use alloc::{boxed::Box, vec::Vec};
 
 pub type SymbolDecoder = dyn BitDecoder<u16, Error>;
+
+/// Tags of the symbol decoders in checkpoints
+pub(crate) const SAVED_RLE: u8 = 0;
+pub(crate) const SAVED_FSE: u8 = 1;
+
+/// Symbol decoders of the `Repeat` mode are saved along with the entropy tables
+impl Checkpoint for Box<SymbolDecoder> {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        BitDecoder::save(&**self, checkpoint);
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self, CheckpointError> {
+        match checkpoint.u8()? {
+            SAVED_RLE => Ok(Box::new(RLEDecoder::new(u16::restore(checkpoint)?))),
+            SAVED_FSE => Ok(Box::new(FseDecoder::new(FseTable::restore(checkpoint)?))),
+            _ => Err(CheckpointError::Corrupted),
+        }
+    }
+}
 pub struct SequenceDecoder<'d> {
     literals_lengths_decoder: &'d mut SymbolDecoder,
     offsets_decoder: &'d mut SymbolDecoder,
@@ -20,6 +43,16 @@ impl<'a> SequenceDecoder<'a> {
             match_lengths_decoder: &mut **ml_d,
         }
     }
+
+    /// States of the literals lengths, offsets and match lengths decoders
+    #[cfg_attr(not(feature = "tracing"), allow(dead_code))]
+    pub fn states(&self) -> [Option<usize>; 3] {
+        [
+            self.literals_lengths_decoder.state(),
+            self.offsets_decoder.state(),
+            self.match_lengths_decoder.state(),
+        ]
+    }
 }
 
 impl BitDecoder<(u16, u16, u16), Error> for SequenceDecoder<'_> {
@@ -46,6 +79,13 @@ impl BitDecoder<(u16, u16, u16), Error> for SequenceDecoder<'_> {
         Ok(zeroes)
     }
 
+    /// The tables of the literals lengths, offsets and match lengths decoders
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.literals_lengths_decoder.save(checkpoint);
+        self.offsets_decoder.save(checkpoint);
+        self.match_lengths_decoder.save(checkpoint);
+    }
+
     fn reset(&mut self) {
         unimplemented!("reset not supported for SequenceDecoder");
     }
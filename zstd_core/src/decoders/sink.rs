@@ -0,0 +1,487 @@
+use super::{reserve_buffer, BufferKind, ContextError, Error, Executor, Result};
+use alloc::{boxed::Box, vec, vec::Vec};
+
+/// Destination of the content decoded by a `DecodingContext`: every write of
+/// the decoder, literals, matches, raw and RLE blocks, goes through it. A sink
+/// keeps the content that offsets can still reference.
+pub trait OutputSink {
+    /// Bytes written since the sink was created or cleared
+    fn len(&self) -> usize;
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Forget the content written, keeping the memory
+    fn clear(&mut self);
+
+    /// Bytes of memory held by the sink
+    fn memory_usage(&self) -> usize;
+
+    /// Farthest back the content is kept, `usize::MAX` when it all is. Frames
+    /// with a larger window fail with `WindowSizeError`.
+    fn max_history(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Bytes that can be written before the sink grows, `usize::MAX` for the
+    /// sinks that never do
+    fn spare_capacity(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Grow to hold `additional` more bytes, exactly or amortized, asking
+    /// `executor` first, see `Executor::allocate`
+    fn reserve(
+        &mut self,
+        _additional: usize,
+        _exact: bool,
+        _executor: &impl Executor,
+    ) -> Result<()> {
+        Ok(())
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()>;
+
+    /// Write `len` times `byte`
+    fn fill(&mut self, byte: u8, len: usize) -> Result<()>;
+
+    /// Write `length` bytes copied from `offset` bytes back, which overlap the
+    /// copied bytes themselves when `offset < length`. The offset has been
+    /// validated: neither zero nor beyond `len` or `max_history`.
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()>;
+
+    /// The last `len` bytes written, in two parts when they wrap around. `len`
+    /// is at most `len()` and `max_history()`.
+    fn tail(&self, len: usize) -> (&[u8], &[u8]);
+
+    /// The sink as a `Vec`, into which the literals of the blocks without
+    /// sequences are decoded in place instead of through a scratch buffer
+    fn as_mut_vec(&mut self) -> Option<&mut Vec<u8>> {
+        None
+    }
+}
+
+/// The whole content, growing as needed
+impl OutputSink for Vec<u8> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.capacity()
+    }
+
+    fn spare_capacity(&self) -> usize {
+        self.capacity() - Vec::len(self)
+    }
+
+    fn reserve(&mut self, additional: usize, exact: bool, executor: &impl Executor) -> Result<()> {
+        reserve_buffer(self, additional, exact, BufferKind::Content, executor)
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    fn fill(&mut self, byte: u8, len: usize) -> Result<()> {
+        self.resize(Vec::len(self) + len, byte);
+        Ok(())
+    }
+
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()> {
+        let start = Vec::len(self) - offset;
+        let mut remaining = length;
+        while remaining > 0 {
+            // The content from `start` repeats with a period of `offset`, so
+            // any of it already produced can be copied at once
+            let len = core::cmp::min(remaining, Vec::len(self) - start);
+            self.extend_from_within(start..start + len);
+            remaining -= len;
+        }
+        Ok(())
+    }
+
+    fn tail(&self, len: usize) -> (&[u8], &[u8]) {
+        (&self[Vec::len(self) - len..], &[])
+    }
+
+    fn as_mut_vec(&mut self) -> Option<&mut Vec<u8>> {
+        Some(self)
+    }
+}
+
+/// Sink filling a buffer of a fixed size, such as the destination buffer of a
+/// C API: writing past its end fails with `OutputTooSmall`
+///
+/// # Example
+/// ```
+/// # use zstd_core::{Frame, Sequential, SliceSink};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let Frame::ZstandardFrame(frame) = Frame::parse_sized(&bytes)? else {
+///     unreachable!()
+/// };
+/// let mut buffer = [0; 4];
+/// let sink = frame.decode_to_sink(SliceSink::new(&mut buffer), &Sequential)?;
+/// assert_eq!(sink.written(), [0xCA, 0xFE]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub struct SliceSink<'a> {
+    buffer: &'a mut [u8],
+    len: usize,
+}
+
+impl<'a> SliceSink<'a> {
+    #[must_use]
+    pub fn new(buffer: &'a mut [u8]) -> Self {
+        Self { buffer, len: 0 }
+    }
+
+    /// The content written, at the start of the buffer
+    #[must_use]
+    pub fn written(&self) -> &[u8] {
+        &self.buffer[..self.len]
+    }
+
+    /// Room for `len` more bytes, failing when the buffer is too small
+    fn claim(&mut self, len: usize) -> Result<core::ops::Range<usize>> {
+        let end = self
+            .len
+            .checked_add(len)
+            .filter(|&end| end <= self.buffer.len())
+            .ok_or(Error::Context(ContextError::OutputTooSmall {
+                capacity: self.buffer.len(),
+            }))?;
+        let range = self.len..end;
+        self.len = end;
+        Ok(range)
+    }
+}
+
+impl OutputSink for SliceSink<'_> {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        let range = self.claim(bytes.len())?;
+        self.buffer[range].copy_from_slice(bytes);
+        Ok(())
+    }
+
+    fn fill(&mut self, byte: u8, len: usize) -> Result<()> {
+        let range = self.claim(len)?;
+        self.buffer[range].fill(byte);
+        Ok(())
+    }
+
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()> {
+        let start = self.len - offset;
+        let mut position = self.claim(length)?.start;
+        let end = self.len;
+        while position < end {
+            // Copies of at most `offset` bytes do not read what they write
+            let len = core::cmp::min(end - position, position - start);
+            self.buffer.copy_within(start..start + len, position);
+            position += len;
+        }
+        Ok(())
+    }
+
+    fn tail(&self, len: usize) -> (&[u8], &[u8]) {
+        (&self.buffer[self.len - len..self.len], &[])
+    }
+}
+
+/// Sink keeping only the last `capacity` bytes written, enough to decode the
+/// frames whose window fits: the content is meant to be taken from `tail` as it
+/// is produced
+///
+/// # Example
+/// ```
+/// # use zstd_core::{OutputSink, RingBuffer};
+/// let mut ring = RingBuffer::new(4);
+/// ring.write(b"abc")?;
+/// ring.copy_match(2, 3)?;
+/// assert_eq!(ring.len(), 6);
+/// assert_eq!(ring.tail(4), (&b"cb"[..], &b"cb"[..]));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub struct RingBuffer {
+    buffer: Box<[u8]>,
+    len: usize,
+}
+
+impl RingBuffer {
+    /// Ring of `capacity` bytes, at least 1
+    #[must_use]
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            buffer: vec![0; core::cmp::max(capacity, 1)].into_boxed_slice(),
+            len: 0,
+        }
+    }
+
+    fn position(&self) -> usize {
+        self.len % self.buffer.len()
+    }
+
+    /// Write `len` bytes, of which only the last `capacity` are kept, with
+    /// `write_part` writing them in at most two parts, given their offset
+    /// from the first byte kept
+    fn write_with(&mut self, len: usize, mut write_part: impl FnMut(&mut [u8], usize)) {
+        let capacity = self.buffer.len();
+        let skipped = len.saturating_sub(capacity);
+        self.len += skipped;
+        let mut written = 0;
+        while written < len - skipped {
+            let position = self.position();
+            let part = core::cmp::min(len - skipped - written, capacity - position);
+            write_part(
+                &mut self.buffer[position..position + part],
+                skipped + written,
+            );
+            written += part;
+            self.len += part;
+        }
+    }
+}
+
+impl OutputSink for RingBuffer {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn max_history(&self) -> usize {
+        self.buffer.len()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.write_with(bytes.len(), |part, offset| {
+            part.copy_from_slice(&bytes[offset..offset + part.len()]);
+        });
+        Ok(())
+    }
+
+    fn fill(&mut self, byte: u8, len: usize) -> Result<()> {
+        self.write_with(len, |part, _| part.fill(byte));
+        Ok(())
+    }
+
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()> {
+        let capacity = self.buffer.len();
+        let mut remaining = length;
+        while remaining > 0 {
+            // Copies of at most `offset` bytes do not read what they write,
+            // nor wrap around the end of the buffer
+            let source = (self.len - offset) % capacity;
+            let target = self.position();
+            let len = [remaining, offset, capacity - source, capacity - target]
+                .into_iter()
+                .min()
+                .unwrap_or(0);
+            self.buffer.copy_within(source..source + len, target);
+            self.len += len;
+            remaining -= len;
+        }
+        Ok(())
+    }
+
+    fn tail(&self, len: usize) -> (&[u8], &[u8]) {
+        let capacity = self.buffer.len();
+        let start = (self.len - len) % capacity;
+        if start + len <= capacity {
+            (&self.buffer[start..start + len], &[])
+        } else {
+            (
+                &self.buffer[start..],
+                &self.buffer[..len - (capacity - start)],
+            )
+        }
+    }
+}
+
+/// Sink writing the content to `W` as it is produced, keeping only the last
+/// `window_size` bytes for the offsets to reference
+///
+/// # Example
+/// ```
+/// # use zstd_core::{Frame, Sequential, WindowWriter};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let Frame::ZstandardFrame(frame) = Frame::parse_sized(&bytes)? else {
+///     unreachable!()
+/// };
+/// let sink = WindowWriter::new(Vec::new(), frame.header().window_size());
+/// assert_eq!(frame.decode_to_sink(sink, &Sequential)?.into_inner(), [0xCA, 0xFE]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+#[cfg(feature = "std")]
+pub struct WindowWriter<W> {
+    window: RingBuffer,
+    writer: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> WindowWriter<W> {
+    #[must_use]
+    pub fn new(writer: W, window_size: usize) -> Self {
+        Self {
+            window: RingBuffer::new(window_size),
+            writer,
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    /// Write out the last `len` bytes of the window
+    fn flush_tail(&mut self, len: usize) -> Result<()> {
+        let (first, second) = self.window.tail(len);
+        self.writer.write_all(first)?;
+        self.writer.write_all(second)?;
+        Ok(())
+    }
+
+    /// Apply `write` to the window in parts of at most its size, writing each
+    /// one out before the next overwrites it
+    fn write_parts(
+        &mut self,
+        len: usize,
+        mut write: impl FnMut(&mut RingBuffer, usize) -> Result<()>,
+    ) -> Result<()> {
+        let mut remaining = len;
+        while remaining > 0 {
+            let part = core::cmp::min(remaining, self.window.max_history());
+            write(&mut self.window, part)?;
+            self.flush_tail(part)?;
+            remaining -= part;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: std::io::Write> OutputSink for WindowWriter<W> {
+    fn len(&self) -> usize {
+        self.window.len()
+    }
+
+    fn clear(&mut self) {
+        self.window.clear();
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.window.memory_usage()
+    }
+
+    fn max_history(&self) -> usize {
+        self.window.max_history()
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<()> {
+        self.window.write(bytes)?;
+        self.writer.write_all(bytes)?;
+        Ok(())
+    }
+
+    fn fill(&mut self, byte: u8, len: usize) -> Result<()> {
+        self.write_parts(len, |window, part| window.fill(byte, part))
+    }
+
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<()> {
+        self.write_parts(length, |window, part| window.copy_match(offset, part))
+    }
+
+    fn tail(&self, len: usize) -> (&[u8], &[u8]) {
+        self.window.tail(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Content whose matches all reach at most 8 bytes back
+    fn write_content(sink: &mut impl OutputSink) -> Result<()> {
+        sink.write(b"abc")?;
+        sink.fill(b'd', 5)?;
+        sink.copy_match(3, 7)?;
+        sink.copy_match(1, 2)?;
+        sink.write(b"0123456789")?;
+        sink.copy_match(8, 12)
+    }
+
+    fn joined((first, second): (&[u8], &[u8])) -> Vec<u8> {
+        [first, second].concat()
+    }
+
+    #[test]
+    fn test_sinks() {
+        let mut expected = Vec::new();
+        write_content(&mut expected).unwrap();
+        assert_eq!(expected.len(), 39);
+        assert_eq!(&expected[..17], b"abcdddddddddddddd");
+        assert_eq!(&expected[27..], b"234567892345");
+
+        let mut buffer = [0; 40];
+        let mut slice = SliceSink::new(&mut buffer);
+        write_content(&mut slice).unwrap();
+        assert_eq!(slice.written(), expected);
+        assert_eq!(joined(slice.tail(5)), expected[34..]);
+
+        for capacity in [8, 13] {
+            let mut ring = RingBuffer::new(capacity);
+            write_content(&mut ring).unwrap();
+            assert_eq!(ring.len(), expected.len());
+            assert_eq!(joined(ring.tail(8)), expected[31..]);
+        }
+        let mut ring = RingBuffer::new(8);
+        ring.write(&expected).unwrap();
+        assert_eq!(joined(ring.tail(8)), expected[31..]);
+
+        #[cfg(feature = "std")]
+        {
+            let mut writer = WindowWriter::new(Vec::new(), 8);
+            write_content(&mut writer).unwrap();
+            assert_eq!(writer.len(), expected.len());
+            assert_eq!(writer.into_inner(), expected);
+        }
+    }
+
+    #[test]
+    fn test_slice_sink_too_small() {
+        let mut buffer = [0; 38];
+        let mut slice = SliceSink::new(&mut buffer);
+        assert!(matches!(
+            write_content(&mut slice),
+            Err(Error::Context(ContextError::OutputTooSmall {
+                capacity: 38
+            }))
+        ));
+        slice.clear();
+        slice.write(b"ab").unwrap();
+        assert_eq!(slice.written(), b"ab");
+    }
+}
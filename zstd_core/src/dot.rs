@@ -0,0 +1,115 @@
+use super::{DecodingContext, Executor, Frame, FrameIterator, Result};
+use alloc::string::String;
+
+/// Entropy tables in use once a block is decoded, as Graphviz graphs, see
+/// `block_tables_dot`. `None` for the tables no block has set yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TablesDot {
+    /// Huffman tree of the literals
+    pub huffman: Option<String>,
+    /// FSE state transitions, or RLE symbol, of the sequences codes
+    pub literals_lengths: Option<String>,
+    pub offsets: Option<String>,
+    pub match_lengths: Option<String>,
+}
+
+impl TablesDot {
+    /// The graphs set, named after the fields
+    pub fn graphs(&self) -> impl Iterator<Item = &str> {
+        [
+            &self.huffman,
+            &self.literals_lengths,
+            &self.offsets,
+            &self.match_lengths,
+        ]
+        .into_iter()
+        .filter_map(Option::as_deref)
+    }
+}
+
+/// Decode the Zstandard frame `frame` of `bytes` up to its block `block`, both
+/// counted from 0, and give the Huffman and FSE tables then in use as
+/// Graphviz graphs, e.g. to render with `dot -Tsvg`. `None` when there is no
+/// such frame or block.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{block_tables_dot, Sequential};
+/// let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+/// let tables = block_tables_dot(bytes, 0, 0, &Sequential)?.unwrap();
+/// assert!(tables.huffman.unwrap().starts_with("digraph huffman {"));
+/// assert!(block_tables_dot(bytes, 1, 0, &Sequential)?.is_none());
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn block_tables_dot(
+    bytes: &[u8],
+    frame: usize,
+    block: usize,
+    executor: &impl Executor,
+) -> Result<Option<TablesDot>> {
+    let frame = FrameIterator::new(bytes)
+        .filter(|frame| !matches!(frame, Ok(Frame::SkippableFrame(_))))
+        .nth(frame)
+        .transpose()?;
+    let Some(Frame::ZstandardFrame(mut frame)) = frame else {
+        return Ok(None);
+    };
+    let window_size = frame.header().window_size();
+    let blocks = frame.take_blocks();
+    if block >= blocks.len() {
+        return Ok(None);
+    }
+
+    let mut context = DecodingContext::default();
+    context.set_limits(executor);
+    context.start_frame(window_size, executor.max_window_size())?;
+    for block in blocks.into_iter().take(block + 1) {
+        block.decode(&mut context, executor)?;
+    }
+
+    let tables = &context.sequence_tables;
+    Ok(Some(TablesDot {
+        huffman: (context.huffman.as_ref()).map(|tree| tree.to_dot("huffman")),
+        literals_lengths: (tables.literals_lengths_decoder.as_ref())
+            .and_then(|decoder| decoder.to_dot("literals_lengths")),
+        offsets: (tables.offsets_decoder.as_ref()).and_then(|decoder| decoder.to_dot("offsets")),
+        match_lengths: (tables.match_lengths_decoder.as_ref())
+            .and_then(|decoder| decoder.to_dot("match_lengths")),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{stream::tests::FRAMES, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_block_tables_dot() {
+        // Raw and RLE blocks only
+        let tables = block_tables_dot(&FRAMES, 0, 1, &Sequential).unwrap();
+        assert_eq!(tables, Some(TablesDot::default()));
+        assert!(block_tables_dot(&FRAMES, 0, 2, &Sequential)
+            .unwrap()
+            .is_none());
+        assert!(block_tables_dot(&FRAMES, 2, 0, &Sequential)
+            .unwrap()
+            .is_none());
+        assert!(block_tables_dot(&FRAMES[..20], 0, 0, &Sequential).is_err());
+
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let tables = block_tables_dot(bytes, 0, 0, &Sequential).unwrap().unwrap();
+        let names: Vec<_> = tables
+            .graphs()
+            .map(|graph| graph.lines().next().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "digraph huffman {",
+                "digraph literals_lengths {",
+                "digraph offsets {",
+                "digraph match_lengths {"
+            ]
+        );
+    }
+}
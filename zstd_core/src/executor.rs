@@ -0,0 +1,154 @@
+use super::{Backend, Result, SkippableFrame, DEFAULT_MAX_WINDOW_SIZE};
+
+/// Runs the independent parts of a block: its literals and sequences sections,
+/// and the 4 Huffman streams of its literals. `zstd_lib` runs them on threads.
+pub trait Executor: Sync {
+    /// Run `a` and `b`, possibly in parallel, and return both results
+    fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send;
+
+    /// Largest frame window accepted: a frame with a larger one fails with
+    /// `WindowSizeError` instead of having its window allocated
+    fn max_window_size(&self) -> usize {
+        DEFAULT_MAX_WINDOW_SIZE
+    }
+
+    /// Work units allowed per frame, `None` for no limit: one per literal byte,
+    /// raw and RLE block bytes included, and one per sequence. A frame needing
+    /// more fails with `BudgetExceeded` before doing the work, whatever the
+    /// threads it runs on.
+    fn budget(&self) -> Option<u64> {
+        None
+    }
+
+    /// Bytes of memory a frame can use, `None` for no limit: the content held
+    /// to decode it, as the output it is appended to or its window, and the
+    /// buffers and tables of its blocks. The content is checked before each
+    /// block, which fails with `MemoryLimitExceeded` when it would not fit.
+    fn max_memory(&self) -> Option<usize> {
+        None
+    }
+
+    /// Implementation decoding the content of blocks
+    fn backend(&self) -> Backend {
+        Backend::default()
+    }
+
+    /// Called before `buffer` grows to hold `bytes` bytes. Returning `false`
+    /// refuses the allocation: the frame fails with `AllocationFailed`, as when
+    /// the allocator cannot serve it, instead of aborting. Lets embedders
+    /// observe the memory of the decoder and veto it against a budget of their
+    /// own: the buffers are still allocated by the global allocator.
+    fn allocate(&self, _buffer: BufferKind, _bytes: usize) -> bool {
+        true
+    }
+
+    /// Called with each skippable frame met by `Frame::decode`, the functions
+    /// decoding whole inputs and `StreamDecoder`, in input order, instead of
+    /// it being discarded: lets applications read the index or metadata they
+    /// store in them while decoding. `StreamDecoder` buffers the payload until
+    /// the frame is complete.
+    fn skippable_frame(&self, _frame: &SkippableFrame<'_>) {}
+}
+
+impl<E: Executor> Executor for &E {
+    fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        (**self).join(a, b)
+    }
+
+    fn max_window_size(&self) -> usize {
+        (**self).max_window_size()
+    }
+
+    fn budget(&self) -> Option<u64> {
+        (**self).budget()
+    }
+
+    fn max_memory(&self) -> Option<usize> {
+        (**self).max_memory()
+    }
+
+    fn backend(&self) -> Backend {
+        (**self).backend()
+    }
+
+    fn allocate(&self, buffer: BufferKind, bytes: usize) -> bool {
+        (**self).allocate(buffer, bytes)
+    }
+
+    fn skippable_frame(&self, frame: &SkippableFrame<'_>) {
+        (**self).skippable_frame(frame);
+    }
+}
+
+/// Large buffer of a decoding context, see `Executor::allocate`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BufferKind {
+    /// Decoded content: the output of the frame, or its window when streaming
+    Content,
+    /// Literals of a compressed block with sequences
+    Literals,
+    /// Sequences of a compressed block
+    Sequences,
+    /// FSE tables of the sequences, of at most 512 states: their allocation
+    /// can fail with `AllocationFailed` but is not given to `allocate`
+    Tables,
+}
+
+/// Run everything on the calling thread
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Sequential;
+
+impl Executor for Sequential {
+    fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        Ok((a(), b()))
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use std::thread;
+
+    /// Run `a` and `b` on scoped threads
+    pub(crate) struct Threads;
+
+    impl Executor for Threads {
+        fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+        where
+            A: FnOnce() -> RA + Send,
+            B: FnOnce() -> RB + Send,
+            RA: Send,
+            RB: Send,
+        {
+            thread::scope(|s| {
+                let a = s.spawn(a);
+                let b = s.spawn(b);
+                Ok((a.join().unwrap(), b.join().unwrap()))
+            })
+        }
+    }
+
+    #[test]
+    fn test_join() {
+        assert_eq!(Sequential.join(|| 1, || "2").unwrap(), (1, "2"));
+        assert_eq!(Threads.join(|| 1, || "2").unwrap(), (1, "2"));
+    }
+}
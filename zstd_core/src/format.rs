@@ -0,0 +1,103 @@
+//! Magic numbers, flags and limits of the Zstandard format (RFC 8878), for
+//! container formats that read or write frames without decoding them
+
+/// Magic number of Zstandard frames, little-endian on the first 4 bytes
+pub const ZSTANDARD_MAGIC: u32 = 0xFD2F_B528;
+
+/// Range of the magic numbers of skippable frames: the low 4 bits are free
+/// for applications to tell their frames apart
+pub const SKIPPABLE_MAGIC_MIN: u32 = 0x184D_2A50;
+pub const SKIPPABLE_MAGIC_MAX: u32 = 0x184D_2A5F;
+
+/// Magic number of the skippable frames tagged with the low 4 bits of `nibble`
+///
+/// # Example
+/// ```
+/// # use zstd_core::format::{skippable_magic, SKIPPABLE_MAGIC_MAX};
+/// assert_eq!(skippable_magic(0xF), SKIPPABLE_MAGIC_MAX);
+/// ```
+#[must_use]
+pub const fn skippable_magic(nibble: u8) -> u32 {
+    SKIPPABLE_MAGIC_MIN | (nibble & 0xF) as u32
+}
+
+/// Magic number and payload size of a skippable frame
+pub const SKIPPABLE_HEADER_SIZE: usize = 8;
+
+/// Bits of the Frame_Header_Descriptor, the byte after the magic number
+pub const SINGLE_SEGMENT_FLAG: u8 = 0b0010_0000;
+pub const CONTENT_CHECKSUM_FLAG: u8 = 0b0000_0100;
+
+/// Size of the Content_Checksum ending a frame with the checksum flag: the
+/// low 4 bytes of the XXH64 digest of the content
+pub const CHECKSUM_SIZE: usize = 4;
+
+/// Bounds of the Window_Size described by a Window_Descriptor: 1 KB with an
+/// exponent and mantissa of 0, 3.75 TB with both at their maximum
+pub const WINDOW_SIZE_MIN: u64 = 1 << 10;
+pub const WINDOW_SIZE_MAX: u64 = (1 << 41) + 7 * (1 << 38);
+
+/// Window_Size described by `window_descriptor`, its exponent in the high 5
+/// bits and its mantissa, in eighths, in the low 3 bits
+///
+/// # Example
+/// ```
+/// # use zstd_core::format::{window_size_from_descriptor, WINDOW_SIZE_MAX, WINDOW_SIZE_MIN};
+/// assert_eq!(window_size_from_descriptor(0), WINDOW_SIZE_MIN);
+/// // exponent 0b10101, mantissa 0b101
+/// assert_eq!(window_size_from_descriptor(0xAD), (1 << 31) + 5 * (1 << 28));
+/// assert_eq!(window_size_from_descriptor(0xFF), WINDOW_SIZE_MAX);
+/// ```
+#[must_use]
+pub const fn window_size_from_descriptor(window_descriptor: u8) -> u64 {
+    let exponent = (window_descriptor >> 3) as u32;
+    let mantissa = (window_descriptor & 0b0000_0111) as u64;
+
+    let window_base = 1_u64 << (10 + exponent);
+    let window_add = (window_base / 8) * mantissa;
+    window_base + window_add
+}
+
+/// Size of a block header: the last block flag, the block type and the
+/// block size, little-endian
+pub const BLOCK_HEADER_SIZE: usize = 3;
+
+/// Block_Type values of a block header
+pub const RAW_BLOCK_FLAG: u8 = 0;
+pub const RLE_BLOCK_FLAG: u8 = 1;
+pub const COMPRESSED_BLOCK_FLAG: u8 = 2;
+pub const RESERVED_BLOCK_FLAG: u8 = 3;
+
+/// Largest content of a block, further limited by the window size
+pub const BLOCK_SIZE_MAX: usize = 1024 * 128; // 128kb
+
+/// Longest Huffman code of the literals
+pub const HUFFMAN_MAX_BITS: u32 = 11;
+/// Largest accuracy log of the FSE table compressing the Huffman weights
+pub const HUFFMAN_WEIGHTS_ACCURACY_LOG_MAX: u32 = 6;
+
+/// Largest accuracy logs of the FSE tables of the sequences, by symbol type
+pub const LITERALS_LENGTH_ACCURACY_LOG_MAX: u8 = 9;
+pub const MATCH_LENGTH_ACCURACY_LOG_MAX: u8 = 9;
+pub const OFFSET_ACCURACY_LOG_MAX: u8 = 8;
+/// Largest accuracy log of any FSE table
+pub const FSE_ACCURACY_LOG_MAX: u8 = 9;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_window_size_from_descriptor() {
+        assert_eq!(window_size_from_descriptor(0), WINDOW_SIZE_MIN);
+        assert_eq!(window_size_from_descriptor(0xFF), WINDOW_SIZE_MAX);
+        // exponent 0b10101, mantissa 0b101: 2^31 + 5 * 2^28
+        assert_eq!(window_size_from_descriptor(0xAD), 3_489_660_928);
+
+        let sizes = (0..=u8::MAX).map(window_size_from_descriptor);
+        assert!(sizes
+            .clone()
+            .all(|size| (WINDOW_SIZE_MIN..=WINDOW_SIZE_MAX).contains(&size)));
+        assert!(sizes.clone().zip(sizes.skip(1)).all(|(a, b)| a < b));
+    }
+}
@@ -0,0 +1,1633 @@
+use super::format::{
+    window_size_from_descriptor, CONTENT_CHECKSUM_FLAG, SINGLE_SEGMENT_FLAG, SKIPPABLE_MAGIC_MIN,
+    ZSTANDARD_MAGIC,
+};
+use super::{
+    Block, BlockHeader, Checkpoint, CheckpointError, ContentHasher, DecodeStats, DecodingContext,
+    DefaultHasher, Error, Executor, ForwardByteParser, OutputSink, Result, BLOCK_SIZE_MAX,
+};
+use alloc::vec::Vec;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum FrameError {
+    #[error("Unrecognized magic number: {0}")]
+    UnrecognizedMagic(u32),
+
+    #[error("Dictionnary not supported: id {id}")]
+    DictNotSupported { id: usize },
+
+    #[error("Frame header reserved bit must be 0")]
+    InvalidReservedBit,
+
+    #[error("Corrupted frame, checksum mismatch")]
+    ChecksumMismatch,
+
+    #[error("Window size {window_size} exceeds the address space")]
+    WindowSizeUnsupported { window_size: u64 },
+
+    #[error("Frame content size is {announced} bytes, {decoded} bytes decoded")]
+    ContentSizeMismatch { announced: u64, decoded: usize },
+
+    #[error("Frame size is announced as {announced} bytes, the frame has {parsed} bytes")]
+    FrameSizeMismatch { announced: usize, parsed: usize },
+}
+use FrameError::*;
+
+#[derive(Debug)]
+pub enum Frame<'a> {
+    ZstandardFrame(ZstandardFrame<'a>),
+    SkippableFrame(SkippableFrame<'a>),
+}
+
+/// Kind of frame announced by a magic number
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameKind {
+    Zstandard,
+    Skippable,
+}
+
+impl FrameKind {
+    /// Return the kind of frame for `magic` or `None` when it is unknown
+    #[must_use]
+    pub fn from_magic(magic: u32) -> Option<Self> {
+        if magic == ZSTANDARD_MAGIC {
+            Some(Self::Zstandard)
+        } else if magic & !0xF == SKIPPABLE_MAGIC_MIN {
+            Some(Self::Skippable)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ZstandardFrame<'a> {
+    frame_header: FrameHeader,
+    blocks: Vec<Block<'a>>,
+    checksum: Option<u32>,
+}
+
+#[derive(Debug)]
+pub struct SkippableFrame<'a> {
+    magic: u32,
+    data: &'a [u8],
+}
+
+#[derive(Debug)]
+#[allow(dead_code)]
+pub struct FrameHeader {
+    window_size: usize,
+    window_descriptor: u8,
+    /// `None` when the header does not provide it: the content size is
+    /// unknown, which is not the same as empty
+    frame_content_size: Option<u64>,
+    content_checksum_flag: bool,
+}
+
+impl<'a> Frame<'a> {
+    pub fn parse(input: &mut ForwardByteParser<'a>) -> Result<Self> {
+        let magic = input.le_u32()?;
+
+        match FrameKind::from_magic(magic) {
+            Some(FrameKind::Zstandard) => Ok(Self::ZstandardFrame(ZstandardFrame::parse(input)?)),
+            Some(FrameKind::Skippable) => {
+                let len = input.le_u32()?;
+                let data = input.slice(len as usize)?;
+                Ok(Self::SkippableFrame(SkippableFrame { magic, data }))
+            }
+            None => Err(Error::Frame(UnrecognizedMagic(magic))),
+        }
+    }
+
+    /// Parse the frame spanning all of `bytes`, whose size is known beforehand,
+    /// e.g. from `pzstd_frames`
+    pub fn parse_sized(bytes: &'a [u8]) -> Result<Self> {
+        let mut input = ForwardByteParser::new(bytes);
+        let frame = Self::parse(&mut input)?;
+        if !input.is_empty() {
+            return Err(Error::Frame(FrameSizeMismatch {
+                announced: bytes.len(),
+                parsed: bytes.len() - input.len(),
+            }));
+        }
+        Ok(frame)
+    }
+
+    /// Size of the frame at the start of `bytes`, found by walking its headers
+    /// without decoding any block. Return a `NotEnoughBytes` error when `bytes`
+    /// does not hold the whole frame.
+    pub fn compressed_size(bytes: &[u8]) -> Result<usize> {
+        Ok(FrameMetadata::scan(bytes, 0)?.compressed_size)
+    }
+
+    /// Parse and decode the next frame from `input` block by block, appending the
+    /// content of every successfully decoded block to `output`. `input_len` is the
+    /// length of the whole input and is used to report the absolute offset of the
+    /// structure (frame, block or checksum) that failed to parse or decode.
+    ///
+    /// On failure, `output` only contains fully decoded blocks. The content of a
+    /// frame whose checksum does not match is not appended at all.
+    pub fn decode_partial(
+        input: &mut ForwardByteParser<'a>,
+        input_len: usize,
+        output: &mut Vec<u8>,
+        executor: &impl Executor,
+    ) -> Result<(), (Error, usize)> {
+        let frame_offset = input_len - input.len();
+
+        let magic = input.le_u32().map_err(at(frame_offset))?;
+        match FrameKind::from_magic(magic) {
+            Some(FrameKind::Zstandard) => {
+                ZstandardFrame::decode_partial(input, input_len, output, executor)
+            }
+            Some(FrameKind::Skippable) => {
+                let len = input.le_u32().map_err(at(frame_offset))?;
+                input.slice(len as usize).map_err(at(frame_offset))?;
+                Ok(())
+            }
+            None => Err((Error::Frame(UnrecognizedMagic(magic)), frame_offset)),
+        }
+    }
+
+    /// Decode the frame, running the sections of its blocks on `executor`
+    pub fn decode(self, executor: &impl Executor) -> Result<Vec<u8>> {
+        self.decode_with_hasher::<DefaultHasher>(executor)
+    }
+
+    /// Decode the frame, verifying its content checksum with `H`
+    pub fn decode_with_hasher<H: ContentHasher>(self, executor: &impl Executor) -> Result<Vec<u8>> {
+        let mut context = DecodingContext::default();
+        self.decode_into::<H>(&mut context, executor, &mut |_| {})?;
+        Ok(context.decoded)
+    }
+
+    /// Decode the frame with `context`, reset beforehand, and return the content
+    /// from it. Reusing a context across frames reuses its buffer.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{DecodingContext, FrameIterator, Sequential};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let mut context = DecodingContext::default();
+    /// for frame in FrameIterator::new(&bytes) {
+    ///     assert_eq!(frame?.decode_with_context(&mut context, &Sequential)?, [0xCA, 0xFE]);
+    /// }
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    pub fn decode_with_context<'c>(
+        self,
+        context: &'c mut DecodingContext,
+        executor: &impl Executor,
+    ) -> Result<&'c [u8]> {
+        self.decode_into::<DefaultHasher>(context, executor, &mut |_| {})?;
+        Ok(&context.decoded)
+    }
+
+    /// Decode the frame with `context`, like `decode_with_context`, calling
+    /// `on_block` with the content of each block as soon as it is decoded. The
+    /// content checksum is only verified after the last block: on a mismatch,
+    /// `on_block` has already been given the whole content.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{DecodingContext, FrameIterator, Sequential};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let mut context = DecodingContext::default();
+    /// let mut blocks = Vec::new();
+    /// for frame in FrameIterator::new(&bytes) {
+    ///     frame?.decode_with_callback(&mut context, &Sequential, |block| {
+    ///         blocks.push(block.to_vec());
+    ///     })?;
+    /// }
+    /// assert_eq!(blocks, [[0xCA, 0xFE]]);
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    pub fn decode_with_callback(
+        self,
+        context: &mut DecodingContext,
+        executor: &impl Executor,
+        mut on_block: impl FnMut(&[u8]),
+    ) -> Result<()> {
+        self.decode_into::<DefaultHasher>(context, executor, &mut on_block)
+    }
+
+    fn decode_into<H: ContentHasher>(
+        self,
+        context: &mut DecodingContext,
+        executor: &impl Executor,
+        on_block: &mut impl FnMut(&[u8]),
+    ) -> Result<()> {
+        match self {
+            Frame::SkippableFrame(frame) => {
+                executor.skippable_frame(&frame);
+                context.reset();
+                context.stats.skippable_frames += 1;
+                Ok(())
+            }
+            Frame::ZstandardFrame(frame) => {
+                context.set_limits(executor);
+                context.start_frame(frame.frame_header.window_size, executor.max_window_size())?;
+                frame.decode_blocks::<H>(context, executor, on_block)
+            }
+        }
+    }
+
+    /// Decode the frame at the end of `output` instead of in a buffer of its
+    /// own, saving a copy when concatenating frames. On failure, `output` is
+    /// left as it was.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{FrameIterator, Sequential};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let mut output = vec![0xBA, 0xBE];
+    /// for frame in FrameIterator::new(&bytes) {
+    ///     frame?.decode_append(&mut output, &Sequential)?;
+    /// }
+    /// assert_eq!(output, [0xBA, 0xBE, 0xCA, 0xFE]);
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    pub fn decode_append(self, output: &mut Vec<u8>, executor: &impl Executor) -> Result<()> {
+        self.decode_append_counted(output, executor, &mut DecodeStats::default())
+    }
+
+    /// `decode_append`, adding the counts of the frame to `stats`
+    pub(crate) fn decode_append_counted(
+        self,
+        output: &mut Vec<u8>,
+        executor: &impl Executor,
+        stats: &mut DecodeStats,
+    ) -> Result<()> {
+        let frame = match self {
+            Frame::ZstandardFrame(frame) => frame,
+            Frame::SkippableFrame(frame) => {
+                executor.skippable_frame(&frame);
+                stats.skippable_frames += 1;
+                return Ok(());
+            }
+        };
+
+        let mut context = DecodingContext::default();
+        context.stats = *stats;
+        context.set_limits(executor);
+        context.start_frame_after(
+            core::mem::take(output),
+            frame.frame_header.window_size,
+            executor.max_window_size(),
+        )?;
+        let result = frame.decode_blocks::<DefaultHasher>(&mut context, executor, &mut |_| {});
+
+        *stats = context.stats;
+        let frame_start = context.frame_start;
+        *output = context.decoded;
+        if result.is_err() {
+            output.truncate(frame_start);
+        }
+        result
+    }
+}
+
+impl<'a> SkippableFrame<'a> {
+    pub(crate) fn new(magic: u32, data: &'a [u8]) -> Self {
+        Self { magic, data }
+    }
+
+    /// Low 4 bits of the magic number, free for the application to use
+    #[must_use]
+    pub fn magic_nibble(&self) -> u8 {
+        (self.magic & 0xF) as u8
+    }
+
+    #[must_use]
+    pub fn payload(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> ZstandardFrame<'a> {
+    #[must_use]
+    pub fn header(&self) -> &FrameHeader {
+        &self.frame_header
+    }
+
+    /// Take the blocks out of the frame, to decode them one by one before
+    /// checking the content with `finish`
+    pub(crate) fn take_blocks(&mut self) -> Vec<Block<'a>> {
+        core::mem::take(&mut self.blocks)
+    }
+
+    #[must_use]
+    pub fn block_count(&self) -> usize {
+        self.blocks.len()
+    }
+
+    /// Upper bound of the content size given by the blocks, which holds even
+    /// when the header lies about it
+    #[must_use]
+    pub fn max_content_size(&self) -> usize {
+        let compressed_max = core::cmp::min(self.frame_header.window_size, BLOCK_SIZE_MAX);
+        self.blocks
+            .iter()
+            .map(|block| match block {
+                Block::Raw(raw) => raw.len(),
+                Block::Rle { repeat, .. } => *repeat,
+                Block::Compressed { .. } => compressed_max,
+            })
+            .fold(0, usize::saturating_add)
+    }
+
+    /// Content checksum, `None` when the header does not announce one
+    #[must_use]
+    pub fn checksum(&self) -> Option<u32> {
+        self.checksum
+    }
+
+    pub fn parse(input: &mut ForwardByteParser<'a>) -> Result<Self> {
+        let _span = span!("parse_frame", input = input.len());
+        let frame_header = FrameHeader::parse(input)?;
+        let mut blocks: Vec<Block> = Vec::new();
+
+        loop {
+            let (block, is_last) = Block::parse(input, frame_header.window_size)?;
+            blocks.push(block);
+            if is_last {
+                break;
+            }
+        }
+
+        let checksum = if frame_header.content_checksum_flag {
+            Some(input.le_u32()?)
+        } else {
+            None
+        };
+        event!(
+            window_size = frame_header.window_size,
+            content_size = frame_header.frame_content_size,
+            blocks = blocks.len(),
+            "frame parsed"
+        );
+
+        Ok(ZstandardFrame {
+            frame_header,
+            blocks,
+            checksum,
+        })
+    }
+
+    /// Decode the frame into `sink` instead of a `Vec`: a `SliceSink` to fill a
+    /// buffer of a fixed size, a `RingBuffer` or a `WindowWriter` to keep only
+    /// the window. The content is hashed as it is written, to verify the
+    /// checksum without keeping it.
+    pub fn decode_to_sink<S: OutputSink>(mut self, sink: S, executor: &impl Executor) -> Result<S> {
+        let mut context = DecodingContext::with_sink(sink);
+        context.set_limits(executor);
+        context.start_frame(self.frame_header.window_size, executor.max_window_size())?;
+        let mut hasher = DefaultHasher::default();
+        for block in self.take_blocks() {
+            let block_start = context.decoded.len();
+            block.decode(&mut context, executor)?;
+            if self.frame_header.content_checksum_flag {
+                // The content of a block fits in the window the sink keeps
+                let (first, second) = context.decoded.tail(context.decoded.len() - block_start);
+                hasher.update(first);
+                hasher.update(second);
+            }
+        }
+        self.frame_header
+            .check_content_size(context.decoded.len())?;
+        if !self.verify_hasher(&hasher)? {
+            return Err(Error::Frame(ChecksumMismatch));
+        }
+        Ok(context.into_sink())
+    }
+
+    /// Decode the blocks with `context`, started for this frame, passing the
+    /// content of each to `on_block`, and verify the content checksum with `H`
+    fn decode_blocks<H: ContentHasher>(
+        mut self,
+        context: &mut DecodingContext,
+        executor: &impl Executor,
+        on_block: &mut impl FnMut(&[u8]),
+    ) -> Result<()> {
+        // hint: decode consume self, but we need to replace blocks, so that it does not borrow self
+        // too soon and let us call frame.verify_checksum.
+        // `take` let us replace frame.blocks with an empty vec.
+        let _span = span!(
+            "decode_frame",
+            window_size = self.frame_header.window_size,
+            blocks = self.blocks.len()
+        );
+        for block in self.take_blocks() {
+            let block_start = context.decoded.len();
+            block.decode(context, executor)?;
+            on_block(&context.decoded[block_start..]);
+        }
+        self.finish::<H>(context)
+    }
+
+    /// Check the content decoded by `context` from the blocks against the
+    /// content size and checksum of the header
+    pub(crate) fn finish<H: ContentHasher>(&self, context: &mut DecodingContext) -> Result<()> {
+        event!(
+            size = context.decoded.len() - context.frame_start,
+            "frame decoded"
+        );
+        self.frame_header
+            .check_content_size(context.decoded.len() - context.frame_start)?;
+        if !self.verify_checksum::<H>(&context.decoded[context.frame_start..])? {
+            return Err(Error::Frame(ChecksumMismatch));
+        }
+        context.stats.count_frame(
+            self.frame_header.content_checksum_flag,
+            H::default().checksum().is_some(),
+        );
+        Ok(())
+    }
+
+    /// See `Frame::decode_partial`, `input` must be positioned after the magic number.
+    fn decode_partial(
+        input: &mut ForwardByteParser<'a>,
+        input_len: usize,
+        output: &mut Vec<u8>,
+        executor: &impl Executor,
+    ) -> Result<(), (Error, usize)> {
+        let offset = |input: &ForwardByteParser| input_len - input.len();
+
+        let header_offset = offset(input);
+        let frame_header = FrameHeader::parse(input).map_err(at(header_offset))?;
+        let mut context = DecodingContext::default();
+        context.set_limits(executor);
+        context
+            .start_frame(frame_header.window_size, executor.max_window_size())
+            .map_err(at(header_offset))?;
+
+        loop {
+            let block_offset = offset(input);
+            let decoded_len = context.decoded.len();
+
+            let decoded =
+                Block::parse(input, frame_header.window_size).and_then(|(block, last)| {
+                    block.decode(&mut context, executor)?;
+                    Ok(last)
+                });
+
+            match decoded {
+                Ok(true) => break,
+                Ok(false) => (),
+                Err(err) => {
+                    // Drop whatever the failing block may have produced
+                    context.decoded.truncate(decoded_len);
+                    output.extend(context.decoded);
+                    return Err((err, block_offset));
+                }
+            }
+        }
+
+        let checksum_offset = offset(input);
+        frame_header
+            .check_content_size(context.decoded.len())
+            .map_err(at(checksum_offset))?;
+        let checksum = if frame_header.content_checksum_flag {
+            match input.le_u32() {
+                Ok(checksum) => Some(checksum),
+                Err(err) => {
+                    // Truncated checksum: blocks are complete but cannot be verified
+                    output.extend(context.decoded);
+                    return Err((Error::from(err), checksum_offset));
+                }
+            }
+        } else {
+            None
+        };
+
+        let frame = ZstandardFrame {
+            frame_header,
+            blocks: Vec::new(),
+            checksum,
+        };
+        if !frame
+            .verify_checksum::<DefaultHasher>(&context.decoded)
+            .map_err(at(checksum_offset))?
+        {
+            return Err((Error::Frame(ChecksumMismatch), checksum_offset));
+        }
+
+        output.extend(context.decoded);
+        Ok(())
+    }
+
+    /// Content of a frame made of raw blocks only, as slices of the input
+    /// instead of a copy: `None` when a block is compressed or RLE. The content
+    /// checksum is verified.
+    pub fn raw_content(&self) -> Result<Option<Vec<&'a [u8]>>> {
+        let mut content = Vec::with_capacity(self.blocks.len());
+        for block in &self.blocks {
+            match block {
+                Block::Raw(raw) => content.push(*raw),
+                Block::Rle { .. } | Block::Compressed { .. } => return Ok(None),
+            }
+        }
+
+        self.frame_header
+            .check_content_size(content.iter().map(|raw| raw.len()).sum())?;
+        if !self.verify_checksum_of::<DefaultHasher>(&content)? {
+            return Err(Error::Frame(ChecksumMismatch));
+        }
+        Ok(Some(content))
+    }
+
+    pub fn verify_checksum<H: ContentHasher>(&self, decoded: &[u8]) -> Result<bool> {
+        self.verify_checksum_of::<H>(&[decoded])
+    }
+
+    /// Verify the checksum of the content made of `parts`
+    fn verify_checksum_of<H: ContentHasher>(&self, parts: &[&[u8]]) -> Result<bool> {
+        if !self.frame_header.content_checksum_flag {
+            return Ok(true);
+        }
+
+        let mut hasher = H::default();
+        for part in parts {
+            hasher.update(part);
+        }
+        self.verify_hasher(&hasher)
+    }
+
+    /// Verify the checksum of the content given to `hasher`
+    fn verify_hasher(&self, hasher: &impl ContentHasher) -> Result<bool> {
+        if !self.frame_header.content_checksum_flag {
+            return Ok(true);
+        }
+        let Some(checksum) = hasher.checksum() else {
+            return Ok(true);
+        };
+        let content_checksum = self.checksum.ok_or(ChecksumMismatch)?;
+
+        Ok(checksum == content_checksum)
+    }
+}
+
+impl FrameHeader {
+    /// Size of the header starting with `frame_header_descriptor`, to read
+    /// exactly the header from a stream before parsing it
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::FrameHeader;
+    /// // Window_Descriptor and a 2 bytes Frame_Content_Size
+    /// assert_eq!(FrameHeader::size(0b0100_0000), 4);
+    /// ```
+    #[must_use]
+    pub fn size(frame_header_descriptor: u8) -> usize {
+        let single_segment_flag = frame_header_descriptor & SINGLE_SEGMENT_FLAG != 0;
+        let window_descriptor = usize::from(!single_segment_flag);
+        let dictionary_id = [0, 1, 2, 4][usize::from(frame_header_descriptor & 0b0000_0011)];
+        let frame_content_size = match frame_header_descriptor >> 6 {
+            0 => usize::from(single_segment_flag),
+            1 => 2,
+            2 => 4,
+            _ => 8,
+        };
+        1 + window_descriptor + dictionary_id + frame_content_size
+    }
+
+    pub fn parse(input: &mut ForwardByteParser) -> Result<Self> {
+        // Frame_Header_Descriptor 	    1 byte
+        // [Window_Descriptor] 	        0-1 byte
+        // [Dictionary_ID] 	            0-4 bytes
+        // [Frame_Content_Size] 	    0-8 bytes
+        let frame_header_descriptor = input.u8()?;
+
+        let frame_content_size_flag = (frame_header_descriptor & 0b1100_0000) >> 6;
+        let single_segment_flag = frame_header_descriptor & SINGLE_SEGMENT_FLAG != 0;
+        let reserved_bit = (frame_header_descriptor & 0b0000_1000) >> 3;
+        let content_checksum_flag = frame_header_descriptor & CONTENT_CHECKSUM_FLAG != 0;
+        let dictionary_id_flag = frame_header_descriptor & 0b0000_0011;
+        let window_descriptor: u8 = if single_segment_flag { 0 } else { input.u8()? };
+
+        if reserved_bit != 0 {
+            return Err(Error::Frame(InvalidReservedBit));
+        }
+
+        // dictionnary is not implemented yet, but we still have to consume its bytes
+        let dictionary_id = match dictionary_id_flag {
+            0 => input.le(0)?,
+            1 => input.le(1)?,
+            2 => input.le(2)?,
+            3 => input.le(4)?,
+            _ => panic!("unexpected dictionary_id_flag {dictionary_id_flag}"),
+        };
+        if dictionary_id != 0 {
+            return Err(Error::Frame(DictNotSupported { id: dictionary_id }));
+        }
+
+        let frame_content_size = match frame_content_size_flag {
+            0 if single_segment_flag => Some(u64::from(input.u8()?)),
+            0 => None,
+            1 => Some(u64::from(input.le_u16()?) + 256),
+            2 => Some(u64::from(input.le_u32()?)),
+            3 => Some(input.le_u64()?),
+            _ => panic!("unexpected frame_content_size_flag {frame_content_size_flag}"),
+        };
+
+        // The window of a single segment holds the whole content. Either may
+        // not fit in usize on 32-bit targets.
+        let window_size = match frame_content_size {
+            Some(content_size) if single_segment_flag => content_size,
+            _ => window_size_from_descriptor(window_descriptor),
+        };
+        let window_size = usize::try_from(window_size)
+            .map_err(|_| Error::Frame(WindowSizeUnsupported { window_size }))?;
+
+        Ok(FrameHeader {
+            window_size,
+            window_descriptor,
+            frame_content_size,
+            content_checksum_flag,
+        })
+    }
+
+    #[must_use]
+    pub fn window_size(&self) -> usize {
+        self.window_size
+    }
+
+    /// Fail with `ContentSizeMismatch` when the header announces a content
+    /// size other than `decoded`. With the Single_Segment_Flag, the content size
+    /// is always announced.
+    pub(crate) fn check_content_size(&self, decoded: usize) -> Result<()> {
+        match self.frame_content_size {
+            Some(announced) if usize::try_from(announced) != Ok(decoded) => {
+                Err(Error::Frame(ContentSizeMismatch { announced, decoded }))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    #[must_use]
+    pub fn content_checksum_flag(&self) -> bool {
+        self.content_checksum_flag
+    }
+
+    /// Frame_Content_Size, `None` when the header does not provide it
+    #[must_use]
+    pub fn content_size(&self) -> Option<u64> {
+        self.frame_content_size
+    }
+}
+
+impl Checkpoint for FrameHeader {
+    fn save(&self, checkpoint: &mut Vec<u8>) {
+        self.window_size.save(checkpoint);
+        self.window_descriptor.save(checkpoint);
+        // The size, 0 when unknown, then whether it is known
+        self.frame_content_size.unwrap_or_default().save(checkpoint);
+        self.frame_content_size.is_some().save(checkpoint);
+        self.content_checksum_flag.save(checkpoint);
+    }
+
+    fn restore(checkpoint: &mut ForwardByteParser) -> Result<Self, CheckpointError> {
+        let window_size = usize::restore(checkpoint)?;
+        let window_descriptor = u8::restore(checkpoint)?;
+        let frame_content_size = u64::restore(checkpoint)?;
+        let has_content_size = bool::restore(checkpoint)?;
+        Ok(Self {
+            window_size,
+            window_descriptor,
+            frame_content_size: has_content_size.then_some(frame_content_size),
+            content_checksum_flag: bool::restore(checkpoint)?,
+        })
+    }
+}
+
+/// Attach an input offset to an error
+fn at<E: Into<Error>>(offset: usize) -> impl Fn(E) -> (Error, usize) {
+    move |err| (err.into(), offset)
+}
+
+/// Iterator parsing the frames of an input, to inspect them and decode only
+/// some of them
+///
+/// # Example
+/// ```
+/// # use zstd_core::{Frame, FrameIterator, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// for frame in FrameIterator::new(&bytes) {
+///     let frame = frame?;
+///     if let Frame::ZstandardFrame(zstandard) = &frame {
+///         assert_eq!(zstandard.header().window_size(), 1024);
+///         assert_eq!(zstandard.block_count(), 1);
+///     }
+///     assert_eq!(frame.decode(&Sequential)?, [0xCA, 0xFE]);
+/// }
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub struct FrameIterator<'a> {
+    parser: ForwardByteParser<'a>,
+}
+
+impl<'a> FrameIterator<'a> {
+    pub fn new(data: &'a [u8]) -> Self {
+        Self {
+            parser: ForwardByteParser::new(data),
+        }
+    }
+}
+
+impl<'a> Iterator for FrameIterator<'a> {
+    type Item = Result<Frame<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.parser.is_empty() {
+            return None;
+        }
+        Some(Frame::parse(&mut self.parser))
+    }
+}
+
+/// Frame description found by walking its frame and block headers, skipping
+/// the block contents, see `frames_metadata`
+#[derive(Debug)]
+pub struct FrameMetadata {
+    pub kind: FrameKind,
+    /// Offset of the frame in the input
+    pub offset: usize,
+    pub compressed_size: usize,
+    /// `None` for a skippable frame
+    pub header: Option<FrameHeader>,
+    pub block_count: usize,
+    pub checksum: Option<u32>,
+}
+
+impl FrameMetadata {
+    /// Scan the frame at the start of `bytes`, found at `offset` in the input
+    pub(crate) fn scan(bytes: &[u8], offset: usize) -> Result<Self> {
+        let mut input = ForwardByteParser::new(bytes);
+        let magic = input.le_u32()?;
+
+        let (kind, header, block_count, checksum) = match FrameKind::from_magic(magic) {
+            Some(FrameKind::Zstandard) => {
+                let header = FrameHeader::parse(&mut input)?;
+                let mut block_count = 0;
+                loop {
+                    let block_header = BlockHeader::parse(&mut input)?;
+                    input.slice(block_header.content_size())?;
+                    block_count += 1;
+                    if block_header.last_block {
+                        break;
+                    }
+                }
+                let checksum = if header.content_checksum_flag {
+                    Some(input.le_u32()?)
+                } else {
+                    None
+                };
+                (FrameKind::Zstandard, Some(header), block_count, checksum)
+            }
+            Some(FrameKind::Skippable) => {
+                let len = input.le_u32()?;
+                input.slice(len as usize)?;
+                (FrameKind::Skippable, None, 0, None)
+            }
+            None => return Err(Error::Frame(UnrecognizedMagic(magic))),
+        };
+
+        Ok(Self {
+            kind,
+            offset,
+            compressed_size: bytes.len() - input.len(),
+            header,
+            block_count,
+            checksum,
+        })
+    }
+}
+
+/// Iterator over the metadata of the frames of an input, see `frames_metadata`
+pub struct FramesMetadata<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FramesMetadata<'a> {
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes, offset: 0 }
+    }
+}
+
+impl Iterator for FramesMetadata<'_> {
+    type Item = Result<FrameMetadata>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.offset >= self.bytes.len() {
+            return None;
+        }
+
+        match FrameMetadata::scan(&self.bytes[self.offset..], self.offset) {
+            Ok(metadata) => {
+                self.offset += metadata.compressed_size;
+                Some(Ok(metadata))
+            }
+            Err(err) => {
+                // Stop after an error, the next frame cannot be located
+                self.offset = self.bytes.len();
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+/// Skippable frame found by `skippable_frames`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SkippableData<'a> {
+    /// Low 4 bits of the magic number, free for the application to use
+    pub magic_nibble: u8,
+    /// Offset of the frame in the input
+    pub offset: usize,
+    pub payload: &'a [u8],
+}
+
+/// Iterator over the skippable frames of an input, walking the headers of the
+/// other frames without decoding them
+pub struct SkippableFrames<'a> {
+    bytes: &'a [u8],
+    frames: FramesMetadata<'a>,
+}
+
+impl<'a> SkippableFrames<'a> {
+    #[must_use]
+    pub fn new(bytes: &'a [u8]) -> Self {
+        Self {
+            bytes,
+            frames: FramesMetadata::new(bytes),
+        }
+    }
+}
+
+impl<'a> Iterator for SkippableFrames<'a> {
+    type Item = Result<SkippableData<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for metadata in self.frames.by_ref() {
+            let metadata = match metadata {
+                Ok(metadata) => metadata,
+                Err(err) => return Some(Err(err)),
+            };
+            if metadata.kind == FrameKind::Skippable {
+                let frame = &self.bytes[metadata.offset..][..metadata.compressed_size];
+                return Some(Ok(SkippableData {
+                    magic_nibble: frame[0] & 0xF,
+                    offset: metadata.offset,
+                    payload: &frame[8..],
+                }));
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::ParsingError, super::Sequential, *};
+
+    mod frame {
+        use super::*;
+
+        mod parse {
+            use super::*;
+
+            #[test]
+            fn test_parse_empty() {
+                let mut parser = ForwardByteParser::new(&[]);
+                assert!(matches!(
+                    Frame::parse(&mut parser),
+                    Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                        requested: 4,
+                        available: 0
+                    }))
+                ))
+            }
+
+            #[test]
+            fn test_parse_skippable_frame() {
+                let mut parser = ForwardByteParser::new(&[
+                    // Skippable frame:
+                    0x53, 0x2a, 0x4d, 0x18, // magic:   0x184d2a53
+                    0x03, 0x00, 0x00, 0x00, // length:  3
+                    0x10, 0x20, 0x30, // content: 0x10 0x20 0x30
+                    0x40, // + extra byte
+                ]);
+                let Frame::SkippableFrame(skippable) = Frame::parse(&mut parser).unwrap() else {
+                    panic!("unexpected frame type")
+                };
+                assert_eq!(skippable.magic, 0x184d2a53);
+                assert_eq!(skippable.data, &[0x10, 0x20, 0x30]);
+                assert_eq!(skippable.magic_nibble(), 3);
+                assert_eq!(skippable.payload(), &[0x10, 0x20, 0x30]);
+                assert_eq!(parser.len(), 1);
+            }
+
+            #[test]
+            fn test_parse_truncated_skippable_frame() {
+                let mut parser = ForwardByteParser::new(&[
+                    // Skippable frame:
+                    0x50, 0x2a, 0x4d, 0x18, // magic:   0x184d2a50
+                    0x03, 0x00, 0x00, 0x00, // length:  3
+                    0x10, 0x20, // content: 0x10 0x20
+                ]);
+                assert!(matches!(
+                    Frame::parse(&mut parser),
+                    Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                        requested: 3,
+                        available: 2
+                    }))
+                ));
+            }
+
+            #[test]
+            fn test_parse_magic_only_skippable_frame() {
+                let mut parser = ForwardByteParser::new(&[
+                    // Skippable frame:
+                    0x50, 0x2a, 0x4d, 0x18, // magic:   0x184d2a50
+                ]);
+                assert!(matches!(
+                    Frame::parse(&mut parser),
+                    Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                        requested: 4,
+                        available: 0
+                    }))
+                ));
+            }
+
+            #[test]
+            fn test_parse_unknown_magic_number() {
+                let mut parser = ForwardByteParser::new(&[
+                    // Unknown frame: (similar to STANDARD_MAGIC_NUMBER with only last 4bits changing)
+                    0x20, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB520
+                ]);
+                assert!(matches!(
+                    Frame::parse(&mut parser),
+                    Err(Error::Frame(FrameError::UnrecognizedMagic(0xFD2FB520)))
+                ));
+            }
+
+            #[test]
+            fn test_parse_standard_frame() {
+                let mut parser = ForwardByteParser::new(&[
+                    // Standard frame:
+                    0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                    0x4, 0x0, // header + checksum flag
+                    0x1, 0x0, 0x0, // block
+                    0x12, 0x34, 0x56, 0x78, // checksum
+                ]);
+                let Frame::ZstandardFrame(standard) = Frame::parse(&mut parser).unwrap() else {
+                    panic!("unexpected frame type")
+                };
+                assert_eq!(standard.checksum, Some(0x78563412));
+                assert_eq!(standard.checksum(), Some(0x78563412));
+                assert_eq!(standard.block_count(), 1);
+                assert!(standard.header().content_checksum_flag());
+                assert_eq!(standard.header().content_size(), None);
+            }
+        }
+
+        mod decode {
+            use super::*;
+
+            fn decode_partial(input: &[u8]) -> (Vec<u8>, Option<(Error, usize)>) {
+                let mut parser = ForwardByteParser::new(input);
+                let mut output = Vec::new();
+                let result =
+                    Frame::decode_partial(&mut parser, input.len(), &mut output, &Sequential);
+                (output, result.err())
+            }
+
+            #[test]
+            fn test_decode_partial() {
+                let (output, err) = decode_partial(&[
+                    0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                    0x0, 0x0, // header
+                    0x10, 0x0, 0x0, // raw block, not last, len 2
+                    0xCA, 0xFE, // content
+                    0x11, 0x0, 0x0, // raw block, last, len 2
+                    0xBA, 0xBE, // content
+                ]);
+                assert_eq!(output, vec![0xCA, 0xFE, 0xBA, 0xBE]);
+                assert!(err.is_none());
+            }
+
+            #[test]
+            fn test_decode_partial_truncated_block() {
+                let (output, err) = decode_partial(&[
+                    0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                    0x0, 0x0, // header
+                    0x10, 0x0, 0x0, // raw block, not last, len 2
+                    0xCA, 0xFE, // content
+                    0x11, 0x0, 0x0,  // raw block, last, len 2
+                    0xBA, // truncated content
+                ]);
+                assert_eq!(output, vec![0xCA, 0xFE]);
+                assert!(matches!(
+                    err,
+                    Some((
+                        Error::Parsing(ParsingError::NotEnoughBytes {
+                            requested: 2,
+                            available: 1
+                        }),
+                        11
+                    ))
+                ));
+            }
+
+            #[test]
+            fn test_decode_partial_truncated_checksum() {
+                let (output, err) = decode_partial(&[
+                    0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                    0x4, 0x0, // header + checksum flag
+                    0x11, 0x0, 0x0, // raw block, last, len 2
+                    0xCA, 0xFE, // content
+                    0x12, 0x34, // truncated checksum
+                ]);
+                assert_eq!(output, vec![0xCA, 0xFE]);
+                assert!(matches!(err, Some((Error::Parsing(_), 11))));
+            }
+
+            #[cfg(feature = "xxhash")]
+            #[test]
+            fn test_decode_partial_checksum_mismatch() {
+                let (output, err) = decode_partial(&[
+                    0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                    0x4, 0x0, // header + checksum flag
+                    0x11, 0x0, 0x0, // raw block, last, len 2
+                    0xCA, 0xFE, // content
+                    0x12, 0x34, 0x56, 0x78, // checksum
+                ]);
+                assert!(output.is_empty());
+                assert!(matches!(
+                    err,
+                    Some((Error::Frame(FrameError::ChecksumMismatch), 11))
+                ));
+            }
+
+            #[test]
+            fn test_decode_skippable() {
+                let frame = Frame::SkippableFrame(SkippableFrame {
+                    magic: 0,
+                    data: &[],
+                });
+                assert_eq!(frame.decode(&Sequential).unwrap(), Vec::new());
+            }
+
+            fn raw_frame(
+                blocks: Vec<Block<'static>>,
+                checksum: Option<u32>,
+            ) -> ZstandardFrame<'static> {
+                ZstandardFrame {
+                    frame_header: FrameHeader {
+                        window_size: 1024,
+                        window_descriptor: 0,
+                        frame_content_size: None,
+                        content_checksum_flag: checksum.is_some(),
+                    },
+                    blocks,
+                    checksum,
+                }
+            }
+
+            #[test]
+            fn test_raw_content() {
+                let frame = raw_frame(vec![Block::Raw(&[0xCA, 0xFE]), Block::Raw(&[0xBE])], None);
+                assert_eq!(
+                    frame.raw_content().unwrap(),
+                    Some(vec![&[0xCA, 0xFE][..], &[0xBE]])
+                );
+
+                let frame = raw_frame(
+                    vec![
+                        Block::Raw(&[0xCA, 0xFE]),
+                        Block::Rle {
+                            byte: 0xBA,
+                            repeat: 1,
+                        },
+                    ],
+                    None,
+                );
+                assert_eq!(frame.raw_content().unwrap(), None);
+            }
+
+            #[cfg(feature = "xxhash")]
+            #[test]
+            fn test_raw_content_checksum_mismatch() {
+                let frame = raw_frame(vec![Block::Raw(&[0xCA, 0xFE])], Some(0));
+                assert!(matches!(
+                    frame.raw_content(),
+                    Err(Error::Frame(ChecksumMismatch))
+                ));
+            }
+
+            #[test]
+            fn test_decode_standard() {
+                let frame = Frame::ZstandardFrame(ZstandardFrame {
+                    frame_header: FrameHeader {
+                        window_size: 0,
+                        window_descriptor: 0,
+                        frame_content_size: None,
+                        content_checksum_flag: false,
+                    },
+                    blocks: vec![
+                        Block::Rle {
+                            byte: 0xAA,
+                            repeat: 2,
+                        },
+                        Block::Raw(&[0xCA, 0xFE]),
+                        Block::Rle {
+                            byte: 0xBA,
+                            repeat: 1,
+                        },
+                        Block::Raw(&[0xBE]),
+                    ],
+                    checksum: None,
+                });
+                assert_eq!(
+                    frame.decode(&Sequential).unwrap(),
+                    vec![0xAA, 0xAA, 0xCA, 0xFE, 0xBA, 0xBE]
+                );
+            }
+
+            #[test]
+            fn test_decode_append() {
+                let frame = raw_frame(vec![Block::Raw(&[0xCA, 0xFE]), Block::Raw(&[0xBE])], None);
+                assert_eq!(frame.max_content_size(), 3);
+                let mut output = vec![0xAA];
+                Frame::ZstandardFrame(frame)
+                    .decode_append(&mut output, &Sequential)
+                    .unwrap();
+                assert_eq!(output, [0xAA, 0xCA, 0xFE, 0xBE]);
+
+                // A corrupted block leaves the output as it was
+                let mut frame = raw_frame(vec![], None);
+                frame.blocks = vec![
+                    Block::Raw(&[0xBA]),
+                    Block::parse(
+                        &mut ForwardByteParser::new(&[
+                            0x25, 0x0, 0x0, // compressed block, last, len 4
+                            0x0, // raw literals, len 0
+                            0x1, 0x0, 0x0, // 1 sequence, predefined tables
+                        ]),
+                        1024,
+                    )
+                    .unwrap()
+                    .0,
+                ];
+                assert!(Frame::ZstandardFrame(frame)
+                    .decode_append(&mut output, &Sequential)
+                    .is_err());
+                assert_eq!(output, [0xAA, 0xCA, 0xFE, 0xBE]);
+            }
+
+            #[test]
+            fn test_decode_with_callback() {
+                let blocks = || {
+                    vec![
+                        Block::Raw(&[0xCA, 0xFE]),
+                        Block::Rle {
+                            byte: 0xBE,
+                            repeat: 3,
+                        },
+                        Block::Raw(&[]),
+                    ]
+                };
+                let mut context = DecodingContext::default();
+                let mut given = Vec::new();
+                Frame::ZstandardFrame(raw_frame(blocks(), None))
+                    .decode_with_callback(&mut context, &Sequential, |block| {
+                        given.push(block.to_vec());
+                    })
+                    .unwrap();
+                assert_eq!(given, [&[0xCA, 0xFE][..], &[0xBE; 3], &[]]);
+                assert_eq!(context.decoded, [0xCA, 0xFE, 0xBE, 0xBE, 0xBE]);
+
+                // The blocks are given before the checksum is verified
+                if !cfg!(feature = "xxhash") {
+                    return;
+                }
+                given.clear();
+                let frame = raw_frame(blocks(), Some(0));
+                assert!(matches!(
+                    Frame::ZstandardFrame(frame).decode_with_callback(
+                        &mut context,
+                        &Sequential,
+                        |block| given.push(block.to_vec())
+                    ),
+                    Err(Error::Frame(ChecksumMismatch))
+                ));
+                assert_eq!(given.len(), 3);
+            }
+
+            #[test]
+            fn test_single_segment() {
+                let decode_all = |bytes: &[u8]| {
+                    let decoded = crate::decode(bytes, &Sequential);
+                    let mut stream = crate::StreamDecoder::with_executor(Sequential);
+                    let mut streamed = Vec::new();
+                    let result = stream.decode(bytes, &mut streamed).and(stream.finish());
+                    assert_eq!(decoded.is_ok(), result.is_ok());
+                    assert_eq!(
+                        crate::decode_partial(bytes, &Sequential).is_ok(),
+                        decoded.is_ok()
+                    );
+                    decoded
+                };
+
+                // SSF, content size 2: the window
+                let ok = [
+                    0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x2, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+                ];
+                assert_eq!(decode_all(&ok).unwrap(), [0xCA, 0xFE]);
+
+                // A block larger than the window
+                let mut larger = ok;
+                larger[5] = 0x1;
+                assert!(matches!(
+                    decode_all(&larger),
+                    Err(Error::Block(crate::BlockError::MaxBlockSize {
+                        got: 2,
+                        allowed: 1
+                    }))
+                ));
+
+                // Less content than announced
+                let mut smaller = ok;
+                smaller[5] = 0x3;
+                assert!(matches!(
+                    decode_all(&smaller),
+                    Err(Error::Frame(ContentSizeMismatch {
+                        announced: 3,
+                        decoded: 2
+                    }))
+                ));
+
+                // Content larger than 128 KB: blocks are still limited to 128 KB
+                let large = [
+                    0x28, 0xB5, 0x2F, 0xFD, 0xA0, 0x40, 0x0D, 0x03,
+                    0x0, // SSF, content size 200000
+                    0x02, 0x00, 0x10, 0xAA, // rle block, repeat 131072
+                    0x03, 0x6A, 0x08, 0xBB, // rle block, last, repeat 68928
+                ];
+                let decoded = decode_all(&large).unwrap();
+                assert_eq!(decoded.len(), 200_000);
+                assert!(decoded[131_071] == 0xAA && decoded[131_072] == 0xBB);
+            }
+
+            #[test]
+            fn test_repeat_mode_scope() {
+                // Block of raw literal "a" and 1 sequence in RLE modes: "aaaa"
+                let rle = [
+                    0x45, 0x0, 0x0, 0x08, b'a', 0x01, 0x54, 0x01, 0x02, 0x00, 0x04,
+                ];
+                // Same block with the sequence in Repeat modes
+                let repeat = [0x2D, 0x0, 0x0, 0x08, b'a', 0x01, 0xFC, 0x04];
+                // 1 KB window, no content size
+                let frame = |blocks: &[&[u8]]| {
+                    let mut frame = vec![0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0];
+                    blocks
+                        .iter()
+                        .for_each(|block| frame.extend_from_slice(block));
+                    frame
+                };
+                fn is_repeat_error<T>(result: Result<T>) -> bool {
+                    matches!(
+                        result,
+                        Err(Error::Sequences(crate::SequencesError::RepeatWithoutTable(
+                            _
+                        )))
+                    )
+                }
+
+                // Repeat after a previous block of the frame
+                let mut not_last = rle;
+                not_last[0] = 0x44;
+                let two_blocks = frame(&[&not_last, &repeat]);
+                assert_eq!(
+                    crate::decode(&two_blocks, &Sequential).unwrap(),
+                    b"aaaaaaaa"
+                );
+
+                // Repeat in the first block of a frame
+                let first = frame(&[&repeat]);
+                assert!(is_repeat_error(crate::decode(&first, &Sequential)));
+                assert!(is_repeat_error(
+                    crate::decode_partial(&first, &Sequential).map_err(|err| err.source)
+                ));
+                let mut stream = crate::StreamDecoder::with_executor(Sequential);
+                assert!(is_repeat_error(stream.decode(&first, &mut Vec::new())));
+
+                // The tables of the previous frame are not reused
+                let mut bytes = frame(&[&rle]);
+                bytes.extend_from_slice(&first);
+                assert!(is_repeat_error(crate::decode(&bytes, &Sequential)));
+                let mut context = DecodingContext::default();
+                let mut frames = FrameIterator::new(&bytes);
+                let previous = frames.next().unwrap().unwrap();
+                assert_eq!(
+                    previous
+                        .decode_with_context(&mut context, &Sequential)
+                        .unwrap(),
+                    b"aaaa"
+                );
+                let next = frames.next().unwrap().unwrap();
+                assert!(is_repeat_error(
+                    next.decode_with_context(&mut context, &Sequential)
+                ));
+            }
+        }
+    }
+
+    mod frame_header {
+        use super::*;
+
+        #[rustfmt::skip]
+        mod parse {
+            use super::*;
+
+            #[test]
+            fn test_decode_null_frame_header() {
+                let mut parser = ForwardByteParser::new(&[0x0, 0xFF]);
+                let frame_header = FrameHeader::parse(&mut parser).unwrap();
+                assert!(!frame_header.content_checksum_flag);
+                assert_eq!(frame_header.window_descriptor, 0xFF);
+            }
+
+            #[test]
+            fn test_empty_frame_header() {
+                let mut parser = ForwardByteParser::new(&[]);
+                assert!(matches!(
+                    FrameHeader::parse(&mut parser),
+                    Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                        requested: 1,
+                        available: 0
+                    }))
+                ))
+            }
+
+            #[test]
+            fn test_parse_frame_header() {
+                let mut parser = ForwardByteParser::new(&[
+                    0b1010_0110,            // FCS 4bytes, no window descriptor, 2byte dict id, checksum
+                    0x0, 0x0,               // dict id
+                    0x10, 0x20, 0x30, 0x40, // FCS
+                    0x42,                   // +extra byte
+                ]);
+                let frame_header = FrameHeader::parse(&mut parser).unwrap();
+                assert!(frame_header.content_checksum_flag);
+                assert_eq!(frame_header.window_descriptor, 0);
+                assert_eq!(frame_header.frame_content_size, Some(0x40_30_20_10));
+                assert_eq!(parser.len(), 1);
+            }
+
+            #[test]
+            fn test_parse_single_segment_flag_true() {
+                let mut parser = ForwardByteParser::new(
+                    &[
+                        0b0010_0000, // SSF true
+                        0xAD,        // FCS (SSF)
+                        0x01,        // +extra byte
+                    ],
+                );
+                let frame_header = FrameHeader::parse(&mut parser).unwrap();
+                assert!(!frame_header.content_checksum_flag);
+                assert_eq!(frame_header.window_descriptor, 0);
+                assert_eq!(frame_header.frame_content_size, Some(0xAD));
+                assert_eq!(frame_header.content_size(), Some(0xAD));
+                assert_eq!(parser.len(), 1);
+            }
+
+            #[test]
+            fn test_parse_single_segment_flag_false() {
+                let mut parser = ForwardByteParser::new(
+                    &[
+                        0b0000_0000, // SSF false
+                        0xAD,        // window descriptor (SSF)
+                        0x01,        // +extra byte
+                    ],
+                );
+                let frame_header = FrameHeader::parse(&mut parser).unwrap();
+                assert!(!frame_header.content_checksum_flag);
+                assert_eq!(frame_header.window_descriptor, 0xAD);
+                assert_eq!(frame_header.frame_content_size, None);
+                assert_eq!(parser.len(), 1);
+            }
+
+            #[test]
+            fn test_parse_empty_and_unknown_content_size() {
+                // SSF, content size 0: known to be empty
+                let header = FrameHeader::parse(&mut ForwardByteParser::new(&[0x20, 0x0])).unwrap();
+                assert_eq!(header.content_size(), Some(0));
+                assert_eq!(header.window_size(), 0);
+                // No SSF, no content size: unknown
+                let header = FrameHeader::parse(&mut ForwardByteParser::new(&[0x0, 0x0])).unwrap();
+                assert_eq!(header.content_size(), None);
+                assert_eq!(header.window_size(), 1024);
+                // 8 bytes content size, beyond 32 bits
+                let header = FrameHeader::parse(&mut ForwardByteParser::new(&[
+                    0xC0, 0x0, // 8 bytes content size, window 1KB
+                    0x0, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0,
+                ])).unwrap();
+                assert_eq!(header.content_size(), Some(1 << 32));
+                assert!(header.check_content_size(0).is_err());
+            }
+        }
+    }
+
+    mod frame_kind {
+        use super::*;
+
+        #[test]
+        fn test_from_magic() {
+            assert_eq!(
+                FrameKind::from_magic(0xFD2F_B528),
+                Some(FrameKind::Zstandard)
+            );
+            assert_eq!(
+                FrameKind::from_magic(0x184D_2A50),
+                Some(FrameKind::Skippable)
+            );
+            assert_eq!(
+                FrameKind::from_magic(0x184D_2A5F),
+                Some(FrameKind::Skippable)
+            );
+            assert_eq!(FrameKind::from_magic(0xFD2F_B520), None);
+            assert_eq!(FrameKind::from_magic(0), None);
+        }
+    }
+
+    mod compressed_size {
+        use super::*;
+
+        const FRAME: [u8; 20] = [
+            0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+            0x04, 0x0, // header: checksum flag
+            0x10, 0x0, 0x0, // raw block, not last, len 2
+            0xCA, 0xFE, // content
+            0x13, 0x0, 0x0,  // rle block, last, repeat 2
+            0xBA, // content
+            0x56, 0x21, 0x60, 0x39, // checksum
+            0x42, // next frame
+        ];
+
+        #[test]
+        fn test_compressed_size() {
+            assert_eq!(Frame::compressed_size(&FRAME).unwrap(), 19);
+            assert_eq!(
+                Frame::compressed_size(&[0x50, 0x2A, 0x4D, 0x18, 0x1, 0x0, 0x0, 0x0, 0x42])
+                    .unwrap(),
+                9
+            );
+        }
+
+        #[test]
+        fn test_compressed_size_incomplete() {
+            for len in [2, 5, 8, 10, 14, 18] {
+                assert!(matches!(
+                    Frame::compressed_size(&FRAME[..len]),
+                    Err(Error::Parsing(ParsingError::NotEnoughBytes { .. }))
+                ));
+            }
+        }
+    }
+
+    mod frame_iterator {
+        use core::panic;
+
+        use super::*;
+
+        #[test]
+        fn test_iterator_empty() {
+            let mut iterator = FrameIterator::new(&[]);
+            assert!(iterator.next().is_none());
+        }
+
+        #[test]
+        fn test_iterator() {
+            let mut iterator = FrameIterator::new(&[
+                // Skippable frame:
+                0x53, 0x2a, 0x4d, 0x18, // magic:   0x184d2a53
+                0x03, 0x00, 0x00, 0x00, // length:  3
+                0x10, 0x20, 0x30, // content: 0x10 0x20 0x30
+                // Standard frame:
+                0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                0x4, 0x0, // header + checksum flag
+                0x1, 0x0, 0x0, // block
+                0x12, 0x34, 0x56, 0x78, // checksum
+            ]);
+
+            let Frame::SkippableFrame(frame) = iterator.next().unwrap().unwrap() else {
+                panic!("unexpected frame type")
+            };
+            assert_eq!(frame.magic, 0x184d2a53);
+            assert_eq!(frame.data, &[0x10, 0x20, 0x30]);
+
+            let Frame::ZstandardFrame(frame) = iterator.next().unwrap().unwrap() else {
+                panic!("unexpected frame type")
+            };
+            assert_eq!(frame.checksum, Some(0x78563412));
+
+            assert!(iterator.next().is_none());
+        }
+    }
+
+    mod skippable_frames {
+        use super::*;
+
+        #[test]
+        fn test_skippable_frames() {
+            let bytes = [
+                0x53, 0x2A, 0x4D, 0x18, // skippable magic:   0x184D2A53
+                0x01, 0x00, 0x00, 0x00, // length:  1
+                0x10, // content
+                0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                0x0, 0x0, // header
+                0x01, 0x0, 0x0, // raw block, last, len 0
+                0x5F, 0x2A, 0x4D, 0x18, // skippable magic:   0x184D2A5F
+                0x00, 0x00, 0x00, 0x00, // length:  0
+            ];
+            let frames = SkippableFrames::new(&bytes)
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(
+                frames,
+                [
+                    SkippableData {
+                        magic_nibble: 3,
+                        offset: 0,
+                        payload: &[0x10],
+                    },
+                    SkippableData {
+                        magic_nibble: 0xF,
+                        offset: 18,
+                        payload: &[],
+                    },
+                ]
+            );
+        }
+
+        #[test]
+        fn test_skippable_frames_truncated() {
+            let bytes = [0x53, 0x2A, 0x4D, 0x18, 0x02, 0x00, 0x00, 0x00, 0x10];
+            let mut frames = SkippableFrames::new(&bytes);
+            assert!(matches!(
+                frames.next(),
+                Some(Err(Error::Parsing(ParsingError::NotEnoughBytes { .. })))
+            ));
+            assert!(frames.next().is_none());
+        }
+    }
+
+    mod frames_metadata {
+        use super::*;
+
+        #[test]
+        fn test_frames_metadata() {
+            let bytes = [
+                0x53, 0x2A, 0x4D, 0x18, // skippable magic:   0x184D2A53
+                0x01, 0x00, 0x00, 0x00, // length:  1
+                0x10, // content
+                0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                0x24, 0x4, // header: checksum flag, single segment, content size 4
+                0x10, 0x0, 0x0, // raw block, not last, len 2
+                0xCA, 0xFE, // content
+                0x13, 0x0, 0x0,  // rle block, last, repeat 2
+                0xBA, // content
+                0x12, 0x34, 0x56, 0x78, // checksum
+            ];
+            let frames = FramesMetadata::new(&bytes)
+                .collect::<Result<Vec<_>>>()
+                .unwrap();
+            assert_eq!(frames.len(), 2);
+
+            assert_eq!(frames[0].kind, FrameKind::Skippable);
+            assert_eq!((frames[0].offset, frames[0].compressed_size), (0, 9));
+            assert!(frames[0].header.is_none());
+
+            assert_eq!(frames[1].kind, FrameKind::Zstandard);
+            assert_eq!((frames[1].offset, frames[1].compressed_size), (9, 19));
+            assert_eq!(frames[1].block_count, 2);
+            assert_eq!(frames[1].checksum, Some(0x78563412));
+            let header = frames[1].header.as_ref().unwrap();
+            assert_eq!(header.content_size(), Some(4));
+        }
+
+        #[test]
+        fn test_frames_metadata_truncated() {
+            let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x10, 0x0, 0x0, 0xCA];
+            let mut frames = FramesMetadata::new(&bytes);
+            assert!(matches!(
+                frames.next(),
+                Some(Err(Error::Parsing(ParsingError::NotEnoughBytes { .. })))
+            ));
+            assert!(frames.next().is_none());
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use super::format::{skippable_magic, SKIPPABLE_HEADER_SIZE};
+use super::{frames_metadata, ForwardByteParser, FrameKind, ParsingError, Result};
+use alloc::vec::Vec;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum IndexError {
+    #[error("Truncated frame index: {0}")]
+    Truncated(#[from] ParsingError),
+
+    #[error("Corrupted frame index")]
+    Corrupted,
+
+    #[error("Frame index of {0} entries too large for a skippable frame")]
+    TooLarge(usize),
+}
+
+/// Low 4 bits of the magic number of the skippable frame holding the index
+pub const INDEX_MAGIC_NIBBLE: u8 = 0xB;
+
+/*
+    The index is a skippable frame appended to the archive, so that it is found
+    from the end of the input without scanning the frames. Its payload is an
+    entry per Zstandard frame, its offset, compressed size and content size
+    (u64::MAX when unknown) on 8 bytes each, followed by a footer: the number
+    of entries on 4 bytes and `INDEX_MAGIC`. Integers are little-endian.
+*/
+const INDEX_MAGIC: [u8; 4] = *b"ZSIX";
+const ENTRY_SIZE: usize = 24;
+const FOOTER_SIZE: usize = 8;
+const UNKNOWN_CONTENT_SIZE: u64 = u64::MAX;
+
+/// Zstandard frame of an archive, see `build_frame_index`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameEntry {
+    /// Offset of the frame in the input
+    pub input_offset: usize,
+    pub compressed_len: usize,
+    /// Content size announced by the frame header, `None` when it does not
+    pub content_size: Option<u64>,
+}
+
+impl FrameEntry {
+    /// Bytes of the frame in `bytes`, to parse with `Frame::parse_sized`.
+    /// `None` when the entry is past the end of `bytes`.
+    #[must_use]
+    pub fn frame<'a>(&self, bytes: &'a [u8]) -> Option<&'a [u8]> {
+        bytes.get(self.input_offset..)?.get(..self.compressed_len)
+    }
+}
+
+/// Index of the Zstandard frames of `bytes`, found from the headers without
+/// decoding anything. Skippable frames, such as an index, are not listed.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{build_frame_index, FrameEntry};
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+///     0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x2, 0x11, 0x0, 0x0, 0xBA, 0xBE,
+/// ];
+/// let index = build_frame_index(&bytes)?;
+/// assert_eq!(index[1], FrameEntry { input_offset: 11, compressed_len: 11, content_size: Some(2) });
+/// assert_eq!(index[0].content_size, None);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn build_frame_index(bytes: &[u8]) -> Result<Vec<FrameEntry>> {
+    let mut index = Vec::new();
+    for metadata in frames_metadata(bytes) {
+        let metadata = metadata?;
+        if let (FrameKind::Zstandard, Some(header)) = (metadata.kind, metadata.header) {
+            index.push(FrameEntry {
+                input_offset: metadata.offset,
+                compressed_len: metadata.compressed_size,
+                content_size: header.content_size(),
+            });
+        }
+    }
+    Ok(index)
+}
+
+/// Skippable frame holding `index`, to append to its archive for
+/// `read_frame_index` to find
+pub fn write_frame_index(index: &[FrameEntry]) -> Result<Vec<u8>> {
+    let count = u32::try_from(index.len()).map_err(|_| IndexError::TooLarge(index.len()))?;
+    let size = u32::try_from(index.len() * ENTRY_SIZE + FOOTER_SIZE)
+        .map_err(|_| IndexError::TooLarge(index.len()))?;
+
+    let mut frame = Vec::with_capacity(SKIPPABLE_HEADER_SIZE + size as usize);
+    let magic = skippable_magic(INDEX_MAGIC_NIBBLE);
+    frame.extend_from_slice(&magic.to_le_bytes());
+    frame.extend_from_slice(&size.to_le_bytes());
+    for entry in index {
+        frame.extend_from_slice(&(entry.input_offset as u64).to_le_bytes());
+        frame.extend_from_slice(&(entry.compressed_len as u64).to_le_bytes());
+        let content_size = entry.content_size.unwrap_or(UNKNOWN_CONTENT_SIZE);
+        frame.extend_from_slice(&content_size.to_le_bytes());
+    }
+    frame.extend_from_slice(&count.to_le_bytes());
+    frame.extend_from_slice(&INDEX_MAGIC);
+    Ok(frame)
+}
+
+/// Index written by `write_frame_index` at the end of `bytes`, read without
+/// scanning the frames. `None` when `bytes` does not end with an index. The
+/// entries are checked to be in order and before the index, not to point at
+/// frames: parse them with `Frame::parse_sized`.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{build_frame_index, read_frame_index, write_frame_index};
+/// let mut bytes = vec![0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(read_frame_index(&bytes)?, None);
+///
+/// let index = build_frame_index(&bytes)?;
+/// bytes.extend_from_slice(&write_frame_index(&index)?);
+/// assert_eq!(read_frame_index(&bytes)?, Some(index));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn read_frame_index(bytes: &[u8]) -> Result<Option<Vec<FrameEntry>>> {
+    let Some(footer) = bytes
+        .len()
+        .checked_sub(FOOTER_SIZE)
+        .map(|start| &bytes[start..])
+    else {
+        return Ok(None);
+    };
+    if footer[4..] != INDEX_MAGIC {
+        return Ok(None);
+    }
+    let count = ForwardByteParser::new(footer).le(4)?;
+
+    // Skippable frame holding the entries and the footer
+    let size = count
+        .checked_mul(ENTRY_SIZE)
+        .and_then(|size| size.checked_add(FOOTER_SIZE))
+        .ok_or(IndexError::Corrupted)?;
+    let start = bytes
+        .len()
+        .checked_sub(size + SKIPPABLE_HEADER_SIZE)
+        .ok_or(IndexError::Corrupted)?;
+    let mut input = ForwardByteParser::new(&bytes[start..]);
+    let magic = skippable_magic(INDEX_MAGIC_NIBBLE);
+    if input.le_u32()? != magic || input.le(4)? != size {
+        return Err(IndexError::Corrupted.into());
+    }
+
+    let mut index = Vec::with_capacity(count);
+    let mut end = 0;
+    for _ in 0..count {
+        let entry = FrameEntry {
+            input_offset: input.le(8)?,
+            compressed_len: input.le(8)?,
+            content_size: Some(input.le_u64()?).filter(|&size| size != UNKNOWN_CONTENT_SIZE),
+        };
+        if entry.input_offset < end {
+            return Err(IndexError::Corrupted.into());
+        }
+        end = entry
+            .input_offset
+            .checked_add(entry.compressed_len)
+            .filter(|&end| end <= start)
+            .ok_or(IndexError::Corrupted)?;
+        index.push(entry);
+    }
+    Ok(Some(index))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{stream::tests::FRAMES, Error, Frame, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_build_frame_index() {
+        let index = build_frame_index(&FRAMES).unwrap();
+        assert_eq!(
+            index,
+            [
+                FrameEntry {
+                    input_offset: 10,
+                    compressed_len: 19,
+                    content_size: None
+                },
+                FrameEntry {
+                    input_offset: 29,
+                    compressed_len: 9,
+                    content_size: None
+                }
+            ]
+        );
+        assert_eq!(index[0].frame(&FRAMES), Some(&FRAMES[10..29]));
+        assert_eq!(index[1].frame(&FRAMES[..37]), None);
+        assert!(build_frame_index(&[]).unwrap().is_empty());
+        assert!(build_frame_index(&FRAMES[..37]).is_err());
+    }
+
+    #[test]
+    fn test_read_frame_index() {
+        let index = build_frame_index(&FRAMES).unwrap();
+        let mut bytes = FRAMES.to_vec();
+        bytes.extend_from_slice(&write_frame_index(&index).unwrap());
+        assert_eq!(read_frame_index(&bytes).unwrap(), Some(index.clone()));
+        // The index frame is skipped when decoding and indexing
+        assert_eq!(build_frame_index(&bytes).unwrap(), index);
+        assert_eq!(
+            super::super::decode(&bytes, &Sequential).unwrap(),
+            super::super::decode(&FRAMES, &Sequential).unwrap()
+        );
+
+        // Random access
+        let frame = index[0].frame(&bytes).unwrap();
+        let decoded = Frame::parse_sized(frame).unwrap().decode(&Sequential);
+        assert_eq!(decoded.unwrap(), [0xCA, 0xFE, 0xBA, 0xBA]);
+
+        let empty = write_frame_index(&[]).unwrap();
+        assert_eq!(read_frame_index(&empty).unwrap(), Some(Vec::new()));
+        assert_eq!(read_frame_index(&FRAMES).unwrap(), None);
+        assert_eq!(read_frame_index(&[]).unwrap(), None);
+
+        // Entries past the index, and out of order
+        let mut corrupted = bytes.clone();
+        corrupted[FRAMES.len() + 8 + 24] = 0xFF;
+        assert!(matches!(
+            read_frame_index(&corrupted),
+            Err(Error::Index(IndexError::Corrupted))
+        ));
+        let swapped = write_frame_index(&[index[1], index[0]]).unwrap();
+        let corrupted = [&FRAMES[..], &swapped].concat();
+        assert!(matches!(
+            read_frame_index(&corrupted),
+            Err(Error::Index(IndexError::Corrupted))
+        ));
+        // Truncated
+        assert!(read_frame_index(&bytes[FRAMES.len() + 1..]).is_err());
+    }
+}
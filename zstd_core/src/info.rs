@@ -0,0 +1,173 @@
+use super::{
+    BlockError, BlockHeader, Error, ForwardByteParser, FrameHeader, FrameMetadata, LiteralsSection,
+    Result, Sequences, COMPRESSED_BLOCK_FLAG, RAW_BLOCK_FLAG, RLE_BLOCK_FLAG,
+};
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlockType {
+    Raw,
+    Rle,
+    Compressed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LiteralsType {
+    Raw,
+    Rle,
+    /// Huffman-coded with a table described in the section
+    Compressed,
+    /// Huffman-coded with the table of the previous compressed section
+    Treeless,
+}
+
+/// Compression mode of the literals lengths, offsets or match lengths codes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SequencesMode {
+    Predefined,
+    Rle,
+    Fse,
+    Repeat,
+}
+
+/// Block found by walking its header, and the headers of its sections when
+/// compressed, see `FrameMetadata::blocks`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Offset of the block header in the input
+    pub offset: usize,
+    pub block_type: BlockType,
+    /// Size of the content in the input, the repeat count for RLE blocks
+    pub block_size: usize,
+    pub last_block: bool,
+    /// `None` unless the block is compressed
+    pub sections: Option<SectionsInfo>,
+}
+
+/// Literals and sequences sections of a compressed block
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SectionsInfo {
+    pub literals_type: LiteralsType,
+    pub regenerated_size: usize,
+    /// Number of Huffman-coded streams, 1 for raw and RLE literals
+    pub streams: usize,
+    pub sequences: usize,
+    /// Modes of the literals lengths, offsets and match lengths codes, `None`
+    /// without sequences
+    pub modes: Option<[SequencesMode; 3]>,
+}
+
+impl SectionsInfo {
+    fn parse(content: &[u8]) -> Result<Self> {
+        let mut input = ForwardByteParser::new(content);
+        let literals = LiteralsSection::parse(&mut input)?;
+        let sequences = Sequences::parse(&mut input)?;
+
+        Ok(Self {
+            literals_type: literals.literals_type(),
+            regenerated_size: literals.regenerated_size(),
+            streams: literals.streams(),
+            sequences: sequences.len(),
+            modes: sequences.modes(),
+        })
+    }
+}
+
+impl FrameMetadata {
+    /// Walk the blocks of the frame, found in `bytes`: the sections of the
+    /// compressed blocks are parsed but not decoded. Empty for a skippable frame.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{frames_metadata, BlockType};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let metadata = frames_metadata(&bytes).next().unwrap()?;
+    /// let blocks = metadata.blocks(&bytes)?;
+    /// assert_eq!((blocks[0].offset, blocks[0].block_type), (6, BlockType::Raw));
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    pub fn blocks(&self, bytes: &[u8]) -> Result<Vec<BlockInfo>> {
+        let mut blocks = Vec::new();
+        if self.header.is_none() {
+            return Ok(blocks);
+        }
+
+        let mut input = ForwardByteParser::new(bytes);
+        input.slice(self.offset)?;
+        let frame_len = input.len();
+        // Magic number and frame header, already parsed for the metadata
+        input.slice(4)?;
+        FrameHeader::parse(&mut input)?;
+
+        loop {
+            let offset = self.offset + frame_len - input.len();
+            let header = BlockHeader::parse(&mut input)?;
+            let content = input.slice(header.content_size())?;
+
+            let (block_type, sections) = match header.block_type {
+                RAW_BLOCK_FLAG => (BlockType::Raw, None),
+                RLE_BLOCK_FLAG => (BlockType::Rle, None),
+                COMPRESSED_BLOCK_FLAG => {
+                    (BlockType::Compressed, Some(SectionsInfo::parse(content)?))
+                }
+                _ => return Err(Error::Block(BlockError::ReservedBlockType)),
+            };
+            blocks.push(BlockInfo {
+                offset,
+                block_type,
+                block_size: header.block_size,
+                last_block: header.last_block,
+                sections,
+            });
+
+            if header.last_block {
+                return Ok(blocks);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames_metadata;
+
+    #[test]
+    fn test_blocks() {
+        let bytes = [
+            0x50, 0x2A, 0x4D, 0x18, 0x0, 0x0, 0x0, 0x0, // skippable
+            0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, // magic, header
+            0x12, 0x0, 0x0, 0xAA, // rle block, len 2
+            0x2D, 0x0, 0x0, // compressed block, last, len 5
+            0x18, b'c', b'd', b'e', // raw literals, len 3
+            0x0,  // no sequences
+        ];
+        let frames = frames_metadata(&bytes).collect::<Result<Vec<_>>>().unwrap();
+        assert!(frames[0].blocks(&bytes).unwrap().is_empty());
+        assert_eq!(
+            frames[1].blocks(&bytes).unwrap(),
+            [
+                BlockInfo {
+                    offset: 14,
+                    block_type: BlockType::Rle,
+                    block_size: 2,
+                    last_block: false,
+                    sections: None,
+                },
+                BlockInfo {
+                    offset: 18,
+                    block_type: BlockType::Compressed,
+                    block_size: 5,
+                    last_block: true,
+                    sections: Some(SectionsInfo {
+                        literals_type: LiteralsType::Raw,
+                        regenerated_size: 3,
+                        streams: 1,
+                        sequences: 0,
+                        modes: None,
+                    }),
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,533 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+#![allow(
+    clippy::missing_errors_doc,
+    clippy::module_name_repetitions,
+    clippy::enum_glob_use,
+    clippy::wildcard_imports,
+    clippy::struct_field_names
+)]
+
+extern crate alloc;
+
+#[macro_use]
+mod trace;
+
+mod backend;
+mod block;
+#[cfg(feature = "block-api")]
+mod block_api;
+mod checkpoint;
+mod checksum;
+mod chunks;
+mod decoders;
+mod dot;
+mod executor;
+pub mod format;
+mod frame;
+mod index;
+mod info;
+mod literals;
+mod metadata;
+mod operations;
+pub mod parsing;
+mod pzstd;
+mod repair;
+mod rewrite;
+mod sequences;
+mod sizes;
+mod split;
+mod stats;
+mod stream;
+mod verify;
+mod warning;
+
+use backend::BlockDecoder;
+use block::*;
+use checkpoint::*;
+use decoders::*;
+use literals::*;
+use parsing::*;
+use sequences::*;
+
+use alloc::{string::String, vec::Vec};
+
+pub use backend::Backend;
+pub use block::{BlockError, BlockHeader};
+#[cfg(feature = "block-api")]
+pub use block_api::decode_block;
+pub use checkpoint::CheckpointError;
+#[cfg(feature = "xxhash")]
+pub use checksum::XxHash64;
+pub use checksum::{ContentHasher, DefaultHasher, NoChecksum};
+pub use chunks::{decoded_chunks, DecodedChunks};
+#[cfg(feature = "std")]
+pub use decoders::WindowWriter;
+pub use decoders::{
+    ContextError, DecoderError, DecodingContext, FseError, HuffmanError, OutputSink, RingBuffer,
+    SliceSink, DEFAULT_MAX_WINDOW_SIZE,
+};
+pub use dot::{block_tables_dot, TablesDot};
+pub use executor::{BufferKind, Executor, Sequential};
+pub use format::{WINDOW_SIZE_MAX, WINDOW_SIZE_MIN};
+pub use frame::{
+    Frame, FrameError, FrameHeader, FrameIterator, FrameKind, FrameMetadata, FramesMetadata,
+    SkippableData, SkippableFrame, SkippableFrames, ZstandardFrame,
+};
+pub use index::{
+    build_frame_index, read_frame_index, write_frame_index, FrameEntry, IndexError,
+    INDEX_MAGIC_NIBBLE,
+};
+pub use info::{BlockInfo, BlockType, LiteralsType, SectionsInfo, SequencesMode};
+pub use literals::LiteralsError;
+pub use metadata::{read_metadata, write_metadata, Metadata, MetadataError, METADATA_MAGIC_NIBBLE};
+pub use operations::{decode_sequences, BlockSequences};
+pub use pzstd::pzstd_frames;
+pub use repair::{repair, Damage, Repair};
+#[cfg(feature = "xxhash")]
+pub use rewrite::add_checksums;
+pub use rewrite::strip_checksums;
+pub use sequences::{SequenceCommand, SequencesError};
+pub use sizes::{decompress_bound, find_frame_compressed_size, get_frame_content_size};
+pub use split::{merge_frames, split_frames};
+pub use stats::DecodeStats;
+pub use stream::{decode_segments, Progress, StreamDecoder};
+pub use verify::{verify, FrameReport, VerifyReport};
+pub use warning::{warnings, Warning};
+
+/*
+    ZstdCore is the format itself: parsing, entropy decoders, frames and blocks.
+    It only needs `alloc`, the `std` feature adds `std::error::Error` and io
+    errors. Running blocks on threads is left to an `Executor`, `zstd_lib`
+    provides the threaded one along with the io adapters.
+
+    Everything decoding builds is plain owned data, without interior
+    mutability: the Huffman and FSE tables, the predefined ones built on first
+    use included, live in the `DecodingContext` of a frame, which can be moved
+    to the thread decoding the next frame, and parsed frames and errors can be
+    shared across threads. The assertions below keep it that way.
+*/
+
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+    send_sync::<DecodingContext>();
+    send_sync::<DecodingContext<RingBuffer>>();
+    send_sync::<Frame<'static>>();
+    send_sync::<FrameMetadata>();
+    send_sync::<ZstdLibError>();
+    send_sync::<DecodeStats>();
+    send_sync::<StreamDecoder<Sequential>>();
+};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum ZstdLibError {
+    #[error(transparent)]
+    Parsing(#[from] ParsingError),
+
+    #[error(transparent)]
+    Block(#[from] BlockError),
+
+    #[error(transparent)]
+    Frame(#[from] FrameError),
+
+    #[error(transparent)]
+    Decoder(#[from] DecoderError),
+
+    #[error(transparent)]
+    Literals(#[from] LiteralsError),
+
+    #[error(transparent)]
+    Sequences(#[from] SequencesError),
+
+    #[error(transparent)]
+    Checkpoint(#[from] CheckpointError),
+
+    #[error(transparent)]
+    Metadata(#[from] MetadataError),
+
+    #[error(transparent)]
+    Index(#[from] IndexError),
+
+    #[error("Input is not Zstandard data")]
+    NotZstandardData,
+
+    #[cfg(feature = "std")]
+    #[error(transparent)]
+    Io(#[from] std::io::Error),
+
+    #[error("Parallel decoding panicked: {0}")]
+    ParallelDecodingError(String),
+}
+
+/// Category of a `ZstdLibError`, see `ZstdLibError::kind`. Unlike the nested
+/// variants, it does not change when the modules raising the errors do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// The input does not start with a frame magic number
+    NotZstandard,
+    /// The input ends in the middle of a frame
+    Truncated,
+    /// The input, or a checkpoint, metadata or index frame, is invalid
+    Corrupted,
+    /// The content does not match the checksum of its frame
+    ChecksumMismatch,
+    /// The input needs a feature this crate does not have, e.g. dictionaries
+    Unsupported,
+    /// Decoding needs a larger window, budget or memory than allowed, or a
+    /// value is too large to be written
+    LimitExceeded,
+    /// Reading the input or writing the content to an `std::io` stream failed
+    Io,
+    /// A decoding thread panicked
+    Internal,
+}
+
+impl ZstdLibError {
+    /// Category of the error, to match on rather than the nested variants
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{decode, ErrorKind, Sequential};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let err = decode(&bytes[..10], &Sequential).unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::Truncated);
+    /// assert_eq!(decode(b"GIF89a", &Sequential).unwrap_err().kind(), ErrorKind::NotZstandard);
+    /// ```
+    #[must_use]
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            Error::Parsing(ParsingError::NotEnoughBytes { .. }) => ErrorKind::Truncated,
+            Error::NotZstandardData | Error::Frame(FrameError::UnrecognizedMagic(_)) => {
+                ErrorKind::NotZstandard
+            }
+            Error::Frame(FrameError::ChecksumMismatch) => ErrorKind::ChecksumMismatch,
+            Error::Frame(
+                FrameError::DictNotSupported { .. } | FrameError::WindowSizeUnsupported { .. },
+            )
+            | Error::Checkpoint(CheckpointError::UnsupportedVersion(_))
+            | Error::Metadata(MetadataError::UnsupportedVersion(_)) => ErrorKind::Unsupported,
+            Error::Decoder(DecoderError::Context(
+                ContextError::WindowSizeError { .. }
+                | ContextError::BudgetExceeded { .. }
+                | ContextError::MemoryLimitExceeded { .. }
+                | ContextError::AllocationFailed { .. }
+                | ContextError::OutputTooSmall { .. },
+            ))
+            | Error::Metadata(MetadataError::TooLarge(_))
+            | Error::Index(IndexError::TooLarge(_)) => ErrorKind::LimitExceeded,
+            #[cfg(feature = "std")]
+            Error::Io(_) | Error::Decoder(DecoderError::Output(_)) => ErrorKind::Io,
+            Error::ParallelDecodingError(_) => ErrorKind::Internal,
+            _ => ErrorKind::Corrupted,
+        }
+    }
+
+    /// Bytes requested and available when the input ends in the middle of a
+    /// frame, i.e. the error is `ErrorKind::Truncated`
+    #[must_use]
+    pub fn requested_available(&self) -> Option<(usize, usize)> {
+        match self {
+            Error::Parsing(ParsingError::NotEnoughBytes {
+                requested,
+                available,
+            }) => Some((*requested, *available)),
+            _ => None,
+        }
+    }
+
+    /// Window size of the frame when it exceeds the maximum window size of
+    /// the executor, or the address space
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{decode, ErrorKind, Sequential};
+    /// // Window_Descriptor of 2^30 bytes
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0xA0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let err = decode(&bytes, &Sequential).unwrap_err();
+    /// assert_eq!((err.kind(), err.window_size()), (ErrorKind::LimitExceeded, Some(1 << 30)));
+    /// ```
+    #[must_use]
+    pub fn window_size(&self) -> Option<u64> {
+        match self {
+            Error::Decoder(DecoderError::Context(ContextError::WindowSizeError {
+                window_size,
+                ..
+            })) => Some(*window_size as u64),
+            Error::Frame(FrameError::WindowSizeUnsupported { window_size }) => Some(*window_size),
+            _ => None,
+        }
+    }
+}
+/// Error returned by `decode_partial`: the decoding error, the input offset of the
+/// frame, block or checksum that failed, and the content successfully decoded before it.
+#[derive(Debug, thiserror::Error)]
+#[error("{source} (input offset {offset}, {} bytes recovered)", .partial.len())]
+pub struct DecodeError {
+    pub partial: Vec<u8>,
+    pub source: ZstdLibError,
+    pub offset: usize,
+}
+
+type Error = ZstdLibError;
+type Result<T, E = ZstdLibError> = core::result::Result<T, E>;
+
+/// Check the magic number at the start of `bytes` without parsing further.
+/// Return `None` when `bytes` does not start with a known frame magic number.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{sniff, FrameKind};
+/// assert_eq!(sniff(&[0x28, 0xB5, 0x2F, 0xFD]), Some(FrameKind::Zstandard));
+/// assert_eq!(sniff(&[0x50, 0x2A, 0x4D, 0x18]), Some(FrameKind::Skippable));
+/// assert_eq!(sniff(b"GIF89a"), None);
+/// ```
+#[must_use]
+pub fn sniff(bytes: &[u8]) -> Option<FrameKind> {
+    let magic = ForwardByteParser::new(bytes).le_u32().ok()?;
+    FrameKind::from_magic(magic)
+}
+
+/// Fail with `NotZstandardData` when `bytes` is not empty and does not start
+/// with a known frame magic number, as the decode functions do before parsing
+///
+/// # Example
+/// ```
+/// # use zstd_core::{check_magic, ErrorKind};
+/// assert!(check_magic(&[0x28, 0xB5, 0x2F, 0xFD]).is_ok());
+/// assert!(check_magic(&[]).is_ok());
+/// assert_eq!(check_magic(b"GIF89a").unwrap_err().kind(), ErrorKind::NotZstandard);
+/// ```
+pub fn check_magic(bytes: &[u8]) -> Result<()> {
+    if !bytes.is_empty() && sniff(bytes).is_none() {
+        return Err(Error::NotZstandardData);
+    }
+    Ok(())
+}
+
+/// Describe the frames of `bytes` from their headers only: block contents are
+/// skipped by size, nothing is decoded
+///
+/// # Example
+/// ```
+/// # use zstd_core::{frames_metadata, FrameKind};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let frame = frames_metadata(&bytes).next().unwrap()?;
+/// assert_eq!(frame.kind, FrameKind::Zstandard);
+/// assert_eq!((frame.compressed_size, frame.block_count), (11, 1));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+#[must_use]
+pub fn frames_metadata(bytes: &[u8]) -> FramesMetadata<'_> {
+    FramesMetadata::new(bytes)
+}
+
+/// Total content size announced by the frame headers of `bytes`, to allocate
+/// the output or enforce a quota before decoding. `None` when a Zstandard frame
+/// does not announce its size, saturated at `usize::MAX` when it does not fit.
+///
+/// # Example
+/// ```
+/// # use zstd_core::content_size_hint;
+/// // Single segment frame, content size 2
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x2, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(content_size_hint(&bytes)?, Some(2));
+/// // No content size in the header
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(content_size_hint(&bytes)?, None);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn content_size_hint(bytes: &[u8]) -> Result<Option<usize>> {
+    let mut total = 0_usize;
+    for metadata in frames_metadata(bytes) {
+        // Skippable frames have no content
+        if let Some(header) = metadata?.header {
+            let Some(size) = header.content_size() else {
+                return Ok(None);
+            };
+            let size = usize::try_from(size).unwrap_or(usize::MAX);
+            total = total.saturating_add(size);
+        }
+    }
+    Ok(Some(total))
+}
+
+/// Iterate over the skippable frames of `bytes`, where applications store
+/// metadata that decoding ignores
+///
+/// # Example
+/// ```
+/// # use zstd_core::skippable_frames;
+/// let bytes = [0x52, 0x2A, 0x4D, 0x18, 0x02, 0x0, 0x0, 0x0, 0xCA, 0xFE];
+/// let frame = skippable_frames(&bytes).next().unwrap()?;
+/// assert_eq!((frame.magic_nibble, frame.offset, frame.payload), (2, 0, &[0xCA, 0xFE][..]));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+#[must_use]
+pub fn skippable_frames(bytes: &[u8]) -> SkippableFrames<'_> {
+    SkippableFrames::new(bytes)
+}
+
+/// Content of `bytes` as slices of the input, without copying, when all its
+/// frames are made of raw blocks (store mode): `None` when a block is
+/// compressed or RLE. Content checksums are verified.
+///
+/// # Example
+/// ```
+/// # use zstd_core::raw_content;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(raw_content(&bytes)?, Some(vec![&bytes[9..]]));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn raw_content(bytes: &[u8]) -> Result<Option<Vec<&[u8]>>> {
+    check_magic(bytes)?;
+
+    let mut content = Vec::new();
+    for frame in FrameIterator::new(bytes) {
+        if let Frame::ZstandardFrame(frame) = frame? {
+            let Some(raw) = frame.raw_content()? else {
+                return Ok(None);
+            };
+            content.extend(raw.into_iter().filter(|raw| !raw.is_empty()));
+        }
+    }
+    Ok(Some(content))
+}
+
+/// Capacity to allocate for the content of `frames`: their announced content
+/// size, bounded by what their blocks can hold
+fn reserved_size(frames: &[Frame]) -> usize {
+    frames
+        .iter()
+        .filter_map(|frame| match frame {
+            Frame::ZstandardFrame(frame) => Some(core::cmp::min(
+                // Nothing is reserved when the content size is unknown
+                frame
+                    .header()
+                    .content_size()
+                    .map_or(0, |size| usize::try_from(size).unwrap_or(usize::MAX)),
+                frame.max_content_size(),
+            )),
+            Frame::SkippableFrame(_) => None,
+        })
+        .fold(0, usize::saturating_add)
+}
+
+/// Decode the frames of `bytes` one after the other, running the sections of
+/// their blocks on `executor`
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(decode(&bytes, &Sequential).unwrap(), [0xCA, 0xFE]);
+/// ```
+pub fn decode(bytes: &[u8], executor: &impl Executor) -> Result<Vec<u8>> {
+    check_magic(bytes)?;
+
+    let frames = FrameIterator::new(bytes).collect::<Result<Vec<Frame>>>()?;
+    let mut decoded = Vec::new();
+    reserve_buffer(
+        &mut decoded,
+        reserved_size(&frames),
+        true,
+        BufferKind::Content,
+        executor,
+    )?;
+    for frame in frames {
+        frame.decode_append(&mut decoded, executor)?;
+    }
+    Ok(decoded)
+}
+
+/// Decode the frames of `bytes` one after the other, calling `on_block` with
+/// the content of each block as soon as it is decoded, to hash, report
+/// progress or write it out without waiting for the whole frame. A frame is
+/// only known to be valid once its last block is given: on error, the blocks
+/// given before belong to content that failed to decode or verify.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode_with_callback, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut size = 0;
+/// decode_with_callback(&bytes, &Sequential, |block| size += block.len())?;
+/// assert_eq!(size, 2);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decode_with_callback(
+    bytes: &[u8],
+    executor: &impl Executor,
+    mut on_block: impl FnMut(&[u8]),
+) -> Result<()> {
+    check_magic(bytes)?;
+
+    // The buffer of the context is reused from one frame to the next
+    let mut context = DecodingContext::default();
+    for frame in FrameIterator::new(bytes) {
+        frame?.decode_with_callback(&mut context, executor, &mut on_block)?;
+    }
+    Ok(())
+}
+
+/// Decode the frames of `bytes` one after the other like `decode`, along with
+/// counts of their blocks, sections, sequences and checksums
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode_with_stats, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let (decoded, stats) = decode_with_stats(&bytes, &Sequential)?;
+/// assert_eq!(decoded, [0xCA, 0xFE]);
+/// assert_eq!((stats.frames, stats.raw_blocks, stats.literal_bytes), (1, 1, 2));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decode_with_stats(bytes: &[u8], executor: &impl Executor) -> Result<(Vec<u8>, DecodeStats)> {
+    check_magic(bytes)?;
+
+    let frames = FrameIterator::new(bytes).collect::<Result<Vec<Frame>>>()?;
+    let mut decoded = Vec::new();
+    reserve_buffer(
+        &mut decoded,
+        reserved_size(&frames),
+        true,
+        BufferKind::Content,
+        executor,
+    )?;
+    let mut stats = DecodeStats::default();
+    for frame in frames {
+        frame.decode_append_counted(&mut decoded, executor, &mut stats)?;
+    }
+    Ok((decoded, stats))
+}
+
+/// Sequentially decode `bytes` block by block. When a frame or a block is corrupted,
+/// the content decoded before it is returned in the error alongside the input offset
+/// of the failure, instead of being discarded. Frames whose window exceeds
+/// `executor.max_window_size()` fail with `WindowSizeError`.
+pub fn decode_partial(bytes: &[u8], executor: &impl Executor) -> Result<Vec<u8>, DecodeError> {
+    let mut decoded = Vec::new();
+
+    if let Err(source) = check_magic(bytes) {
+        return Err(DecodeError {
+            partial: decoded,
+            source,
+            offset: 0,
+        });
+    }
+
+    let mut parser = ForwardByteParser::new(bytes);
+    while !parser.is_empty() {
+        if let Err((source, offset)) =
+            Frame::decode_partial(&mut parser, bytes.len(), &mut decoded, executor)
+        {
+            return Err(DecodeError {
+                partial: decoded,
+                source,
+                offset,
+            });
+        }
+    }
+
+    Ok(decoded)
+}
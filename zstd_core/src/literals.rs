@@ -0,0 +1,537 @@
+use super::{
+    BackwardBitParser, Error, Executor, ForwardByteParser, HuffmanDecoder, LiteralsType, Result,
+};
+use alloc::vec::Vec;
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum LiteralsError {
+    #[error("Missing huffman decoder")]
+    MissingHuffmanDecoder,
+
+    #[error("Data corrupted")]
+    CorruptedDataError,
+
+    #[error("Compressed size is invalid")]
+    InvalidCompressedSize,
+
+    #[error("Literals stream {stream}: {got} bytes decoded, {expected} expected")]
+    StreamSizeMismatch {
+        stream: usize,
+        got: usize,
+        expected: usize,
+    },
+
+    #[error("Jump table {sizes:?} does not partition {total} bytes into 4 streams")]
+    InvalidJumpTable { sizes: [usize; 3], total: usize },
+}
+use LiteralsError::*;
+
+#[derive(Debug, PartialEq)]
+pub enum LiteralsSection<'a> {
+    Raw(RawLiteralsBlock<'a>),
+    Rle(RLELiteralsBlock),
+    Compressed(CompressedLiteralsBlock<'a>),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct RawLiteralsBlock<'a>(&'a [u8]);
+
+#[derive(Debug, PartialEq)]
+pub struct RLELiteralsBlock {
+    byte: u8,
+    repeat: usize,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CompressedLiteralsBlock<'a> {
+    huffman: Option<HuffmanDecoder>,
+    regenerated_size: usize,
+    jump_table: Option<[usize; 3]>,
+    data: &'a [u8],
+}
+
+const RAW_LITERALS_BLOCK: u8 = 0;
+const RLE_LITERALS_BLOCK: u8 = 1;
+const COMPRESSED_LITERALS_BLOCK: u8 = 2;
+const TREELESS_LITERALS_BLOCK: u8 = 3;
+
+const MAX_LITERALS_SIZE: usize = 1024 * 128; // 128kb
+
+impl<'a> LiteralsSection<'a> {
+    /// Size of the decompressed literals
+    pub fn regenerated_size(&self) -> usize {
+        match self {
+            LiteralsSection::Raw(block) => block.0.len(),
+            LiteralsSection::Rle(block) => block.repeat,
+            LiteralsSection::Compressed(block) => block.regenerated_size,
+        }
+    }
+
+    pub(crate) fn literals_type(&self) -> LiteralsType {
+        match self {
+            LiteralsSection::Raw(_) => LiteralsType::Raw,
+            LiteralsSection::Rle(_) => LiteralsType::Rle,
+            LiteralsSection::Compressed(block) if block.huffman.is_some() => {
+                LiteralsType::Compressed
+            }
+            LiteralsSection::Compressed(_) => LiteralsType::Treeless,
+        }
+    }
+
+    /// Number of Huffman-coded streams, 1 for raw and RLE literals
+    pub(crate) fn streams(&self) -> usize {
+        match self {
+            LiteralsSection::Compressed(block) if block.jump_table.is_some() => 4,
+            _ => 1,
+        }
+    }
+
+    /// Decompress the literals section at the end of `output`.
+    /// Update the `huffman` decoder if appropriate (compressed literals block
+    /// with a Huffman table inside). The 4 streams of a compressed literals
+    /// block run on `executor`.
+    pub fn decode(
+        self,
+        huffman: &mut Option<HuffmanDecoder>,
+        output: &mut Vec<u8>,
+        executor: &impl Executor,
+    ) -> Result<()> {
+        let _span = span!(
+            "decode_literals",
+            size = self.regenerated_size(),
+            streams = self.streams()
+        );
+        match self {
+            LiteralsSection::Raw(block) => {
+                output.extend_from_slice(block.0);
+                Ok(())
+            }
+            LiteralsSection::Rle(block) => {
+                output.resize(output.len() + block.repeat, block.byte);
+                Ok(())
+            }
+            LiteralsSection::Compressed(block) => match block.jump_table {
+                None => decode_1_stream(huffman, block, output),
+                Some(jump_table) => decode_4_streams(jump_table, huffman, block, output, executor),
+            },
+        }
+    }
+
+    #[allow(clippy::too_many_lines)]
+    pub fn parse(input: &mut ForwardByteParser<'a>) -> Result<Self> {
+        let header = input.u8()?;
+        let block_type = header & 0b0000_0011;
+        let size_format = (header & 0b0000_1100) >> 2;
+
+        match block_type {
+            RAW_LITERALS_BLOCK | RLE_LITERALS_BLOCK => {
+                let regenerated_size: usize = match size_format {
+                    // use 5bits (8 - 3)
+                    0b00 | 0b10 => (header >> 3).into(),
+                    // use 12bits (8 + 4)
+                    0b01 => header as usize >> 4 | (input.u8()? as usize) << 4,
+                    // use 20bits (8 + 8 + 4)
+                    0b11 => {
+                        header as usize >> 4
+                            | (input.u8()? as usize) << 4
+                            | (input.u8()? as usize) << 12
+                    }
+                    _ => panic!("unexpected size_format {size_format}"),
+                };
+
+                if regenerated_size > MAX_LITERALS_SIZE {
+                    return Err(Error::Literals(CorruptedDataError));
+                }
+
+                match block_type {
+                    RAW_LITERALS_BLOCK => Ok(LiteralsSection::Raw(RawLiteralsBlock(
+                        input.slice(regenerated_size)?,
+                    ))),
+                    RLE_LITERALS_BLOCK => Ok(LiteralsSection::Rle(RLELiteralsBlock {
+                        byte: input.u8()?,
+                        repeat: regenerated_size,
+                    })),
+                    _ => panic!("unexpected block_type {block_type}"),
+                }
+            }
+
+            COMPRESSED_LITERALS_BLOCK | TREELESS_LITERALS_BLOCK => {
+                let header: usize = header.into();
+                let streams = match size_format {
+                    0b00 => 1,
+                    #[allow(clippy::manual_range_patterns)]
+                    0b01 | 0b10 | 0b11 => 4,
+                    _ => panic!("unexpected size_format {size_format}"),
+                };
+                let (regenerated_size, compressed_size) = match size_format {
+                    0b00 | 0b01 => {
+                        let header1 = input.u8()? as usize;
+                        let header2 = input.u8()? as usize;
+
+                        // both size on 10bits
+                        let re_size = header >> 4 | (header1 & 0b0011_1111) << 4;
+                        let cp_size = header1 >> 6 | header2 << 2;
+
+                        (re_size, cp_size)
+                    }
+                    0b10 => {
+                        let header1 = input.u8()? as usize;
+                        let header2 = input.u8()? as usize;
+                        let header3 = input.u8()? as usize;
+
+                        // both size on 14bits
+                        let re_size = header >> 4 | header1 << 4 | (header2 & 0b0000_0011) << 12;
+                        let cp_size = header2 >> 2 | header3 << 6;
+
+                        (re_size, cp_size)
+                    }
+                    0b11 => {
+                        let header1 = input.u8()? as usize;
+                        let header2 = input.u8()? as usize;
+                        let header3 = input.u8()? as usize;
+                        let header4 = input.u8()? as usize;
+
+                        // both size on 18bits
+                        let re_size = header >> 4 | header1 << 4 | (header2 & 0b0011_1111) << 12;
+                        let cp_size = header2 >> 6 | header3 << 2 | header4 << 10;
+
+                        (re_size, cp_size)
+                    }
+                    _ => panic!("unexpected size_format {size_format}"),
+                };
+
+                if regenerated_size > MAX_LITERALS_SIZE {
+                    return Err(Error::Literals(CorruptedDataError));
+                }
+
+                let mut huffman = None;
+                let mut huffman_description_size = 0;
+
+                if block_type == COMPRESSED_LITERALS_BLOCK {
+                    let size_before = input.len();
+                    huffman = Some(HuffmanDecoder::parse(input)?);
+                    let size_after = input.len();
+                    assert!(size_before > size_after);
+                    huffman_description_size = size_before - size_after;
+                }
+
+                // Actual total_streams_size depend on the number of streams.
+                // If there are 4 streams, 6bytes are removed from the total size to store
+                // the respective streams size.
+                if compressed_size < huffman_description_size {
+                    return Err(Error::Literals(InvalidCompressedSize));
+                }
+                let mut total_streams_size: usize = compressed_size - huffman_description_size;
+
+                let jump_table = match streams {
+                    1 => None,
+                    4 => {
+                        let sizes = [input.le(2)?, input.le(2)?, input.le(2)?];
+
+                        // Every stream holds at least its end mark: the 4th stream size
+                        // is what remains once the jump table and the 3 streams are removed.
+                        if sizes.contains(&0)
+                            || total_streams_size < sizes.iter().sum::<usize>() + 6 + 1
+                        {
+                            return Err(Error::Literals(InvalidJumpTable {
+                                sizes,
+                                total: total_streams_size,
+                            }));
+                        }
+
+                        total_streams_size -= 6;
+
+                        Some(sizes)
+                    }
+                    _ => panic!("unexpected number of streams {streams}"),
+                };
+
+                let data = input.slice(total_streams_size)?;
+
+                Ok(LiteralsSection::Compressed(CompressedLiteralsBlock {
+                    huffman,
+                    regenerated_size,
+                    jump_table,
+                    data,
+                }))
+            }
+            _ => panic!("unexpected block_type {block_type}"),
+        }
+    }
+}
+
+fn update_decoder(
+    huffman: &mut Option<HuffmanDecoder>,
+    block_huffman: Option<HuffmanDecoder>,
+) -> Result<&HuffmanDecoder> {
+    if let Some(block_huffman) = block_huffman {
+        *huffman = Some(block_huffman);
+    }
+
+    Ok(huffman.as_ref().ok_or(MissingHuffmanDecoder)?)
+}
+
+fn decode_1_stream(
+    huffman: &mut Option<HuffmanDecoder>,
+    block: CompressedLiteralsBlock,
+    output: &mut Vec<u8>,
+) -> Result<()> {
+    let huffman = update_decoder(huffman, block.huffman)?;
+    let start = output.len();
+    output.resize(start + block.regenerated_size, 0);
+    let decoded = decode_stream(huffman, block.data, &mut output[start..])?;
+
+    if decoded != block.regenerated_size {
+        return Err(Error::Literals(StreamSizeMismatch {
+            stream: 1,
+            got: decoded,
+            expected: block.regenerated_size,
+        }));
+    }
+
+    Ok(())
+}
+
+/// Decode a single Huffman-coded stream into `output`. Return the number of
+/// symbols of the stream, which exceeds the size of `output` when it is corrupted.
+fn decode_stream(huffman: &HuffmanDecoder, data: &[u8], output: &mut [u8]) -> Result<usize> {
+    let mut stream = BackwardBitParser::new(data)?;
+    let mut decoded = 0;
+    while stream.available_bits() > 0 {
+        let symbol = huffman.decode(&mut stream)?;
+        if let Some(byte) = output.get_mut(decoded) {
+            *byte = symbol;
+        }
+        decoded += 1;
+    }
+
+    Ok(decoded)
+}
+
+fn decode_4_streams(
+    jump_table: [usize; 3],
+    huffman: &mut Option<HuffmanDecoder>,
+    block: CompressedLiteralsBlock,
+    output: &mut Vec<u8>,
+    executor: &impl Executor,
+) -> Result<()> {
+    let huffman = update_decoder(huffman, block.huffman)?;
+
+    let idx2 = jump_table[0];
+    let idx3 = idx2 + jump_table[1];
+    let idx4 = idx3 + jump_table[2];
+    if jump_table.contains(&0) || idx4 >= block.data.len() {
+        return Err(Error::Literals(InvalidJumpTable {
+            sizes: jump_table,
+            total: block.data.len() + 6,
+        }));
+    }
+
+    let ranges: [(usize, usize); 4] = [
+        (0, idx2),
+        (idx2, idx3),
+        (idx3, idx4),
+        (idx4, block.data.len()),
+    ];
+
+    // Streams 1 to 3 regenerate the same size, the 4th regenerates the remaining
+    let regenerated_stream_size = block.regenerated_size.div_ceil(4);
+    let last_stream_size = block
+        .regenerated_size
+        .checked_sub(3 * regenerated_stream_size)
+        // e.g. 5 bytes cannot be split into 2 + 2 + 2 + x
+        .ok_or(Error::Literals(CorruptedDataError))?;
+
+    // Each stream decodes into its own part of the output
+    let start = output.len();
+    output.resize(start + block.regenerated_size, 0);
+    let (output_1, rest) = output[start..].split_at_mut(regenerated_stream_size);
+    let (output_2, rest) = rest.split_at_mut(regenerated_stream_size);
+    let (output_3, output_4) = rest.split_at_mut(regenerated_stream_size);
+
+    let stream = |(start, end): (usize, usize), output: &mut [u8]| {
+        decode_stream(huffman, &block.data[start..end], output)
+    };
+    let (first, last) = executor.join(
+        || {
+            executor.join(
+                || stream(ranges[0], output_1),
+                || stream(ranges[1], output_2),
+            )
+        },
+        || {
+            executor.join(
+                || stream(ranges[2], output_3),
+                || stream(ranges[3], output_4),
+            )
+        },
+    )?;
+    let ((stream_1, stream_2), (stream_3, stream_4)) = (first?, last?);
+    let streams = [stream_1, stream_2, stream_3, stream_4];
+
+    for (id, decoded) in streams.into_iter().enumerate() {
+        let decoded = decoded?;
+
+        let expected = if id < 3 {
+            regenerated_stream_size
+        } else {
+            last_stream_size
+        };
+        if decoded != expected {
+            return Err(Error::Literals(StreamSizeMismatch {
+                stream: id + 1,
+                got: decoded,
+                expected,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{executor::tests::Threads, Sequential},
+        *,
+    };
+
+    #[test]
+    fn test_parse_raw_literal() {
+        let mut input = ForwardByteParser::new(&[0b0000_1000, 0xFF]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Raw(RawLiteralsBlock(&[0xFF]))
+        );
+
+        let mut input = ForwardByteParser::new(&[0b0000_0000]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Raw(RawLiteralsBlock(&[]))
+        );
+
+        let mut input = ForwardByteParser::new(&[0b0100_0100, 0x0000_0000, 0xAA, 0xBB, 0xCC, 0xDD]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Raw(RawLiteralsBlock(&[0xAA, 0xBB, 0xCC, 0xDD]))
+        );
+
+        let mut input = ForwardByteParser::new(&[0b0010_1100, 0x0, 0x0, 0xAA, 0xBB]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Raw(RawLiteralsBlock(&[0xAA, 0xBB]))
+        );
+    }
+
+    /// Treeless 4 streams literals: regenerated size 8, compressed size 10
+    fn treeless_4_streams(jump_table: [u8; 6], streams: [u8; 4]) -> Vec<u8> {
+        let mut input = vec![0x87, 0x80, 0x02];
+        input.extend_from_slice(&jump_table);
+        input.extend_from_slice(&streams);
+        input
+    }
+
+    fn decode(section: LiteralsSection) -> Result<Vec<u8>> {
+        decode_with(section, &Sequential)
+    }
+
+    fn decode_with(section: LiteralsSection, executor: &impl Executor) -> Result<Vec<u8>> {
+        let mut huffman = Some(HuffmanDecoder::Tree(
+            Box::new(HuffmanDecoder::Symbol(b'a')),
+            Box::new(HuffmanDecoder::Symbol(b'b')),
+        ));
+        let mut output = Vec::new();
+        section.decode(&mut huffman, &mut output, executor)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_parse_invalid_jump_table() {
+        let input = treeless_4_streams([1, 0, 0, 0, 1, 0], [0b110; 4]);
+        assert!(matches!(
+            LiteralsSection::parse(&mut ForwardByteParser::new(&input)),
+            Err(Error::Literals(InvalidJumpTable {
+                sizes: [1, 0, 1],
+                total: 10
+            }))
+        ));
+
+        // no byte left for the 4th stream
+        let input = treeless_4_streams([1, 0, 1, 0, 2, 0], [0b110; 4]);
+        assert!(matches!(
+            LiteralsSection::parse(&mut ForwardByteParser::new(&input)),
+            Err(Error::Literals(InvalidJumpTable {
+                sizes: [1, 1, 2],
+                total: 10
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_decode_4_streams() {
+        let input = treeless_4_streams([1, 0, 1, 0, 1, 0], [0b110; 4]);
+        let section = LiteralsSection::parse(&mut ForwardByteParser::new(&input)).unwrap();
+        assert_eq!(decode(section).unwrap(), b"babababa");
+
+        let section = LiteralsSection::parse(&mut ForwardByteParser::new(&input)).unwrap();
+        assert_eq!(decode_with(section, &Threads).unwrap(), b"babababa");
+    }
+
+    #[test]
+    fn test_decode_4_streams_size_mismatch() {
+        // 1st stream regenerates 3 bytes instead of 2
+        let input = treeless_4_streams([1, 0, 1, 0, 1, 0], [0b1110, 0b110, 0b110, 0b110]);
+        let section = LiteralsSection::parse(&mut ForwardByteParser::new(&input)).unwrap();
+        assert!(matches!(
+            decode(section),
+            Err(Error::Literals(StreamSizeMismatch {
+                stream: 1,
+                got: 3,
+                expected: 2
+            }))
+        ));
+
+        // 4th stream regenerates 1 byte instead of 2
+        let input = treeless_4_streams([1, 0, 1, 0, 1, 0], [0b110, 0b110, 0b110, 0b11]);
+        let section = LiteralsSection::parse(&mut ForwardByteParser::new(&input)).unwrap();
+        assert!(matches!(
+            decode(section),
+            Err(Error::Literals(StreamSizeMismatch {
+                stream: 4,
+                got: 1,
+                expected: 2
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_rle_literal() {
+        let mut input = ForwardByteParser::new(&[0b0000_0001, 0xFF]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Rle(RLELiteralsBlock {
+                byte: 0xFF,
+                repeat: 0
+            })
+        );
+
+        let mut input = ForwardByteParser::new(&[0b0000_1001, 0xFF]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Rle(RLELiteralsBlock {
+                byte: 0xFF,
+                repeat: 1
+            })
+        );
+
+        let mut input = ForwardByteParser::new(&[0b0101_0101, 0b1101_0101, 0xFF]);
+        assert_eq!(
+            LiteralsSection::parse(&mut input).unwrap(),
+            LiteralsSection::Rle(RLELiteralsBlock {
+                byte: 0xFF,
+                repeat: 0b1101_0101_0101,
+            })
+        );
+    }
+}
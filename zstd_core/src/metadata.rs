@@ -0,0 +1,268 @@
+use super::format::{skippable_magic, SKIPPABLE_HEADER_SIZE};
+use super::{skippable_frames, ForwardByteParser, ParsingError, Result};
+use alloc::{string::String, vec::Vec};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum MetadataError {
+    #[error("Unsupported metadata version: {0}")]
+    UnsupportedVersion(u8),
+
+    #[error("Truncated metadata: {0}")]
+    Truncated(#[from] ParsingError),
+
+    #[error("Corrupted metadata field {0}")]
+    Corrupted(u8),
+
+    #[error("Metadata field {0} too large for a skippable frame")]
+    TooLarge(u8),
+}
+
+/// Low 4 bits of the magic number of the skippable frames holding metadata
+pub const METADATA_MAGIC_NIBBLE: u8 = 0xA;
+
+/// Start of the payload of metadata frames, followed by the version of its
+/// layout
+const METADATA_MAGIC: [u8; 4] = *b"ZSMD";
+const METADATA_VERSION: u8 = 1;
+
+/*
+    After the version, the payload is a list of fields: a tag byte, the size
+    of the value on 4 bytes and the value, integers being little-endian.
+    Fields with an unknown tag are skipped, so that new ones can be added
+    without a new version.
+*/
+const FILENAME: u8 = 1;
+const MTIME: u8 = 2;
+const CONTENT_HASH: u8 = 3;
+const ENTRY: u8 = 4;
+
+/// Metadata stored in a skippable frame along with the content, see
+/// `read_metadata` and `write_metadata`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Metadata {
+    /// Name of the original file
+    pub filename: Option<String>,
+    /// Modification time of the original file, in seconds since the Unix epoch
+    pub mtime: Option<u64>,
+    /// XXH64 digest (seed 0) of the content of the archive
+    pub content_hash: Option<u64>,
+    /// Application keys and values, in order
+    pub entries: Vec<(String, String)>,
+}
+
+impl Metadata {
+    /// Parse the payload of a metadata frame, `None` when it is not one
+    pub fn parse(payload: &[u8]) -> Result<Option<Self>, MetadataError> {
+        let mut input = ForwardByteParser::new(payload);
+        if input.slice(METADATA_MAGIC.len()).ok() != Some(&METADATA_MAGIC[..]) {
+            return Ok(None);
+        }
+        let version = input.u8()?;
+        if version != METADATA_VERSION {
+            return Err(MetadataError::UnsupportedVersion(version));
+        }
+
+        let mut metadata = Self::default();
+        while !input.is_empty() {
+            let tag = input.u8()?;
+            let size = input.le(4)?;
+            let mut value = ForwardByteParser::new(input.slice(size)?);
+            let string = |value: &[u8]| {
+                String::from_utf8(value.to_vec()).map_err(|_| MetadataError::Corrupted(tag))
+            };
+            match tag {
+                FILENAME => metadata.filename = Some(string(value.slice(size)?)?),
+                MTIME => metadata.mtime = Some(value.le_u64()?),
+                CONTENT_HASH => metadata.content_hash = Some(value.le_u64()?),
+                ENTRY => {
+                    let key_size = usize::from(value.le_u16()?);
+                    let key = string(value.slice(key_size)?)?;
+                    let value = string(value.slice(value.len())?)?;
+                    metadata.entries.push((key, value));
+                }
+                _ => continue,
+            }
+            if !value.is_empty() {
+                return Err(MetadataError::Corrupted(tag));
+            }
+        }
+        Ok(Some(metadata))
+    }
+
+    /// Skippable frame holding the metadata
+    pub fn to_frame(&self) -> Result<Vec<u8>, MetadataError> {
+        let mut payload = METADATA_MAGIC.to_vec();
+        payload.push(METADATA_VERSION);
+        if let Some(filename) = &self.filename {
+            write_field(&mut payload, FILENAME, &[filename.as_bytes()])?;
+        }
+        if let Some(mtime) = self.mtime {
+            write_field(&mut payload, MTIME, &[&mtime.to_le_bytes()])?;
+        }
+        if let Some(content_hash) = self.content_hash {
+            write_field(&mut payload, CONTENT_HASH, &[&content_hash.to_le_bytes()])?;
+        }
+        for (key, value) in &self.entries {
+            let key_size = u16::try_from(key.len()).map_err(|_| MetadataError::TooLarge(ENTRY))?;
+            let parts = [
+                &key_size.to_le_bytes()[..],
+                key.as_bytes(),
+                value.as_bytes(),
+            ];
+            write_field(&mut payload, ENTRY, &parts)?;
+        }
+
+        let size = u32::try_from(payload.len()).map_err(|_| MetadataError::TooLarge(0))?;
+        let magic = skippable_magic(METADATA_MAGIC_NIBBLE);
+        let mut frame = Vec::with_capacity(SKIPPABLE_HEADER_SIZE + payload.len());
+        frame.extend_from_slice(&magic.to_le_bytes());
+        frame.extend_from_slice(&size.to_le_bytes());
+        frame.extend_from_slice(&payload);
+        Ok(frame)
+    }
+}
+
+/// Append the field `tag` whose value is the concatenation of `parts`
+fn write_field(payload: &mut Vec<u8>, tag: u8, parts: &[&[u8]]) -> Result<(), MetadataError> {
+    let size = parts.iter().map(|part| part.len()).sum::<usize>();
+    let size = u32::try_from(size).map_err(|_| MetadataError::TooLarge(tag))?;
+    payload.push(tag);
+    payload.extend_from_slice(&size.to_le_bytes());
+    for part in parts {
+        payload.extend_from_slice(part);
+    }
+    Ok(())
+}
+
+/// Metadata of the first metadata frame of `bytes`, found from the headers
+/// without decoding anything. `None` when there is none.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{read_metadata, write_metadata, Metadata};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(read_metadata(&bytes)?, None);
+///
+/// let metadata = Metadata {
+///     filename: Some("cafe.bin".to_string()),
+///     entries: vec![("origin".to_string(), "doc".to_string())],
+///     ..Metadata::default()
+/// };
+/// let archive = write_metadata(&bytes, &metadata)?;
+/// assert_eq!(read_metadata(&archive)?, Some(metadata));
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn read_metadata(bytes: &[u8]) -> Result<Option<Metadata>> {
+    for frame in skippable_frames(bytes) {
+        let frame = frame?;
+        if frame.magic_nibble == METADATA_MAGIC_NIBBLE {
+            if let Some(metadata) = Metadata::parse(frame.payload)? {
+                return Ok(Some(metadata));
+            }
+        }
+    }
+    Ok(None)
+}
+
+/// Archive made of a metadata frame holding `metadata` followed by the frames
+/// of `bytes`, without the metadata frames it had. Decoding ignores it.
+pub fn write_metadata(bytes: &[u8], metadata: &Metadata) -> Result<Vec<u8>> {
+    let mut archive = metadata.to_frame()?;
+    archive.reserve(bytes.len());
+    let mut start = 0;
+    for frame in skippable_frames(bytes) {
+        let frame = frame?;
+        if frame.magic_nibble == METADATA_MAGIC_NIBBLE && Metadata::parse(frame.payload)?.is_some()
+        {
+            archive.extend_from_slice(&bytes[start..frame.offset]);
+            start = frame.offset + SKIPPABLE_HEADER_SIZE + frame.payload.len();
+        }
+    }
+    archive.extend_from_slice(&bytes[start..]);
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Error, Sequential};
+    use super::*;
+    use alloc::string::ToString;
+
+    #[test]
+    fn test_metadata_frame() {
+        let metadata = Metadata {
+            filename: Some("moby.txt".to_string()),
+            mtime: Some(1_700_000_000),
+            content_hash: Some(0xCAFE_BABE),
+            entries: vec![
+                ("author".to_string(), "Melville".to_string()),
+                ("empty".to_string(), String::new()),
+            ],
+        };
+        let frame = metadata.to_frame().unwrap();
+        assert_eq!(frame[..4], [0x5A, 0x2A, 0x4D, 0x18]);
+        assert_eq!(Metadata::parse(&frame[8..]).unwrap(), Some(metadata));
+
+        let empty = Metadata::default().to_frame().unwrap();
+        assert_eq!(empty[8..], *b"ZSMD\x01");
+        assert_eq!(
+            Metadata::parse(&empty[8..]).unwrap(),
+            Some(Metadata::default())
+        );
+
+        // Other payloads, and unknown fields
+        assert_eq!(Metadata::parse(b"other").unwrap(), None);
+        assert_eq!(
+            Metadata::parse(b"ZSMD\x01\x09\x02\x00\x00\x00ab").unwrap(),
+            Some(Metadata::default())
+        );
+        assert!(matches!(
+            Metadata::parse(b"ZSMD\x02"),
+            Err(MetadataError::UnsupportedVersion(2))
+        ));
+        assert!(matches!(
+            Metadata::parse(b"ZSMD\x01\x02\x04\x00\x00\x00abcd"),
+            Err(MetadataError::Truncated(_))
+        ));
+        assert!(matches!(
+            Metadata::parse(b"ZSMD\x01\x01\x01\x00\x00\x00\xFF"),
+            Err(MetadataError::Corrupted(FILENAME))
+        ));
+        assert!(matches!(
+            Metadata::parse(b"ZSMD\x01\x01\x04\x00\x00\x00ab"),
+            Err(MetadataError::Truncated(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_write_metadata() {
+        assert_eq!(read_metadata(&FRAMES).unwrap(), None);
+
+        let metadata = Metadata {
+            filename: Some("cafe".to_string()),
+            ..Metadata::default()
+        };
+        let archive = write_metadata(&FRAMES, &metadata).unwrap();
+        assert_eq!(archive[archive.len() - FRAMES.len()..], FRAMES);
+        assert_eq!(read_metadata(&archive).unwrap(), Some(metadata));
+        assert_eq!(
+            decode(&archive, &Sequential).unwrap(),
+            decode(&FRAMES, &Sequential).unwrap()
+        );
+
+        // Replaced rather than added
+        let metadata = Metadata {
+            mtime: Some(0),
+            ..Metadata::default()
+        };
+        let rewritten = write_metadata(&archive, &metadata).unwrap();
+        assert_eq!(rewritten, write_metadata(&FRAMES, &metadata).unwrap());
+        assert_eq!(read_metadata(&rewritten).unwrap(), Some(metadata));
+
+        assert!(matches!(
+            read_metadata(&FRAMES[..FRAMES.len() - 1]),
+            Err(Error::Parsing(_))
+        ));
+    }
+}
@@ -0,0 +1,156 @@
+use super::{
+    BlockType, DecodingContext, DefaultHasher, Executor, Frame, FrameIterator, Result,
+    SequenceCommand,
+};
+
+/// Block decoded by `decode_sequences`, with the LZ77 operations producing it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockSequences<'a> {
+    /// Index of the Zstandard frame, from 0, skippable frames not counted
+    pub frame: usize,
+    /// Index of the block in the frame, from 0
+    pub block: usize,
+    pub block_type: BlockType,
+    /// Offset of the content of the block in the output of all the frames
+    pub output_offset: usize,
+    pub content_size: usize,
+    /// Sequences of a compressed block, their offsets resolved to distances:
+    /// each copies `literal_length` literals then `match_length` bytes from
+    /// `offset` bytes back. The literals left after the last one end the
+    /// block. Empty for the other blocks.
+    pub sequences: &'a [SequenceCommand],
+    /// Sequences whose offset is one of the repeat offsets
+    pub repeat_offsets: usize,
+}
+
+impl BlockSequences<'_> {
+    /// Literals copied after the last sequence
+    #[must_use]
+    pub fn trailing_literals(&self) -> usize {
+        let sequenced = self
+            .sequences
+            .iter()
+            .map(|sequence| sequence.literal_length + sequence.match_length)
+            .sum::<usize>();
+        self.content_size - sequenced
+    }
+}
+
+/// Decode the frames of `bytes`, calling `on_block` with the sequences of each
+/// block once it is decoded. The content itself is not kept past the frame,
+/// the content size and checksum are verified at its end.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode_sequences, BlockType, Sequential};
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x10, 0x0, 0x0, 0xCA, 0xFE, // raw block
+///     0x13, 0x0, 0x0, 0xBA, // last block, RLE
+/// ];
+/// let mut blocks = Vec::new();
+/// decode_sequences(&bytes, &Sequential, |block| {
+///     blocks.push((block.block_type, block.output_offset, block.content_size));
+/// })?;
+/// assert_eq!(blocks, [(BlockType::Raw, 0, 2), (BlockType::Rle, 2, 2)]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decode_sequences(
+    bytes: &[u8],
+    executor: &impl Executor,
+    mut on_block: impl FnMut(&BlockSequences<'_>),
+) -> Result<()> {
+    let mut context = DecodingContext::default();
+    let mut output_offset = 0;
+    let frames =
+        FrameIterator::new(bytes).filter(|frame| !matches!(frame, Ok(Frame::SkippableFrame(_))));
+    for (index, frame) in frames.enumerate() {
+        let Frame::ZstandardFrame(mut frame) = frame? else {
+            continue;
+        };
+        context.set_limits(executor);
+        context.start_frame(frame.header().window_size(), executor.max_window_size())?;
+        for (block_index, block) in frame.take_blocks().into_iter().enumerate() {
+            let block_type = block.block_type();
+            let has_sequences = block.has_sequences();
+            let block_start = context.decoded.len();
+            let repeat_offsets = context.stats.repeat_offsets;
+            block.decode(&mut context, executor)?;
+            on_block(&BlockSequences {
+                frame: index,
+                block: block_index,
+                block_type,
+                output_offset: output_offset + block_start,
+                content_size: context.decoded.len() - block_start,
+                sequences: if has_sequences {
+                    &context.scratch.sequences
+                } else {
+                    &[]
+                },
+                repeat_offsets: context.stats.repeat_offsets - repeat_offsets,
+            });
+        }
+        frame.finish::<DefaultHasher>(&mut context)?;
+        output_offset += context.decoded.len();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Error, Sequential};
+    use super::*;
+    use alloc::vec::Vec;
+
+    #[test]
+    fn test_decode_sequences() {
+        let mut blocks = Vec::new();
+        decode_sequences(&FRAMES, &Sequential, |block| {
+            assert!(block.sequences.is_empty());
+            blocks.push((
+                block.frame,
+                block.block,
+                block.output_offset,
+                block.content_size,
+            ));
+        })
+        .unwrap();
+        assert_eq!(blocks, [(0, 0, 0, 2), (0, 1, 2, 2), (1, 0, 4, 0)]);
+
+        assert!(matches!(
+            decode_sequences(&FRAMES[..37], &Sequential, |_| {}),
+            Err(Error::Parsing(_))
+        ));
+    }
+
+    #[test]
+    fn test_replay_sequences() {
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let content = decode(bytes, &Sequential).unwrap();
+
+        // Replaying the matches of the sequences gives the content back
+        let mut replayed = Vec::new();
+        let mut compressed = 0;
+        decode_sequences(bytes, &Sequential, |block| {
+            assert_eq!(block.output_offset, replayed.len());
+            if block.block_type != BlockType::Compressed {
+                let end = replayed.len() + block.content_size;
+                replayed.extend_from_slice(&content[replayed.len()..end]);
+                return;
+            }
+            compressed += 1;
+            assert!(block.repeat_offsets <= block.sequences.len());
+            for sequence in block.sequences {
+                let literals = replayed.len();
+                replayed.extend_from_slice(&content[literals..literals + sequence.literal_length]);
+                for _ in 0..sequence.match_length {
+                    replayed.push(replayed[replayed.len() - sequence.offset]);
+                }
+            }
+            let end = replayed.len() + block.trailing_literals();
+            replayed.extend_from_slice(&content[replayed.len()..end]);
+        })
+        .unwrap();
+        assert!(compressed > 0);
+        assert_eq!(replayed, content);
+    }
+}
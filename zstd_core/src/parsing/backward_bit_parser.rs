@@ -11,7 +11,7 @@ impl<'a> BackwardBitParser<'a> {
     /// The parser is initialized skipping all 0 and the first 1 from MSB.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{BackwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{BackwardBitParser, ParsingError};
     /// let mut parser = BackwardBitParser::new(&[0b0001_1010, 0b0110_0000])?;
     /// // stream: 0b0001_1010, 0b0011_0000
     /// //                        --^ skipped initial zeroes and first one from MSB to LSB
@@ -50,7 +50,7 @@ impl<'a> BackwardBitParser<'a> {
     /// **Note**: partially parsed byte are **not** included.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{BackwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{BackwardBitParser, ParsingError};
     /// let mut parser = BackwardBitParser::new(&[0b0001_1010, 0b0110_0000])?;
     /// assert_eq!(parser.len(), 1);    // 2nd byte is partially parsed
     /// parser.take(6)?;                // consume all bits of 2nd byte
@@ -69,7 +69,7 @@ impl<'a> BackwardBitParser<'a> {
     /// Check if the bitstream is exhausted
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{BackwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{BackwardBitParser, ParsingError};
     /// let mut parser = BackwardBitParser::new(&[0b0000_0001])?; // creates an empty parser
     /// assert_eq!(parser.is_empty(), true);
     /// # Ok::<(), ParsingError>(())
@@ -82,7 +82,7 @@ impl<'a> BackwardBitParser<'a> {
     /// Return the number of available bits in the parser
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{BackwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{BackwardBitParser, ParsingError};
     /// let mut parser = BackwardBitParser::new(&[0b0100_1010])?;
     /// // stream: 0b0100_1010
     /// //           --^ skip the first 0 and first 1
@@ -103,7 +103,7 @@ impl<'a> BackwardBitParser<'a> {
     /// Panics when `len > 64` for obvious reason.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{BackwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{BackwardBitParser, ParsingError};
     /// let mut parser = BackwardBitParser::new(&[0b0111_1011])?;
     /// // stream: 0b0111_1011
     /// //           --^ skip the first 0 and first 1
@@ -116,7 +116,7 @@ impl<'a> BackwardBitParser<'a> {
         if len == 0 {
             return Ok(0);
         }
-        let available_bits = std::cmp::min(self.available_bits(), 64);
+        let available_bits = core::cmp::min(self.available_bits(), 64);
         if len > available_bits {
             return Err(Error::NotEnoughBits {
                 requested: len,
@@ -124,49 +124,29 @@ impl<'a> BackwardBitParser<'a> {
             });
         }
 
-        let reversed_stream = self.bitstream.iter().rev();
-        let mut result: u64 = 0;
-        let mut bits_remaining = len;
-        let mut byte_read = 0;
-
-        for byte in reversed_stream {
-            byte_read += 1;
-            // read up to position+1 per byte, position is in [0,7]
-            let bits_to_read = std::cmp::min(bits_remaining, self.position + 1);
-
-            // apply position offset in order to discard left-hand-side bits
-            let offset = 7 - self.position;
-            let bits = byte << offset;
-
-            // read bits, shift in order to discard right-hand-side bits
-            let bits = bits >> (8 - bits_to_read);
-
-            // shift result to make space for new bits
-            result <<= bits_to_read;
-
-            // merge read bits into result;
-            result |= u64::from(bits);
-
-            // update remaining bits count to read
-            bits_remaining -= bits_to_read;
-
-            // update position by removing bits read modulo u8
-            // (+8 is a trick to prevent int substrack overflow)
-            self.position = ((self.position + 8) - bits_to_read) % 8;
-
-            // no more bits to read, exit
-            if bits_remaining == 0 {
-                break;
+        // The stream is a little-endian number whose most significant bits are
+        // read first: the next `len` bits are the bits [remaining, available)
+        let available_bits = self.available_bits();
+        let remaining = available_bits - len;
+        let result = match self.bitstream.len().checked_sub(8) {
+            // Single load of the last 8 bytes, which hold the `len` bits unless
+            // more than 57 are read
+            Some(start) if remaining >= 8 * start => {
+                let window = u64::from_le_bytes(self.bitstream[start..].try_into().unwrap());
+                window >> (remaining - 8 * start)
             }
-        }
+            _ => {
+                let window = self.bitstream[remaining >> 3..]
+                    .iter()
+                    .rev()
+                    .fold(0_u128, |window, &byte| window << 8 | u128::from(byte));
+                (window >> (remaining & 7)) as u64
+            }
+        } & (u64::MAX >> (64 - len));
 
-        // Last byte has unread bits
-        let include_last_byte = self.position != 7;
-        let remaining_bytes = self.bitstream.len() - byte_read;
-        let (new_bitstream, _) = self
-            .bitstream
-            .split_at(remaining_bytes + usize::from(include_last_byte));
-        self.bitstream = new_bitstream;
+        // Keep the bytes holding the remaining bits, the last one possibly partially
+        self.bitstream = &self.bitstream[..(remaining + 7) >> 3];
+        self.position = (remaining + 7) & 7;
 
         Ok(result)
     }
@@ -243,7 +223,7 @@ mod tests {
         assert_eq!(parser.available_bits(), 8);
 
         let parser = BackwardBitParser::new(&[0b0000_0001]).unwrap();
-        assert_eq!(parser.is_empty(), true);
+        assert!(parser.is_empty());
         assert_eq!(parser.available_bits(), 0);
     }
 
@@ -299,7 +279,7 @@ mod tests {
         fn test_take_consumme_last_byte() {
             let bitstream: &[u8; 2] = &[0b0011_1100, 0b0001_0111];
             let mut parser = BackwardBitParser::new(bitstream).unwrap();
-            assert_eq!(parser.take(10).unwrap(), 0b0111_0011_11);
+            assert_eq!(parser.take(10).unwrap(), 0b01_1100_1111);
             assert_eq!(parser.bitstream, &[bitstream[0]]);
             assert_eq!(parser.position, 1);
 
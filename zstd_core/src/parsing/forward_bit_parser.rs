@@ -20,7 +20,7 @@ impl<'a> ForwardBitParser<'a> {
     /// **Note**: partially parsed byte are **not** included.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardBitParser, ParsingError};
     /// let mut parser = ForwardBitParser::new(&[0b0001_1010, 0b0110_0000]);
     /// assert_eq!(parser.len(), 2);
     /// parser.take(6)?;                // consume partially 1st byte
@@ -40,7 +40,7 @@ impl<'a> ForwardBitParser<'a> {
     /// Check if the bitstream is exhausted
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardBitParser};
+    /// # use zstd_core::parsing::{ForwardBitParser};
     /// let mut parser = ForwardBitParser::new(&[]);
     /// assert_eq!(parser.is_empty(), true);
     /// ```
@@ -52,7 +52,7 @@ impl<'a> ForwardBitParser<'a> {
     /// Return the number of available bits in the parser
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardBitParser, ParsingError};
     /// let mut parser = ForwardBitParser::new(&[0b0100_1010]);
     /// assert_eq!(parser.available_bits(), 8);
     /// parser.take(2)?;
@@ -71,7 +71,7 @@ impl<'a> ForwardBitParser<'a> {
     /// Return an error when bit stream is empty. Returned value is either 0 or 1.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardBitParser, ParsingError};
     /// let mut parser = ForwardBitParser::new(&[0b000_0010]);
     /// assert_eq!(parser.peek()?, 0);
     /// parser.take(1)?;
@@ -96,7 +96,7 @@ impl<'a> ForwardBitParser<'a> {
     /// Panics when `len > 64` for obvious reason.
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardBitParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardBitParser, ParsingError};
     /// let mut parser = ForwardBitParser::new(&[0b0111_1011, 0b1101_0010]);
     /// assert_eq!(parser.take(10)?, 0b10_0111_1011);
     /// # Ok::<(), ParsingError>(())
@@ -105,7 +105,7 @@ impl<'a> ForwardBitParser<'a> {
         if len == 0 {
             return Ok(0);
         }
-        let available_bits = std::cmp::min(self.available_bits(), 64);
+        let available_bits = core::cmp::min(self.available_bits(), 64);
         if len > available_bits {
             return Err(Error::NotEnoughBits {
                 requested: len,
@@ -113,44 +113,27 @@ impl<'a> ForwardBitParser<'a> {
             });
         }
 
-        let stream = self.bitstream.iter();
-        let mut result: u64 = 0;
-        let mut bits_remaining = len;
-        let mut byte_read = 0;
-
-        for byte in stream {
-            byte_read += 1;
-            // read up to 8-position per byte, position is in [0,7]
-            let bits_to_read = std::cmp::min(bits_remaining, 8 - self.position);
-            let offset = self.position;
-
-            // read bits, shift in order to discard LHS bits
-            let bits = byte << (8 - bits_to_read - offset);
-
-            // apply position offset in order to discard RHS bits
-            let bits = bits >> (8 - bits_to_read);
-
-            // merge read bits into result;
-            result |= u64::from(bits) << (len - bits_remaining);
-
-            // update remaining bits count to read
-            bits_remaining -= bits_to_read;
-
-            // update position by adding bits read modulo u8
-            self.position = (self.position + bits_to_read) % 8;
-
-            // no more bits to read, exit
-            if bits_remaining == 0 {
-                break;
+        // The stream is a little-endian number whose least significant bits are
+        // read first: the next `len` bits follow the `position` consumed ones
+        let end = self.position + len;
+        let result = match self.bitstream.get(..8) {
+            // Single load of the first 8 bytes, which hold the `len` bits unless
+            // more than 57 are read
+            Some(bytes) if end <= 64 => {
+                u64::from_le_bytes(bytes.try_into().unwrap()) >> self.position
             }
-        }
+            _ => {
+                let window = self.bitstream[..(end + 7) >> 3]
+                    .iter()
+                    .rev()
+                    .fold(0_u128, |window, &byte| window << 8 | u128::from(byte));
+                (window >> self.position) as u64
+            }
+        } & (u64::MAX >> (64 - len));
 
-        // first byte has unread bits
-        let include_first_byte = self.position != 0;
-        let (_, new_bitstream) = self
-            .bitstream
-            .split_at(byte_read - usize::from(include_first_byte));
-        self.bitstream = new_bitstream;
+        // Drop the fully consumed bytes
+        self.bitstream = &self.bitstream[end >> 3..];
+        self.position = end & 7;
 
         Ok(result)
     }
@@ -266,7 +249,7 @@ mod tests {
             assert_eq!(parser.bitstream, bitstream);
             assert_eq!(parser.position, 2);
 
-            assert_eq!(parser.take(14).unwrap(), 0b0111_0111_1010_01);
+            assert_eq!(parser.take(14).unwrap(), 0b01_1101_1110_1001);
             assert_eq!(parser.bitstream, &[bitstream[2]]);
             assert_eq!(parser.position, 0);
         }
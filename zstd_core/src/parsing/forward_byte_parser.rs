@@ -15,7 +15,7 @@ impl<'a> ForwardByteParser<'a> {
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError};
     /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03]);
     /// assert_eq!(parser.u8()?, 0x01);
     /// assert_eq!(parser.u8()?, 0x02);
@@ -35,7 +35,7 @@ impl<'a> ForwardByteParser<'a> {
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser};
+    /// # use zstd_core::parsing::{ForwardByteParser};
     /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03]);
     /// assert_eq!(parser.len(), 3);
     /// parser.u8();
@@ -50,7 +50,7 @@ impl<'a> ForwardByteParser<'a> {
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser};
+    /// # use zstd_core::parsing::{ForwardByteParser};
     /// let mut parser = ForwardByteParser::new(&[0x01]);
     /// assert_eq!(parser.is_empty(), false);
     /// parser.u8();
@@ -65,7 +65,7 @@ impl<'a> ForwardByteParser<'a> {
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser, ParsingError::{self, *}};
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError::{self, *}};
     /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03, 0x04]);
     /// assert_eq!(parser.slice(2)?, &[0x01, 0x02]);
     /// assert!(matches!(
@@ -89,11 +89,42 @@ impl<'a> ForwardByteParser<'a> {
         Ok(slice)
     }
 
+    /// Consume and return a u16 in little-endian format or `NotEnoughByte` error.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError};
+    /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03]);
+    /// assert_eq!(parser.le_u16()?, 0x0201);
+    /// # Ok::<(), ParsingError>(())
+    /// ```
+    #[allow(clippy::missing_panics_doc)]
+    pub fn le_u16(&mut self) -> Result<u16> {
+        // Will never panic, the slice is 2 bytes long
+        Ok(u16::from_le_bytes(self.slice(2)?.try_into().unwrap()))
+    }
+
+    /// Consume and return a u64 in little-endian format or `NotEnoughByte` error,
+    /// whatever the size of usize.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError};
+    /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+    /// assert_eq!(parser.le_u64()?, 0x0807_0605_0403_0201);
+    /// # Ok::<(), ParsingError>(())
+    /// ```
+    #[allow(clippy::missing_panics_doc)]
+    pub fn le_u64(&mut self) -> Result<u64> {
+        // Will never panic, the slice is 8 bytes long
+        Ok(u64::from_le_bytes(self.slice(8)?.try_into().unwrap()))
+    }
+
     /// Consume and return a u32 in little-endian format or `NotEnoughByte` error.
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError};
     /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03, 0x04, 0x05]);
     /// assert_eq!(parser.le_u32()?, 0x0403_0201);
     /// # Ok::<(), ParsingError>(())
@@ -113,7 +144,7 @@ impl<'a> ForwardByteParser<'a> {
     ///
     /// # Example
     /// ```
-    /// # use zstd_lib::parsing::{ForwardByteParser, ParsingError};
+    /// # use zstd_core::parsing::{ForwardByteParser, ParsingError};
     /// let mut parser = ForwardByteParser::new(&[0x01, 0x02, 0x03, 0x04, 0x05]);
     /// assert_eq!(parser.le(2)?, 0x0201);
     /// # Ok::<(), ParsingError>(())
@@ -176,9 +207,9 @@ mod tests {
     #[test]
     fn test_is_empty() {
         let parser = ForwardByteParser::new(&[0x12, 0x23, 0x34]);
-        assert_eq!(false, parser.is_empty());
+        assert!(!parser.is_empty());
         let parser = ForwardByteParser::new(&[]);
-        assert_eq!(true, parser.is_empty());
+        assert!(parser.is_empty());
     }
 
     #[test]
@@ -7,6 +7,7 @@ pub use forward_bit_parser::ForwardBitParser;
 pub use forward_byte_parser::ForwardByteParser;
 
 #[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
 pub enum ParsingError {
     #[error("Not enough bytes: {requested} requested out of {available} available")]
     NotEnoughBytes { requested: usize, available: usize },
@@ -19,4 +20,4 @@ pub enum ParsingError {
 }
 
 type Error = ParsingError;
-type Result<T, E = ParsingError> = std::result::Result<T, E>;
+type Result<T, E = ParsingError> = core::result::Result<T, E>;
@@ -0,0 +1,106 @@
+use super::format::skippable_magic;
+use super::{sniff, ForwardByteParser, FrameKind};
+use alloc::vec::Vec;
+use core::ops::Range;
+
+/*
+    pzstd, the parallel zstd, writes each Zstandard frame after a skippable
+    frame of magic 0x184D2A50 whose 4-byte payload is the size of the frame.
+    The frames are then found by reading these sizes, without walking their
+    block headers, and decoded in parallel from the start.
+*/
+
+/// Magic number of the skippable frames written by pzstd
+const PZSTD_MAGIC: u32 = skippable_magic(0);
+/// Size of their payload, the compressed size of the next frame
+const PZSTD_PAYLOAD_SIZE: u32 = 4;
+
+/// Ranges of the Zstandard frames of `bytes` when it is written by pzstd,
+/// found from the skippable frames announcing their sizes. `None` when `bytes`
+/// is not made of such pairs only: its frames must be found from their
+/// headers. The frames themselves are not checked past their magic number.
+///
+/// # Example
+/// ```
+/// # use zstd_core::pzstd_frames;
+/// let bytes = [
+///     0x50, 0x2A, 0x4D, 0x18, 0x4, 0x0, 0x0, 0x0, 0xB, 0x0, 0x0, 0x0, // frame size
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+/// ];
+/// assert_eq!(pzstd_frames(&bytes), Some(vec![12..23]));
+/// assert_eq!(pzstd_frames(&bytes[12..]), None);
+/// ```
+#[must_use]
+pub fn pzstd_frames(bytes: &[u8]) -> Option<Vec<Range<usize>>> {
+    let mut input = ForwardByteParser::new(bytes);
+    let mut frames = Vec::new();
+    while !input.is_empty() {
+        if input.le_u32().ok()? != PZSTD_MAGIC || input.le_u32().ok()? != PZSTD_PAYLOAD_SIZE {
+            return None;
+        }
+        let size = input.le(4).ok()?;
+        let start = bytes.len() - input.len();
+        let frame = input.slice(size).ok()?;
+        if sniff(frame) != Some(FrameKind::Zstandard) {
+            return None;
+        }
+        frames.push(start..start + size);
+    }
+    (!frames.is_empty()).then_some(frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{stream::tests::FRAMES, Error, Frame, FrameError};
+    use super::*;
+
+    /// Zstandard frames of `FRAMES`, each after a pzstd skippable frame
+    fn pzstd_archive() -> Vec<u8> {
+        let mut archive = Vec::new();
+        for frame in [&FRAMES[10..29], &FRAMES[29..]] {
+            archive.extend_from_slice(&PZSTD_MAGIC.to_le_bytes());
+            archive.extend_from_slice(&PZSTD_PAYLOAD_SIZE.to_le_bytes());
+            archive.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            archive.extend_from_slice(frame);
+        }
+        archive
+    }
+
+    #[test]
+    fn test_pzstd_frames() {
+        let archive = pzstd_archive();
+        assert_eq!(pzstd_frames(&archive), Some(vec![12..31, 43..52]));
+
+        // Not written by pzstd
+        assert_eq!(pzstd_frames(&FRAMES), None);
+        assert_eq!(pzstd_frames(&[]), None);
+        // Size past the end, or not landing on a frame
+        assert_eq!(pzstd_frames(&archive[..archive.len() - 1]), None);
+        let mut shifted = archive.clone();
+        shifted[8] -= 1;
+        assert_eq!(pzstd_frames(&shifted), None);
+        // Zstandard frame without its skippable frame
+        assert_eq!(
+            pzstd_frames(&[&archive[..31], &FRAMES[29..]].concat()),
+            None
+        );
+    }
+
+    #[test]
+    fn test_parse_sized() {
+        let archive = pzstd_archive();
+        for range in pzstd_frames(&archive).unwrap() {
+            assert!(Frame::parse_sized(&archive[range]).is_ok());
+        }
+
+        // Size announced past the end of the frame
+        let bytes = [&FRAMES[10..29], &FRAMES[..10]].concat();
+        assert!(matches!(
+            Frame::parse_sized(&bytes),
+            Err(Error::Frame(FrameError::FrameSizeMismatch {
+                announced: 29,
+                parsed: 19
+            }))
+        ));
+    }
+}
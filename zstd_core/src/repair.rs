@@ -0,0 +1,223 @@
+use super::{
+    BlockHeader, Error, ForwardByteParser, Frame, FrameError, FrameHeader, Result, Sequential,
+};
+use alloc::vec::Vec;
+
+/// Error that made a repair necessary, with the input offset of the frame,
+/// block or checksum that failed
+#[derive(Debug)]
+pub struct Damage {
+    pub offset: usize,
+    pub error: Error,
+}
+
+#[derive(Debug)]
+pub struct Repair {
+    /// Valid archive made of every frame and block decodable from the input
+    pub archive: Vec<u8>,
+    /// Size of the content decoded from `archive`
+    pub content_size: usize,
+    /// `None` when the input is intact, `archive` is then a copy of the input
+    pub damage: Option<Damage>,
+}
+
+/// Scan a damaged input and build a truncated but valid archive ending at the
+/// last fully decodable block. Frames decoded successfully are kept as is. The
+/// damaged frame keeps its decodable blocks: the last of them is flagged as the
+/// last block, the now wrong checksum is dropped and the content size is updated.
+/// A frame whose checksum does not match is dropped entirely as there is no way
+/// to tell which of its blocks are corrupted.
+pub fn repair(bytes: &[u8]) -> Result<Repair> {
+    let mut parser = ForwardByteParser::new(bytes);
+    let mut decoded = Vec::new();
+
+    while !parser.is_empty() {
+        let frame_offset = bytes.len() - parser.len();
+        let frame_decoded = decoded.len();
+
+        if let Err((error, offset)) =
+            Frame::decode_partial(&mut parser, bytes.len(), &mut decoded, &Sequential)
+        {
+            let mut archive = bytes[..frame_offset].to_vec();
+
+            // Only keep blocks when the magic number and frame header are valid
+            let checksum_mismatch = matches!(error, Error::Frame(FrameError::ChecksumMismatch));
+            if offset > frame_offset + 4 && !checksum_mismatch {
+                let frame = &bytes[frame_offset..offset];
+                truncate_frame(frame, decoded.len() - frame_decoded, &mut archive)?;
+            }
+
+            return Ok(Repair {
+                archive,
+                content_size: decoded.len(),
+                damage: Some(Damage { offset, error }),
+            });
+        }
+    }
+
+    Ok(Repair {
+        archive: bytes.to_vec(),
+        content_size: decoded.len(),
+        damage: None,
+    })
+}
+
+/// Append to `archive` the Zstandard `frame` (magic number, header and blocks) of
+/// `content_size` bytes whose last block is not flagged as such. Nothing is appended
+/// when `frame` does not contain any block.
+fn truncate_frame(frame: &[u8], content_size: usize, archive: &mut Vec<u8>) -> Result<()> {
+    let (magic, header) = frame.split_at(4);
+
+    let mut input = ForwardByteParser::new(header);
+    FrameHeader::parse(&mut input)?;
+    let blocks = <&[u8]>::from(input);
+
+    let mut input = ForwardByteParser::new(blocks);
+    let mut last_block = None;
+    while !input.is_empty() {
+        let position = blocks.len() - input.len();
+        let block_header = BlockHeader::parse(&mut input)?;
+        input.slice(block_header.content_size())?;
+        last_block = Some(position);
+    }
+
+    let Some(last_block) = last_block else {
+        return Ok(());
+    };
+
+    archive.extend_from_slice(magic);
+    write_frame_header(header, content_size, archive);
+
+    let blocks_start = archive.len();
+    archive.extend_from_slice(blocks);
+    archive[blocks_start + last_block] |= 0b0000_0001;
+
+    Ok(())
+}
+
+/// Rewrite a valid frame `header` without checksum nor dictionary, and with
+/// `content_size` as Frame_Content_Size if the original header has one.
+fn write_frame_header(header: &[u8], content_size: usize, archive: &mut Vec<u8>) {
+    let descriptor = header[0];
+    let single_segment_flag = descriptor & 0b0010_0000 != 0;
+    let has_content_size = single_segment_flag || descriptor >> 6 != 0;
+
+    // Frame_Content_Size_Flag, field size and value
+    let content_size = content_size as u64;
+    let frame_content_size = has_content_size.then(|| match content_size {
+        0..=255 if single_segment_flag => (0, 1, content_size),
+        256..=65791 => (1, 2, content_size - 256),
+        _ if content_size <= u64::from(u32::MAX) => (2, 4, content_size),
+        _ => (3, 8, content_size),
+    });
+
+    // Keep Single_Segment_Flag, clear Content_Checksum_Flag and Dictionary_ID_Flag
+    let mut new_descriptor = descriptor & 0b0010_0000;
+    if let Some((flag, _, _)) = frame_content_size {
+        new_descriptor |= flag << 6;
+    }
+
+    archive.push(new_descriptor);
+    if !single_segment_flag {
+        // Window_Descriptor
+        archive.push(header[1]);
+    }
+    if let Some((_, size, value)) = frame_content_size {
+        archive.extend_from_slice(&value.to_le_bytes()[..size]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        super::{decode, Sequential},
+        *,
+    };
+
+    const FRAME: [u8; 20] = [
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x84, 0x0, 0x0, 0x0, 0x0, 0x0, // header: FCS 4 bytes, checksum flag
+        0x10, 0x0, 0x0, // raw block, not last, len 2
+        0xCA, 0xFE, // content
+        0x12, 0x08, 0x0,  // rle block, not last, repeat 0x102
+        0xBA, // content
+        0x11, // truncated block header
+    ];
+
+    #[test]
+    fn test_repair_intact() {
+        let bytes = [
+            0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+            0x0, 0x0, // header
+            0x11, 0x0, 0x0, // raw block, last, len 2
+            0xCA, 0xFE, // content
+        ];
+        let repair = repair(&bytes).unwrap();
+        assert!(repair.damage.is_none());
+        assert_eq!(repair.archive, bytes);
+        assert_eq!(repair.content_size, 2);
+    }
+
+    #[test]
+    fn test_repair_truncated_block() {
+        let repair = repair(&FRAME).unwrap();
+        let damage = repair.damage.unwrap();
+        assert_eq!(damage.offset, 19);
+        assert!(matches!(damage.error, Error::Parsing(_)));
+        assert_eq!(repair.content_size, 0x104);
+        assert_eq!(
+            repair.archive,
+            vec![
+                0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+                0x40, 0x0, 0x04, 0x0, // header: FCS 2 bytes
+                0x10, 0x0, 0x0, // raw block, not last, len 2
+                0xCA, 0xFE, // content
+                0x13, 0x08, 0x0,  // rle block, last, repeat 0x102
+                0xBA, // content
+            ]
+        );
+
+        let decoded = decode(&repair.archive, &Sequential).unwrap();
+        assert_eq!(decoded.len(), repair.content_size);
+        assert_eq!(decoded[..3], [0xCA, 0xFE, 0xBA]);
+    }
+
+    #[test]
+    fn test_repair_drop_frame() {
+        let mut bytes = vec![
+            0x53, 0x2a, 0x4d, 0x18, // skippable magic:   0x184d2a53
+            0x01, 0x00, 0x00, 0x00, // length:  1
+            0x10, // content
+        ];
+        let skippable_len = bytes.len();
+        // frame truncated in its first block
+        bytes.extend_from_slice(&FRAME[..13]);
+
+        let repair = repair(&bytes).unwrap();
+        assert_eq!(repair.damage.unwrap().offset, skippable_len + 10);
+        assert_eq!(repair.archive, bytes[..skippable_len]);
+        assert_eq!(repair.content_size, 0);
+    }
+
+    #[test]
+    fn test_write_frame_header() {
+        let mut header = vec![];
+        write_frame_header(&[0b0010_0100, 0x42], 0x42, &mut header);
+        assert_eq!(header, vec![0b0010_0000, 0x42]);
+
+        let mut header = vec![];
+        write_frame_header(&[0b0010_0100, 0x42], 0x142, &mut header);
+        assert_eq!(header, vec![0b0110_0000, 0x42, 0x0]);
+
+        let mut header = vec![];
+        write_frame_header(&[0b0000_0111, 0x50, 0x0, 0x0, 0x0, 0x0], 0x42, &mut header);
+        assert_eq!(header, vec![0b0000_0000, 0x50]);
+
+        let mut header = vec![];
+        write_frame_header(&[0b1100_0000, 0x50], 0x1_0000_0000, &mut header);
+        assert_eq!(
+            header,
+            vec![0b1100_0000, 0x50, 0x0, 0x0, 0x0, 0x0, 0x1, 0x0, 0x0, 0x0]
+        );
+    }
+}
@@ -0,0 +1,129 @@
+use super::format::{CHECKSUM_SIZE, CONTENT_CHECKSUM_FLAG};
+use super::{check_magic, frames_metadata, Result};
+#[cfg(feature = "xxhash")]
+use super::{ContentHasher, DecodingContext, Executor, ForwardByteParser, Frame, XxHash64};
+use alloc::vec::Vec;
+
+/// Offset of the Frame_Header_Descriptor, which follows the magic number
+const DESCRIPTOR_OFFSET: usize = 4;
+
+/// Rewrite the Zstandard frames of `bytes` without a content checksum to end
+/// with one, computed by decoding them. The blocks are copied as is, only the
+/// checksum flag of the header is set. Frames which have a checksum and
+/// skippable frames are copied without decoding them.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{add_checksums, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let archive = add_checksums(&bytes, &Sequential)?;
+/// assert_eq!(archive[4], 0x04);
+/// assert_eq!(archive[11..], [0x23, 0xAE, 0x5C, 0xB0]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+#[cfg(feature = "xxhash")]
+pub fn add_checksums(bytes: &[u8], executor: &impl Executor) -> Result<Vec<u8>> {
+    check_magic(bytes)?;
+
+    let mut archive = Vec::with_capacity(bytes.len());
+    // The buffer of the context is reused from one frame to the next
+    let mut context = DecodingContext::default();
+    for metadata in frames_metadata(bytes) {
+        let metadata = metadata?;
+        let frame = &bytes[metadata.offset..metadata.offset + metadata.compressed_size];
+        let start = archive.len();
+        archive.extend_from_slice(frame);
+        if metadata.header.is_none() || metadata.checksum.is_some() {
+            continue;
+        }
+
+        let mut hasher = XxHash64::default();
+        Frame::parse(&mut ForwardByteParser::new(frame))?.decode_with_callback(
+            &mut context,
+            executor,
+            |block| hasher.update(block),
+        )?;
+        archive[start + DESCRIPTOR_OFFSET] |= CONTENT_CHECKSUM_FLAG;
+        archive.extend_from_slice(&hasher.digest().to_le_bytes());
+    }
+    Ok(archive)
+}
+
+/// Rewrite the Zstandard frames of `bytes` without their content checksum,
+/// found from the headers without decoding anything. The blocks are copied as
+/// is, only the checksum flag of the header is cleared.
+///
+/// # Example
+/// ```
+/// # use zstd_core::strip_checksums;
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x04, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE, // frame
+///     0x23, 0xAE, 0x5C, 0xB0, // checksum
+/// ];
+/// let archive = strip_checksums(&bytes)?;
+/// assert_eq!(archive, [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn strip_checksums(bytes: &[u8]) -> Result<Vec<u8>> {
+    check_magic(bytes)?;
+
+    let mut archive = Vec::with_capacity(bytes.len());
+    for metadata in frames_metadata(bytes) {
+        let metadata = metadata?;
+        let frame = &bytes[metadata.offset..metadata.offset + metadata.compressed_size];
+        if metadata.checksum.is_none() {
+            archive.extend_from_slice(frame);
+            continue;
+        }
+
+        let start = archive.len();
+        archive.extend_from_slice(&frame[..frame.len() - CHECKSUM_SIZE]);
+        archive[start + DESCRIPTOR_OFFSET] &= !CONTENT_CHECKSUM_FLAG;
+    }
+    Ok(archive)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Error, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_strip_checksums() {
+        let stripped = strip_checksums(&FRAMES).unwrap();
+        let mut expected = FRAMES[..25].to_vec();
+        expected[14] = 0x0;
+        expected.extend_from_slice(&FRAMES[29..]);
+        assert_eq!(stripped, expected);
+        assert_eq!(
+            decode(&stripped, &Sequential).unwrap(),
+            decode(&FRAMES, &Sequential).unwrap()
+        );
+
+        // Nothing left to strip
+        assert_eq!(strip_checksums(&stripped).unwrap(), stripped);
+        assert!(strip_checksums(&[]).unwrap().is_empty());
+        assert!(matches!(
+            strip_checksums(b"GIF89a"),
+            Err(Error::NotZstandardData)
+        ));
+        assert!(strip_checksums(&FRAMES[..28]).is_err());
+    }
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn test_add_checksums() {
+        let archive = add_checksums(&FRAMES, &Sequential).unwrap();
+        // The checksum of the empty frame is added, the other is kept
+        let mut expected = FRAMES.to_vec();
+        expected[33] = 0x04;
+        expected.extend_from_slice(&[0x99, 0xE9, 0xD8, 0x51]);
+        assert_eq!(archive, expected);
+        assert_eq!(add_checksums(&archive, &Sequential).unwrap(), archive);
+
+        // Round trip through stripping
+        let stripped = strip_checksums(&FRAMES).unwrap();
+        assert_eq!(add_checksums(&stripped, &Sequential).unwrap(), expected);
+        assert!(add_checksums(&FRAMES[..37], &Sequential).is_err());
+    }
+}
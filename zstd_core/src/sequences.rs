@@ -0,0 +1,649 @@
+use super::{
+    BackwardBitParser, BitDecoder, Error, ForwardBitParser, ForwardByteParser, FseDecoder,
+    FseTable, RLEDecoder, Result, SequenceDecoder, SequenceTables, SequencesMode, SymbolDecoder,
+};
+use alloc::{boxed::Box, vec::Vec};
+
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SequencesError {
+    #[error("Invalid reserved bits value")]
+    InvalidDataError,
+
+    /// `Repeat` mode without a previous table in the frame, e.g. in its first
+    /// compressed block
+    #[error("Repeat mode without a previous table in the frame: {0:?}")]
+    RepeatWithoutTable(SymbolType),
+
+    #[error("Symbol code unknown")]
+    SymbolCodeUnknown,
+
+    #[error("FSE AL is too large")]
+    ALTooLarge,
+
+    #[error("Number_of_Sequences of {size} bytes truncated to {available}")]
+    TruncatedNumberOfSequences { size: usize, available: usize },
+
+    #[error("Offset code {code} exceeds the maximum of {max} for the window size")]
+    OffsetCodeTooLarge { code: u16, max: u16 },
+
+    #[error("Data after a sequences section without sequences")]
+    TrailingData,
+
+    /// The bitstream must be consumed exactly by the last sequence, which does
+    /// not update the FSE states
+    #[error("{0} bits left in the sequences bitstream after the last sequence")]
+    UnconsumedBits(usize),
+}
+use SequencesError::{
+    ALTooLarge, InvalidDataError, OffsetCodeTooLarge, RepeatWithoutTable, SymbolCodeUnknown,
+    TrailingData, TruncatedNumberOfSequences, UnconsumedBits,
+};
+
+#[allow(clippy::redundant_field_names)]
+#[derive(Debug)]
+pub struct Sequences<'a> {
+    number: usize,
+    literal_lengths_mode: SymbolCompressor,
+    offsets_mode: SymbolCompressor,
+    match_lengths_mode: SymbolCompressor,
+    bitstream: &'a [u8],
+}
+
+#[derive(Debug)]
+struct SymbolCompressor {
+    compression_mode: CompressionMode,
+    symbol_type: SymbolType,
+}
+
+#[derive(Debug)]
+enum CompressionMode {
+    Predefined,
+    Rle(u8),
+    FseCompressed(FseTable),
+    Repeat,
+}
+use CompressionMode::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SymbolType {
+    LiteralsLength,
+    MatchLength,
+    Offset,
+}
+use SymbolType::{LiteralsLength, MatchLength, Offset};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SequenceCommand {
+    pub literal_length: usize,
+    pub match_length: usize,
+    pub offset: usize,
+}
+
+struct DefaultDistribution<'a> {
+    accuracy_log: u8,
+    distribution: &'a [i16],
+}
+
+const LITERALS_LENGTH_DEFAULT_DISTRIBUTION: DefaultDistribution<'_> = DefaultDistribution {
+    accuracy_log: 6,
+    distribution: &[
+        4, 3, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 2, 1, 1, 1, 2, 2, 2, 2, 2, 2, 2, 2, 2, 3, 2, 1, 1, 1,
+        1, 1, -1, -1, -1, -1,
+    ],
+};
+const MATCH_LENGTH_DEFAULT_DISTRIBUTION: DefaultDistribution<'_> = DefaultDistribution {
+    accuracy_log: 6,
+    distribution: &[
+        1, 4, 3, 2, 2, 2, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
+        1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1, -1, -1,
+    ],
+};
+const OFFSET_CODE_DEFAULT_DISTRIBUTION: DefaultDistribution<'_> = DefaultDistribution {
+    accuracy_log: 5,
+    distribution: &[
+        1, 1, 1, 1, 1, 1, 2, 2, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, -1, -1, -1, -1, -1,
+    ],
+};
+
+/// FSE tables of the `Predefined` mode, built on first use and then kept in
+/// the scratch of the decoding context. Built through `&mut self` rather than
+/// a lazily initialized static, they are `Send + Sync` without locking.
+#[derive(Default)]
+pub struct PredefinedTables {
+    literals_lengths: Option<FseTable>,
+    match_lengths: Option<FseTable>,
+    offsets: Option<FseTable>,
+}
+
+impl PredefinedTables {
+    /// Bytes of memory allocated for the tables built so far
+    pub(crate) fn heap_size(&self) -> usize {
+        [&self.literals_lengths, &self.match_lengths, &self.offsets]
+            .into_iter()
+            .flatten()
+            .map(FseTable::heap_size)
+            .sum()
+    }
+
+    fn get(&mut self, symbol_type: &SymbolType) -> Result<&FseTable> {
+        let (table, default) = match symbol_type {
+            LiteralsLength => (
+                &mut self.literals_lengths,
+                LITERALS_LENGTH_DEFAULT_DISTRIBUTION,
+            ),
+            MatchLength => (&mut self.match_lengths, MATCH_LENGTH_DEFAULT_DISTRIBUTION),
+            Offset => (&mut self.offsets, OFFSET_CODE_DEFAULT_DISTRIBUTION),
+        };
+        if table.is_none() {
+            *table = Some(FseTable::from_distribution(
+                default.accuracy_log,
+                default.distribution,
+            )?);
+        }
+        Ok(table.as_ref().unwrap())
+    }
+}
+
+impl SymbolCompressor {
+    /// Parse the compression mode
+    fn parse(mode: u8, symbol_type: SymbolType, input: &mut ForwardByteParser) -> Result<Self> {
+        let compression_mode = match mode {
+            0 => Predefined,
+            1 => Rle(input.u8()?),
+            2 => {
+                let mut parser = ForwardBitParser::from(*input);
+                let fse_table = FseTable::parse(&mut parser)?;
+                *input = ForwardByteParser::from(parser);
+
+                // Not sure about this part, from the doc:
+                //      Note that the maximum allowed accuracy log for literals length code and match length code tables is 9,
+                //      and the maximum accuracy log for the offset code table is 8.
+                //      This mode must not be used when only one symbol is present;
+                //      RLE_Mode should be used instead (although any other mode will work).
+                let max_al = match symbol_type {
+                    MatchLength | LiteralsLength => 9,
+                    Offset => 8,
+                };
+                if fse_table.accuracy_log() > max_al {
+                    return Err(Error::Sequences(ALTooLarge));
+                }
+
+                if fse_table.accuracy_log() == 0 {
+                    Predefined
+                } else {
+                    FseCompressed(fse_table)
+                }
+            }
+            3 => Repeat,
+            _ => panic!("unexpected compression mode value {mode}"),
+        };
+
+        Ok(Self {
+            compression_mode,
+            symbol_type,
+        })
+    }
+
+    /// Parse the compression mode respective decoder
+    fn parse_decoder(
+        &self,
+        tables: &mut SequenceTables,
+        predefined_tables: &mut PredefinedTables,
+        parser: &mut BackwardBitParser,
+    ) -> Result<Box<SymbolDecoder>> {
+        let decoder = match &self.compression_mode {
+            Predefined => {
+                let fse_table = predefined_tables.get(&self.symbol_type)?.clone();
+                let mut fse_decoder = FseDecoder::new(fse_table);
+                fse_decoder.initialize(parser)?;
+                Box::new(fse_decoder) as Box<SymbolDecoder>
+            }
+            Rle(byte) => {
+                let rle_decoder = RLEDecoder::new(u16::from(*byte));
+                Box::new(rle_decoder) as Box<SymbolDecoder>
+            }
+            FseCompressed(fse_table) => {
+                let mut fse_decoder = FseDecoder::new(fse_table.clone());
+                fse_decoder.initialize(parser)?;
+                Box::new(fse_decoder) as Box<SymbolDecoder>
+            }
+            Repeat => {
+                // The tables are reset at the start of each frame: they are only
+                // set by a previous sequences section of the same frame
+                let table = match &self.symbol_type {
+                    LiteralsLength => &mut tables.literals_lengths_decoder,
+                    MatchLength => &mut tables.match_lengths_decoder,
+                    Offset => &mut tables.offsets_decoder,
+                };
+                let Some(mut repeat_decoder) = table.take() else {
+                    return Err(Error::Sequences(RepeatWithoutTable(self.symbol_type)));
+                };
+
+                repeat_decoder.reset();
+                repeat_decoder.initialize(parser)?;
+                repeat_decoder
+            }
+        };
+
+        Ok(decoder)
+    }
+}
+
+impl<'a> Sequences<'a> {
+    /// Number_of_Sequences takes 1 to 3 bytes depending on its first one: up to
+    /// 127, up to 0x7EFF, and up to 0xFFFF + 0x7F00
+    fn parse_number_of_sequences(input: &mut ForwardByteParser) -> Result<usize> {
+        let byte_0 = input.u8()?;
+        let available = input.len() + 1;
+        let truncated = |size| TruncatedNumberOfSequences { size, available };
+
+        let number = match byte_0 {
+            0..=127 => usize::from(byte_0),
+            128..=254 => {
+                let byte_1 = input.u8().map_err(|_| truncated(2))?;
+                (usize::from(byte_0 - 0x80) << 8) + usize::from(byte_1)
+            }
+            255 => usize::from(input.le_u16().map_err(|_| truncated(3))?) + 0x7F00,
+        };
+        Ok(number)
+    }
+
+    #[allow(clippy::similar_names)]
+    fn parse_compression_modes(
+        input: &mut ForwardByteParser,
+    ) -> Result<(SymbolCompressor, SymbolCompressor, SymbolCompressor)> {
+        let modes = input.u8()?;
+
+        let ll_mode = (modes & 0b1100_0000) >> 6;
+        let of_mode = (modes & 0b0011_0000) >> 4;
+        let ml_mode = (modes & 0b0000_1100) >> 2;
+
+        // Parse order: [literal][offset][match]
+        let ll = SymbolCompressor::parse(ll_mode, LiteralsLength, input)?;
+        let of = SymbolCompressor::parse(of_mode, Offset, input)?;
+        let ml = SymbolCompressor::parse(ml_mode, MatchLength, input)?;
+
+        let reserved = modes & 0b11;
+        if reserved != 0 {
+            return Err(Error::Sequences(InvalidDataError));
+        }
+
+        Ok((ll, of, ml))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.number
+    }
+
+    /// Modes of the literals lengths, offsets and match lengths codes, `None`
+    /// without sequences
+    pub(crate) fn modes(&self) -> Option<[SequencesMode; 3]> {
+        let modes = [
+            &self.literal_lengths_mode,
+            &self.offsets_mode,
+            &self.match_lengths_mode,
+        ];
+        (self.number != 0).then(|| {
+            modes.map(|mode| match mode.compression_mode {
+                Predefined => SequencesMode::Predefined,
+                Rle(_) => SequencesMode::Rle,
+                FseCompressed(_) => SequencesMode::Fse,
+                Repeat => SequencesMode::Repeat,
+            })
+        })
+    }
+
+    /// Whether the section has no sequences, the literals being the whole block
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.number == 0
+    }
+
+    /// Parse the sequences data from the stream
+    pub fn parse(input: &mut ForwardByteParser<'a>) -> Result<Self> {
+        let number = Self::parse_number_of_sequences(input)?;
+        if number == 0 {
+            // The block ends with the section header
+            if !input.is_empty() {
+                return Err(Error::Sequences(TrailingData));
+            }
+            return Ok(Sequences {
+                number: 0,
+                literal_lengths_mode: SymbolCompressor {
+                    compression_mode: Predefined,
+                    symbol_type: LiteralsLength,
+                },
+                offsets_mode: SymbolCompressor {
+                    compression_mode: Predefined,
+                    symbol_type: Offset,
+                },
+                match_lengths_mode: SymbolCompressor {
+                    compression_mode: Predefined,
+                    symbol_type: MatchLength,
+                },
+                bitstream: &[],
+            });
+        }
+
+        let (ll, of, ml) = Self::parse_compression_modes(input)?;
+
+        let bitstream = <&[u8]>::from(*input);
+
+        Ok(Sequences {
+            number,
+            literal_lengths_mode: ll,
+            offsets_mode: of,
+            match_lengths_mode: ml,
+            bitstream,
+        })
+    }
+
+    /// Parse the symbol decoders and update the tables
+    #[allow(clippy::similar_names)]
+    fn parse_sequence_decoder(
+        &'a self,
+        parser: &mut BackwardBitParser,
+        tables: &'a mut SequenceTables,
+        predefined_tables: &mut PredefinedTables,
+    ) -> Result<SequenceDecoder<'a>> {
+        // initialize order: literals > offsets > match
+        let ll_decoder =
+            self.literal_lengths_mode
+                .parse_decoder(tables, predefined_tables, parser)?;
+        let of_decoder = self
+            .offsets_mode
+            .parse_decoder(tables, predefined_tables, parser)?;
+        let ml_decoder =
+            self.match_lengths_mode
+                .parse_decoder(tables, predefined_tables, parser)?;
+
+        tables.literals_lengths_decoder = Some(ll_decoder);
+        tables.offsets_decoder = Some(of_decoder);
+        tables.match_lengths_decoder = Some(ml_decoder);
+
+        Ok(tables.get_sequence_decoder()?)
+    }
+
+    fn decode_sequence(
+        decoder: &mut SequenceDecoder,
+        input: &mut BackwardBitParser,
+        is_last: bool,
+        max_offset_code: u16,
+    ) -> Result<SequenceCommand> {
+        // decode order: offset > match > literals
+        let (literals_symbol, offset_symbol, match_symbol) = decoder.symbol();
+
+        if offset_symbol > max_offset_code {
+            return Err(Error::Sequences(OffsetCodeTooLarge {
+                code: offset_symbol,
+                max: max_offset_code,
+            }));
+        }
+
+        // offset
+        let offset_code = (1_usize << offset_symbol)
+            + usize::try_from(input.take(offset_symbol.into())?).unwrap();
+
+        // match
+        let (value, num_bits) = match_lengths_code_lookup(match_symbol)?;
+        let match_code = value + usize::try_from(input.take(num_bits)?).unwrap();
+
+        // literals
+        let (value, num_bits) = literals_lengths_code_lookup(literals_symbol)?;
+        let literals_code = value + usize::try_from(input.take(num_bits)?).unwrap();
+
+        trace_event!(
+            states = ?decoder.states(),
+            codes = ?[literals_symbol, offset_symbol, match_symbol],
+            extra_bits = ?[
+                literals_code - value,
+                offset_code - (1 << offset_symbol),
+                match_code - match_lengths_code_lookup(match_symbol)?.0,
+            ],
+            literals_length = literals_code,
+            offset_value = offset_code,
+            match_length = match_code,
+            "sequence"
+        );
+
+        // update bits if it is not the last sequence
+        if !is_last {
+            decoder.update_bits(input)?;
+        }
+
+        Ok(SequenceCommand {
+            literal_length: literals_code,
+            match_length: match_code,
+            offset: offset_code,
+        })
+        // sequence_decoder.update_bits(&mut parser)?;
+    }
+
+    /// Decode the (literals length, offset value, match length) of the sequences
+    /// into `output`, which is cleared first, and update the symbol decoder
+    /// `tables` if appropriate.
+    ///
+    /// The sequences are collected rather than executed as they are decoded:
+    /// the section is decoded while the literals are, which executing needs.
+    /// `output` is the scratch buffer of the context and is sized once from
+    /// Number_of_Sequences. Offset codes above `max_offset_code`, which depends
+    /// on the window size, are rejected.
+    pub fn decode(
+        self,
+        tables: &mut SequenceTables,
+        predefined_tables: &mut PredefinedTables,
+        max_offset_code: u16,
+        output: &mut Vec<SequenceCommand>,
+    ) -> Result<()> {
+        let _span = span!("decode_sequences", sequences = self.number);
+        output.clear();
+        if self.number == 0 {
+            return Ok(());
+        }
+
+        let mut parser = BackwardBitParser::new(self.bitstream)?;
+        let mut sequence_decoder =
+            self.parse_sequence_decoder(&mut parser, tables, predefined_tables)?;
+
+        output.reserve_exact(self.number);
+        for i in 0..self.number {
+            let is_last = i == self.number - 1;
+            let command = Self::decode_sequence(
+                &mut sequence_decoder,
+                &mut parser,
+                is_last,
+                max_offset_code,
+            )?;
+            output.push(command);
+        }
+
+        match parser.available_bits() {
+            0 => Ok(()),
+            bits => Err(Error::Sequences(UnconsumedBits(bits))),
+        }
+    }
+}
+
+/// (Baseline, Number_of_Bits) of the literals length codes, indexed by symbol
+/// (RFC 8878, section 3.1.1.3.2.1.1)
+#[rustfmt::skip]
+const LITERALS_LENGTH_CODES: [(usize, usize); 36] = [
+    /*  0 */ (0, 0), (1, 0), (2, 0), (3, 0), (4, 0), (5, 0), (6, 0), (7, 0),
+    /*  8 */ (8, 0), (9, 0), (10, 0), (11, 0), (12, 0), (13, 0), (14, 0), (15, 0),
+    /* 16 */ (16, 1), (18, 1), (20, 1), (22, 1), (24, 2), (28, 2), (32, 3), (40, 3),
+    /* 24 */ (48, 4), (64, 6), (128, 7), (256, 8), (512, 9), (1024, 10), (2048, 11), (4096, 12),
+    /* 32 */ (8192, 13), (16384, 14), (32768, 15), (65536, 16),
+];
+
+/// (Baseline, Number_of_Bits) of the match length codes, indexed by symbol
+/// (RFC 8878, section 3.1.1.3.2.1.1)
+#[rustfmt::skip]
+const MATCH_LENGTH_CODES: [(usize, usize); 53] = [
+    /*  0 */ (3, 0), (4, 0), (5, 0), (6, 0), (7, 0), (8, 0), (9, 0), (10, 0),
+    /*  8 */ (11, 0), (12, 0), (13, 0), (14, 0), (15, 0), (16, 0), (17, 0), (18, 0),
+    /* 16 */ (19, 0), (20, 0), (21, 0), (22, 0), (23, 0), (24, 0), (25, 0), (26, 0),
+    /* 24 */ (27, 0), (28, 0), (29, 0), (30, 0), (31, 0), (32, 0), (33, 0), (34, 0),
+    /* 32 */ (35, 1), (37, 1), (39, 1), (41, 1), (43, 2), (47, 2), (51, 3), (59, 3),
+    /* 40 */ (67, 4), (83, 4), (99, 5), (131, 7), (259, 8), (515, 9), (1027, 10), (2051, 11),
+    /* 48 */ (4099, 12), (8195, 13), (16387, 14), (32771, 15), (65539, 16),
+];
+
+fn literals_lengths_code_lookup(symbol: u16) -> Result<(usize, usize)> {
+    LITERALS_LENGTH_CODES
+        .get(usize::from(symbol))
+        .copied()
+        .ok_or(Error::Sequences(SymbolCodeUnknown))
+}
+
+fn match_lengths_code_lookup(symbol: u16) -> Result<(usize, usize)> {
+    MATCH_LENGTH_CODES
+        .get(usize::from(symbol))
+        .copied()
+        .ok_or(Error::Sequences(SymbolCodeUnknown))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_length_codes() {
+        // The codes of a table cover contiguous ranges of lengths
+        for codes in [&LITERALS_LENGTH_CODES[..], &MATCH_LENGTH_CODES[..]] {
+            for pair in codes.windows(2) {
+                let ((baseline, num_bits), (next, _)) = (pair[0], pair[1]);
+                assert_eq!(baseline + (1 << num_bits), next);
+            }
+        }
+        assert_eq!(literals_lengths_code_lookup(35).unwrap(), (65536, 16));
+        assert_eq!(match_lengths_code_lookup(0).unwrap(), (3, 0));
+        assert!(matches!(
+            literals_lengths_code_lookup(36),
+            Err(Error::Sequences(SymbolCodeUnknown))
+        ));
+        assert!(matches!(
+            match_lengths_code_lookup(53),
+            Err(Error::Sequences(SymbolCodeUnknown))
+        ));
+    }
+
+    fn decode(bytes: &[u8], tables: &mut SequenceTables) -> Result<Vec<SequenceCommand>> {
+        decode_with_max_offset_code(bytes, tables, 31)
+    }
+
+    fn decode_with_max_offset_code(
+        bytes: &[u8],
+        tables: &mut SequenceTables,
+        max_offset_code: u16,
+    ) -> Result<Vec<SequenceCommand>> {
+        let sequences = Sequences::parse(&mut ForwardByteParser::new(bytes))?;
+        let mut output = Vec::new();
+        let predefined_tables = &mut PredefinedTables::default();
+        sequences.decode(tables, predefined_tables, max_offset_code, &mut output)?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_decode_max_offset_code() {
+        // 1 sequence, RLE modes: literals length 1, offset code 4 + 2 bits, match length 3
+        let rle = [0x01, 0x54, 0x01, 0x02, 0x00, 0x04];
+        let mut tables = SequenceTables::default();
+        assert_eq!(
+            decode_with_max_offset_code(&rle, &mut tables, 2).unwrap()[0].offset,
+            4
+        );
+        assert!(matches!(
+            decode_with_max_offset_code(&rle, &mut tables, 1),
+            Err(Error::Sequences(OffsetCodeTooLarge { code: 2, max: 1 }))
+        ));
+    }
+
+    #[test]
+    fn test_parse_number_of_sequences() {
+        let number = |bytes: &[u8]| {
+            let mut input = ForwardByteParser::new(bytes);
+            let number = Sequences::parse_number_of_sequences(&mut input);
+            (number, input.len())
+        };
+
+        assert_eq!(number(&[0x00]).0.unwrap(), 0);
+        assert_eq!(number(&[0x7F, 0xAA]).0.unwrap(), 127);
+        let (result, left) = number(&[0x80, 0x80, 0xAA]);
+        assert_eq!((result.unwrap(), left), (128, 1));
+        assert_eq!(number(&[0xFE, 0xFF]).0.unwrap(), 0x7EFF);
+        let (result, left) = number(&[0xFF, 0x00, 0x00, 0xAA]);
+        assert_eq!((result.unwrap(), left), (0x7F00, 1));
+        assert_eq!(number(&[0xFF, 0xFF, 0xFF]).0.unwrap(), 0xFFFF + 0x7F00);
+
+        assert!(matches!(
+            number(&[]).0,
+            Err(Error::Parsing(crate::ParsingError::NotEnoughBytes { .. }))
+        ));
+        assert!(matches!(
+            number(&[0x80]).0,
+            Err(Error::Sequences(TruncatedNumberOfSequences {
+                size: 2,
+                available: 1
+            }))
+        ));
+        assert!(matches!(
+            number(&[0xFF, 0x01]).0,
+            Err(Error::Sequences(TruncatedNumberOfSequences {
+                size: 3,
+                available: 2
+            }))
+        ));
+    }
+
+    #[test]
+    fn test_decode_repeat() {
+        // 1 sequence, RLE modes: literals length 1, offset code 4 + 2 bits, match length 3
+        let rle = [0x01, 0x54, 0x01, 0x02, 0x00, 0x04];
+        // 1 sequence, Repeat modes
+        let repeat = [0x01, 0xFC, 0x04];
+        let command = SequenceCommand {
+            literal_length: 1,
+            match_length: 3,
+            offset: 4,
+        };
+
+        let mut tables = SequenceTables::default();
+        assert!(matches!(
+            decode(&repeat, &mut tables),
+            Err(Error::Sequences(RepeatWithoutTable(LiteralsLength)))
+        ));
+
+        assert_eq!(decode(&rle, &mut tables).unwrap(), [command]);
+        assert_eq!(decode(&repeat, &mut tables).unwrap(), [command]);
+        // A section without sequences keeps the tables
+        decode(&[0x00], &mut tables).unwrap();
+        assert_eq!(decode(&repeat, &mut tables).unwrap(), [command]);
+
+        // Repeat reuses the FSE tables, not their final states: the states are
+        // read again from the bitstream
+        let mut tables = SequenceTables::default();
+        let predefined = decode(&[0x01, 0x00, 0x00, 0x00, 0x02], &mut tables).unwrap();
+        assert_eq!(
+            decode(&[0x01, 0xFC, 0x00, 0x00, 0x02], &mut tables).unwrap(),
+            predefined
+        );
+        assert_eq!(
+            decode(&[0x01, 0xFC, 0x00, 0x00, 0x02], &mut tables).unwrap(),
+            predefined
+        );
+
+        // Bits left after the last sequence
+        assert!(matches!(
+            decode(&[0x01, 0x54, 0x01, 0x02, 0x00, 0x08], &mut tables),
+            Err(Error::Sequences(UnconsumedBits(1)))
+        ));
+
+        // Only the offsets table in Repeat mode
+        let mut tables = SequenceTables::default();
+        assert!(matches!(
+            decode(&[0x01, 0x74, 0x01, 0x00, 0x04], &mut tables),
+            Err(Error::Sequences(RepeatWithoutTable(Offset)))
+        ));
+    }
+}
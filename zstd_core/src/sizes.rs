@@ -0,0 +1,133 @@
+use super::format::{BLOCK_SIZE_MAX, SKIPPABLE_HEADER_SIZE};
+use super::{
+    frames_metadata, Error, ForwardByteParser, FrameError, FrameHeader, FrameKind, FrameMetadata,
+    Result,
+};
+
+/*
+    Counterparts of the size functions of libzstd, for code ported from it:
+    they read headers only and fail where libzstd returns an error code.
+        - ZSTD_getFrameContentSize: get_frame_content_size, `None` for
+          ZSTD_CONTENTSIZE_UNKNOWN
+        - ZSTD_findFrameCompressedSize: find_frame_compressed_size
+        - ZSTD_decompressBound: decompress_bound
+*/
+
+/// Content size announced by the header of the frame at the start of `bytes`,
+/// `None` when it does not announce one, 0 for a skippable frame. Only the
+/// header is read: `bytes` can end after it.
+///
+/// # Example
+/// ```
+/// # use zstd_core::get_frame_content_size;
+/// // Single segment frame, content size 2
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x2];
+/// assert_eq!(get_frame_content_size(&bytes)?, Some(2));
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0];
+/// assert_eq!(get_frame_content_size(&bytes)?, None);
+/// assert!(get_frame_content_size(&bytes[..5]).is_err());
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn get_frame_content_size(bytes: &[u8]) -> Result<Option<u64>> {
+    let mut input = ForwardByteParser::new(bytes);
+    let magic = input.le_u32()?;
+    match FrameKind::from_magic(magic) {
+        Some(FrameKind::Zstandard) => Ok(FrameHeader::parse(&mut input)?.content_size()),
+        Some(FrameKind::Skippable) => {
+            input.slice(SKIPPABLE_HEADER_SIZE - 4)?;
+            Ok(Some(0))
+        }
+        None => Err(Error::Frame(FrameError::UnrecognizedMagic(magic))),
+    }
+}
+
+/// Size of the frame at the start of `bytes`, Zstandard or skippable, found by
+/// walking its block headers: the offset of the next frame. Fails when the
+/// frame is incomplete.
+///
+/// # Example
+/// ```
+/// # use zstd_core::find_frame_compressed_size;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE, 0x28];
+/// assert_eq!(find_frame_compressed_size(&bytes)?, 11);
+/// assert!(find_frame_compressed_size(&bytes[..10]).is_err());
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn find_frame_compressed_size(bytes: &[u8]) -> Result<usize> {
+    Ok(FrameMetadata::scan(bytes, 0)?.compressed_size)
+}
+
+/// Upper bound of the content size of all the frames of `bytes`: the content
+/// size announced by a frame, or its number of blocks times their largest
+/// size when it does not announce one. Saturated at `u64::MAX`. Fails when a
+/// frame is incomplete.
+///
+/// # Example
+/// ```
+/// # use zstd_core::decompress_bound;
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x2, 0x11, 0x0, 0x0, 0xCA, 0xFE, // content size 2
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE, // 1 block, 1 KB window
+/// ];
+/// assert_eq!(decompress_bound(&bytes)?, 2 + 1024);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decompress_bound(bytes: &[u8]) -> Result<u64> {
+    let mut bound = 0_u64;
+    for metadata in frames_metadata(bytes) {
+        // Skippable frames have no content
+        let metadata = metadata?;
+        let Some(header) = metadata.header else {
+            continue;
+        };
+        let size = header.content_size().unwrap_or_else(|| {
+            let block_size_max = core::cmp::min(header.window_size(), BLOCK_SIZE_MAX);
+            (metadata.block_count as u64).saturating_mul(block_size_max as u64)
+        });
+        bound = bound.saturating_add(size);
+    }
+    Ok(bound)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, ParsingError, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_get_frame_content_size() {
+        // Skippable frame, then a frame without content size
+        assert_eq!(get_frame_content_size(&FRAMES).unwrap(), Some(0));
+        assert_eq!(get_frame_content_size(&FRAMES[..8]).unwrap(), Some(0));
+        assert_eq!(get_frame_content_size(&FRAMES[10..]).unwrap(), None);
+        assert!(matches!(
+            get_frame_content_size(&FRAMES[..7]),
+            Err(Error::Parsing(ParsingError::NotEnoughBytes { .. }))
+        ));
+        assert!(matches!(
+            get_frame_content_size(&[0xCA, 0xFE, 0xBA, 0xBE]),
+            Err(Error::Frame(FrameError::UnrecognizedMagic(0xBEBA_FECA)))
+        ));
+    }
+
+    #[test]
+    fn test_find_frame_compressed_size() {
+        assert_eq!(find_frame_compressed_size(&FRAMES).unwrap(), 10);
+        assert_eq!(find_frame_compressed_size(&FRAMES[10..]).unwrap(), 19);
+        assert_eq!(find_frame_compressed_size(&FRAMES[29..]).unwrap(), 9);
+        assert!(find_frame_compressed_size(&FRAMES[29..37]).is_err());
+        assert!(find_frame_compressed_size(&[]).is_err());
+    }
+
+    #[test]
+    fn test_decompress_bound() {
+        // Two blocks then one, of at most the 1 KB window each
+        assert_eq!(decompress_bound(&FRAMES).unwrap(), 3 * 1024);
+        assert_eq!(decompress_bound(&[]).unwrap(), 0);
+        assert!(decompress_bound(&FRAMES[..37]).is_err());
+
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let content_size = decode(bytes, &Sequential).unwrap().len() as u64;
+        assert!(decompress_bound(bytes).unwrap() >= content_size);
+    }
+}
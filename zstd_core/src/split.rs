@@ -0,0 +1,109 @@
+use super::{check_magic, frames_metadata, FrameKind, Result};
+use alloc::vec::Vec;
+
+/// Split `bytes` into archives of one Zstandard frame each, as slices of the
+/// input, finding the frames from their headers without decoding them.
+/// Skippable frames stay with the Zstandard frame that follows them, or with
+/// the last one when they end the input.
+///
+/// # Example
+/// ```
+/// # use zstd_core::split_frames;
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xBA, 0xBE,
+/// ];
+/// assert_eq!(split_frames(&bytes)?, [&bytes[..11], &bytes[11..]]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn split_frames(bytes: &[u8]) -> Result<Vec<&[u8]>> {
+    check_magic(bytes)?;
+
+    let mut parts = Vec::new();
+    let mut start = 0;
+    for frame in frames_metadata(bytes) {
+        let frame = frame?;
+        if frame.kind == FrameKind::Zstandard {
+            let end = frame.offset + frame.compressed_size;
+            parts.push(&bytes[start..end]);
+            start = end;
+        }
+    }
+
+    // Trailing skippable frames
+    match parts.last_mut() {
+        Some(last) if start < bytes.len() => {
+            *last = &bytes[start - last.len()..];
+        }
+        None if !bytes.is_empty() => parts.push(bytes),
+        _ => (),
+    }
+    Ok(parts)
+}
+
+/// Concatenate `archives` into one multi-frame archive, which decodes to the
+/// concatenation of their contents. Each is checked to be made of complete
+/// frames, from their headers only, so that a truncated archive does not
+/// corrupt the ones after it.
+///
+/// # Example
+/// ```
+/// # use zstd_core::merge_frames;
+/// let first = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let second = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xBA, 0xBE];
+/// assert_eq!(merge_frames([&first[..], &second])?, [first, second].concat());
+/// assert!(merge_frames([&first[..], &second[..5]]).is_err());
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn merge_frames<'a>(archives: impl IntoIterator<Item = &'a [u8]>) -> Result<Vec<u8>> {
+    let mut merged = Vec::new();
+    for archive in archives {
+        check_magic(archive)?;
+        for frame in frames_metadata(archive) {
+            frame?;
+        }
+        merged.extend_from_slice(archive);
+    }
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{stream::tests::FRAMES, Error};
+    use super::*;
+
+    #[test]
+    fn test_split_frames() {
+        // The skippable frame goes with the first Zstandard frame
+        assert_eq!(
+            split_frames(&FRAMES).unwrap(),
+            [&FRAMES[..29], &FRAMES[29..]]
+        );
+
+        // Trailing skippable frames go with the last one
+        let bytes = [&FRAMES[10..], &FRAMES[..10]].concat();
+        assert_eq!(split_frames(&bytes).unwrap(), [&bytes[..19], &bytes[19..]]);
+
+        // Only skippable frames
+        assert_eq!(split_frames(&FRAMES[..10]).unwrap(), [&FRAMES[..10]]);
+        assert!(split_frames(&[]).unwrap().is_empty());
+
+        assert!(matches!(
+            split_frames(b"GIF89a"),
+            Err(Error::NotZstandardData)
+        ));
+        assert!(split_frames(&FRAMES[..FRAMES.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn test_merge_frames() {
+        let parts = split_frames(&FRAMES).unwrap();
+        assert_eq!(merge_frames(parts).unwrap(), FRAMES);
+        assert!(merge_frames([]).unwrap().is_empty());
+        assert!(matches!(
+            merge_frames([&FRAMES[..], b"GIF89a"]),
+            Err(Error::NotZstandardData)
+        ));
+        assert!(merge_frames([&FRAMES[..20]]).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use super::{Block, LiteralsType};
+
+/// Counts gathered while decoding, see `decode_with_stats` and
+/// `DecodingContext::stats`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DecodeStats {
+    pub frames: usize,
+    pub skippable_frames: usize,
+
+    pub raw_blocks: usize,
+    pub rle_blocks: usize,
+    pub compressed_blocks: usize,
+
+    /// Literals sections of the compressed blocks, by type
+    pub raw_literals: usize,
+    pub rle_literals: usize,
+    pub compressed_literals: usize,
+    pub treeless_literals: usize,
+
+    pub sequences: usize,
+    /// Sequences whose offset is one of the repeat offsets
+    pub repeat_offsets: usize,
+    /// Content bytes copied from the literals, raw and RLE blocks included,
+    /// and from earlier content by the matches of the sequences
+    pub literal_bytes: usize,
+    pub match_bytes: usize,
+
+    /// Frames whose content checksum is verified, is present but not verified
+    /// (without a hasher), or is absent
+    pub checksums_verified: usize,
+    pub checksums_unverified: usize,
+    pub checksums_absent: usize,
+}
+
+impl DecodeStats {
+    /// Count `block`, about to be decoded
+    pub(crate) fn count_block(&mut self, block: &Block) {
+        match block {
+            Block::Raw(raw) => {
+                self.raw_blocks += 1;
+                self.literal_bytes += raw.len();
+            }
+            Block::Rle { repeat, .. } => {
+                self.rle_blocks += 1;
+                self.literal_bytes += repeat;
+            }
+            Block::Compressed {
+                literals,
+                sequences,
+            } => {
+                self.compressed_blocks += 1;
+                match literals.literals_type() {
+                    LiteralsType::Raw => self.raw_literals += 1,
+                    LiteralsType::Rle => self.rle_literals += 1,
+                    LiteralsType::Compressed => self.compressed_literals += 1,
+                    LiteralsType::Treeless => self.treeless_literals += 1,
+                }
+                self.sequences += sequences.len();
+                // Every literal ends up in the content
+                self.literal_bytes += literals.regenerated_size();
+            }
+        }
+    }
+
+    /// Count a Zstandard frame, decoded, and its content checksum
+    pub(crate) fn count_frame(&mut self, checksum_flag: bool, verified: bool) {
+        self.frames += 1;
+        match (checksum_flag, verified) {
+            (false, _) => self.checksums_absent += 1,
+            (true, true) => self.checksums_verified += 1,
+            (true, false) => self.checksums_unverified += 1,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode_with_stats, stream::tests::FRAMES, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_decode_with_stats() {
+        let (decoded, stats) = decode_with_stats(&FRAMES, &Sequential).unwrap();
+        assert_eq!(decoded, [0xCA, 0xFE, 0xBA, 0xBA]);
+        let (verified, unverified) = if cfg!(feature = "xxhash") {
+            (1, 0)
+        } else {
+            (0, 1)
+        };
+        assert_eq!(
+            stats,
+            DecodeStats {
+                frames: 2,
+                skippable_frames: 1,
+                raw_blocks: 2,
+                rle_blocks: 1,
+                literal_bytes: 4,
+                checksums_verified: verified,
+                checksums_unverified: unverified,
+                checksums_absent: 1,
+                ..DecodeStats::default()
+            }
+        );
+
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let (decoded, stats) = decode_with_stats(bytes, &Sequential).unwrap();
+        assert_eq!(stats.literal_bytes + stats.match_bytes, decoded.len());
+        assert!(stats.compressed_blocks > 0 && stats.sequences > 0);
+        assert!(stats.repeat_offsets > 0 && stats.repeat_offsets < stats.sequences);
+        assert_eq!(
+            stats.compressed_blocks,
+            stats.raw_literals
+                + stats.rle_literals
+                + stats.compressed_literals
+                + stats.treeless_literals
+        );
+    }
+}
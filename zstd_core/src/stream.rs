@@ -0,0 +1,802 @@
+use super::{
+    format::skippable_magic, Block, BlockHeader, Checkpoint, CheckpointError, ContentHasher,
+    DecodingContext, DefaultHasher, Error, Executor, ForwardByteParser, FrameError, FrameHeader,
+    FrameKind, ParsingError, Result, Sequential, SkippableFrame, CHECKPOINT_MAGIC,
+    CHECKPOINT_VERSION,
+};
+use alloc::{boxed::Box, vec::Vec};
+
+/// Structure of the input `StreamDecoder` is waiting for
+enum State<H> {
+    /// Magic number of the next frame, and its length for a skippable frame
+    Magic,
+    /// Header of a Zstandard frame
+    FrameHeader,
+    /// Next block of a Zstandard frame
+    Block(Box<FrameState<H>>),
+    /// Content checksum of a Zstandard frame
+    Checksum(Box<FrameState<H>>),
+    /// Payload of a skippable frame
+    Skippable(SkippableState),
+}
+
+/// Tags of the states in checkpoints
+const SAVED_MAGIC: u8 = 0;
+const SAVED_FRAME_HEADER: u8 = 1;
+const SAVED_BLOCK: u8 = 2;
+const SAVED_CHECKSUM: u8 = 3;
+const SAVED_SKIPPABLE: u8 = 4;
+
+struct FrameState<H> {
+    header: FrameHeader,
+    context: DecodingContext,
+    /// `None` when the frame was resumed from a checkpoint, which does not hold
+    /// the hasher state: its content checksum is not verified
+    hasher: Option<H>,
+    /// Size of the content produced so far, `None` when resumed from a
+    /// checkpoint, as for the hasher: the content size is not verified
+    produced: Option<usize>,
+}
+
+struct SkippableState {
+    magic: u32,
+    /// Bytes of the payload not received yet
+    remaining: usize,
+    /// Payload received by the previous inputs, handed to
+    /// `Executor::skippable_frame` with the rest once complete
+    payload: Vec<u8>,
+}
+
+/// Progress of a `StreamDecoder`, reported after each block by
+/// `decode_with_progress`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Input bytes consumed
+    pub input: usize,
+    /// Content bytes produced
+    pub output: usize,
+    /// Index of the frame of the block in the input, skippable frames included
+    pub frame: usize,
+}
+
+/// Incremental decoder: the input is pushed as it arrives, in chunks of any
+/// size, and the content is produced block by block. Only the window of the
+/// current frame is kept in memory. The sections of each block run on `E`,
+/// the content checksums are verified with `H`.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{Sequential, StreamDecoder};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut decoder = StreamDecoder::with_executor(Sequential);
+/// let mut output = Vec::new();
+/// for chunk in bytes.chunks(3) {
+///     decoder.decode(chunk, &mut output)?;
+/// }
+/// decoder.finish()?;
+/// assert_eq!(output, [0xCA, 0xFE]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub struct StreamDecoder<E = Sequential, H = DefaultHasher> {
+    state: State<H>,
+    /// Input received but not consumed yet: an incomplete structure
+    input: Vec<u8>,
+    /// Number of input bytes consumed
+    consumed: usize,
+    /// Content produced and frames completed, since the start or the resume
+    produced: usize,
+    frames: usize,
+    executor: E,
+}
+
+impl<E: Executor + Default, H: ContentHasher> Default for StreamDecoder<E, H> {
+    fn default() -> Self {
+        Self::with_hasher(E::default())
+    }
+}
+
+impl<E: Executor> StreamDecoder<E> {
+    #[must_use]
+    pub fn new() -> Self
+    where
+        E: Default,
+    {
+        Self::with_executor(E::default())
+    }
+
+    #[must_use]
+    pub fn with_executor(executor: E) -> Self {
+        Self::with_hasher(executor)
+    }
+}
+
+impl<E: Executor, H: ContentHasher> StreamDecoder<E, H> {
+    /// Verify the content checksums with `H` instead of `DefaultHasher`
+    ///
+    /// ```
+    /// # use zstd_core::{NoChecksum, Sequential, StreamDecoder};
+    /// let decoder = StreamDecoder::<_, NoChecksum>::with_hasher(Sequential);
+    /// ```
+    #[must_use]
+    pub fn with_hasher(executor: E) -> Self {
+        Self {
+            state: State::Magic,
+            input: Vec::new(),
+            consumed: 0,
+            produced: 0,
+            frames: 0,
+            executor,
+        }
+    }
+
+    /// Offset in the input of the first byte not consumed yet. Input received
+    /// past it is buffered until the structure it starts is complete.
+    #[must_use]
+    pub fn input_offset(&self) -> usize {
+        self.consumed
+    }
+
+    /// Bytes of memory held by the decoder: the buffered input, and the
+    /// window, buffers and tables of the frame being decoded
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        let context = match &self.state {
+            State::Block(frame) | State::Checksum(frame) => frame.context.memory_usage(),
+            State::Skippable(skippable) => skippable.payload.capacity(),
+            _ => 0,
+        };
+        self.input.capacity() + context
+    }
+
+    /// Snapshot the decoder, to resume decoding later with `resume`, possibly
+    /// in another process: the input offset, and for the frame being decoded
+    /// its window, entropy tables and repeat offsets. The buffered input is left
+    /// out, it must be pushed again from `input_offset` after resuming.
+    ///
+    /// The hasher state cannot be saved: the content checksum of the frame being
+    /// decoded is not verified once resumed.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{Sequential, StreamDecoder};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x08, 0x0, 0x0, 0xCA, 0x09, 0x0, 0x0, 0xFE];
+    /// let mut decoder = StreamDecoder::with_executor(Sequential);
+    /// let mut output = Vec::new();
+    /// decoder.decode(&bytes[..12], &mut output)?;
+    /// let checkpoint = decoder.checkpoint();
+    ///
+    /// let mut decoder: StreamDecoder = StreamDecoder::resume(&checkpoint, Sequential)?;
+    /// decoder.decode(&bytes[decoder.input_offset()..], &mut output)?;
+    /// decoder.finish()?;
+    /// assert_eq!(output, [0xCA, 0xFE]);
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    #[must_use]
+    pub fn checkpoint(&self) -> Vec<u8> {
+        let mut checkpoint = CHECKPOINT_MAGIC.to_vec();
+        checkpoint.push(CHECKPOINT_VERSION);
+        self.consumed.save(&mut checkpoint);
+
+        match &self.state {
+            State::Magic => checkpoint.push(SAVED_MAGIC),
+            State::FrameHeader => checkpoint.push(SAVED_FRAME_HEADER),
+            State::Block(frame) | State::Checksum(frame) => {
+                let tag = if matches!(self.state, State::Block(_)) {
+                    SAVED_BLOCK
+                } else {
+                    SAVED_CHECKSUM
+                };
+                checkpoint.push(tag);
+                frame.header.save(&mut checkpoint);
+                frame.context.save(&mut checkpoint);
+            }
+            State::Skippable(skippable) => {
+                checkpoint.push(SAVED_SKIPPABLE);
+                ((skippable.magic & 0xF) as u8).save(&mut checkpoint);
+                skippable.remaining.save(&mut checkpoint);
+                skippable.payload.save(&mut checkpoint);
+            }
+        }
+        checkpoint
+    }
+
+    /// Restore a decoder from a `checkpoint`, the input must then be pushed from
+    /// `input_offset`
+    pub fn resume(checkpoint: &[u8], executor: E) -> Result<Self> {
+        let mut parser = ForwardByteParser::new(checkpoint);
+        if parser.slice(CHECKPOINT_MAGIC.len()).ok() != Some(&CHECKPOINT_MAGIC[..]) {
+            return Err(CheckpointError::InvalidMagic.into());
+        }
+        let version = u8::restore(&mut parser)?;
+        if version != CHECKPOINT_VERSION {
+            return Err(CheckpointError::UnsupportedVersion(version).into());
+        }
+        let consumed = usize::restore(&mut parser)?;
+
+        let state = match u8::restore(&mut parser)? {
+            SAVED_MAGIC => State::Magic,
+            SAVED_FRAME_HEADER => State::FrameHeader,
+            tag @ (SAVED_BLOCK | SAVED_CHECKSUM) => {
+                let header = FrameHeader::restore(&mut parser)?;
+                let mut context =
+                    DecodingContext::restore(&mut parser, executor.max_window_size())?;
+                // The work done before the checkpoint is not saved
+                context.set_limits(&executor);
+                let frame = Box::new(FrameState {
+                    header,
+                    context,
+                    hasher: None,
+                    produced: None,
+                });
+                if tag == SAVED_BLOCK {
+                    State::Block(frame)
+                } else {
+                    State::Checksum(frame)
+                }
+            }
+            SAVED_SKIPPABLE => {
+                let nibble = u8::restore(&mut parser)?;
+                if nibble > 0xF {
+                    return Err(CheckpointError::Corrupted.into());
+                }
+                State::Skippable(SkippableState {
+                    magic: skippable_magic(nibble),
+                    remaining: usize::restore(&mut parser)?,
+                    payload: Vec::restore(&mut parser)?,
+                })
+            }
+            _ => return Err(CheckpointError::Corrupted.into()),
+        };
+        if !parser.is_empty() {
+            return Err(CheckpointError::Corrupted.into());
+        }
+
+        Ok(Self {
+            state,
+            input: Vec::new(),
+            consumed,
+            produced: 0,
+            frames: 0,
+            executor,
+        })
+    }
+
+    /// Push `input` and append to `output` the content of every block completed
+    /// by it. The decoder must not be used anymore after an error.
+    pub fn decode(&mut self, input: &[u8], output: &mut Vec<u8>) -> Result<()> {
+        self.decode_with_progress(input, output, |_| {})
+    }
+
+    /// Same as `decode`, calling `on_progress` after each block and content
+    /// checksum, to report the progress of a long input pushed at once. The
+    /// counts start over from a checkpoint, except for the input consumed.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_core::{Progress, Sequential, StreamDecoder};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let mut decoder = StreamDecoder::with_executor(Sequential);
+    /// let mut progress = Vec::new();
+    /// decoder.decode_with_progress(&bytes, &mut Vec::new(), |p| progress.push(p))?;
+    /// assert_eq!(progress, [Progress { input: 11, output: 2, frame: 0 }]);
+    /// # Ok::<(), zstd_core::ZstdLibError>(())
+    /// ```
+    pub fn decode_with_progress(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        mut on_progress: impl FnMut(Progress),
+    ) -> Result<()> {
+        // Only the structure left incomplete by the previous input is copied,
+        // with as few bytes of `input` as possible: they are doubled until it
+        // is complete, the rest of `input` is decoded in place
+        let mut input = input;
+        while !self.input.is_empty() && !input.is_empty() {
+            let len = core::cmp::min(input.len(), core::cmp::max(self.input.len(), 64));
+            self.input.extend_from_slice(&input[..len]);
+            let buffered = core::mem::take(&mut self.input);
+            self.process(&buffered, output, false, &mut on_progress)?;
+            let left = self.input.len();
+            if left <= len {
+                // What is left of the buffer is the start of `input`
+                self.input.clear();
+                input = &input[len - left..];
+            } else {
+                input = &input[len..];
+            }
+        }
+        if self.input.is_empty() {
+            self.process(input, output, false, &mut on_progress)?;
+        }
+        Ok(())
+    }
+
+    /// Signal the end of the input. Return an error when it ends in the middle
+    /// of a frame.
+    pub fn finish(&mut self) -> Result<()> {
+        let buffered = core::mem::take(&mut self.input);
+        self.process(&buffered, &mut Vec::new(), true, &mut |_| {})
+    }
+
+    /// Decode the complete structures of `input`, keeping the rest for the
+    /// next input
+    fn process(
+        &mut self,
+        input: &[u8],
+        output: &mut Vec<u8>,
+        eof: bool,
+        on_progress: &mut impl FnMut(Progress),
+    ) -> Result<()> {
+        let mut parser = ForwardByteParser::new(input);
+
+        let result = loop {
+            // Reported after the blocks, and the checksum ending their frame
+            let report = matches!(self.state, State::Block(_) | State::Checksum(_));
+            let magic = matches!(self.state, State::Magic);
+            let len = output.len();
+            let frame = self.frames;
+            match self.step(&mut parser, output, eof) {
+                Ok(true) => {
+                    self.produced += output.len() - len;
+                    if !magic && matches!(self.state, State::Magic) {
+                        self.frames += 1;
+                    }
+                    if report {
+                        on_progress(Progress {
+                            input: self.consumed + input.len() - parser.len(),
+                            output: self.produced,
+                            frame,
+                        });
+                    }
+                }
+                Ok(false) => break Ok(()),
+                Err(err) => break Err(err),
+            }
+        };
+
+        self.consumed += input.len() - parser.len();
+        self.input.clear();
+        self.input.extend_from_slice(parser.into());
+        result
+    }
+
+    /// Consume the next structure from `input` when it is complete. Return
+    /// `false` when nothing can be done until more input is received.
+    fn step(
+        &mut self,
+        input: &mut ForwardByteParser,
+        output: &mut Vec<u8>,
+        eof: bool,
+    ) -> Result<bool> {
+        // Work on a copy so that an incomplete structure is left unconsumed
+        let mut parser = *input;
+
+        let state = match &mut self.state {
+            State::Magic if parser.is_empty() => return Ok(false),
+
+            State::Magic => {
+                let Some(magic) = complete(parser.le_u32().map_err(Error::from), eof)? else {
+                    return Ok(false);
+                };
+                match FrameKind::from_magic(magic) {
+                    Some(FrameKind::Zstandard) => State::FrameHeader,
+                    Some(FrameKind::Skippable) => {
+                        let Some(len) = complete(parser.le_u32().map_err(Error::from), eof)? else {
+                            return Ok(false);
+                        };
+                        State::Skippable(SkippableState {
+                            magic,
+                            remaining: len as usize,
+                            payload: Vec::new(),
+                        })
+                    }
+                    None => return Err(Error::Frame(FrameError::UnrecognizedMagic(magic))),
+                }
+            }
+
+            State::FrameHeader => {
+                let Some(header) = complete(FrameHeader::parse(&mut parser), eof)? else {
+                    return Ok(false);
+                };
+                let mut context = DecodingContext::default();
+                context.set_limits(&self.executor);
+                context.start_frame(header.window_size(), self.executor.max_window_size())?;
+                State::Block(Box::new(FrameState {
+                    header,
+                    context,
+                    hasher: Some(H::default()),
+                    produced: Some(0),
+                }))
+            }
+
+            State::Block(frame) => {
+                // Make sure the whole block is there before parsing it, so that
+                // a parsing error means corrupted data
+                let mut probe = parser;
+                let Some(block_header) = complete(BlockHeader::parse(&mut probe), eof)? else {
+                    return Ok(false);
+                };
+                if complete(
+                    probe
+                        .slice(block_header.content_size())
+                        .map_err(Error::from),
+                    eof,
+                )?
+                .is_none()
+                {
+                    return Ok(false);
+                }
+
+                let (block, last_block) = Block::parse(&mut parser, frame.header.window_size())?;
+                let FrameState {
+                    context,
+                    hasher,
+                    produced,
+                    ..
+                } = &mut **frame;
+                let mut emit = |decoded: &[u8]| {
+                    if let Some(hasher) = hasher {
+                        hasher.update(decoded);
+                    }
+                    if let Some(produced) = produced {
+                        *produced += decoded.len();
+                    }
+                    output.extend_from_slice(decoded);
+                };
+
+                if let Block::Rle { byte, repeat } = block {
+                    context.charge(repeat)?;
+                    context.decode_rle(byte, repeat, &self.executor, emit)?;
+                    context.record_memory_usage()?;
+                } else {
+                    let start = context.decoded.len();
+                    block.decode(context, &self.executor)?;
+                    emit(&context.decoded[start..]);
+                    context.trim_to_window();
+                }
+
+                if !last_block {
+                    *input = parser;
+                    return Ok(true);
+                }
+
+                if let Some(produced) = frame.produced {
+                    frame.header.check_content_size(produced)?;
+                }
+                let State::Block(frame) = core::mem::replace(&mut self.state, State::Magic) else {
+                    unreachable!()
+                };
+                if frame.header.content_checksum_flag() {
+                    State::Checksum(frame)
+                } else {
+                    State::Magic
+                }
+            }
+
+            State::Checksum(frame) => {
+                let Some(checksum) = complete(parser.le_u32().map_err(Error::from), eof)? else {
+                    return Ok(false);
+                };
+                if frame
+                    .hasher
+                    .as_ref()
+                    .and_then(ContentHasher::checksum)
+                    .is_some_and(|digest| digest != checksum)
+                {
+                    return Err(Error::Frame(FrameError::ChecksumMismatch));
+                }
+                State::Magic
+            }
+
+            State::Skippable(skippable) => {
+                let len = core::cmp::min(skippable.remaining, parser.len());
+                if len == 0 && skippable.remaining > 0 {
+                    if eof {
+                        return Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                            requested: skippable.remaining,
+                            available: 0,
+                        }));
+                    }
+                    return Ok(false);
+                }
+                let received = parser.slice(len)?;
+                skippable.remaining -= len;
+                if skippable.remaining > 0 {
+                    skippable.payload.extend_from_slice(received);
+                    *input = parser;
+                    return Ok(true);
+                }
+
+                // A payload within a single input is handed over in place
+                let payload = if skippable.payload.is_empty() {
+                    received
+                } else {
+                    skippable.payload.extend_from_slice(received);
+                    &skippable.payload
+                };
+                self.executor
+                    .skippable_frame(&SkippableFrame::new(skippable.magic, payload));
+                State::Magic
+            }
+        };
+
+        self.state = state;
+        *input = parser;
+        Ok(true)
+    }
+}
+
+/// Decode the frames of the chained `segments`, such as the chunks of a rope
+/// or of network buffers, without concatenating them: the blocks are decoded
+/// in place, only the few bytes of a structure straddling two segments are
+/// copied
+///
+/// # Example
+/// ```
+/// # use zstd_core::{decode_segments, Sequential};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let segments = [&bytes[..5], &bytes[5..10], &bytes[10..]];
+/// assert_eq!(decode_segments(segments, &Sequential)?, [0xCA, 0xFE]);
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn decode_segments<'a>(
+    segments: impl IntoIterator<Item = &'a [u8]>,
+    executor: &impl Executor,
+) -> Result<Vec<u8>> {
+    let mut decoder = StreamDecoder::with_executor(executor);
+    let mut decoded = Vec::new();
+    for segment in segments {
+        decoder.decode(segment, &mut decoded)?;
+    }
+    decoder.finish()?;
+    Ok(decoded)
+}
+
+/// `None` when the input does not hold the whole structure yet, unless it has
+/// ended (`eof`) in which case the structure is truncated.
+fn complete<T>(result: Result<T>, eof: bool) -> Result<Option<T>> {
+    match result {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::Parsing(ParsingError::NotEnoughBytes { .. })) if !eof => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    pub(crate) const FRAMES: [u8; 38] = [
+        0x50, 0x2A, 0x4D, 0x18, // skippable magic:   0x184D2A50
+        0x02, 0x0, 0x0, 0x0, // length:  2
+        0x10, 0x20, // content
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x04, 0x0, // header: checksum flag
+        0x10, 0x0, 0x0, // raw block, not last, len 2
+        0xCA, 0xFE, // content
+        0x13, 0x0, 0x0,  // rle block, last, repeat 2
+        0xBA, // content
+        0x56, 0x21, 0x60, 0x39, // checksum
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x0, 0x0, // header
+        0x01, 0x0, 0x0, // raw block, last, len 0
+    ];
+
+    fn decode_chunks(bytes: &[u8], chunk_size: usize) -> Result<Vec<u8>> {
+        let mut decoder = StreamDecoder::with_executor(Sequential);
+        let mut output = Vec::new();
+        for chunk in bytes.chunks(chunk_size) {
+            decoder.decode(chunk, &mut output)?;
+        }
+        decoder.finish()?;
+        Ok(output)
+    }
+
+    #[test]
+    fn test_decode_chunks() {
+        let expected = super::super::decode(&FRAMES, &Sequential).unwrap();
+        assert_eq!(expected, [0xCA, 0xFE, 0xBA, 0xBA]);
+        for chunk_size in 1..=FRAMES.len() {
+            assert_eq!(decode_chunks(&FRAMES, chunk_size).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_segments() {
+        let expected = super::super::decode(&FRAMES, &Sequential).unwrap();
+        for i in 0..FRAMES.len() {
+            for j in i..FRAMES.len() {
+                let segments = [&FRAMES[..i], &FRAMES[i..j], &FRAMES[j..]];
+                assert_eq!(decode_segments(segments, &Sequential).unwrap(), expected);
+            }
+        }
+        assert!(decode_segments([&FRAMES[..10], &FRAMES[11..]], &Sequential).is_err());
+
+        // Only what straddles two segments is buffered
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let mut decoder = StreamDecoder::with_executor(Sequential);
+        let mut output = Vec::new();
+        for segment in bytes.chunks(100_000) {
+            decoder.decode(segment, &mut output).unwrap();
+            assert!(decoder.input.capacity() < 2 * crate::format::BLOCK_SIZE_MAX);
+        }
+        decoder.finish().unwrap();
+        assert_eq!(output, super::super::decode(bytes, &Sequential).unwrap());
+    }
+
+    #[test]
+    fn test_decode_block_by_block() {
+        let mut decoder = StreamDecoder::with_executor(Sequential);
+        let mut output = Vec::new();
+        decoder.decode(&FRAMES[..21], &mut output).unwrap();
+        assert_eq!(output, [0xCA, 0xFE]);
+        decoder.decode(&FRAMES[21..], &mut output).unwrap();
+        assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBA]);
+    }
+
+    #[test]
+    fn test_decode_with_progress() {
+        let expected = [
+            Progress {
+                input: 21,
+                output: 2,
+                frame: 1,
+            },
+            Progress {
+                input: 25,
+                output: 4,
+                frame: 1,
+            },
+            Progress {
+                input: 29,
+                output: 4,
+                frame: 1,
+            },
+            Progress {
+                input: 38,
+                output: 4,
+                frame: 2,
+            },
+        ];
+        for chunk_size in [1, 7, FRAMES.len()] {
+            let mut decoder = StreamDecoder::with_executor(Sequential);
+            let mut progress = Vec::new();
+            for chunk in FRAMES.chunks(chunk_size) {
+                decoder
+                    .decode_with_progress(chunk, &mut Vec::new(), |p| progress.push(p))
+                    .unwrap();
+            }
+            assert_eq!(progress, expected);
+        }
+    }
+
+    #[test]
+    fn test_truncated() {
+        for len in [1, 9, 12, 20, 24, 28, 35] {
+            assert!(matches!(
+                decode_chunks(&FRAMES[..len], 4),
+                Err(Error::Parsing(ParsingError::NotEnoughBytes { .. }))
+            ));
+        }
+    }
+
+    #[cfg(feature = "xxhash")]
+    #[test]
+    fn test_checksum_mismatch() {
+        let mut bytes = FRAMES;
+        bytes[28] ^= 0xFF;
+        assert!(matches!(
+            decode_chunks(&bytes, 5),
+            Err(Error::Frame(FrameError::ChecksumMismatch))
+        ));
+
+        let mut decoder = StreamDecoder::<_, super::super::NoChecksum>::with_hasher(Sequential);
+        let mut output = Vec::new();
+        decoder.decode(&bytes, &mut output).unwrap();
+        decoder.finish().unwrap();
+        assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBA]);
+    }
+
+    /// `Sequential` recording the skippable frames
+    #[derive(Default)]
+    struct Skippables(std::sync::Mutex<Vec<(u8, Vec<u8>)>>);
+
+    impl Executor for Skippables {
+        fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+        where
+            A: FnOnce() -> RA + Send,
+            B: FnOnce() -> RB + Send,
+            RA: Send,
+            RB: Send,
+        {
+            Sequential.join(a, b)
+        }
+
+        fn skippable_frame(&self, frame: &SkippableFrame<'_>) {
+            let frame = (frame.magic_nibble(), frame.payload().to_vec());
+            self.0.lock().unwrap().push(frame);
+        }
+    }
+
+    #[test]
+    fn test_skippable_frame() {
+        for chunk_size in 1..=FRAMES.len() {
+            let skippables = Skippables::default();
+            let mut decoder = StreamDecoder::with_executor(&skippables);
+            for chunk in FRAMES.chunks(chunk_size) {
+                decoder.decode(chunk, &mut Vec::new()).unwrap();
+            }
+            decoder.finish().unwrap();
+            assert_eq!(*skippables.0.lock().unwrap(), [(0, vec![0x10, 0x20])]);
+        }
+    }
+
+    #[test]
+    fn test_checkpoint() {
+        for split in 0..=FRAMES.len() {
+            let skippables = Skippables::default();
+            let mut decoder = StreamDecoder::with_executor(&skippables);
+            let mut output = Vec::new();
+            decoder.decode(&FRAMES[..split], &mut output).unwrap();
+            let checkpoint = decoder.checkpoint();
+
+            let mut decoder: StreamDecoder<_> =
+                StreamDecoder::resume(&checkpoint, &skippables).unwrap();
+            assert!(decoder.input_offset() <= split);
+            decoder
+                .decode(&FRAMES[decoder.input_offset()..], &mut output)
+                .unwrap();
+            decoder.finish().unwrap();
+            assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBA], "split at {split}");
+            assert_eq!(*skippables.0.lock().unwrap(), [(0, vec![0x10, 0x20])]);
+        }
+    }
+
+    #[test]
+    fn test_invalid_checkpoint() {
+        let checkpoint = StreamDecoder::<Sequential>::new().checkpoint();
+        let resume = |checkpoint: &[u8]| StreamDecoder::<_>::resume(checkpoint, Sequential);
+        assert!(resume(&checkpoint).is_ok());
+
+        assert!(matches!(
+            resume(b"not a checkpoint"),
+            Err(Error::Checkpoint(CheckpointError::InvalidMagic))
+        ));
+
+        let mut version = checkpoint.clone();
+        version[4] = 0xFF;
+        assert!(matches!(
+            resume(&version),
+            Err(Error::Checkpoint(CheckpointError::UnsupportedVersion(0xFF)))
+        ));
+        // Frame headers were saved without the content size flag, then
+        // skippable frames without their payload
+        for old in [1, 2] {
+            version[4] = old;
+            assert!(matches!(
+                resume(&version),
+                Err(Error::Checkpoint(CheckpointError::UnsupportedVersion(v))) if v == old
+            ));
+        }
+
+        assert!(matches!(
+            resume(&checkpoint[..checkpoint.len() - 1]),
+            Err(Error::Checkpoint(CheckpointError::Truncated(_)))
+        ));
+
+        let mut trailing = checkpoint.clone();
+        trailing.push(0);
+        assert!(matches!(
+            resume(&trailing),
+            Err(Error::Checkpoint(CheckpointError::Corrupted))
+        ));
+    }
+
+    #[test]
+    fn test_unrecognized_magic() {
+        assert!(matches!(
+            decode_chunks(b"not zstd", 8),
+            Err(Error::Frame(FrameError::UnrecognizedMagic(_)))
+        ));
+    }
+}
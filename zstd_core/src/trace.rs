@@ -0,0 +1,125 @@
+/*
+    Spans and events of the `tracing` feature, at the debug level: frames,
+    blocks, literals and sequences with their sizes, timed by the subscriber
+    through the spans. At the trace level, every sequence is detailed as in
+    the walkthroughs of RFC 8878: the FSE states, the codes and extra bits,
+    the lengths and offset, and the repeat offsets. Without the feature, they
+    expand to nothing and their fields are not evaluated.
+*/
+
+/// Enter a span until the end of the scope, `let _span = span!("name", field = value);`
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($($args:tt)+) => {
+        tracing::debug_span!($($args)+).entered()
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($args:tt)+) => {
+        crate::trace::NoSpan
+    };
+}
+
+/// Record an event in the current span, `event!(field = value, "message")`
+#[cfg(feature = "tracing")]
+macro_rules! event {
+    ($($args:tt)+) => {
+        tracing::debug!($($args)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! event {
+    ($($args:tt)+) => {};
+}
+
+/// Record an event at the trace level, `trace_event!(field = value, "message")`,
+/// for the details of every sequence
+#[cfg(feature = "tracing")]
+macro_rules! trace_event {
+    ($($args:tt)+) => {
+        tracing::trace!($($args)+)
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! trace_event {
+    ($($args:tt)+) => {};
+}
+
+/// Span entered without the `tracing` feature
+#[cfg(not(feature = "tracing"))]
+pub(crate) struct NoSpan;
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use crate::{decode, Sequential};
+    use std::sync::{Arc, Mutex};
+    use tracing::{span, Event, Level, Metadata, Subscriber};
+
+    /// Collect the names of the spans created, and the modules of the trace
+    /// level events
+    #[derive(Default)]
+    struct Recorder {
+        spans: Mutex<Vec<&'static str>>,
+        events: Mutex<Vec<&'static str>>,
+    }
+
+    struct Spans(Arc<Recorder>);
+
+    impl Subscriber for Spans {
+        fn enabled(&self, _: &Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, span: &span::Attributes<'_>) -> span::Id {
+            let mut spans = self.0.spans.lock().unwrap();
+            spans.push(span.metadata().name());
+            span::Id::from_u64(spans.len() as u64)
+        }
+
+        fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+        fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+        fn event(&self, event: &Event<'_>) {
+            let metadata = event.metadata();
+            if *metadata.level() == Level::TRACE {
+                let module = metadata.module_path().unwrap_or_default();
+                self.0.events.lock().unwrap().push(module);
+            }
+        }
+
+        fn enter(&self, _: &span::Id) {}
+        fn exit(&self, _: &span::Id) {}
+    }
+
+    #[test]
+    fn test_spans() {
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let recorder = Arc::new(Recorder::default());
+        tracing::subscriber::with_default(Spans(recorder.clone()), || {
+            decode(bytes, &Sequential).unwrap();
+        });
+
+        let spans = recorder.spans.lock().unwrap();
+        for name in [
+            "parse_frame",
+            "decode_frame",
+            "decode_block",
+            "decode_literals",
+            "decode_sequences",
+            "execute_sequences",
+        ] {
+            assert!(spans.contains(&name), "{name}");
+        }
+
+        // A "sequence" and an "offset" event per sequence
+        let events = recorder.events.lock().unwrap();
+        let count = |module| events.iter().filter(|&&event| event == module).count();
+        let sequences = count("zstd_core::sequences");
+        assert!(sequences > 0);
+        assert_eq!(count("zstd_core::decoders::decoding_context"), sequences);
+    }
+}
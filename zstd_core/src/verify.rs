@@ -0,0 +1,181 @@
+use super::{
+    check_magic, frames_metadata, ContentHasher, DefaultHasher, Executor, Frame, OutputSink,
+    Result, RingBuffer, ZstdLibError,
+};
+use alloc::vec::Vec;
+
+/// Outcome of `verify`, a report per Zstandard frame
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub frames: Vec<FrameReport>,
+    pub skippable_frames: usize,
+}
+
+impl VerifyReport {
+    /// Whether all the frames decoded and verified
+    #[must_use]
+    pub fn is_valid(&self) -> bool {
+        self.frames.iter().all(|frame| frame.error.is_none())
+    }
+
+    /// Content size of the frames that decoded
+    #[must_use]
+    pub fn content_size(&self) -> u64 {
+        self.frames
+            .iter()
+            .filter_map(|frame| frame.content_size)
+            .sum()
+    }
+}
+
+/// Zstandard frame checked by `verify`
+#[derive(Debug)]
+pub struct FrameReport {
+    /// Offset of the frame in the input
+    pub offset: usize,
+    pub compressed_size: usize,
+    /// Size of the content, `None` when the frame failed to decode
+    pub content_size: Option<u64>,
+    /// The content checksum was verified: the frame has one, and the checksum
+    /// is computed with the `xxhash` feature
+    pub checksum_verified: bool,
+    /// Why the frame is invalid, `None` when it is not
+    pub error: Option<ZstdLibError>,
+}
+
+/// Decode the frames of `bytes` to check their content sizes and checksums,
+/// discarding the content: only the window of a frame is held, not its
+/// content. A frame that fails is reported and the next ones are checked,
+/// unless it cannot be delimited, e.g. when truncated: it is then the last
+/// one reported.
+///
+/// # Example
+/// ```
+/// # use zstd_core::{verify, Sequential};
+/// let mut bytes = vec![0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// bytes.extend_from_within(..9);
+/// let report = verify(&bytes, &Sequential)?;
+/// assert_eq!(report.frames[0].content_size, Some(2));
+/// assert!(report.frames[1].error.is_some());
+/// assert!(!report.is_valid());
+/// # Ok::<(), zstd_core::ZstdLibError>(())
+/// ```
+pub fn verify(bytes: &[u8], executor: &impl Executor) -> Result<VerifyReport> {
+    check_magic(bytes)?;
+
+    let mut report = VerifyReport::default();
+    // Reused by the frames whose window fits
+    let mut window: Option<RingBuffer> = None;
+    let mut offset = 0;
+    for metadata in frames_metadata(bytes) {
+        let metadata = match metadata {
+            Ok(metadata) => metadata,
+            Err(error) => {
+                report.frames.push(FrameReport {
+                    offset,
+                    compressed_size: bytes.len() - offset,
+                    content_size: None,
+                    checksum_verified: false,
+                    error: Some(error),
+                });
+                break;
+            }
+        };
+        offset = metadata.offset + metadata.compressed_size;
+
+        let frame = match Frame::parse_sized(&bytes[metadata.offset..offset])? {
+            Frame::ZstandardFrame(frame) => frame,
+            Frame::SkippableFrame(frame) => {
+                executor.skippable_frame(&frame);
+                report.skippable_frames += 1;
+                continue;
+            }
+        };
+        let header = frame.header();
+        let checksum_verified =
+            header.content_checksum_flag() && DefaultHasher::default().checksum().is_some();
+
+        // A window larger than accepted fails with `WindowSizeError` without
+        // being allocated
+        let size = core::cmp::min(header.window_size(), executor.max_window_size());
+        let ring = match window.take() {
+            Some(ring) if ring.max_history() >= size => ring,
+            _ => RingBuffer::new(size),
+        };
+        let (content_size, error) = match frame.decode_to_sink(ring, executor) {
+            Ok(ring) => {
+                let content_size = ring.len() as u64;
+                window = Some(ring);
+                (Some(content_size), None)
+            }
+            Err(error) => (None, Some(error)),
+        };
+        report.frames.push(FrameReport {
+            offset: metadata.offset,
+            compressed_size: metadata.compressed_size,
+            content_size,
+            checksum_verified: checksum_verified && error.is_none(),
+            error,
+        });
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{decode, stream::tests::FRAMES, Error, FrameError, Sequential};
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let report = verify(&FRAMES, &Sequential).unwrap();
+        assert!(report.is_valid());
+        assert_eq!(report.skippable_frames, 1);
+        assert_eq!(report.content_size(), 4);
+        let frames = report
+            .frames
+            .iter()
+            .map(|frame| (frame.offset, frame.compressed_size, frame.content_size))
+            .collect::<Vec<_>>();
+        assert_eq!(frames, [(10, 19, Some(4)), (29, 9, Some(0))]);
+        assert_eq!(report.frames[0].checksum_verified, cfg!(feature = "xxhash"));
+        assert!(!report.frames[1].checksum_verified);
+
+        let bytes = include_bytes!("../../tests/txt/mobydick.zst");
+        let report = verify(bytes, &Sequential).unwrap();
+        assert!(report.is_valid());
+        let content_size = decode(bytes, &Sequential).unwrap().len();
+        assert_eq!(report.content_size(), content_size as u64);
+
+        assert!(verify(&[], &Sequential).unwrap().frames.is_empty());
+        assert!(matches!(
+            verify(&[0xCA, 0xFE, 0xBA, 0xBE], &Sequential),
+            Err(Error::NotZstandardData)
+        ));
+    }
+
+    #[test]
+    fn test_verify_failures() {
+        // The frames after a corrupted one are still checked
+        let mut corrupted = FRAMES;
+        corrupted[25] ^= 1;
+        let report = verify(&corrupted, &Sequential).unwrap();
+        assert!(!report.is_valid());
+        assert_eq!(report.frames.len(), 2);
+        if cfg!(feature = "xxhash") {
+            assert!(matches!(
+                report.frames[0].error,
+                Some(Error::Frame(FrameError::ChecksumMismatch))
+            ));
+        }
+        assert!(report.frames[1].error.is_none());
+
+        // A truncated frame is the last one reported
+        let report = verify(&FRAMES[..37], &Sequential).unwrap();
+        assert_eq!(report.frames.len(), 2);
+        assert_eq!(report.frames[1].offset, 29);
+        assert_eq!(report.frames[1].compressed_size, 8);
+        assert!(matches!(report.frames[1].error, Some(Error::Parsing(_))));
+        assert_eq!(report.content_size(), 4);
+    }
+}
@@ -0,0 +1,127 @@
+use super::{frames_metadata, FrameKind, Result};
+use alloc::vec::Vec;
+use core::fmt;
+
+/// Decoders are recommended to support windows up to 8 MB, larger ones may be
+/// rejected by memory constrained decoders
+const RECOMMENDED_WINDOW_SIZE: usize = 8 * 1024 * 1024;
+
+/// Magic nibbles of the skippable frames written by known tools: `0x0` by most
+/// of them (e.g. pzstd), `0xE` by the seekable format for its seek table
+const KNOWN_SKIPPABLE_NIBBLES: [u8; 2] = [0x0, 0xE];
+
+/// Issue of a valid input that does not prevent decoding it, with the input
+/// offset of the frame it concerns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Warning {
+    /// Skippable frame with a magic nibble no known tool writes
+    UnknownSkippableMagic { offset: usize, magic_nibble: u8 },
+    /// Zstandard frame without content checksum: a corrupted content may go unnoticed
+    MissingChecksum { offset: usize },
+    /// Zstandard frame with a window larger than the recommended 8 MB
+    LargeWindow { offset: usize, window_size: usize },
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Warning::UnknownSkippableMagic {
+                offset,
+                magic_nibble,
+            } => write!(
+                f,
+                "Skippable frame at offset {offset}: unknown magic nibble {magic_nibble:#X}"
+            ),
+            Warning::MissingChecksum { offset } => {
+                write!(f, "Frame at offset {offset}: no content checksum")
+            }
+            Warning::LargeWindow {
+                offset,
+                window_size,
+            } => write!(
+                f,
+                "Frame at offset {offset}: window size {window_size} larger than the recommended {RECOMMENDED_WINDOW_SIZE}"
+            ),
+        }
+    }
+}
+
+/// Check the frame headers of `bytes` for issues that do not prevent decoding
+/// it, to report the quality of an archive. Block contents are not decoded.
+pub fn warnings(bytes: &[u8]) -> Result<Vec<Warning>> {
+    let mut warnings = Vec::new();
+
+    for metadata in frames_metadata(bytes) {
+        let metadata = metadata?;
+        let offset = metadata.offset;
+
+        match (metadata.kind, metadata.header) {
+            (FrameKind::Skippable, _) => {
+                let magic_nibble = bytes[offset] & 0xF;
+                if !KNOWN_SKIPPABLE_NIBBLES.contains(&magic_nibble) {
+                    warnings.push(Warning::UnknownSkippableMagic {
+                        offset,
+                        magic_nibble,
+                    });
+                }
+            }
+            (FrameKind::Zstandard, Some(header)) => {
+                if !header.content_checksum_flag() {
+                    warnings.push(Warning::MissingChecksum { offset });
+                }
+                if header.window_size() > RECOMMENDED_WINDOW_SIZE {
+                    warnings.push(Warning::LargeWindow {
+                        offset,
+                        window_size: header.window_size(),
+                    });
+                }
+            }
+            (FrameKind::Zstandard, None) => unreachable!("Zstandard frames have a header"),
+        }
+    }
+
+    Ok(warnings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_warnings() {
+        let bytes = [
+            0x50, 0x2A, 0x4D, 0x18, 0x0, 0x0, 0x0, 0x0, // skippable, nibble 0x0
+            0x53, 0x2A, 0x4D, 0x18, 0x0, 0x0, 0x0, 0x0, // skippable, nibble 0x3
+            0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+            0x04, 0x0, // header: checksum flag, window 1KB
+            0x01, 0x0, 0x0, // raw block, last, len 0
+            0x99, 0xE9, 0xD8, 0x51, // checksum
+            0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+            0x0, 0x70, // header: window 16MB
+            0x01, 0x0, 0x0, // raw block, last, len 0
+        ];
+        assert_eq!(
+            warnings(&bytes).unwrap(),
+            [
+                Warning::UnknownSkippableMagic {
+                    offset: 8,
+                    magic_nibble: 3
+                },
+                Warning::MissingChecksum { offset: 29 },
+                Warning::LargeWindow {
+                    offset: 29,
+                    window_size: 16 * 1024 * 1024
+                },
+            ]
+        );
+        assert_eq!(
+            Warning::MissingChecksum { offset: 29 }.to_string(),
+            "Frame at offset 29: no content checksum"
+        );
+    }
+
+    #[test]
+    fn test_warnings_truncated() {
+        assert!(warnings(&[0x28, 0xB5, 0x2F, 0xFD, 0x0]).is_err());
+    }
+}
@@ -0,0 +1,33 @@
+/*
+ * Decode-only subset of the libzstd one-shot API.
+ * Build with `cargo rustc -p zstd_lib --release --features ffi --crate-type cdylib`
+ * and link against target/release/libzstd_lib.so (or .dylib / .dll).
+ */
+#ifndef RZSTD_H
+#define RZSTD_H
+
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define RZSTD_CONTENTSIZE_UNKNOWN (0ULL - 1)
+#define RZSTD_CONTENTSIZE_ERROR (0ULL - 2)
+
+/* Same semantics as ZSTD_decompress */
+size_t rzstd_decompress(void *dst, size_t dst_capacity, const void *src, size_t src_size);
+
+/* Same semantics as ZSTD_getFrameContentSize */
+unsigned long long rzstd_get_frame_content_size(const void *src, size_t src_size);
+
+/* Same semantics as ZSTD_isError, ZSTD_getErrorCode and ZSTD_getErrorName */
+unsigned rzstd_is_error(size_t code);
+unsigned rzstd_get_error_code(size_t code);
+const char *rzstd_get_error_name(size_t code);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* RZSTD_H */
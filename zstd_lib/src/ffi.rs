@@ -0,0 +1,296 @@
+/*
+    C API: a decode-only subset of libzstd's one-shot functions, see
+    `include/rzstd.h`. Functions returning a `size_t` return either a size or an
+    error code, to be checked with `rzstd_is_error` like `ZSTD_isError`.
+*/
+
+use super::{DecodeOptions, Error, Frame, FrameIterator, FrameKind, SliceSink};
+use std::{
+    ffi::{c_char, c_uint, c_ulonglong, c_void, CStr},
+    slice,
+};
+use zstd_core::{
+    parsing::{ForwardByteParser, ParsingError},
+    ContextError, DecoderError, FrameError, FrameHeader,
+};
+
+/// `ZSTD_ErrorCode` values from `zstd_errors.h`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[repr(usize)]
+enum ErrorCode {
+    NoError = 0,
+    Generic = 1,
+    PrefixUnknown = 10,
+    FrameParameterWindowTooLarge = 16,
+    CorruptionDetected = 20,
+    ChecksumWrong = 22,
+    DictionaryWrong = 32,
+    DstSizeTooSmall = 70,
+    SrcSizeWrong = 72,
+    DstBufferNull = 74,
+    MaxCode = 120,
+}
+
+impl ErrorCode {
+    /// Error codes are returned as `(size_t)-code`
+    fn to_result(self) -> usize {
+        (self as usize).wrapping_neg()
+    }
+
+    fn from_result(result: usize) -> Self {
+        if rzstd_is_error(result) == 0 {
+            return ErrorCode::NoError;
+        }
+        match result.wrapping_neg() {
+            1 => ErrorCode::Generic,
+            10 => ErrorCode::PrefixUnknown,
+            16 => ErrorCode::FrameParameterWindowTooLarge,
+            20 => ErrorCode::CorruptionDetected,
+            22 => ErrorCode::ChecksumWrong,
+            32 => ErrorCode::DictionaryWrong,
+            70 => ErrorCode::DstSizeTooSmall,
+            72 => ErrorCode::SrcSizeWrong,
+            74 => ErrorCode::DstBufferNull,
+            _ => ErrorCode::MaxCode,
+        }
+    }
+
+    /// Same strings as `ZSTD_getErrorName`
+    fn name(self) -> &'static CStr {
+        match self {
+            ErrorCode::NoError => c"No error detected",
+            ErrorCode::Generic => c"Error (generic)",
+            ErrorCode::PrefixUnknown => c"Unknown frame descriptor",
+            ErrorCode::FrameParameterWindowTooLarge => {
+                c"Frame requires too much memory for decoding"
+            }
+            ErrorCode::CorruptionDetected => c"Data corruption detected",
+            ErrorCode::ChecksumWrong => c"Restored data doesn't match checksum",
+            ErrorCode::DictionaryWrong => c"Dictionary mismatch",
+            ErrorCode::DstSizeTooSmall => c"Destination buffer is too small",
+            ErrorCode::SrcSizeWrong => c"Src size is incorrect",
+            ErrorCode::DstBufferNull => c"Operation on NULL destination buffer",
+            ErrorCode::MaxCode => c"Unspecified error code",
+        }
+    }
+}
+
+impl From<&Error> for ErrorCode {
+    fn from(err: &Error) -> Self {
+        match err {
+            Error::NotZstandardData | Error::Frame(FrameError::UnrecognizedMagic(_)) => {
+                ErrorCode::PrefixUnknown
+            }
+            Error::Frame(FrameError::ChecksumMismatch) => ErrorCode::ChecksumWrong,
+            Error::Frame(FrameError::DictNotSupported { .. }) => ErrorCode::DictionaryWrong,
+            Error::Parsing(ParsingError::NotEnoughBytes { .. }) => ErrorCode::SrcSizeWrong,
+            Error::Decoder(DecoderError::Context(ContextError::WindowSizeError { .. })) => {
+                ErrorCode::FrameParameterWindowTooLarge
+            }
+            Error::Decoder(DecoderError::Context(ContextError::OutputTooSmall { .. })) => {
+                ErrorCode::DstSizeTooSmall
+            }
+            Error::ParallelDecodingError(_) => ErrorCode::Generic,
+            _ => ErrorCode::CorruptionDetected,
+        }
+    }
+}
+
+/// `ZSTD_CONTENTSIZE_UNKNOWN`
+pub const RZSTD_CONTENTSIZE_UNKNOWN: c_ulonglong = c_ulonglong::MAX;
+/// `ZSTD_CONTENTSIZE_ERROR`
+pub const RZSTD_CONTENTSIZE_ERROR: c_ulonglong = c_ulonglong::MAX - 1;
+
+/// Build a slice from a C buffer, which may be NULL when empty
+unsafe fn input<'a>(src: *const c_void, src_size: usize) -> Option<&'a [u8]> {
+    if src_size == 0 {
+        Some(&[])
+    } else if src.is_null() {
+        None
+    } else {
+        Some(slice::from_raw_parts(src.cast::<u8>(), src_size))
+    }
+}
+
+/// Decompress the `src_size` bytes of `src` (one or more frames) into `dst`.
+/// Return the decompressed size or an error code, like `ZSTD_decompress`.
+///
+/// # Safety
+/// `src` must be valid for reads of `src_size` bytes and `dst` valid for writes
+/// of `dst_capacity` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rzstd_decompress(
+    dst: *mut c_void,
+    dst_capacity: usize,
+    src: *const c_void,
+    src_size: usize,
+) -> usize {
+    let Some(src) = input(src, src_size) else {
+        return ErrorCode::SrcSizeWrong.to_result();
+    };
+    // Only empty content fits in a NULL buffer
+    let output = if dst.is_null() {
+        &mut []
+    } else {
+        slice::from_raw_parts_mut(dst.cast::<u8>(), dst_capacity)
+    };
+
+    match decompress_into(output, src) {
+        Ok(written) => written,
+        Err(err) => match ErrorCode::from(&err) {
+            ErrorCode::DstSizeTooSmall if dst.is_null() => ErrorCode::DstBufferNull.to_result(),
+            code => code.to_result(),
+        },
+    }
+}
+
+/// Decode the frames of `src` straight into `dst`, one after the other, and
+/// return the size of their content
+fn decompress_into(dst: &mut [u8], src: &[u8]) -> Result<usize, Error> {
+    let options = DecodeOptions::new();
+    let mut written = 0;
+    for frame in FrameIterator::new(src) {
+        if let Frame::ZstandardFrame(frame) = frame? {
+            let sink = SliceSink::new(&mut dst[written..]);
+            written += frame.decode_to_sink(sink, &options)?.written().len();
+        }
+    }
+    Ok(written)
+}
+
+/// Return the content size announced by the header of the first frame of `src`,
+/// like `ZSTD_getFrameContentSize`: `RZSTD_CONTENTSIZE_UNKNOWN` when the header
+/// does not provide it, `RZSTD_CONTENTSIZE_ERROR` when the header is invalid or
+/// truncated, and 0 for a skippable frame.
+///
+/// # Safety
+/// `src` must be valid for reads of `src_size` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn rzstd_get_frame_content_size(
+    src: *const c_void,
+    src_size: usize,
+) -> c_ulonglong {
+    let Some(src) = input(src, src_size) else {
+        return RZSTD_CONTENTSIZE_ERROR;
+    };
+
+    let mut parser = ForwardByteParser::new(src);
+    let Ok(magic) = parser.le_u32() else {
+        return RZSTD_CONTENTSIZE_ERROR;
+    };
+
+    match FrameKind::from_magic(magic) {
+        Some(FrameKind::Skippable) => 0,
+        Some(FrameKind::Zstandard) => match FrameHeader::parse(&mut parser) {
+            Ok(header) => header.content_size().unwrap_or(RZSTD_CONTENTSIZE_UNKNOWN),
+            Err(_) => RZSTD_CONTENTSIZE_ERROR,
+        },
+        None => RZSTD_CONTENTSIZE_ERROR,
+    }
+}
+
+/// Tell whether a `size_t` function result is an error code, like `ZSTD_isError`
+#[no_mangle]
+pub extern "C" fn rzstd_is_error(code: usize) -> c_uint {
+    c_uint::from(code > ErrorCode::MaxCode.to_result())
+}
+
+/// Return the `ZSTD_ErrorCode` of a function result, 0 when it is not an error,
+/// like `ZSTD_getErrorCode`
+#[no_mangle]
+pub extern "C" fn rzstd_get_error_code(code: usize) -> c_uint {
+    if rzstd_is_error(code) == 0 {
+        return 0;
+    }
+    c_uint::try_from(code.wrapping_neg()).unwrap_or(ErrorCode::MaxCode as c_uint)
+}
+
+/// Return a static, NUL-terminated description of a function result, like
+/// `ZSTD_getErrorName`
+#[no_mangle]
+pub extern "C" fn rzstd_get_error_name(code: usize) -> *const c_char {
+    ErrorCode::from_result(code).name().as_ptr()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FRAME: [u8; 12] = [
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x20, 0x2, // header: SSF, FCS 2
+        0x11, 0x0, 0x0, // raw block, last, len 2
+        0xCA, 0xFE, // content
+        0x0,  // trailing byte
+    ];
+
+    fn decompress(src: &[u8], dst: &mut [u8]) -> usize {
+        unsafe {
+            rzstd_decompress(
+                dst.as_mut_ptr().cast(),
+                dst.len(),
+                src.as_ptr().cast(),
+                src.len(),
+            )
+        }
+    }
+
+    fn error_name(code: usize) -> &'static str {
+        unsafe { CStr::from_ptr(rzstd_get_error_name(code)) }
+            .to_str()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_decompress() {
+        let mut dst = [0; 4];
+        assert_eq!(decompress(&FRAME[..11], &mut dst), 2);
+        assert_eq!(dst[..2], [0xCA, 0xFE]);
+        assert_eq!(rzstd_is_error(2), 0);
+        assert_eq!(rzstd_get_error_code(2), 0);
+        assert_eq!(error_name(2), "No error detected");
+    }
+
+    #[test]
+    fn test_decompress_errors() {
+        let code = decompress(&FRAME[..11], &mut [0; 1]);
+        assert_eq!(rzstd_is_error(code), 1);
+        assert_eq!(rzstd_get_error_code(code), 70);
+        assert_eq!(error_name(code), "Destination buffer is too small");
+
+        let code = decompress(&FRAME[..10], &mut [0; 4]);
+        assert_eq!(rzstd_get_error_code(code), 72);
+
+        let code = decompress(b"not zstd", &mut [0; 4]);
+        assert_eq!(rzstd_get_error_code(code), 10);
+        assert_eq!(error_name(code), "Unknown frame descriptor");
+
+        let code = decompress(&FRAME, &mut [0; 4]);
+        assert_eq!(rzstd_is_error(code), 1);
+
+        // The second frame does not fit
+        let frames = [&FRAME[..11], &FRAME[..11]].concat();
+        assert_eq!(rzstd_get_error_code(decompress(&frames, &mut [0; 3])), 70);
+        assert_eq!(decompress(&frames, &mut [0; 4]), 4);
+
+        let code = unsafe { rzstd_decompress(std::ptr::null_mut(), 4, FRAME.as_ptr().cast(), 11) };
+        assert_eq!(rzstd_get_error_code(code), 74);
+    }
+
+    #[test]
+    fn test_get_frame_content_size() {
+        let content_size =
+            |src: &[u8]| unsafe { rzstd_get_frame_content_size(src.as_ptr().cast(), src.len()) };
+        assert_eq!(content_size(&FRAME), 2);
+        assert_eq!(content_size(&FRAME[..5]), RZSTD_CONTENTSIZE_ERROR);
+        assert_eq!(
+            content_size(&[0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x50]),
+            RZSTD_CONTENTSIZE_UNKNOWN
+        );
+        assert_eq!(
+            content_size(&[0x50, 0x2A, 0x4D, 0x18, 0x0, 0x0, 0x0, 0x0]),
+            0
+        );
+        assert_eq!(content_size(b"not zstd"), RZSTD_CONTENTSIZE_ERROR);
+    }
+}
@@ -6,90 +6,1454 @@
     clippy::struct_field_names
 )]
 
-mod block;
-mod decoders;
-mod frame;
-mod literals;
-pub mod parsing;
-mod sequences;
-
-use block::*;
-use decoders::*;
-use frame::*;
-use literals::*;
-use parsing::*;
-use sequences::*;
-
-use std::thread;
+#[cfg(feature = "ffi")]
+mod ffi;
+mod spill;
+mod stream;
+mod threads;
+#[cfg(feature = "wasm")]
+mod wasm;
+
+pub use spill::{SpillWindow, TempFile};
+#[cfg(feature = "threads")]
+use threads::Helper;
+use zstd_core::{check_magic, ContextError, DecoderError, Executor, Sequential};
+
+#[cfg(feature = "futures")]
+pub use stream::decode_stream;
+#[cfg(feature = "tokio")]
+pub use stream::AsyncDecoder;
+#[cfg(feature = "tokio-util")]
+pub use stream::FrameCodec;
+pub use stream::{decode_from, frames_metadata_from, Decoder, FramesMetadataFrom, StreamDecoder};
+pub use zstd_core::{
+    block_tables_dot, build_frame_index, content_size_hint, decode_sequences, decompress_bound,
+    find_frame_compressed_size, format, frames_metadata, get_frame_content_size, merge_frames,
+    parsing, pzstd_frames, raw_content, read_frame_index, read_metadata, repair, skippable_frames,
+    sniff, split_frames, strip_checksums, warnings, write_frame_index, write_metadata, Backend,
+    BlockInfo, BlockSequences, BlockType, BufferKind, Damage, DecodeError, DecodeStats,
+    DecodedChunks, DecodingContext, ErrorKind, Frame, FrameEntry, FrameHeader, FrameIterator,
+    FrameKind, FrameMetadata, FrameReport, LiteralsType, Metadata, OutputSink, Progress, Repair,
+    RingBuffer, SectionsInfo, SequenceCommand, SequencesMode, SkippableData, SkippableFrame,
+    SliceSink, TablesDot, VerifyReport, Warning, WindowWriter, ZstandardFrame, ZstdLibError,
+    DEFAULT_MAX_WINDOW_SIZE, INDEX_MAGIC_NIBBLE, METADATA_MAGIC_NIBBLE,
+};
+#[cfg(feature = "block-api")]
+pub use zstd_core::{decode_block, BlockHeader};
+
+/// Nested errors carried by the variants of `ZstdLibError`. They follow the
+/// modules of zstd_core and are not stable: match on `ZstdLibError::kind`
+/// unless a specific failure matters, e.g. in tests.
+pub mod errors {
+    pub use zstd_core::{
+        parsing::ParsingError, BlockError, CheckpointError, ContextError, DecoderError, FrameError,
+        FseError, HuffmanError, IndexError, LiteralsError, MetadataError, SequencesError,
+    };
+}
+
+use std::{any::Any, borrow::Cow, fmt, io::Write, path::PathBuf, sync::Arc};
 
 /*
-    ZstdLib only export 2+1 things:
-        - pub fn decode
-        - ZstdLibError
-        (- parsing module)
+    ZstdLib is the std layer over zstd_core, which implements the format: it
+    runs frames and blocks on threads and adapts decoding to std::io, async
+    runtimes, C and JS. The item docs describe the API; it exports:
+        - decode functions, from the simplest `decode` to the ones taking
+          `DecodeOptions`, which carry every setting so that they do not
+          multiply into parameters
+        - the frame and block types of zstd_core that callers inspect or drive
+          themselves, re-exported rather than wrapped
+        - ZstdLibError, matched by kind (`ErrorKind`) rather than by the nested
+          errors of zstd_core, which may change with its modules; they are
+          re-exported together in `errors`, outside the stable API
+        (- parsing and format modules)
 
     I think this is a clean design because as a user of the library I dont
     want to know the inner implementation details. I only want a handle to decode
     and a CustomError type.
 
+    Every type is Send + Sync when its parameters are: build DecodeOptions once
+    and share it, and move decoders and DecodingContexts across threads. There
+    is no shared state to prepare beyond that: the predefined FSE tables are
+    built per context, and dictionaries are not supported.
+
     (Parsing module is exported for the sake of doc tests. It is not 100% relevant
     and we could remove them anyway and make the module private.)
 */
 
-#[derive(Debug, thiserror::Error)]
-pub enum ZstdLibError {
-    #[error(transparent)]
-    Parsing(#[from] ParsingError),
+// Options are shared by the threads decoding frames, and decoders moved to
+// the thread serving a request, see the same assertions in zstd_core
+const _: () = {
+    const fn send_sync<T: Send + Sync>() {}
+    send_sync::<DecodeOptions>();
+    send_sync::<StreamDecoder>();
+    send_sync::<Decoder<std::fs::File>>();
+    send_sync::<DecodedChunks<'static, DecodeOptions>>();
+    send_sync::<SpillWindow<std::fs::File>>();
+};
+
+type Error = ZstdLibError;
+type Result<T, E = ZstdLibError> = std::result::Result<T, E>;
+
+/// Without the `threads` feature, or on `wasm32-unknown-unknown` which cannot
+/// spawn threads, decoding always runs on the calling thread
+const THREADS_SUPPORTED: bool =
+    cfg!(feature = "threads") && !cfg!(all(target_arch = "wasm32", target_os = "unknown"));
+
+/// Turn the payload of a panicked decoding thread into an error, keeping the
+/// panic message when there is one.
+fn panic_error(payload: Box<dyn Any + Send>) -> Error {
+    let message = match payload.downcast::<&str>() {
+        Ok(message) => (*message).to_string(),
+        Err(payload) => match payload.downcast::<String>() {
+            Ok(message) => *message,
+            Err(_) => "unknown panic payload".to_string(),
+        },
+    };
+    Error::ParallelDecodingError(message)
+}
+
+fn parse_frames(bytes: &[u8]) -> Result<Vec<Frame<'_>>> {
+    FrameIterator::new(bytes).collect()
+}
+
+/// Decoding settings, see `decode_with_options`. Cheap to clone: the handlers
+/// are shared.
+#[derive(Clone, Default)]
+pub struct DecodeOptions {
+    threads: usize,
+    window_log_max: Option<u32>,
+    max_in_flight: Option<usize>,
+    budget: Option<u64>,
+    max_memory: Option<usize>,
+    backend: Backend,
+    allocation_hook: Option<AllocationHook>,
+    skippable_handlers: [Option<SkippableHandler>; 16],
+    spill_window: Option<usize>,
+}
+
+/// Callback observing or vetoing the growth of the large buffers, see
+/// `DecodeOptions::allocation_hook`
+pub type AllocationHook = Arc<dyn Fn(BufferKind, usize) -> bool + Send + Sync>;
+
+/// Callback given the payload of skippable frames, see
+/// `DecodeOptions::skippable_handler`
+pub type SkippableHandler = Arc<dyn Fn(&[u8]) + Send + Sync>;
+
+/// Bound on the content of the frames decoded ahead of the writer by
+/// `decode_to`, unless set with `DecodeOptions::max_in_flight`
+pub const DEFAULT_MAX_IN_FLIGHT: usize = 256 * 1024 * 1024;
+
+impl DecodeOptions {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decode frames and literals streams on the calling thread instead of
+    /// spawning threads. Slower, but deterministic when reproducing a failure
+    /// or profiling. Always the case on `wasm32-unknown-unknown`, and without
+    /// the `threads` feature.
+    /// The same as `threads(1)`, or `threads(0)` when `false`.
+    #[must_use]
+    pub fn single_threaded(self, single_threaded: bool) -> Self {
+        self.threads(usize::from(single_threaded))
+    }
+
+    /// Decode on at most `threads` threads, the calling one included: frames,
+    /// and the sections and literals streams of blocks, run on up to
+    /// `threads - 1` helper threads. `0`, the default, is one per core, and `1`
+    /// never spawns a thread.
+    ///
+    /// The helper threads of concurrent decodings count toward each other's
+    /// limit.
+    #[must_use]
+    pub fn threads(mut self, threads: usize) -> Self {
+        self.threads = threads;
+        self
+    }
+
+    /// Number of threads to decode on, `0` resolved
+    fn thread_count(&self) -> usize {
+        match self.threads {
+            _ if !THREADS_SUPPORTED => 1,
+            0 => threads::available_parallelism(),
+            threads => threads,
+        }
+    }
+
+    /// Accept frames with windows up to `2^window_log_max` bytes instead of
+    /// `DEFAULT_MAX_WINDOW_SIZE`, as `zstd --long=window_log_max` does. Larger
+    /// windows let an input claim more memory.
+    #[must_use]
+    pub fn window_log_max(mut self, window_log_max: u32) -> Self {
+        self.window_log_max = Some(window_log_max);
+        self
+    }
+
+    /// Fail frames that need more than `units` work units with
+    /// `BudgetExceeded`, to bound the time spent on untrusted input whatever
+    /// the threads or the machine. A unit is a literal byte, raw and RLE block
+    /// bytes included, or a sequence.
+    #[must_use]
+    pub fn budget(mut self, units: u64) -> Self {
+        self.budget = Some(units);
+        self
+    }
+
+    /// Fail frames that need more than `bytes` of memory with
+    /// `MemoryLimitExceeded`: their content, as the output of `decode` holding
+    /// the previous frames too, or their window when streaming, along with the
+    /// buffers and tables of their blocks. Frames decoded in parallel each
+    /// have the limit.
+    #[must_use]
+    pub fn max_memory(mut self, bytes: usize) -> Self {
+        self.max_memory = Some(bytes);
+        self
+    }
 
-    #[error(transparent)]
-    Block(#[from] BlockError),
+    /// Decode the content of blocks with `backend` instead of the default one
+    #[must_use]
+    pub fn backend(mut self, backend: Backend) -> Self {
+        self.backend = backend;
+        self
+    }
 
-    #[error(transparent)]
-    Frame(#[from] FrameError),
+    /// Call `hook` before the content, literals and sequences buffers grow,
+    /// with the buffer and its new size in bytes: returning `false` fails the
+    /// frame with `AllocationFailed`. Lets an embedding application observe
+    /// the memory of the decoder and veto it against a budget of its own; the
+    /// buffers are still allocated by the global allocator. Allocation failures
+    /// of the allocator fail the frame the same way, with or without a hook.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_lib::{decode_with_options, DecodeOptions};
+    /// # use std::sync::{atomic::{AtomicUsize, Ordering}, Arc};
+    /// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+    /// let allocated = Arc::new(AtomicUsize::new(0));
+    /// let observed = Arc::clone(&allocated);
+    /// let options = DecodeOptions::new().allocation_hook(move |_, bytes| {
+    ///     observed.fetch_max(bytes, Ordering::Relaxed);
+    ///     bytes <= 1024
+    /// });
+    /// assert_eq!(decode_with_options(&bytes, options)?, [0xCA, 0xFE]);
+    /// assert!(allocated.load(Ordering::Relaxed) >= 2);
+    /// # Ok::<(), zstd_lib::ZstdLibError>(())
+    /// ```
+    #[must_use]
+    pub fn allocation_hook(
+        mut self,
+        hook: impl Fn(BufferKind, usize) -> bool + Send + Sync + 'static,
+    ) -> Self {
+        self.allocation_hook = Some(Arc::new(hook));
+        self
+    }
 
-    #[error(transparent)]
-    Decoder(#[from] DecoderError),
+    /// Call `handler` with the payload of the skippable frames whose magic
+    /// number ends with the low 4 bits of `magic_nibble`, in input order, on
+    /// the calling thread, instead of discarding them. Replaces the handler
+    /// registered before for the nibble.
+    ///
+    /// # Example
+    /// ```
+    /// # use zstd_lib::{decode_with_options, DecodeOptions};
+    /// # use std::sync::{Arc, Mutex};
+    /// let bytes = [
+    ///     0x53, 0x2A, 0x4D, 0x18, 0x2, 0x0, 0x0, 0x0, 0x10, 0x20, // skippable, nibble 3
+    ///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+    /// ];
+    /// let index = Arc::new(Mutex::new(Vec::new()));
+    /// let payloads = Arc::clone(&index);
+    /// let options = DecodeOptions::new().skippable_handler(3, move |payload| {
+    ///     payloads.lock().unwrap().extend_from_slice(payload);
+    /// });
+    /// assert_eq!(decode_with_options(&bytes, options)?, [0xCA, 0xFE]);
+    /// assert_eq!(*index.lock().unwrap(), [0x10, 0x20]);
+    /// # Ok::<(), zstd_lib::ZstdLibError>(())
+    /// ```
+    #[must_use]
+    pub fn skippable_handler(
+        mut self,
+        magic_nibble: u8,
+        handler: impl Fn(&[u8]) + Send + Sync + 'static,
+    ) -> Self {
+        self.skippable_handlers[usize::from(magic_nibble & 0xF)] = Some(Arc::new(handler));
+        self
+    }
 
-    #[error(transparent)]
-    Literals(#[from] LiteralsError),
+    fn has_skippable_handlers(&self) -> bool {
+        self.skippable_handlers.iter().any(Option::is_some)
+    }
 
-    #[error(transparent)]
-    Sequences(#[from] SequencesError),
+    /// Bound the decoded content held in memory by `decode_to` to `bytes`
+    /// instead of `DEFAULT_MAX_IN_FLIGHT`. A frame larger than that is
+    /// decoded alone.
+    #[must_use]
+    pub fn max_in_flight(mut self, bytes: usize) -> Self {
+        self.max_in_flight = Some(bytes);
+        self
+    }
 
-    #[error("Parallel decoding panicked")]
-    ParallelDecodingError,
+    /// Have `decode_to` keep at most `hot_size` bytes of the window of a frame
+    /// in memory, at least 128 KB, and the rest in a temporary file, see
+    /// `SpillWindow`: frames of windows up to `window_log_max` are decoded
+    /// whatever the memory, e.g. `zstd --long=30` archives on small machines,
+    /// at the cost of I/O. Frames are then decoded one at a time.
+    #[must_use]
+    pub fn spill_window(mut self, hot_size: usize) -> Self {
+        self.spill_window = Some(hot_size);
+        self
+    }
 }
-type Error = ZstdLibError;
-type Result<T, E = ZstdLibError> = std::result::Result<T, E>;
 
-fn parse_frames(bytes: &[u8], info: bool) -> Result<Vec<Frame>> {
-    let frames = FrameIterator::new(bytes).collect::<Result<Vec<Frame>>>()?;
+impl fmt::Debug for DecodeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        // The magic nibbles with a handler
+        let skippable_handlers: Vec<usize> = (0..16)
+            .filter(|&nibble| self.skippable_handlers[nibble].is_some())
+            .collect();
+        f.debug_struct("DecodeOptions")
+            .field("threads", &self.threads)
+            .field("window_log_max", &self.window_log_max)
+            .field("max_in_flight", &self.max_in_flight)
+            .field("budget", &self.budget)
+            .field("max_memory", &self.max_memory)
+            .field("backend", &self.backend)
+            .field("allocation_hook", &self.allocation_hook.is_some())
+            .field("skippable_handlers", &skippable_handlers)
+            .field("spill_window", &self.spill_window)
+            .finish()
+    }
+}
 
-    if info {
-        for frame in frames {
-            println!("{frame:#?}");
+/// Literals and sequences sections, and literals streams, run on helper threads
+impl Executor for DecodeOptions {
+    fn join<A, B, RA, RB>(&self, a: A, b: B) -> Result<(RA, RB)>
+    where
+        A: FnOnce() -> RA + Send,
+        B: FnOnce() -> RB + Send,
+        RA: Send,
+        RB: Send,
+    {
+        // `a` runs on the calling thread, `b` on a helper if there is one left
+        #[cfg(feature = "threads")]
+        if let Some(helper) = Helper::reserve(self.thread_count()) {
+            return threads::join(helper, a, b);
+        }
+        Sequential.join(a, b)
+    }
+
+    fn max_window_size(&self) -> usize {
+        self.window_log_max.map_or(DEFAULT_MAX_WINDOW_SIZE, |log| {
+            1_usize.checked_shl(log).unwrap_or(usize::MAX)
+        })
+    }
+
+    fn budget(&self) -> Option<u64> {
+        self.budget
+    }
+
+    fn max_memory(&self) -> Option<usize> {
+        self.max_memory
+    }
+
+    fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    fn allocate(&self, buffer: BufferKind, bytes: usize) -> bool {
+        self.allocation_hook
+            .as_ref()
+            .is_none_or(|hook| hook(buffer, bytes))
+    }
+
+    fn skippable_frame(&self, frame: &SkippableFrame<'_>) {
+        if let Some(handler) = &self.skippable_handlers[usize::from(frame.magic_nibble())] {
+            handler(frame.payload());
         }
-        Ok(vec![])
-    } else {
-        Ok(frames)
     }
 }
 
+/// Decode `bytes` with the default options. With `info`, the frames are
+/// only parsed and an empty content is returned: nothing is printed, the
+/// frames are given to `tracing` as debug events with the `tracing` feature.
+/// Use `frames_metadata` to get their headers and blocks instead.
 pub fn decode(bytes: &[u8], info: bool) -> Result<Vec<u8>> {
-    thread::scope(|s| -> Result<Vec<u8>> {
-        let frames = parse_frames(bytes, info)?;
-        let mut decoded: Vec<u8> = Vec::new();
+    if info {
+        for frame in FrameIterator::new(bytes) {
+            let frame = frame?;
+            #[cfg(feature = "tracing")]
+            tracing::debug!(?frame, "parsed frame");
+            #[cfg(not(feature = "tracing"))]
+            let _ = frame;
+        }
+        return Ok(Vec::new());
+    }
+    decode_with_options(bytes, DecodeOptions::default())
+}
 
-        let handles: Vec<_> = frames
-            .into_iter()
-            .map(|frame| s.spawn(|| frame.decode()))
-            .collect();
+/// Decode `bytes` according to `options`. On the calling thread, each frame
+/// is decoded at the end of the output. On helper threads, each frame is
+/// decoded into its region of the output when the frame headers give the
+/// content sizes, otherwise into a buffer of its own, copied to the output.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_with_options, DecodeOptions};
+/// let options = DecodeOptions::new().single_threaded(true);
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(decode_with_options(&bytes, options).unwrap(), [0xCA, 0xFE]);
+/// ```
+pub fn decode_with_options(bytes: &[u8], options: DecodeOptions) -> Result<Vec<u8>> {
+    if options.thread_count() == 1 {
+        // Frames are decoded at the end of the output, without copy
+        return zstd_core::decode(bytes, &options);
+    }
+    check_magic(bytes)?;
+
+    // Each frame with the input it was parsed from
+    let mut frames = Vec::new();
+    let mut input = parsing::ForwardByteParser::new(bytes);
+    while !input.is_empty() {
+        let start = bytes.len() - input.len();
+        match Frame::parse(&mut input)? {
+            Frame::ZstandardFrame(frame) => {
+                frames.push((frame, &bytes[start..bytes.len() - input.len()]));
+            }
+            Frame::SkippableFrame(frame) => options.skippable_frame(&frame),
+        }
+    }
+
+    // The sizes are trusted only when the blocks can hold them, so that a
+    // corrupted header does not claim memory
+    let sizes: Option<Vec<usize>> = frames
+        .iter()
+        .map(|(frame, _)| {
+            let size = usize::try_from(frame.header().content_size()?).ok()?;
+            (size <= frame.max_content_size()).then_some(size)
+        })
+        .collect();
+    let Some(sizes) = sizes else {
+        let frames = frames.into_iter().map(|(frame, _)| frame).collect();
+        return concat_frames(frames, &options);
+    };
+
+    let bytes = sizes
+        .iter()
+        .fold(0, |total: usize, &size| total.saturating_add(size));
+    let mut decoded = Vec::new();
+    if bytes > 0
+        && (!options.allocate(BufferKind::Content, bytes)
+            || decoded.try_reserve_exact(bytes).is_err())
+    {
+        let buffer = BufferKind::Content;
+        let error = ContextError::AllocationFailed { buffer, bytes };
+        return Err(Error::Decoder(error.into()));
+    }
+    decoded.resize(bytes, 0);
+
+    let mut regions = Vec::with_capacity(frames.len());
+    let mut rest = decoded.as_mut_slice();
+    for (frame, size) in frames.into_iter().zip(sizes) {
+        let (region, tail) = rest.split_at_mut(size);
+        regions.push((frame, region));
+        rest = tail;
+    }
+    threads::map(
+        regions,
+        options.thread_count(),
+        |((frame, input), region)| decode_region(frame, input, region, &options),
+    )
+    .into_iter()
+    .collect::<Result<()>>()?;
+    Ok(decoded)
+}
+
+/// Decode `frame`, parsed from `input`, into `region`, the size announced by
+/// its header
+fn decode_region(
+    frame: ZstandardFrame<'_>,
+    input: &[u8],
+    region: &mut [u8],
+    options: &DecodeOptions,
+) -> Result<()> {
+    match frame.decode_to_sink(SliceSink::new(region), options) {
+        Ok(_) => Ok(()),
+        // More content than announced: decoded alone for the actual error
+        Err(err @ Error::Decoder(DecoderError::Context(ContextError::OutputTooSmall { .. }))) => {
+            Err(Frame::parse_sized(input)?
+                .decode(options)
+                .err()
+                .unwrap_or(err))
+        }
+        Err(err) => Err(err),
+    }
+}
+
+/// Decode `frames` each in a buffer of its own on helper threads, then append
+/// the others to the first one: their content is copied once
+fn concat_frames(frames: Vec<ZstandardFrame<'_>>, options: &DecodeOptions) -> Result<Vec<u8>> {
+    let mut frames = threads::map(frames, options.thread_count(), |frame| {
+        Frame::ZstandardFrame(frame).decode(options)
+    })
+    .into_iter()
+    .collect::<Result<Vec<_>>>()?
+    .into_iter();
+    let mut decoded = frames.next().unwrap_or_default();
+    let additional = frames.as_slice().iter().map(Vec::len).sum::<usize>();
+    let bytes = decoded.len().saturating_add(additional);
+    if bytes > decoded.capacity()
+        && (!options.allocate(BufferKind::Content, bytes)
+            || decoded.try_reserve_exact(additional).is_err())
+    {
+        let buffer = BufferKind::Content;
+        let error = ContextError::AllocationFailed { buffer, bytes };
+        return Err(Error::Decoder(error.into()));
+    }
+    for frame in frames {
+        decoded.extend_from_slice(&frame);
+    }
+    Ok(decoded)
+}
+
+/// Decode `bytes` with the default options, keeping the content decoded before
+/// a failure, see `decode_partial_with_options`
+pub fn decode_partial(bytes: &[u8]) -> Result<Vec<u8>, DecodeError> {
+    decode_partial_with_options(bytes, DecodeOptions::default())
+}
+
+/// Decode `bytes` block by block according to `options`. When a frame or a
+/// block is corrupted, the content decoded before it is returned in the error
+/// alongside the input offset of the failure, see `zstd_core::decode_partial`.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_partial_with_options, DecodeOptions};
+/// // Window of 128 MB, accepted with `window_log_max`, then a truncated block
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x88, 0x10, 0x0, 0x0, 0xCA, 0xFE, 0x11];
+/// let err = decode_partial_with_options(&bytes, DecodeOptions::new()).unwrap_err();
+/// assert_eq!(err.offset, 4);
+/// let options = DecodeOptions::new().window_log_max(27);
+/// let err = decode_partial_with_options(&bytes, options).unwrap_err();
+/// assert_eq!((err.partial.as_slice(), err.offset), (&[0xCA, 0xFE][..], 11));
+/// ```
+pub fn decode_partial_with_options(
+    bytes: &[u8],
+    options: DecodeOptions,
+) -> Result<Vec<u8>, DecodeError> {
+    zstd_core::decode_partial(bytes, &options)
+}
+
+/// Check the integrity of `bytes` with the default options, decoding its
+/// frames without keeping their content, see `verify_with_options`
+pub fn verify(bytes: &[u8]) -> Result<VerifyReport> {
+    verify_with_options(bytes, DecodeOptions::default())
+}
+
+/// Decode the frames of `bytes` according to `options` to check their content
+/// sizes and checksums, and report on each. Only the window of a frame is held
+/// in memory, not its content, see `zstd_core::verify`.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{verify_with_options, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let report = verify_with_options(&bytes, DecodeOptions::new())?;
+/// assert!(report.is_valid());
+/// assert_eq!(report.content_size(), 2);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn verify_with_options(bytes: &[u8], options: DecodeOptions) -> Result<VerifyReport> {
+    zstd_core::verify(bytes, &options)
+}
+
+/// Decode `bytes` according to `options`, along with the issues that did not
+/// prevent decoding it (e.g. a frame without content checksum), see `warnings`
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_with_warnings, DecodeOptions, Warning};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let (decoded, warnings) = decode_with_warnings(&bytes, DecodeOptions::new())?;
+/// assert_eq!(decoded, [0xCA, 0xFE]);
+/// assert_eq!(warnings, [Warning::MissingChecksum { offset: 0 }]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_with_warnings(
+    bytes: &[u8],
+    options: DecodeOptions,
+) -> Result<(Vec<u8>, Vec<Warning>)> {
+    let decoded = decode_with_options(bytes, options)?;
+    Ok((decoded, warnings(bytes)?))
+}
+
+/// Decode `bytes` according to `options`, calling `on_block` with the content
+/// of each block as soon as it is decoded instead of returning the whole
+/// content, e.g. to hash it or pipe it. Blocks are given before the checksum
+/// of their frame is verified.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_with_callback, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut decoded = Vec::new();
+/// decode_with_callback(&bytes, DecodeOptions::new(), |block| {
+///     decoded.extend_from_slice(block);
+/// })?;
+/// assert_eq!(decoded, [0xCA, 0xFE]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_with_callback(
+    bytes: &[u8],
+    options: DecodeOptions,
+    on_block: impl FnMut(&[u8]),
+) -> Result<()> {
+    zstd_core::decode_with_callback(bytes, &options, on_block)
+}
+
+/// Decode `bytes` according to `options` one block at a time as the chunks
+/// are pulled, see `zstd_core::decoded_chunks`. Frames are decoded one after
+/// the other.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decoded_chunks, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut chunks = decoded_chunks(&bytes, DecodeOptions::new());
+/// assert_eq!(chunks.next().transpose()?, Some(vec![0xCA, 0xFE]));
+/// assert!(chunks.next().is_none());
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decoded_chunks(bytes: &[u8], options: DecodeOptions) -> DecodedChunks<'_, DecodeOptions> {
+    zstd_core::decoded_chunks(bytes, options)
+}
+
+/// Decode the chained `segments` according to `options`, as if they were
+/// concatenated but without copying them into one buffer: only the bytes of a
+/// structure straddling two segments are. Frames are decoded one after the
+/// other.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_segments, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let segments = [&bytes[..5], &bytes[5..]];
+/// assert_eq!(decode_segments(segments, DecodeOptions::new())?, [0xCA, 0xFE]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_segments<'a>(
+    segments: impl IntoIterator<Item = &'a [u8]>,
+    options: DecodeOptions,
+) -> Result<Vec<u8>> {
+    zstd_core::decode_segments(segments, &options)
+}
+
+/// Decode `bytes` according to `options`, along with counts of the frames,
+/// blocks, sections, sequences and checksums decoded. Frames are decoded one
+/// after the other.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_with_stats, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let (decoded, stats) = decode_with_stats(&bytes, DecodeOptions::new())?;
+/// assert_eq!((decoded.len(), stats.frames, stats.checksums_absent), (2, 1, 1));
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_with_stats(bytes: &[u8], options: DecodeOptions) -> Result<(Vec<u8>, DecodeStats)> {
+    zstd_core::decode_with_stats(bytes, &options)
+}
+
+/// Rewrite the Zstandard frames of `bytes` without a content checksum to end
+/// with one, decoding them according to `options`, see `strip_checksums` for
+/// the reverse
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{add_checksums, strip_checksums, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let archive = add_checksums(&bytes, DecodeOptions::new())?;
+/// assert_eq!(archive.len(), bytes.len() + 4);
+/// assert_eq!(strip_checksums(&archive)?, bytes);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+#[cfg(feature = "xxhash")]
+pub fn add_checksums(bytes: &[u8], options: DecodeOptions) -> Result<Vec<u8>> {
+    zstd_core::add_checksums(bytes, &options)
+}
+
+/// Size of the input chunks pushed to the decoder by `decode_with_progress`
+const PROGRESS_CHUNK_SIZE: usize = 1024 * 1024;
+
+/// Decode `bytes` according to `options`, calling `on_progress` after each
+/// block with the input consumed, the content produced and the index of the
+/// frame, e.g. for a progress bar or metrics. Frames are decoded one after the
+/// other.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_with_progress, DecodeOptions};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut consumed = 0;
+/// let decoded = decode_with_progress(&bytes, DecodeOptions::new(), |progress| {
+///     consumed = progress.input;
+/// })?;
+/// assert_eq!((decoded, consumed), (vec![0xCA, 0xFE], bytes.len()));
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_with_progress(
+    bytes: &[u8],
+    options: DecodeOptions,
+    mut on_progress: impl FnMut(Progress),
+) -> Result<Vec<u8>> {
+    check_magic(bytes)?;
+
+    // Pushed in chunks, the decoder buffers the input it has not consumed
+    let mut decoder = StreamDecoder::with_executor(options);
+    let mut decoded = Vec::new();
+    for chunk in bytes.chunks(PROGRESS_CHUNK_SIZE) {
+        decoder.decode_with_progress(chunk, &mut decoded, &mut on_progress)?;
+    }
+    decoder.finish()?;
+    Ok(decoded)
+}
 
-        for handle in handles {
-            let result = handle.join().map_err(|_| Error::ParallelDecodingError)??;
-            decoded.extend(result);
+/// Decode `bytes`, borrowing the content from the input instead of copying it
+/// when it is stored in a single raw block (store mode, e.g. for an already
+/// compressed payload), see `raw_content`
+///
+/// # Example
+/// ```
+/// # use std::borrow::Cow;
+/// # use zstd_lib::decode_cow;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert!(matches!(decode_cow(&bytes).unwrap(), Cow::Borrowed(&[0xCA, 0xFE])));
+/// ```
+pub fn decode_cow(bytes: &[u8]) -> Result<Cow<'_, [u8]>> {
+    match raw_content(bytes)? {
+        Some(content) if content.len() <= 1 => {
+            Ok(Cow::Borrowed(content.first().copied().unwrap_or_default()))
         }
+        Some(content) => Ok(Cow::Owned(content.concat())),
+        None => decode_with_options(bytes, DecodeOptions::default()).map(Cow::Owned),
+    }
+}
+
+/// Decode `bytes` to one output per Zstandard frame instead of concatenating
+/// them, for files where each frame is a record. Skippable frames are left out.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::decode_frames;
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xBA, 0xBE,
+/// ];
+/// assert_eq!(decode_frames(&bytes).unwrap(), [[0xCA, 0xFE], [0xBA, 0xBE]]);
+/// ```
+pub fn decode_frames(bytes: &[u8]) -> Result<Vec<Vec<u8>>> {
+    decode_each_frame(bytes, &DecodeOptions::default())
+}
+
+/// Decode the Zstandard frames of `bytes`, in parallel as `options` allows
+fn decode_each_frame(bytes: &[u8], options: &DecodeOptions) -> Result<Vec<Vec<u8>>> {
+    check_magic(bytes)?;
+
+    // The frames of pzstd archives are parsed by the threads decoding them,
+    // unless their skippable frames are handled
+    if let Some(frames) = pzstd_frames(bytes).filter(|_| !options.has_skippable_handlers()) {
+        return threads::map(frames, options.thread_count(), |frame| {
+            Frame::parse_sized(&bytes[frame])?.decode(options)
+        })
+        .into_iter()
+        .collect();
+    }
+
+    // Skippable frames decode to nothing
+    let frames = parse_frames(bytes)?
+        .into_iter()
+        .filter(|frame| match frame {
+            Frame::ZstandardFrame(_) => true,
+            Frame::SkippableFrame(frame) => {
+                options.skippable_frame(frame);
+                false
+            }
+        })
+        .collect();
+
+    threads::map(frames, options.thread_count(), |frame| {
+        frame.decode(options)
+    })
+    .into_iter()
+    .collect()
+}
+
+/// Decode `bytes` to `writer` according to `options` and return the number of
+/// bytes written. Frames are decoded on the helper threads allowed by
+/// `DecodeOptions::threads`, and written in order as soon as the previous ones
+/// are: the frames decoded ahead
+/// of the writer are at most `DecodeOptions::max_in_flight` bytes, bounded by
+/// the sizes of their blocks.
+///
+/// On failure, the content of the frames before the failing one is written.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{decode_to, DecodeOptions};
+/// let bytes = [
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+///     0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xBA, 0xBE,
+/// ];
+/// let mut output = Vec::new();
+/// assert_eq!(decode_to(&bytes, &mut output, DecodeOptions::new())?, 4);
+/// assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBE]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn decode_to(bytes: &[u8], mut writer: impl Write, options: DecodeOptions) -> Result<u64> {
+    check_magic(bytes)?;
 
-        Ok(decoded)
+    // Parsed as they are decoded, so that the frames before a truncated or
+    // corrupted one are written
+    let frames = FrameIterator::new(bytes).filter_map(|frame| match frame {
+        Ok(Frame::ZstandardFrame(frame)) => Some(Ok(frame)),
+        Ok(Frame::SkippableFrame(frame)) => {
+            options.skippable_frame(&frame);
+            None
+        }
+        Err(err) => Some(Err(err)),
+    });
+    if let Some(hot_size) = options.spill_window {
+        return decode_to_spilled(frames, writer, &options, hot_size);
+    }
+    let mut write = |decoded: &[u8]| -> Result<u64> {
+        writer.write_all(decoded)?;
+        Ok(decoded.len() as u64)
+    };
+
+    #[cfg(feature = "threads")]
+    if options.thread_count() > 1 {
+        return decode_to_threads(frames, &options, write);
+    }
+    let mut written = 0;
+    for frame in frames {
+        written += write(&Frame::ZstandardFrame(frame?).decode(&options)?)?;
+    }
+    Ok(written)
+}
+
+/// `decode_to` through the window of each frame, in a temporary file when it
+/// does not fit in `hot_size`
+fn decode_to_spilled<'a>(
+    frames: impl Iterator<Item = Result<ZstandardFrame<'a>>>,
+    mut writer: impl Write,
+    options: &DecodeOptions,
+    hot_size: usize,
+) -> Result<u64> {
+    let mut written = 0;
+    for frame in frames {
+        let frame = frame?;
+        let window_size = frame.header().window_size();
+        let len = if window_size <= hot_size {
+            let sink = WindowWriter::new(&mut writer, window_size);
+            frame.decode_to_sink(sink, options)?.len()
+        } else {
+            let sink = SpillWindow::new(&mut writer, TempFile::new()?, window_size, hot_size);
+            frame.decode_to_sink(sink, options)?.len()
+        };
+        written += len as u64;
+    }
+    Ok(written)
+}
+
+/// `decode_to` on helper threads: frames are decoded ahead, as far as
+/// `max_in_flight` allows, and written in order
+#[cfg(feature = "threads")]
+fn decode_to_threads<'a>(
+    frames: impl Iterator<Item = Result<ZstandardFrame<'a>>>,
+    options: &DecodeOptions,
+    mut write: impl FnMut(&[u8]) -> Result<u64>,
+) -> Result<u64> {
+    let threads = options.thread_count();
+    let mut frames = frames.peekable();
+    let mut written = 0;
+    let max_in_flight = options.max_in_flight.unwrap_or(DEFAULT_MAX_IN_FLIGHT);
+    std::thread::scope(|s| {
+        // Frames being decoded on helpers, oldest first, with their maximum
+        // content size
+        let mut in_flight = std::collections::VecDeque::new();
+        let mut in_flight_size = 0_usize;
+        let result = loop {
+            // Start frames while they fit, at least one, up to a parsing error
+            while let Some(Ok(frame)) = frames.peek() {
+                let size = frame.max_content_size();
+                let fits = in_flight_size.saturating_add(size) <= max_in_flight;
+                if !(fits || in_flight.is_empty()) {
+                    break;
+                }
+                let Some(helper) = Helper::reserve(threads) else {
+                    break;
+                };
+                let Some(Ok(frame)) = frames.next() else {
+                    unreachable!()
+                };
+                let frame = Frame::ZstandardFrame(frame);
+                let handle = s.spawn(move || {
+                    let _helper = helper;
+                    frame.decode(options)
+                });
+                in_flight.push_back((handle, size));
+                in_flight_size = in_flight_size.saturating_add(size);
+            }
+
+            let decoded = match in_flight.pop_front() {
+                Some((handle, size)) => {
+                    in_flight_size -= size;
+                    handle.join().map_err(panic_error).and_then(|result| result)
+                }
+                // No helper left, or the frames before a parsing error are
+                // written: decode the next frame on the calling thread
+                None => match frames.next() {
+                    Some(frame) => {
+                        frame.and_then(|frame| Frame::ZstandardFrame(frame).decode(options))
+                    }
+                    None => break Ok(written),
+                },
+            };
+            match decoded.and_then(|decoded| write(&decoded)) {
+                Ok(len) => written += len,
+                Err(err) => break Err(err),
+            }
+        };
+
+        // After a failure, wait for the frames decoded ahead, panics included
+        for (handle, _) in in_flight {
+            let _ = handle.join();
+        }
+        result
     })
 }
+
+/// Decode the file at `path`, see `decode_file_with_options`
+#[cfg(feature = "memmap")]
+pub fn decode_file(path: impl AsRef<std::path::Path>) -> Result<Vec<u8>> {
+    decode_file_with_options(path, DecodeOptions::default())
+}
+
+/// Decode the file at `path` according to `options`. The file is memory-mapped
+/// instead of read: the OS only pages in the parts of it that are touched.
+///
+/// The file must not be modified while it is decoded.
+#[cfg(feature = "memmap")]
+pub fn decode_file_with_options(
+    path: impl AsRef<std::path::Path>,
+    options: DecodeOptions,
+) -> Result<Vec<u8>> {
+    let file = std::fs::File::open(path)?;
+    // SAFETY: the file is only read, the caller guarantees it is not modified meanwhile
+    let bytes = unsafe { memmap2::Mmap::map(&file)? };
+    decode_with_options(&bytes, options)
+}
+
+/// Decode each file of `paths` according to `options`, and return the results
+/// in the same order. The files are decoded in parallel on the threads allowed
+/// by `DecodeOptions::threads`, so that at most that many files and outputs
+/// are being processed at once.
+///
+/// # Example
+/// ```no_run
+/// # use zstd_lib::{decode_files, DecodeOptions};
+/// let paths = ["a.zst", "b.zst"].map(std::path::PathBuf::from);
+/// for (path, result) in decode_files(paths, DecodeOptions::new()) {
+///     match result {
+///         Ok(decoded) => println!("{}: {} bytes", path.display(), decoded.len()),
+///         Err(err) => eprintln!("{}: {err}", path.display()),
+///     }
+/// }
+/// ```
+pub fn decode_files<I: IntoIterator<Item = PathBuf>>(
+    paths: I,
+    options: DecodeOptions,
+) -> Vec<(PathBuf, Result<Vec<u8>>)> {
+    let paths: Vec<PathBuf> = paths.into_iter().collect();
+    let results = threads::map(paths.iter().collect(), options.thread_count(), |path| {
+        decode_with_options(&std::fs::read(path)?, options.clone())
+    });
+    paths.into_iter().zip(results).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::thread;
+    use zstd_core::FrameError;
+
+    fn mobydick() -> Vec<u8> {
+        include_bytes!("../../tests/txt/mobydick.zst").to_vec()
+    }
+
+    #[test]
+    fn test_panic_error() {
+        let payload = thread::spawn(|| panic!("static message"))
+            .join()
+            .unwrap_err();
+        assert!(matches!(
+            panic_error(payload),
+            Error::ParallelDecodingError(message) if message == "static message"
+        ));
+
+        let index = 42;
+        let payload = thread::spawn(move || panic!("formatted message {index}"))
+            .join()
+            .unwrap_err();
+        assert!(matches!(
+            panic_error(payload),
+            Error::ParallelDecodingError(message) if message == "formatted message 42"
+        ));
+
+        let payload = thread::spawn(|| std::panic::panic_any(42))
+            .join()
+            .unwrap_err();
+        assert!(matches!(
+            panic_error(payload),
+            Error::ParallelDecodingError(message) if message == "unknown panic payload"
+        ));
+    }
+
+    #[test]
+    fn test_decode_cow() {
+        let decoded = decode_cow(&stream::tests::FRAMES).unwrap();
+        assert!(matches!(decoded, Cow::Owned(_)));
+        assert_eq!(*decoded, [0xCA, 0xFE, 0xBA, 0xBA]);
+
+        let raw = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, // magic, header
+            0x10, 0x0, 0x0, 0xCA, 0xFE, // raw block, not last, len 2
+            0x09, 0x0, 0x0, 0xBA, // raw block, last, len 1
+        ];
+        let decoded = decode_cow(&raw).unwrap();
+        assert!(matches!(decoded, Cow::Owned(_)));
+        assert_eq!(*decoded, [0xCA, 0xFE, 0xBA]);
+
+        assert!(matches!(decode_cow(&[]).unwrap(), Cow::Borrowed(&[])));
+    }
+
+    #[test]
+    fn test_window_log_max() {
+        let bytes = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x88, // magic, header: window 128MB
+            0x01, 0x0, 0x0, // raw block, last, len 0
+        ];
+        assert!(matches!(
+            decode_with_options(&bytes, DecodeOptions::new()),
+            Err(Error::Decoder(DecoderError::Context(
+                ContextError::WindowSizeError {
+                    window_size: 134_217_728,
+                    max: DEFAULT_MAX_WINDOW_SIZE
+                }
+            )))
+        ));
+        let err = decode_with_options(&bytes, DecodeOptions::new()).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::LimitExceeded);
+        assert_eq!(err.window_size(), Some(134_217_728));
+        let options = DecodeOptions::new().window_log_max(27);
+        assert!(decode_with_options(&bytes, options.clone())
+            .unwrap()
+            .is_empty());
+        assert!(decode_with_options(&bytes, options.window_log_max(26)).is_err());
+    }
+
+    #[test]
+    fn test_decode_frames() {
+        let expected = [vec![0xCA, 0xFE, 0xBA, 0xBA], vec![]];
+        assert_eq!(decode_frames(&stream::tests::FRAMES).unwrap(), expected);
+
+        let options = DecodeOptions::new().single_threaded(true);
+        let frames = decode_each_frame(&stream::tests::FRAMES, &options).unwrap();
+        assert_eq!(frames, expected);
+    }
+
+    #[test]
+    fn test_decode_pzstd() {
+        // Each Zstandard frame after a skippable frame holding its size
+        let frames = [&stream::tests::FRAMES[10..29], &stream::tests::FRAMES[29..]];
+        let mut bytes = Vec::new();
+        for frame in frames {
+            bytes.extend_from_slice(&[0x50, 0x2A, 0x4D, 0x18, 0x4, 0x0, 0x0, 0x0]);
+            bytes.extend_from_slice(&(frame.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(frame);
+        }
+        assert!(pzstd_frames(&bytes).is_some());
+        let frames = decode_each_frame(&bytes, &DecodeOptions::new().threads(2)).unwrap();
+        assert_eq!(frames, [vec![0xCA, 0xFE, 0xBA, 0xBA], vec![]]);
+
+        // A frame shorter than announced
+        bytes[8] += 1;
+        bytes.insert(31, 0);
+        assert!(pzstd_frames(&bytes).is_some());
+        assert!(matches!(
+            decode_each_frame(&bytes, &DecodeOptions::new()),
+            Err(Error::Frame(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_info() {
+        // Only parsed, nothing is decoded nor printed
+        let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+        assert!(decode(&bytes, true).unwrap().is_empty());
+        assert!(decode(&bytes[..10], true).is_err());
+    }
+
+    #[test]
+    fn test_decode_with_callback() {
+        let bytes = mobydick();
+        let (mut decoded, mut blocks) = (Vec::new(), 0);
+        decode_with_callback(&bytes, DecodeOptions::new(), |block| {
+            decoded.extend_from_slice(block);
+            blocks += 1;
+        })
+        .unwrap();
+        assert_eq!(decoded, decode(&bytes, false).unwrap());
+        assert!(blocks > 1);
+    }
+
+    #[test]
+    fn test_budget() {
+        let bytes = mobydick();
+        let expected = decode(&bytes, false).unwrap();
+        let is_exceeded = |result: Result<Vec<u8>>| {
+            matches!(
+                result,
+                Err(Error::Decoder(DecoderError::Context(
+                    ContextError::BudgetExceeded { budget: 1000 }
+                )))
+            )
+        };
+        for options in [DecodeOptions::new(), DecodeOptions::new().threads(1)] {
+            assert!(is_exceeded(decode_with_options(
+                &bytes,
+                options.clone().budget(1000)
+            )));
+            assert_eq!(
+                decode_with_options(&bytes, options.budget(u64::MAX)).unwrap(),
+                expected
+            );
+        }
+
+        let mut decoder = StreamDecoder::with_executor(DecodeOptions::new().budget(1000));
+        let mut output = Vec::new();
+        assert!(is_exceeded(
+            decoder.decode(&bytes, &mut output).map(|()| output)
+        ));
+    }
+
+    #[test]
+    fn test_decode_with_progress() {
+        let bytes = mobydick();
+        let mut progress = Vec::new();
+        let decoded =
+            decode_with_progress(&bytes, DecodeOptions::new(), |p| progress.push(p)).unwrap();
+        assert_eq!(decoded, decode(&bytes, false).unwrap());
+        assert!(progress.len() > 1);
+        assert!(progress.windows(2).all(|p| p[0].input < p[1].input));
+        let last = progress.last().unwrap();
+        assert_eq!((last.input, last.output), (bytes.len(), decoded.len()));
+    }
+
+    #[test]
+    fn test_allocation_hook() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let bytes = mobydick();
+        let expected = decode(&bytes, false).unwrap();
+        let largest = Arc::new(AtomicUsize::new(0));
+        let observed = Arc::clone(&largest);
+        let options = DecodeOptions::new().allocation_hook(move |buffer, size| {
+            if buffer == BufferKind::Content {
+                observed.fetch_max(size, Ordering::Relaxed);
+            }
+            true
+        });
+        assert_eq!(decode_with_options(&bytes, options).unwrap(), expected);
+        assert!(largest.load(Ordering::Relaxed) >= expected.len());
+
+        let refuse_large = |_, size| size < 100_000;
+        for options in [DecodeOptions::new(), DecodeOptions::new().threads(1)] {
+            let result = decode_with_options(&bytes, options.allocation_hook(refuse_large));
+            let error = result.unwrap_err();
+            assert_eq!(error.kind(), ErrorKind::LimitExceeded);
+            assert!(matches!(
+                error,
+                Error::Decoder(DecoderError::Context(ContextError::AllocationFailed {
+                    buffer: BufferKind::Content,
+                    ..
+                }))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_skippable_handler() {
+        use std::{io::Read, sync::Mutex};
+
+        let frame = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+        let index = write_frame_index(&build_frame_index(&frame).unwrap()).unwrap();
+        let other = [0x50, 0x2A, 0x4D, 0x18, 0x1, 0x0, 0x0, 0x0, 0x42];
+        let bytes = [&other[..], &frame, &index, &other].concat();
+
+        let payloads = Arc::new(Mutex::new(Vec::new()));
+        let recorded = Arc::clone(&payloads);
+        let record = move |payload: &[u8]| recorded.lock().unwrap().push(payload.to_vec());
+        let options = DecodeOptions::new().skippable_handler(INDEX_MAGIC_NIBBLE, record);
+        for options in [
+            options.clone(),
+            options.clone().threads(1),
+            options.threads(2),
+        ] {
+            let decoded = decode_with_options(&bytes, options.clone()).unwrap();
+            assert_eq!(decoded, [0xCA, 0xFE]);
+            let mut output = Vec::new();
+            decode_to(&bytes, &mut output, options.clone()).unwrap();
+            assert_eq!(output, [0xCA, 0xFE]);
+
+            // The streaming decoders, with payloads split across inputs
+            let mut decoder = StreamDecoder::with_executor(options.clone());
+            let mut output = Vec::new();
+            for chunk in bytes.chunks(5) {
+                decoder
+                    .decode_with_progress(chunk, &mut output, |_| {})
+                    .unwrap();
+            }
+            decoder.finish().unwrap();
+            assert_eq!(output, [0xCA, 0xFE]);
+            assert_eq!(
+                decode_segments(bytes.chunks(5), options.clone()).unwrap(),
+                [0xCA, 0xFE]
+            );
+            let mut output = Vec::new();
+            Decoder::with_options(&bytes[..], options.clone())
+                .read_to_end(&mut output)
+                .unwrap();
+            assert_eq!(output, [0xCA, 0xFE]);
+            #[cfg(feature = "tokio")]
+            {
+                use tokio::io::AsyncReadExt;
+                let runtime = tokio::runtime::Builder::new_current_thread()
+                    .build()
+                    .unwrap();
+                let mut output = Vec::new();
+                let mut decoder = AsyncDecoder::with_options(&bytes[..], options);
+                runtime.block_on(decoder.read_to_end(&mut output)).unwrap();
+                assert_eq!(output, [0xCA, 0xFE]);
+            }
+        }
+        let calls = if cfg!(feature = "tokio") { 6 } else { 5 };
+        let payloads = payloads.lock().unwrap();
+        assert_eq!(payloads.len(), 3 * calls);
+        assert!(payloads.iter().all(|payload| payload[..] == index[8..]));
+    }
+
+    #[test]
+    fn test_max_memory() {
+        let bytes = mobydick();
+        let expected = decode(&bytes, false).unwrap();
+        let is_exceeded = |result: Result<Vec<u8>>| {
+            matches!(
+                result,
+                Err(Error::Decoder(DecoderError::Context(
+                    ContextError::MemoryLimitExceeded { limit: 100_000, .. }
+                )))
+            )
+        };
+        for options in [DecodeOptions::new(), DecodeOptions::new().threads(1)] {
+            assert!(is_exceeded(decode_with_options(
+                &bytes,
+                options.clone().max_memory(100_000)
+            )));
+            let options = options.max_memory(2 * expected.len());
+            assert_eq!(decode_with_options(&bytes, options).unwrap(), expected);
+        }
+
+        // Streaming holds up to twice the window, the whole content here
+        let options = DecodeOptions::new().max_memory(3 * expected.len());
+        let mut decoder = StreamDecoder::with_executor(options);
+        let mut output = Vec::new();
+        decoder
+            .decode(&bytes[..bytes.len() / 2], &mut output)
+            .unwrap();
+        assert!(decoder.memory_usage() >= output.len());
+        decoder
+            .decode(&bytes[bytes.len() / 2..], &mut output)
+            .unwrap();
+        decoder.finish().unwrap();
+        assert_eq!(output, expected);
+    }
+
+    #[test]
+    fn test_threads() {
+        // One thread: both sides of a join run on the caller
+        let caller = thread::current().id();
+        let options = DecodeOptions::new().threads(1);
+        let (a, b) = options
+            .join(|| thread::current().id(), || thread::current().id())
+            .unwrap();
+        assert_eq!((a, b), (caller, caller));
+
+        let bytes = mobydick();
+        let expected = decode_with_options(&bytes, DecodeOptions::new()).unwrap();
+        for threads in [1, 2, 3] {
+            let options = DecodeOptions::new().threads(threads);
+            assert_eq!(decode_with_options(&bytes, options).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn test_decode_regions() {
+        // Frames announcing their content size, in their own region of the
+        // output, or not
+        let sized = |size| {
+            [
+                0x28, 0xB5, 0x2F, 0xFD, 0x20, size, 0x11, 0x0, 0x0, 0xCA, 0xFE,
+            ]
+        };
+        let unknown = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xBA, 0xBE];
+        let options = DecodeOptions::new().threads(2);
+        for (bytes, expected) in [
+            ([sized(2), sized(2)].concat(), [0xCA, 0xFE, 0xCA, 0xFE]),
+            ([&sized(2)[..], &unknown].concat(), [0xCA, 0xFE, 0xBA, 0xBE]),
+        ] {
+            assert_eq!(
+                decode_with_options(&bytes, options.clone()).unwrap(),
+                expected
+            );
+        }
+
+        // Content sizes announced wrongly, beyond the region or short of it
+        let longer = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x20, 0x3, // magic, header: SSF, FCS 3
+            0x10, 0x0, 0x0, 0xCA, 0xFE, // raw block, len 2
+            0x11, 0x0, 0x0, 0xBA, 0xBE, // raw block, last, len 2
+        ];
+        for (frame, decoded) in [(&longer[..], 4), (&sized(3), 2)] {
+            let bytes = [&sized(2)[..], frame].concat();
+            assert!(matches!(
+                decode_with_options(&bytes, options.clone()),
+                Err(Error::Frame(FrameError::ContentSizeMismatch { announced: 3, decoded: d }))
+                    if d == decoded
+            ));
+        }
+    }
+
+    #[test]
+    fn test_decode_to() {
+        let single_threaded = DecodeOptions::new().single_threaded(true);
+        for options in [DecodeOptions::new(), single_threaded.clone()] {
+            let mut output = Vec::new();
+            assert_eq!(
+                decode_to(&stream::tests::FRAMES, &mut output, options).unwrap(),
+                4
+            );
+            assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBA]);
+        }
+
+        let bytes = mobydick().repeat(3);
+        let expected = include_bytes!("../../tests/txt/mobydick.txt").repeat(3);
+        // Frames larger than the bound are decoded one at a time, as are the
+        // windows spilled to a file or not
+        for options in [
+            DecodeOptions::new(),
+            DecodeOptions::new().max_in_flight(1),
+            DecodeOptions::new().spill_window(0),
+            DecodeOptions::new().spill_window(usize::MAX),
+        ] {
+            let mut output = Vec::new();
+            decode_to(&bytes, &mut output, options).unwrap();
+            assert!(output == expected);
+        }
+
+        // The frames before the failing one are written
+        let frame = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+        let treeless = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, // magic, header
+            0x2D, 0x0, 0x0, // compressed block, last, len 5
+            0x83, 0x40, 0x0, 0x80, // treeless literals without Huffman table
+            0x0,  // no sequences
+        ];
+        let bytes = [&frame[..], &treeless, &frame].concat();
+        let truncated = [&frame[..], &frame[..8]].concat();
+        for options in [
+            DecodeOptions::new().threads(3),
+            single_threaded.clone(),
+            single_threaded.spill_window(0),
+        ] {
+            for bytes in [&bytes, &truncated] {
+                let mut output = Vec::new();
+                assert!(decode_to(bytes, &mut output, options.clone()).is_err());
+                assert_eq!(output, [0xCA, 0xFE]);
+            }
+        }
+
+        let mut full = [0_u8; 3];
+        assert!(matches!(
+            decode_to(
+                &stream::tests::FRAMES,
+                full.as_mut_slice(),
+                DecodeOptions::new()
+            ),
+            Err(Error::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_decode_files() {
+        let tests = PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/../tests"));
+        let paths = [
+            tests.join("txt/mobydick.zst"),
+            tests.join("txt/missing.zst"),
+            tests.join("txt/mobydick.txt"),
+            tests.join("golden/block-128k.zst"),
+        ];
+
+        for options in [
+            DecodeOptions::new(),
+            DecodeOptions::new().single_threaded(true),
+        ] {
+            let results = decode_files(paths.clone(), options);
+            let order: Vec<_> = results.iter().map(|(path, _)| path.clone()).collect();
+            assert_eq!(order, paths);
+
+            let [(_, moby), (_, missing), (_, text), (_, block)] = &results[..] else {
+                panic!("{} results", results.len());
+            };
+            let expected = std::fs::read(tests.join("txt/mobydick.txt")).unwrap();
+            assert_eq!(moby.as_ref().unwrap(), &expected);
+            assert!(matches!(missing, Err(Error::Io(_))));
+            assert!(matches!(text, Err(Error::NotZstandardData)));
+            let expected = std::fs::read(tests.join("golden/block-128k.bin")).unwrap();
+            assert_eq!(block.as_ref().unwrap(), &expected);
+        }
+
+        assert!(decode_files(Vec::new(), DecodeOptions::new()).is_empty());
+    }
+
+    #[cfg(feature = "memmap")]
+    #[test]
+    fn test_decode_file() {
+        let tests = concat!(env!("CARGO_MANIFEST_DIR"), "/../tests/txt");
+        let decoded = decode_file(format!("{tests}/mobydick.zst")).unwrap();
+        assert_eq!(
+            decoded,
+            std::fs::read(format!("{tests}/mobydick.txt")).unwrap()
+        );
+
+        let empty = std::env::temp_dir().join("zstd_lib_test_decode_file_empty.zst");
+        std::fs::write(&empty, []).unwrap();
+        assert_eq!(decode_file(&empty).unwrap(), Vec::<u8>::new());
+        std::fs::remove_file(empty).unwrap();
+
+        assert!(matches!(
+            decode_file(format!("{tests}/missing.zst")),
+            Err(Error::Io(_))
+        ));
+    }
+}
@@ -0,0 +1,314 @@
+use std::{
+    cmp, fs,
+    fs::{File, OpenOptions},
+    io::{self, Read, Seek, SeekFrom, Write},
+    path::PathBuf,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+use zstd_core::{format::BLOCK_SIZE_MAX, DecoderError, OutputSink, RingBuffer};
+
+/// File of the temporary directory, removed once dropped: the default storage
+/// of `SpillWindow`
+#[derive(Debug)]
+pub struct TempFile {
+    file: File,
+    path: PathBuf,
+}
+
+impl TempFile {
+    pub fn new() -> io::Result<Self> {
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let dir = std::env::temp_dir();
+        loop {
+            let name = format!(
+                "rzstd-window-{}-{}",
+                std::process::id(),
+                COUNTER.fetch_add(1, Ordering::Relaxed)
+            );
+            let path = dir.join(name);
+            let file = OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create_new(true)
+                .open(&path);
+            match file {
+                Ok(file) => return Ok(Self { file, path }),
+                Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+                Err(err) => return Err(err),
+            }
+        }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
+
+impl Read for TempFile {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.file.read(buf)
+    }
+}
+
+impl Write for TempFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.file.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+impl Seek for TempFile {
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        self.file.seek(pos)
+    }
+}
+
+/// Sink writing the content to `W` as it is produced, like `WindowWriter`, but
+/// keeping the window in `F`, a temporary file by default, instead of memory:
+/// only its last `hot_size` bytes are, the matches reaching further back are
+/// read from `F`. Decodes frames whose window does not fit in memory, e.g.
+/// written with `zstd --long=30`, at the cost of I/O.
+///
+/// `F` can be any storage, e.g. a `Cursor` over a memory-mapped file. It holds
+/// up to `window_size` bytes, written circularly.
+///
+/// # Example
+/// ```
+/// # use std::io::Cursor;
+/// # use zstd_lib::{DecodeOptions, Frame, SpillWindow};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let Frame::ZstandardFrame(frame) = Frame::parse_sized(&bytes)? else {
+///     unreachable!()
+/// };
+/// let storage = Cursor::new(Vec::new());
+/// let sink = SpillWindow::new(Vec::new(), storage, frame.header().window_size(), 0);
+/// let sink = frame.decode_to_sink(sink, &DecodeOptions::new())?;
+/// assert_eq!(sink.into_inner(), [0xCA, 0xFE]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub struct SpillWindow<W, F = TempFile> {
+    writer: W,
+    storage: F,
+    window_size: usize,
+    /// Last bytes written, holding the content not written to `storage` yet
+    hot: RingBuffer,
+    /// Content before this offset is in `storage`
+    flushed: usize,
+    /// Bytes of a match read from `storage`
+    scratch: Vec<u8>,
+}
+
+impl<W: Write, F: Read + Write + Seek> SpillWindow<W, F> {
+    /// Sink for frames of windows up to `window_size`, keeping `hot_size`
+    /// bytes in memory: at least the size of a block, 128 KB, and at most the
+    /// window
+    #[must_use]
+    pub fn new(writer: W, storage: F, window_size: usize, hot_size: usize) -> Self {
+        let window_size = cmp::max(window_size, 1);
+        let hot_size = hot_size.clamp(cmp::min(BLOCK_SIZE_MAX, window_size), window_size);
+        Self {
+            writer,
+            storage,
+            window_size,
+            hot: RingBuffer::new(hot_size),
+            flushed: 0,
+            scratch: Vec::new(),
+        }
+    }
+
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn hot_size(&self) -> usize {
+        self.hot.max_history()
+    }
+
+    /// Write the content held by the hot tail only to `storage` when writing
+    /// `len` more bytes would overwrite some of it
+    fn make_room(&mut self, len: usize) -> io::Result<()> {
+        let end = self.hot.len();
+        if (end + len).saturating_sub(self.hot_size()) <= self.flushed {
+            return Ok(());
+        }
+        let (first, second) = self.hot.tail(end - self.flushed);
+        let mut position = self.flushed;
+        for part in [first, second] {
+            write_circular(&mut self.storage, self.window_size, position, part)?;
+            position += part.len();
+        }
+        self.flushed = end;
+        Ok(())
+    }
+
+    /// Write out the last `len` bytes of the hot tail
+    fn write_tail(&mut self, len: usize) -> io::Result<()> {
+        let (first, second) = self.hot.tail(len);
+        self.writer.write_all(first)?;
+        self.writer.write_all(second)
+    }
+
+    /// The `len` bytes from `offset` bytes back, which reach past the hot
+    /// tail, into `scratch`
+    fn read_back(&mut self, offset: usize, len: usize) -> io::Result<()> {
+        let end = self.hot.len();
+        let start = end - offset;
+        let hot_start = end - self.hot_size();
+        let stored = cmp::min(len, hot_start - start);
+
+        self.scratch.resize(len, 0);
+        read_circular(
+            &mut self.storage,
+            self.window_size,
+            start,
+            &mut self.scratch[..stored],
+        )?;
+        // The rest is at the start of the hot tail
+        let (first, second) = self.hot.tail(self.hot_size());
+        let hot = first.iter().chain(second).take(len - stored);
+        for (byte, &hot) in self.scratch[stored..].iter_mut().zip(hot) {
+            *byte = hot;
+        }
+        Ok(())
+    }
+}
+
+/// Write `bytes` at `position` of a storage of `size` bytes, wrapping around
+fn write_circular(
+    storage: &mut (impl Write + Seek),
+    size: usize,
+    mut position: usize,
+    mut bytes: &[u8],
+) -> io::Result<()> {
+    while !bytes.is_empty() {
+        let start = position % size;
+        let len = cmp::min(bytes.len(), size - start);
+        storage.seek(SeekFrom::Start(start as u64))?;
+        storage.write_all(&bytes[..len])?;
+        bytes = &bytes[len..];
+        position += len;
+    }
+    Ok(())
+}
+
+/// Fill `buf` from `position` of a storage of `size` bytes, wrapping around
+fn read_circular(
+    storage: &mut (impl Read + Seek),
+    size: usize,
+    mut position: usize,
+    mut buf: &mut [u8],
+) -> io::Result<()> {
+    while !buf.is_empty() {
+        let start = position % size;
+        let len = cmp::min(buf.len(), size - start);
+        storage.seek(SeekFrom::Start(start as u64))?;
+        storage.read_exact(&mut buf[..len])?;
+        buf = &mut buf[len..];
+        position += len;
+    }
+    Ok(())
+}
+
+impl<W: Write, F: Read + Write + Seek> OutputSink for SpillWindow<W, F> {
+    fn len(&self) -> usize {
+        self.hot.len()
+    }
+
+    fn clear(&mut self) {
+        self.hot.clear();
+        self.flushed = 0;
+    }
+
+    fn memory_usage(&self) -> usize {
+        self.hot.memory_usage() + self.scratch.capacity()
+    }
+
+    fn max_history(&self) -> usize {
+        self.window_size
+    }
+
+    fn write(&mut self, bytes: &[u8]) -> Result<(), DecoderError> {
+        for part in bytes.chunks(self.hot_size()) {
+            self.make_room(part.len())?;
+            self.hot.write(part)?;
+            self.writer.write_all(part)?;
+        }
+        Ok(())
+    }
+
+    fn fill(&mut self, byte: u8, len: usize) -> Result<(), DecoderError> {
+        let mut remaining = len;
+        while remaining > 0 {
+            let part = cmp::min(remaining, self.hot_size());
+            self.make_room(part)?;
+            self.hot.fill(byte, part)?;
+            self.write_tail(part)?;
+            remaining -= part;
+        }
+        Ok(())
+    }
+
+    fn copy_match(&mut self, offset: usize, length: usize) -> Result<(), DecoderError> {
+        let mut remaining = length;
+        while remaining > 0 {
+            let part = cmp::min(remaining, self.hot_size());
+            if offset <= self.hot_size() {
+                self.make_room(part)?;
+                self.hot.copy_match(offset, part)?;
+                self.write_tail(part)?;
+            } else {
+                // The part is at most the hot tail, shorter than the offset:
+                // it does not overlap what it copies
+                self.read_back(offset, part)?;
+                let scratch = std::mem::take(&mut self.scratch);
+                let written = self.write(&scratch);
+                self.scratch = scratch;
+                written?;
+            }
+            remaining -= part;
+        }
+        Ok(())
+    }
+
+    fn tail(&self, len: usize) -> (&[u8], &[u8]) {
+        self.hot.tail(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    #[test]
+    fn test_spill_window() {
+        // Matches reaching past the hot tail of 128 KB, and a window of 1 MB
+        // wrapping around the storage
+        let mut expected = Vec::new();
+        let mut sink = SpillWindow::new(Vec::new(), Cursor::new(Vec::new()), 1 << 20, 0);
+        for (i, part) in (0..400_000_u32).map(u32::to_le_bytes).enumerate() {
+            expected.extend_from_slice(&part);
+            sink.write(&part).unwrap();
+            if i % 100_000 == 99_999 && i > 100_000 {
+                for (offset, length) in [(700_000, 50_000), (1000, 5000), (200_000, 500_000)] {
+                    let start = expected.len() - offset;
+                    for index in start..start + length {
+                        expected.push(expected[index]);
+                    }
+                    sink.copy_match(offset, length).unwrap();
+                }
+            }
+        }
+        sink.fill(7, 300_000).unwrap();
+        expected.resize(expected.len() + 300_000, 7);
+        assert_eq!(sink.len(), expected.len());
+        assert_eq!(sink.tail(4), (&[7; 4][..], &[][..]));
+        assert!(sink.memory_usage() < 1 << 20);
+        assert_eq!(sink.into_inner(), expected);
+    }
+}
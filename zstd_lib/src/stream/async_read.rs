@@ -0,0 +1,114 @@
+use super::{invalid_data, OutputBuffer, StreamDecoder, CHUNK_SIZE};
+use crate::DecodeOptions;
+use std::{
+    io,
+    pin::Pin,
+    task::{ready, Context, Poll},
+};
+use tokio::io::{AsyncRead, ReadBuf};
+
+/// Asynchronous streaming decoder reading compressed data from `R`. Frames are
+/// decoded as their blocks arrive, on the task polling the decoder.
+///
+/// # Example
+/// ```
+/// # use tokio::io::AsyncReadExt;
+/// # use zstd_lib::AsyncDecoder;
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut decoded = Vec::new();
+/// AsyncDecoder::new(bytes.as_slice()).read_to_end(&mut decoded).await?;
+/// assert_eq!(decoded, [0xCA, 0xFE]);
+/// # Ok::<(), std::io::Error>(())
+/// # }).unwrap();
+/// ```
+pub struct AsyncDecoder<R> {
+    reader: R,
+    decoder: StreamDecoder,
+    output: OutputBuffer,
+    chunk: Box<[u8]>,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncDecoder<R> {
+    pub fn new(reader: R) -> Self {
+        // Blocks are small enough to be decoded on the polling task
+        Self::with_options(reader, DecodeOptions::new().single_threaded(true))
+    }
+
+    pub fn with_options(reader: R, options: DecodeOptions) -> Self {
+        Self {
+            reader,
+            decoder: StreamDecoder::with_executor(options),
+            output: OutputBuffer::default(),
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            eof: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for AsyncDecoder<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+
+        while this.output.is_empty() && !this.eof {
+            let mut chunk = ReadBuf::new(&mut this.chunk);
+            ready!(Pin::new(&mut this.reader).poll_read(cx, &mut chunk))?;
+
+            let output = this.output.reset();
+            if chunk.filled().is_empty() {
+                this.eof = true;
+                this.decoder.finish().map_err(invalid_data)?;
+            } else {
+                this.decoder
+                    .decode(chunk.filled(), output)
+                    .map_err(invalid_data)?;
+            }
+        }
+
+        let len = this.output.read(buf.initialize_unfilled());
+        buf.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::FRAMES, *};
+    use tokio::io::AsyncReadExt;
+
+    #[tokio::test]
+    async fn test_async_decoder() {
+        // Deliver the input a few bytes at a time
+        let (mut writer, reader) = tokio::io::duplex(3);
+        let writer =
+            tokio::spawn(
+                async move { tokio::io::AsyncWriteExt::write_all(&mut writer, &FRAMES).await },
+            );
+
+        let mut decoded = Vec::new();
+        AsyncDecoder::new(reader)
+            .read_to_end(&mut decoded)
+            .await
+            .unwrap();
+        writer.await.unwrap().unwrap();
+        assert_eq!(decoded, [0xCA, 0xFE, 0xBA, 0xBA]);
+    }
+
+    #[tokio::test]
+    async fn test_async_decoder_truncated() {
+        let err = AsyncDecoder::new(&FRAMES[..30])
+            .read_to_end(&mut Vec::new())
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+}
@@ -0,0 +1,120 @@
+use super::invalid_data;
+use crate::{decode_with_options, parsing::ParsingError, DecodeOptions, Error, Frame};
+use bytes::{Buf, BytesMut};
+use std::io;
+
+/// `tokio_util` codec for protocols sending one Zstandard frame per message:
+/// every complete frame received is decoded into a message. Skippable frames
+/// are consumed without producing any message.
+///
+/// # Example
+/// ```
+/// # use bytes::BytesMut;
+/// # use tokio_util::codec::Decoder;
+/// # use zstd_lib::FrameCodec;
+/// let mut codec = FrameCodec::new();
+/// let mut input = BytesMut::from(&[0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0][..]);
+/// assert_eq!(codec.decode(&mut input)?, None);
+/// input.extend_from_slice(&[0x0, 0xCA, 0xFE]);
+/// assert_eq!(codec.decode(&mut input)?, Some(vec![0xCA, 0xFE]));
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct FrameCodec {
+    options: DecodeOptions,
+}
+
+impl FrameCodec {
+    #[must_use]
+    pub fn new() -> Self {
+        // A frame is decoded on the polling task
+        Self::with_options(DecodeOptions::new().single_threaded(true))
+    }
+
+    #[must_use]
+    pub fn with_options(options: DecodeOptions) -> Self {
+        Self { options }
+    }
+}
+
+impl tokio_util::codec::Decoder for FrameCodec {
+    type Item = Vec<u8>;
+    type Error = io::Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        loop {
+            if src.is_empty() {
+                return Ok(None);
+            }
+
+            let size = match Frame::compressed_size(src) {
+                Ok(size) => size,
+                Err(Error::Parsing(ParsingError::NotEnoughBytes { .. })) => return Ok(None),
+                Err(err) => return Err(invalid_data(err)),
+            };
+
+            let frame = src.split_to(size);
+            if crate::sniff(&frame) == Some(crate::FrameKind::Skippable) {
+                continue;
+            }
+
+            return decode_with_options(&frame, self.options.clone())
+                .map(Some)
+                .map_err(invalid_data);
+        }
+    }
+
+    fn decode_eof(&mut self, src: &mut BytesMut) -> io::Result<Option<Vec<u8>>> {
+        match self.decode(src)? {
+            Some(frame) => Ok(Some(frame)),
+            None if src.is_empty() => Ok(None),
+            None => {
+                let err = Frame::compressed_size(src).expect_err("incomplete frame");
+                src.advance(src.len());
+                Err(invalid_data(err))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::FRAMES, *};
+    use tokio_util::codec::Decoder;
+
+    #[test]
+    fn test_frame_codec() {
+        let mut codec = FrameCodec::new();
+        let mut src = BytesMut::new();
+        let mut messages = Vec::new();
+
+        for byte in FRAMES {
+            src.extend_from_slice(&[byte]);
+            if let Some(message) = codec.decode(&mut src).unwrap() {
+                messages.push(message);
+            }
+        }
+
+        assert_eq!(messages, [vec![0xCA, 0xFE, 0xBA, 0xBA], vec![]]);
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn test_frame_codec_eof() {
+        let mut codec = FrameCodec::new();
+        let mut src = BytesMut::from(&FRAMES[..30]);
+        assert_eq!(
+            codec.decode_eof(&mut src).unwrap(),
+            Some(vec![0xCA, 0xFE, 0xBA, 0xBA])
+        );
+        let err = codec.decode_eof(&mut src).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_frame_codec_corrupted() {
+        let mut codec = FrameCodec::new();
+        let mut src = BytesMut::from(&b"not zstd"[..]);
+        assert!(codec.decode(&mut src).is_err());
+    }
+}
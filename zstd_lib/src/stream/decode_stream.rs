@@ -0,0 +1,75 @@
+use super::StreamDecoder;
+use crate::{DecodeOptions, Result};
+use bytes::Bytes;
+use futures_util::{stream, Stream, StreamExt};
+
+/// Decode a stream of compressed chunks, such as an HTTP body, into a stream
+/// of decoded chunks. A decoded chunk is produced as soon as a block is complete,
+/// the stream ends after the first error.
+///
+/// # Example
+/// ```
+/// # use futures_util::{stream, StreamExt};
+/// # use zstd_lib::decode_stream;
+/// # tokio::runtime::Builder::new_current_thread().build().unwrap().block_on(async {
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let chunks = bytes.chunks(4).map(bytes::Bytes::copy_from_slice);
+/// let decoded: Vec<_> = decode_stream(stream::iter(chunks)).collect().await;
+/// assert_eq!(decoded.len(), 1);
+/// assert_eq!(decoded[0].as_ref().unwrap().as_ref(), [0xCA, 0xFE]);
+/// # });
+/// ```
+pub fn decode_stream<S>(input: S) -> impl Stream<Item = Result<Bytes>>
+where
+    S: Stream<Item = Bytes>,
+{
+    // Blocks are small enough to be decoded on the polling task
+    let decoder = StreamDecoder::with_executor(DecodeOptions::new().single_threaded(true));
+
+    stream::unfold(Some((Box::pin(input), decoder)), |state| async move {
+        let (mut input, mut decoder) = state?;
+
+        while let Some(chunk) = input.next().await {
+            let mut decoded = Vec::new();
+            if let Err(err) = decoder.decode(&chunk, &mut decoded) {
+                return Some((Err(err), None));
+            }
+            if !decoded.is_empty() {
+                return Some((Ok(Bytes::from(decoded)), Some((input, decoder))));
+            }
+        }
+
+        decoder.finish().err().map(|err| (Err(err), None))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{super::tests::FRAMES, *};
+    use crate::Error;
+
+    async fn decode_chunks(bytes: &[u8], chunk_size: usize) -> Vec<Result<Bytes>> {
+        let chunks = bytes.chunks(chunk_size).map(Bytes::copy_from_slice);
+        decode_stream(stream::iter(chunks)).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_decode_stream() {
+        let decoded = decode_chunks(&FRAMES, 1).await;
+        let decoded: Vec<Bytes> = decoded.into_iter().map(|chunk| chunk.unwrap()).collect();
+        // One chunk per block
+        assert_eq!(decoded, [&[0xCA, 0xFE][..], &[0xBA, 0xBA]]);
+
+        let decoded = decode_chunks(&FRAMES, FRAMES.len()).await;
+        assert_eq!(decoded.len(), 1);
+        assert_eq!(decoded[0].as_ref().unwrap(), &[0xCA, 0xFE, 0xBA, 0xBA][..]);
+    }
+
+    #[tokio::test]
+    async fn test_decode_stream_truncated() {
+        let decoded = decode_chunks(&FRAMES[..24], 5).await;
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[0].as_ref().unwrap(), &[0xCA, 0xFE][..]);
+        assert!(matches!(decoded[1], Err(Error::Parsing(_))));
+    }
+}
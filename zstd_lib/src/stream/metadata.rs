@@ -0,0 +1,204 @@
+use super::super::{Error, Result};
+use std::io::{self, Read};
+use zstd_core::{
+    parsing::{ForwardByteParser, ParsingError},
+    BlockHeader, FrameError, FrameHeader, FrameKind, FrameMetadata,
+};
+
+/// Iterator over the metadata of the frames read from `R`, see `frames_metadata_from`
+pub struct FramesMetadataFrom<R> {
+    reader: R,
+    offset: usize,
+    failed: bool,
+}
+
+impl<R: Read> FramesMetadataFrom<R> {
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            offset: 0,
+            failed: false,
+        }
+    }
+
+    /// Read into `buf` until it is full or the input ends, return the number of
+    /// bytes read
+    fn fill(&mut self, buf: &mut [u8]) -> Result<usize> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.reader.read(&mut buf[read..]) {
+                Ok(0) => break,
+                Ok(len) => read += len,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err.into()),
+            }
+        }
+        self.offset += read;
+        Ok(read)
+    }
+
+    /// Read exactly `buf.len()` bytes, a truncated input is reported as the
+    /// byte parsers do
+    fn read_exact(&mut self, buf: &mut [u8]) -> Result<()> {
+        let read = self.fill(buf)?;
+        if read < buf.len() {
+            return Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                requested: buf.len(),
+                available: read,
+            }));
+        }
+        Ok(())
+    }
+
+    fn read_u32(&mut self) -> Result<u32> {
+        let mut bytes = [0; 4];
+        self.read_exact(&mut bytes)?;
+        Ok(u32::from_le_bytes(bytes))
+    }
+
+    /// Skip `len` bytes without buffering them
+    fn skip(&mut self, len: usize) -> Result<()> {
+        let skipped = io::copy(&mut self.reader.by_ref().take(len as u64), &mut io::sink())?;
+        let skipped = usize::try_from(skipped).unwrap();
+        self.offset += skipped;
+        if skipped < len {
+            return Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                requested: len,
+                available: skipped,
+            }));
+        }
+        Ok(())
+    }
+
+    /// Walk the headers of the next frame, `None` at the end of the input
+    fn scan(&mut self) -> Result<Option<FrameMetadata>> {
+        let offset = self.offset;
+        let mut magic = [0; 4];
+        match self.fill(&mut magic)? {
+            0 => return Ok(None),
+            4 => (),
+            read => {
+                return Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                    requested: 4,
+                    available: read,
+                }))
+            }
+        }
+        let magic = u32::from_le_bytes(magic);
+
+        let (kind, header, block_count, checksum) = match FrameKind::from_magic(magic) {
+            Some(FrameKind::Zstandard) => {
+                // Only the header is buffered, its size is given by its first byte
+                let mut bytes = [0; 14];
+                self.read_exact(&mut bytes[..1])?;
+                let size = FrameHeader::size(bytes[0]);
+                self.read_exact(&mut bytes[1..size])?;
+                let header = FrameHeader::parse(&mut ForwardByteParser::new(&bytes[..size]))?;
+
+                let mut block_count = 0;
+                loop {
+                    let mut bytes = [0; 3];
+                    self.read_exact(&mut bytes)?;
+                    let block_header = BlockHeader::parse(&mut ForwardByteParser::new(&bytes))?;
+                    self.skip(block_header.content_size())?;
+                    block_count += 1;
+                    if block_header.last_block {
+                        break;
+                    }
+                }
+
+                let checksum = if header.content_checksum_flag() {
+                    Some(self.read_u32()?)
+                } else {
+                    None
+                };
+                (FrameKind::Zstandard, Some(header), block_count, checksum)
+            }
+            Some(FrameKind::Skippable) => {
+                let len = self.read_u32()?;
+                self.skip(len as usize)?;
+                (FrameKind::Skippable, None, 0, None)
+            }
+            None => return Err(Error::Frame(FrameError::UnrecognizedMagic(magic))),
+        };
+
+        Ok(Some(FrameMetadata {
+            kind,
+            offset,
+            compressed_size: self.offset - offset,
+            header,
+            block_count,
+            checksum,
+        }))
+    }
+}
+
+impl<R: Read> Iterator for FramesMetadataFrom<R> {
+    type Item = Result<FrameMetadata>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let metadata = self.scan().transpose();
+        self.failed = matches!(metadata, Some(Err(_)));
+        metadata
+    }
+}
+
+/// Iterate over the metadata of the frames `reader` yields, as `frames_metadata`
+/// does for an input in memory: only the frame and block headers are read into
+/// memory, the block contents are skipped. Works on pipes, which cannot seek.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::{frames_metadata_from, FrameKind};
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let metadata = frames_metadata_from(bytes.as_slice()).next().unwrap()?;
+/// assert_eq!((metadata.kind, metadata.compressed_size), (FrameKind::Zstandard, 11));
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub fn frames_metadata_from<R: Read>(reader: R) -> FramesMetadataFrom<R> {
+    FramesMetadataFrom::new(reader)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frames_metadata;
+
+    const FRAMES: [u8; 29] = [
+        0x52, 0x2A, 0x4D, 0x18, 0x2, 0x0, 0x0, 0x0, 0xCA, 0xFE, // skippable, len 2
+        0x28, 0xB5, 0x2F, 0xFD, 0x4, 0x0, // magic, header: checksum flag
+        0x10, 0x0, 0x0, 0xBA, 0xBE, // raw block, len 2
+        0x3, 0x0, 0x0, 0xAA, // rle block, last
+        0x12, 0x34, 0x56, 0x78, // checksum
+    ];
+
+    #[test]
+    fn test_same_as_in_memory() {
+        let bytes = FRAMES.as_slice();
+        let expected = frames_metadata(bytes)
+            .map(|metadata| format!("{:?}", metadata.unwrap()))
+            .collect::<Vec<_>>();
+        let metadata = frames_metadata_from(bytes)
+            .map(|metadata| format!("{:?}", metadata.unwrap()))
+            .collect::<Vec<_>>();
+        assert_eq!(metadata.len(), 2);
+        assert_eq!(metadata, expected);
+    }
+
+    #[test]
+    fn test_truncated() {
+        let mut metadata = frames_metadata_from(&FRAMES[..FRAMES.len() - 1]);
+        assert_eq!(metadata.next().unwrap().unwrap().compressed_size, 10);
+        assert!(matches!(
+            metadata.next(),
+            Some(Err(Error::Parsing(ParsingError::NotEnoughBytes {
+                requested: 4,
+                available: 3
+            })))
+        ));
+        assert!(metadata.next().is_none());
+    }
+}
@@ -0,0 +1,269 @@
+#[cfg(feature = "tokio")]
+mod async_read;
+#[cfg(feature = "tokio-util")]
+mod codec;
+#[cfg(feature = "futures")]
+mod decode_stream;
+mod metadata;
+
+#[cfg(feature = "tokio")]
+pub use async_read::AsyncDecoder;
+#[cfg(feature = "tokio-util")]
+pub use codec::FrameCodec;
+#[cfg(feature = "futures")]
+pub use decode_stream::decode_stream;
+pub use metadata::{frames_metadata_from, FramesMetadataFrom};
+
+use super::{DecodeOptions, Error, Result};
+use std::io::{self, BufRead, Read};
+
+/// Size of the chunks read from the underlying reader
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Incremental decoder running blocks according to `DecodeOptions`. This is
+/// the state machine behind `Decoder` and `AsyncDecoder`.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::StreamDecoder;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut decoder = StreamDecoder::new();
+/// let mut output = Vec::new();
+/// for chunk in bytes.chunks(3) {
+///     decoder.decode(chunk, &mut output)?;
+/// }
+/// decoder.finish()?;
+/// assert_eq!(output, [0xCA, 0xFE]);
+/// # Ok::<(), zstd_lib::ZstdLibError>(())
+/// ```
+pub type StreamDecoder = zstd_core::StreamDecoder<DecodeOptions>;
+
+/// Decoded content waiting to be read
+#[derive(Default)]
+struct OutputBuffer {
+    buffer: Vec<u8>,
+    position: usize,
+}
+
+impl OutputBuffer {
+    fn is_empty(&self) -> bool {
+        self.position == self.buffer.len()
+    }
+
+    fn pending(&self) -> &[u8] {
+        &self.buffer[self.position..]
+    }
+
+    /// Mark `len` pending bytes as read
+    fn consume(&mut self, len: usize) {
+        self.position = core::cmp::min(self.position + len, self.buffer.len());
+    }
+
+    /// Copy as much pending content as possible to `buf`
+    fn read(&mut self, buf: &mut [u8]) -> usize {
+        let pending = self.pending();
+        let len = core::cmp::min(pending.len(), buf.len());
+        buf[..len].copy_from_slice(&pending[..len]);
+        self.position += len;
+        len
+    }
+
+    /// Clear the buffer, once read, to receive new content
+    fn reset(&mut self) -> &mut Vec<u8> {
+        self.buffer.clear();
+        self.position = 0;
+        &mut self.buffer
+    }
+}
+
+fn invalid_data(err: Error) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, err)
+}
+
+/// Streaming decoder reading compressed data from `R`. As a `BufRead`, it
+/// lends the content it decoded without copying it to a buffer of the caller.
+///
+/// # Example
+/// ```
+/// # use std::io::{BufRead, Read};
+/// # use zstd_lib::Decoder;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// let mut decoded = Vec::new();
+/// Decoder::new(bytes.as_slice()).read_to_end(&mut decoded)?;
+/// assert_eq!(decoded, [0xCA, 0xFE]);
+///
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x21, 0x0, 0x0, b'a', b'\n', b'b', b'\n'];
+/// let lines = Decoder::new(bytes.as_slice()).lines().collect::<Result<Vec<_>, _>>()?;
+/// assert_eq!(lines, ["a", "b"]);
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub struct Decoder<R> {
+    reader: R,
+    decoder: StreamDecoder,
+    output: OutputBuffer,
+    chunk: Box<[u8]>,
+    eof: bool,
+}
+
+impl<R: Read> Decoder<R> {
+    pub fn new(reader: R) -> Self {
+        Self::with_options(reader, DecodeOptions::default())
+    }
+
+    pub fn with_options(reader: R, options: DecodeOptions) -> Self {
+        Self {
+            reader,
+            decoder: StreamDecoder::with_executor(options),
+            output: OutputBuffer::default(),
+            chunk: vec![0; CHUNK_SIZE].into_boxed_slice(),
+            eof: false,
+        }
+    }
+
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.fill_buf()?;
+        Ok(self.output.read(buf))
+    }
+}
+
+/// The buffer is the content decoded from the last chunk of input read
+impl<R: Read> BufRead for Decoder<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.output.is_empty() && !self.eof {
+            let len = self.reader.read(&mut self.chunk)?;
+            let output = self.output.reset();
+            if len == 0 {
+                self.eof = true;
+                self.decoder.finish().map_err(invalid_data)?;
+            } else {
+                self.decoder
+                    .decode(&self.chunk[..len], output)
+                    .map_err(invalid_data)?;
+            }
+        }
+
+        Ok(self.output.pending())
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.output.consume(amt);
+    }
+}
+
+/// Decode everything `reader` yields. Unlike `decode`, the input is not read
+/// into memory first: only the structure being decoded and the window of its
+/// frame are buffered.
+///
+/// # Example
+/// ```
+/// # use zstd_lib::decode_from;
+/// let bytes = [0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x0, 0x11, 0x0, 0x0, 0xCA, 0xFE];
+/// assert_eq!(decode_from(bytes.as_slice()).unwrap(), [0xCA, 0xFE]);
+/// ```
+pub fn decode_from(mut reader: impl Read) -> Result<Vec<u8>> {
+    let mut decoder = StreamDecoder::new();
+    let mut chunk = vec![0; CHUNK_SIZE];
+    let mut decoded = Vec::new();
+    loop {
+        match reader.read(&mut chunk) {
+            Ok(0) => break,
+            Ok(len) => decoder.decode(&chunk[..len], &mut decoded)?,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => (),
+            Err(err) => return Err(err.into()),
+        }
+    }
+    decoder.finish()?;
+    Ok(decoded)
+}
+
+#[cfg(test)]
+pub(super) mod tests {
+    use super::*;
+
+    pub(crate) const FRAMES: [u8; 38] = [
+        0x50, 0x2A, 0x4D, 0x18, // skippable magic:   0x184D2A50
+        0x02, 0x0, 0x0, 0x0, // length:  2
+        0x10, 0x20, // content
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x04, 0x0, // header: checksum flag
+        0x10, 0x0, 0x0, // raw block, not last, len 2
+        0xCA, 0xFE, // content
+        0x13, 0x0, 0x0,  // rle block, last, repeat 2
+        0xBA, // content
+        0x56, 0x21, 0x60, 0x39, // checksum
+        0x28, 0xB5, 0x2F, 0xFD, // magic:   0xFD2FB528
+        0x0, 0x0, // header
+        0x01, 0x0, 0x0, // raw block, last, len 0
+    ];
+
+    #[test]
+    fn test_reader() {
+        let mut decoded = Vec::new();
+        Decoder::new(FRAMES.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, [0xCA, 0xFE, 0xBA, 0xBA]);
+
+        let err = Decoder::new(&FRAMES[..30])
+            .read_to_end(&mut Vec::new())
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_buf_read() {
+        let mut decoder = Decoder::new(FRAMES.as_slice());
+        assert_eq!(decoder.fill_buf().unwrap(), [0xCA, 0xFE, 0xBA, 0xBA]);
+        decoder.consume(3);
+        let mut rest = Vec::new();
+        decoder.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [0xBA]);
+        assert!(decoder.fill_buf().unwrap().is_empty());
+
+        // The content of the first frame is given before the second fails
+        let mut decoder = Decoder::new(&FRAMES[..30]);
+        assert_eq!(decoder.fill_buf().unwrap().len(), 4);
+        decoder.consume(4);
+        let err = decoder.fill_buf().unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_checkpoint_large_window() {
+        // Window of 128 MB, accepted with `window_log_max`
+        let bytes = [
+            0x28, 0xB5, 0x2F, 0xFD, 0x0, 0x88, // magic, header
+            0x10, 0x0, 0x0, 0xCA, 0xFE, // raw block, not last, len 2
+            0x11, 0x0, 0x0, 0xBA, 0xBE, // raw block, last, len 2
+        ];
+        let options = DecodeOptions::new().window_log_max(27);
+        let mut decoder = StreamDecoder::with_executor(options.clone());
+        let mut output = Vec::new();
+        decoder.decode(&bytes[..11], &mut output).unwrap();
+        let checkpoint = decoder.checkpoint();
+
+        let mut decoder = StreamDecoder::resume(&checkpoint, options).unwrap();
+        decoder
+            .decode(&bytes[decoder.input_offset()..], &mut output)
+            .unwrap();
+        decoder.finish().unwrap();
+        assert_eq!(output, [0xCA, 0xFE, 0xBA, 0xBE]);
+
+        assert!(StreamDecoder::resume(&checkpoint, DecodeOptions::new()).is_err());
+    }
+
+    #[test]
+    fn test_decode_from() {
+        assert_eq!(
+            decode_from(FRAMES.as_slice()).unwrap(),
+            [0xCA, 0xFE, 0xBA, 0xBA]
+        );
+        assert!(matches!(decode_from(&FRAMES[..30]), Err(Error::Parsing(_))));
+    }
+}
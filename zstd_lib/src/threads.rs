@@ -0,0 +1,204 @@
+use super::{panic_error, Result};
+use std::panic::{self, AssertUnwindSafe};
+#[cfg(feature = "threads")]
+use {
+    super::Error,
+    std::{
+        num::NonZeroUsize,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Mutex, OnceLock,
+        },
+        thread,
+    },
+};
+
+/*
+    Threads spawned by the library. Besides the calling thread, at most
+    `threads - 1` helper threads run at once (see `DecodeOptions::threads`).
+    They are counted over the whole process, so that nested parallelism, the
+    frames and then the sections and streams of their blocks, stays within the
+    limit without passing a budget down to zstd_core.
+
+    Without the `threads` feature, nothing here touches `std::thread`: there
+    is no helper and `map` runs the items one after the other.
+*/
+
+/// Helper threads running
+#[cfg(feature = "threads")]
+static HELPERS: AtomicUsize = AtomicUsize::new(0);
+
+/// `thread::available_parallelism` reads the CPU affinity and cgroups: look it
+/// up once
+#[cfg(feature = "threads")]
+pub(crate) fn available_parallelism() -> usize {
+    static AVAILABLE: OnceLock<usize> = OnceLock::new();
+    *AVAILABLE.get_or_init(|| thread::available_parallelism().map_or(1, NonZeroUsize::get))
+}
+
+#[cfg(not(feature = "threads"))]
+pub(crate) fn available_parallelism() -> usize {
+    1
+}
+
+/// Slot of a running helper thread, released when dropped
+#[cfg(feature = "threads")]
+pub(crate) struct Helper(());
+
+#[cfg(feature = "threads")]
+impl Helper {
+    /// Reserve a slot if fewer than `threads - 1` helpers are running
+    pub(crate) fn reserve(threads: usize) -> Option<Self> {
+        HELPERS
+            .fetch_update(Ordering::AcqRel, Ordering::Acquire, |running| {
+                (running + 1 < threads).then_some(running + 1)
+            })
+            .ok()
+            .map(|_| Helper(()))
+    }
+}
+
+#[cfg(feature = "threads")]
+impl Drop for Helper {
+    fn drop(&mut self) {
+        HELPERS.fetch_sub(1, Ordering::AcqRel);
+    }
+}
+
+/// Run `a` on the calling thread and `b` on the helper thread of `helper`
+#[cfg(feature = "threads")]
+pub(crate) fn join<A, B, RA, RB>(helper: Helper, a: A, b: B) -> Result<(RA, RB)>
+where
+    A: FnOnce() -> RA + Send,
+    B: FnOnce() -> RB + Send,
+    RA: Send,
+    RB: Send,
+{
+    thread::scope(|s| {
+        let b = s.spawn(move || {
+            let _helper = helper;
+            b()
+        });
+        let a = panic::catch_unwind(AssertUnwindSafe(a)).map_err(panic_error);
+        let b = b.join().map_err(panic_error);
+        Ok((a?, b?))
+    })
+}
+
+/// Run `task` on each item, on the calling thread and on as many helpers as
+/// `threads` allows, and return the results in the order of `items`. A
+/// panicking task fails its item only.
+#[cfg(feature = "threads")]
+pub(crate) fn map<T, R>(
+    items: Vec<T>,
+    threads: usize,
+    task: impl Fn(T) -> Result<R> + Sync,
+) -> Vec<Result<R>>
+where
+    T: Send,
+    R: Send,
+{
+    let len = items.len();
+    let queue = Mutex::new(items.into_iter().enumerate());
+    // Take the next item from the queue until it is empty
+    let work = || {
+        let mut done = Vec::new();
+        loop {
+            let next = queue.lock().unwrap().next();
+            let Some((index, item)) = next else {
+                return done;
+            };
+            let result = panic::catch_unwind(AssertUnwindSafe(|| task(item)))
+                .unwrap_or_else(|payload| Err(panic_error(payload)));
+            done.push((index, result));
+        }
+    };
+
+    let mut results: Vec<Option<Result<R>>> = (0..len).map(|_| None).collect();
+    thread::scope(|s| {
+        // The calling thread takes an item too
+        let helpers: Vec<_> = (1..len)
+            .map_while(|_| Helper::reserve(threads))
+            .map(|helper| {
+                s.spawn(move || {
+                    let _helper = helper;
+                    work()
+                })
+            })
+            .collect();
+
+        let done = helpers
+            .into_iter()
+            .filter_map(|helper| helper.join().ok())
+            .fold(work(), |mut done, helper_done| {
+                done.extend(helper_done);
+                done
+            });
+        for (index, result) in done {
+            results[index] = Some(result);
+        }
+    });
+
+    results
+        .into_iter()
+        .map(|result| {
+            result.unwrap_or_else(|| {
+                Err(Error::ParallelDecodingError(
+                    "helper thread panicked".to_string(),
+                ))
+            })
+        })
+        .collect()
+}
+
+/// Run `task` on each item on the calling thread, see the threaded `map`
+#[cfg(not(feature = "threads"))]
+pub(crate) fn map<T, R>(
+    items: Vec<T>,
+    _threads: usize,
+    task: impl Fn(T) -> Result<R>,
+) -> Vec<Result<R>> {
+    items
+        .into_iter()
+        .map(|item| {
+            panic::catch_unwind(AssertUnwindSafe(|| task(item)))
+                .unwrap_or_else(|payload| Err(panic_error(payload)))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::Error;
+    use super::*;
+
+    #[test]
+    fn test_map() {
+        for threads in [1, 2, 16] {
+            let results = map((0..100).collect(), threads, |i| match i {
+                13 => panic!("unlucky"),
+                i => Ok(2 * i),
+            });
+            assert_eq!(results.len(), 100);
+            for (i, result) in results.into_iter().enumerate() {
+                match result {
+                    Err(Error::ParallelDecodingError(message)) => {
+                        assert_eq!((i, message.as_str()), (13, "unlucky"));
+                    }
+                    result => assert_eq!(result.unwrap(), 2 * i),
+                }
+            }
+        }
+        assert!(map(Vec::<u8>::new(), 4, |_| Ok(())).is_empty());
+    }
+
+    #[cfg(feature = "threads")]
+    #[test]
+    fn test_single_thread() {
+        // No helper besides the calling thread
+        assert!(Helper::reserve(1).is_none());
+        let caller = thread::current().id();
+        let results = map(vec![(); 8], 1, |()| Ok(thread::current().id()));
+        assert!(results.into_iter().all(|id| id.unwrap() == caller));
+    }
+}
@@ -0,0 +1,15 @@
+use super::{decode_with_options, DecodeOptions};
+use wasm_bindgen::prelude::*;
+
+/// Decode a Zstandard `Uint8Array`, throw an `Error` when the input is invalid
+///
+/// ```js
+/// import init, { decode } from "./pkg/zstd_lib.js";
+///
+/// await init();
+/// const decoded = decode(new Uint8Array(await response.arrayBuffer()));
+/// ```
+#[wasm_bindgen(js_name = decode)]
+pub fn wasm_decode(bytes: &[u8]) -> Result<Vec<u8>, JsError> {
+    decode_with_options(bytes, DecodeOptions::new()).map_err(|err| JsError::new(&err.to_string()))
+}